@@ -0,0 +1,65 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+
+use crate::attrib::FieldAttrs;
+
+pub fn derive_args(item: &syn::ItemStruct) -> syn::Result<TokenStream> {
+    let enc = syn::Ident::new("enc", Span::mixed_site());
+    let index = syn::Ident::new("index", Span::mixed_site());
+    let type_pos = syn::Ident::new("type_pos", Span::mixed_site());
+
+    let fields = match &item.fields {
+        syn::Fields::Named(fields) => fields,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &item.fields,
+                "only named fields are supported",
+            ));
+        }
+    };
+    let mut names = Vec::with_capacity(fields.named.len());
+    let mut encode_fields = Vec::with_capacity(fields.named.len());
+    for (field_index, field) in fields.named.iter().enumerate() {
+        let attrs = FieldAttrs::from_syn(&field.attrs)?;
+        let unsupported = [
+            ("json", attrs.json),
+            ("default", attrs.default),
+            ("skip", attrs.skip),
+            ("flatten", attrs.flatten),
+        ];
+        if let Some((attr, _)) = unsupported.iter().find(|(_, set)| *set) {
+            return Err(syn::Error::new_spanned(
+                field,
+                format!("`{attr}` attribute is not supported for query arguments"),
+            ));
+        }
+        let ident = field.ident.as_ref().expect("a named field");
+        names.push(attrs.rename.unwrap_or_else(|| ident.to_string()));
+        encode_fields.push(quote! {
+            #field_index => {
+                ::gel_protocol::query_arg::QueryArg::check_descriptor(
+                    &self.#ident, #enc.ctx, #type_pos)?;
+                ::gel_protocol::query_arg::QueryArg::encode_slot(&self.#ident, #enc)
+            }
+        });
+    }
+
+    let name = &item.ident;
+    let (impl_generics, ty_generics, where_c) = item.generics.split_for_impl();
+    let expanded = quote! {
+        impl #impl_generics ::gel_protocol::query_arg::QueryArgs
+            for #name #ty_generics
+            #where_c
+        {
+            fn encode(&self, #enc: &mut ::gel_protocol::query_arg::Encoder)
+                -> ::std::result::Result<(), ::gel_protocol::query_arg::Error>
+            {
+                #enc.named_args(&[#(#names),*], |#enc, #index, #type_pos| match #index {
+                    #(#encode_fields)*
+                    _ => ::std::unreachable!(),
+                })
+            }
+        }
+    };
+    Ok(expanded)
+}
@@ -4,6 +4,15 @@ use syn::punctuated::Punctuated;
 #[derive(Debug)]
 enum FieldAttr {
     Json,
+    Rename(String),
+    Default,
+    Skip,
+    Flatten,
+}
+
+#[derive(Debug)]
+enum VariantAttr {
+    TypeName(String),
 }
 
 #[derive(Debug)]
@@ -12,10 +21,19 @@ enum ContainerAttr {
 }
 
 struct FieldAttrList(pub Punctuated<FieldAttr, syn::Token![,]>);
+struct VariantAttrList(pub Punctuated<VariantAttr, syn::Token![,]>);
 struct ContainerAttrList(pub Punctuated<ContainerAttr, syn::Token![,]>);
 
 pub struct FieldAttrs {
     pub json: bool,
+    pub rename: Option<String>,
+    pub default: bool,
+    pub skip: bool,
+    pub flatten: bool,
+}
+
+pub struct VariantAttrs {
+    pub tname: Option<String>,
 }
 
 pub struct ContainerAttrs {
@@ -24,6 +42,11 @@ pub struct ContainerAttrs {
 
 mod kw {
     syn::custom_keyword!(json);
+    syn::custom_keyword!(rename);
+    syn::custom_keyword!(default);
+    syn::custom_keyword!(skip);
+    syn::custom_keyword!(flatten);
+    syn::custom_keyword!(tname);
 }
 
 impl Parse for FieldAttr {
@@ -32,6 +55,34 @@ impl Parse for FieldAttr {
         if lookahead.peek(kw::json) {
             let _ident: syn::Ident = input.parse()?;
             Ok(FieldAttr::Json)
+        } else if lookahead.peek(kw::rename) {
+            let _ident: syn::Ident = input.parse()?;
+            let _eq: syn::Token![=] = input.parse()?;
+            let name: syn::LitStr = input.parse()?;
+            Ok(FieldAttr::Rename(name.value()))
+        } else if lookahead.peek(kw::default) {
+            let _ident: syn::Ident = input.parse()?;
+            Ok(FieldAttr::Default)
+        } else if lookahead.peek(kw::skip) {
+            let _ident: syn::Ident = input.parse()?;
+            Ok(FieldAttr::Skip)
+        } else if lookahead.peek(kw::flatten) {
+            let _ident: syn::Ident = input.parse()?;
+            Ok(FieldAttr::Flatten)
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+impl Parse for VariantAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let lookahead = input.lookahead1();
+        if lookahead.peek(kw::tname) {
+            let _ident: syn::Ident = input.parse()?;
+            let _eq: syn::Token![=] = input.parse()?;
+            let name: syn::LitStr = input.parse()?;
+            Ok(VariantAttr::TypeName(name.value()))
         } else {
             Err(lookahead.error())
         }
@@ -56,6 +107,12 @@ impl Parse for ContainerAttrList {
     }
 }
 
+impl Parse for VariantAttrList {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Punctuated::parse_terminated(input).map(VariantAttrList)
+    }
+}
+
 impl Parse for FieldAttrList {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         Punctuated::parse_terminated(input).map(FieldAttrList)
@@ -64,7 +121,13 @@ impl Parse for FieldAttrList {
 
 impl FieldAttrs {
     fn default() -> FieldAttrs {
-        FieldAttrs { json: false }
+        FieldAttrs {
+            json: false,
+            rename: None,
+            default: false,
+            skip: false,
+            flatten: false,
+        }
     }
     pub fn from_syn(attrs: &[syn::Attribute]) -> syn::Result<FieldAttrs> {
         let mut res = FieldAttrs::default();
@@ -74,6 +137,30 @@ impl FieldAttrs {
                 for item in chunk.0 {
                     match item {
                         FieldAttr::Json => res.json = true,
+                        FieldAttr::Rename(name) => res.rename = Some(name),
+                        FieldAttr::Default => res.default = true,
+                        FieldAttr::Skip => res.skip = true,
+                        FieldAttr::Flatten => res.flatten = true,
+                    }
+                }
+            }
+        }
+        Ok(res)
+    }
+}
+
+impl VariantAttrs {
+    fn default() -> VariantAttrs {
+        VariantAttrs { tname: None }
+    }
+    pub fn from_syn(attrs: &[syn::Attribute]) -> syn::Result<VariantAttrs> {
+        let mut res = VariantAttrs::default();
+        for attr in attrs {
+            if matches!(attr.style, syn::AttrStyle::Outer) && attr.path().is_ident("gel") {
+                let chunk: VariantAttrList = attr.parse_args()?;
+                for item in chunk.0 {
+                    match item {
+                        VariantAttr::TypeName(name) => res.tname = Some(name),
                     }
                 }
             }
@@ -1,24 +1,29 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use quote::quote;
 
+use crate::attrib::VariantAttrs;
+use crate::shape::{self, FieldsCode};
+
 pub fn derive_enum(s: &syn::ItemEnum) -> syn::Result<TokenStream> {
+    let mut is_object = false;
+    for variant in &s.variants {
+        let attrs = VariantAttrs::from_syn(&variant.attrs)?;
+        is_object |= attrs.tname.is_some() || !matches!(variant.fields, syn::Fields::Unit);
+    }
+    if is_object {
+        return derive_object_enum(s);
+    }
     let type_name = &s.ident;
     let (impl_generics, ty_generics, _) = s.generics.split_for_impl();
     let branches = s
         .variants
         .iter()
-        .map(|v| match v.fields {
-            syn::Fields::Unit => {
-                let name = &v.ident;
-                let name_bstr = syn::LitByteStr::new(name.to_string().as_bytes(), name.span());
-                Ok(quote!(#name_bstr => Ok(#type_name::#name)))
-            }
-            _ => Err(syn::Error::new_spanned(
-                &v.fields,
-                "fields are not allowed in enum variants",
-            )),
+        .map(|v| {
+            let name = &v.ident;
+            let name_bstr = syn::LitByteStr::new(name.to_string().as_bytes(), name.span());
+            quote!(#name_bstr => Ok(#type_name::#name))
         })
-        .collect::<Result<Vec<_>, _>>()?;
+        .collect::<Vec<_>>();
     let expanded = quote! {
         impl #impl_generics ::gel_protocol::queryable::Queryable
             for #type_name #ty_generics {
@@ -54,3 +59,177 @@ pub fn derive_enum(s: &syn::ItemEnum) -> syn::Result<TokenStream> {
     };
     Ok(expanded)
 }
+
+/// Enum decoded from objects of different types, the variant is chosen by
+/// the `__tname__` element of the shape
+fn derive_object_enum(s: &syn::ItemEnum) -> syn::Result<TokenStream> {
+    let type_name = &s.ident;
+    let decoder = syn::Ident::new("decoder", Span::mixed_site());
+    let buf = syn::Ident::new("buf", Span::mixed_site());
+    let tname = syn::Ident::new("tname", Span::mixed_site());
+    let nfields = syn::Ident::new("nfields", Span::mixed_site());
+    let variants = syn::Ident::new("variants", Span::mixed_site());
+    let all_fields = syn::Ident::new("all_fields", Span::mixed_site());
+    let fields_ident = syn::Ident::new("fields", Span::mixed_site());
+    let order = syn::Ident::new("order", Span::mixed_site());
+    let slot = syn::Ident::new("slot", Span::mixed_site());
+    let sub_args = syn::Ident::new("sub_args", Span::mixed_site());
+    let (impl_generics, ty_generics, _) = s.generics.split_for_impl();
+
+    let mut args_ty = Vec::with_capacity(s.variants.len());
+    let mut branches = Vec::with_capacity(s.variants.len());
+    let mut checks = Vec::with_capacity(s.variants.len());
+    for (index, variant) in s.variants.iter().enumerate() {
+        let attrs = VariantAttrs::from_syn(&variant.attrs)?;
+        let name = &variant.ident;
+        let tname_str = attrs.tname.unwrap_or_else(|| format!("default::{name}"));
+        let tname_bstr = syn::LitByteStr::new(tname_str.as_bytes(), name.span());
+        let index_lit = syn::Index::from(index);
+        let variant_args = quote::format_ident!("variant_{index}");
+        let (variant_ty, decoders, variant_checks, construct, value) = match &variant.fields {
+            syn::Fields::Named(named) => {
+                let fields = shape::named_fields(named)?;
+                let FieldsCode {
+                    decoders,
+                    checks,
+                    construct_args,
+                    args_ty,
+                } = shape::fields_code(&fields);
+                let fieldname = fields.iter().map(|f| &f.name);
+                (
+                    quote! { (#args_ty) },
+                    decoders,
+                    checks,
+                    quote! { (#construct_args) },
+                    quote! { #type_name::#name { #(#fieldname,)* } },
+                )
+            }
+            // the only field is decoded from the whole shape, like
+            // `#[gel(flatten)]` field of a structure
+            syn::Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+                let ty = &unnamed.unnamed[0].ty;
+                (
+                    quote! { <#ty as ::gel_protocol::queryable::QueryableFields>::FieldArgs },
+                    quote! {
+                        let value =
+                            <#ty as ::gel_protocol::queryable::QueryableFields>
+                            ::decode_fields(#decoder, #sub_args, #fields_ident, #order, #slot)?;
+                    },
+                    quote! {
+                        let args =
+                            <#ty as ::gel_protocol::queryable::QueryableFields>
+                            ::check_fields(ctx, elements, order)?;
+                    },
+                    quote! { args },
+                    quote! { #type_name::#name(value) },
+                )
+            }
+            syn::Fields::Unnamed(unnamed) => {
+                return Err(syn::Error::new_spanned(
+                    unnamed,
+                    "tuple variants must have a single field",
+                ));
+            }
+            syn::Fields::Unit => (
+                quote! { () },
+                quote! {},
+                quote! {},
+                quote! { () },
+                quote! { #type_name::#name },
+            ),
+        };
+        args_ty.push(quote! { (::std::vec::Vec<usize>, #variant_ty) });
+        branches.push(quote! {
+            ::std::option::Option::Some(#tname_bstr) => {
+                let (#order, #sub_args) = &#variants.#index_lit;
+                let #slot = &mut 0;
+                #decoders
+                ::std::result::Result::Ok(#value)
+            }
+        });
+        if matches!(variant.fields, syn::Fields::Unit) {
+            checks.push(quote! {
+                let #variant_args = (::std::vec::Vec::new(), ());
+            });
+            continue;
+        }
+        checks.push(quote! {
+            let #variant_args = {
+                let mut elements = ::std::clone::Clone::clone(&all_elements);
+                let mut order = ::std::vec::Vec::new();
+                let args = {
+                    let elements = &mut elements;
+                    let order = &mut order;
+                    #variant_checks
+                    #construct
+                };
+                (order, args)
+            };
+        });
+    }
+    let variant_args = (0..s.variants.len()).map(|index| quote::format_ident!("variant_{index}"));
+
+    let expanded = quote! {
+        impl #impl_generics ::gel_protocol::queryable::Queryable
+            for #type_name #ty_generics {
+            type Args = (usize, usize, (#(#args_ty,)*));
+
+            // variants may have no fields
+            #[allow(unused_variables)]
+            fn decode(
+                #decoder: &::gel_protocol::queryable::Decoder,
+                (#tname, #nfields, #variants): &Self::Args,
+                #buf: &[u8],
+            ) -> ::std::result::Result<Self, ::gel_protocol::errors::DecodeError> {
+                let mut elements =
+                    ::gel_protocol::serialization::decode::DecodeTupleLike
+                    ::new_object(#buf, *#nfields)?;
+                let #all_fields = elements.read_n(*#nfields)?;
+                let #fields_ident: &[::std::option::Option<&[u8]>] = &#all_fields;
+                match #fields_ident[*#tname] {
+                    #(#branches)*
+                    ::std::option::Option::Some(type_name) => ::std::result::Result::Err(
+                        ::gel_protocol::errors::ExtraTypeName {
+                            type_name: ::std::string::String::from_utf8_lossy(type_name),
+                        }
+                        .build(),
+                    ),
+                    ::std::option::Option::None => ::std::result::Result::Err(
+                        ::gel_protocol::errors::MissingRequiredElement.build(),
+                    ),
+                }
+            }
+            // variants may have no fields
+            #[allow(unused_variables, unused_mut)]
+            fn check_descriptor(
+                ctx: &::gel_protocol::queryable::DescriptorContext,
+                type_pos: ::gel_protocol::descriptors::TypePos,
+            ) -> ::std::result::Result<Self::Args, ::gel_protocol::queryable::DescriptorMismatch>
+            {
+                use ::gel_protocol::descriptors::Descriptor::ObjectShape;
+                use ::std::iter::Iterator;
+                let desc = ctx.get(type_pos)?;
+                let ObjectShape(shape) = desc else {
+                    return ::std::result::Result::Err(ctx.wrong_type(desc, "object"));
+                };
+                // all elements of polymorphic shapes are sent, elements of
+                // other types are empty, so variants may skip elements
+                let mut all_elements =
+                    ::std::collections::HashMap::with_capacity(shape.elements.len());
+                for (position, element) in shape.elements.iter().enumerate() {
+                    all_elements.insert(element.name.as_str(), (position, element.type_pos));
+                }
+                let ::std::option::Option::Some((tname, tname_pos)) =
+                    all_elements.remove("__tname__")
+                else {
+                    return ::std::result::Result::Err(ctx.expected("field __tname__"));
+                };
+                <::std::string::String as ::gel_protocol::queryable::Queryable>
+                    ::check_descriptor(ctx, tname_pos)?;
+                #(#checks)*
+                ::std::result::Result::Ok((tname, shape.elements.len(), (#(#variant_args,)*)))
+            }
+        }
+    };
+    Ok(expanded)
+}
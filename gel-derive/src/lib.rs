@@ -3,10 +3,11 @@ Derive macro that allows structs and enums to be populated by database
 queries.
 
 This derive can be used on structures with named fields (which correspond
-to "shapes" in Gel). Note that field order matters, so the struct below
-corresponds to an Gel `User` query with `first_name` followed by `age`.
-A `DescriptorMismatch` will be returned if the fields in the Rust struct
-are not in the same order as those in the query shape.
+to "shapes" in Gel). Fields are matched to the elements of the query shape
+by name, in any order, so the struct below corresponds to an Gel `User`
+query with `first_name` and `age`. A `DescriptorMismatch` will be returned
+if the shape lacks an element for one of the fields or has an element that
+doesn't correspond to any field.
 
 ```rust
 # use gel_derive::Queryable;
@@ -48,6 +49,66 @@ struct User {
 }
 ```
 
+## Rename
+
+The `#[gel(rename = "name")]` attribute matches the field to the shape
+element `name` instead of the name of the field. This is useful for element
+names that aren't valid (or idiomatic) Rust identifiers.
+
+```rust
+# use gel_derive::Queryable;
+#[derive(Queryable)]
+struct User {
+    #[gel(rename = "type")]
+    kind: String,
+}
+```
+
+## Default
+
+A field with the `#[gel(default)]` attribute may be absent from the query
+shape, in which case it's initialized with `Default::default()`. This
+allows using the same structure for queries selecting different sets of
+elements.
+
+```rust
+# use gel_derive::Queryable;
+#[derive(Queryable)]
+struct User {
+    first_name: String,
+    #[gel(default)]
+    age: Option<i32>,
+}
+```
+
+## Skip
+
+A field with the `#[gel(skip)]` attribute isn't decoded from the query
+shape at all and is always initialized with `Default::default()`.
+
+## Flatten
+
+The `#[gel(flatten)]` attribute decodes the fields of another structure,
+which must also derive `Queryable`, from the elements of the same shape.
+
+```rust
+# use gel_derive::Queryable;
+#[derive(Queryable)]
+struct Name {
+    first_name: String,
+    last_name: String,
+}
+
+#[derive(Queryable)]
+struct User {
+    #[gel(flatten)]
+    name: Name,
+    age: i32,
+}
+```
+
+This corresponds to `select User { first_name, last_name, age }`.
+
 # Container attributes
 
 ## JSON
@@ -74,12 +135,95 @@ let query = "select <json>JsonData { field1, field2 };";
 let query_res: Vec<JsonData> = client.query(query, &()).await?;
 ```
 
+# Tuple structs
+
+Tuple structs are decoded from tuples and named tuples, elements are
+matched to the fields by position. Structs with a single field are
+"newtypes", which are decoded the same way as the type of the field:
+
+```rust
+# use gel_derive::Queryable;
+# use gel_protocol::model::Uuid;
+#[derive(Queryable)]
+struct UserId(Uuid);
+
+#[derive(Queryable)]
+struct Pair(String, UserId);
+```
+
+# Enums
+
+Enums with unit variants only are decoded from Gel enums, variants match
+the values of the enum by name.
+
+Enums with fields are decoded from the results of polymorphic queries. The
+shape must contain the `__tname__` element, whose value chooses the variant
+(`default::` followed by the name of the variant unless overridden using
+`#[gel(tname = "...")]`). Variants with named fields are decoded as
+structures, a tuple variant with a single field is decoded like a
+`#[gel(flatten)]` field, and unit variants don't use any elements.
+
+```rust
+# use gel_derive::Queryable;
+#[derive(Queryable)]
+struct Show {
+    title: String,
+    seasons: i64,
+}
+
+#[derive(Queryable)]
+enum Content {
+    #[gel(tname = "default::Movie")]
+    Film { title: String, director: String },
+    Show(Show),
+}
+```
+
+```rust,ignore
+let query = "select Content {
+    __tname__ := .__type__.name,
+    title,
+    [is Movie].director,
+    [is Show].seasons,
+}";
+let query_res: Vec<Content> = client.query(query, &()).await?;
+```
+
+# Query arguments
+
+`#[derive(QueryArgs)]` allows passing a structure as named arguments of a
+query. Fields are matched to the `$name` parameters of the query by name,
+`Option` fields can be used for optional parameters.
+
+```rust
+# use gel_derive::QueryArgs;
+#[derive(QueryArgs)]
+struct NewUser {
+    first_name: String,
+    #[gel(rename = "age")]
+    user_age: Option<i32>,
+}
+```
+
+```rust,ignore
+let args = NewUser { first_name: "John".into(), user_age: None };
+client.execute(
+    "insert User { first_name := <str>$first_name, age := <optional int32>$age }",
+    &args,
+).await?;
+```
+
+Encoding fails with `MissingArgumentError` if the query has a parameter
+that isn't a field of the structure, and with `UnknownArgumentError` if the
+structure has a field that isn't a parameter of the query.
+
 */
 extern crate proc_macro;
 
 use proc_macro::TokenStream;
 use syn::parse_macro_input;
 
+mod args;
 mod attrib;
 mod enums;
 mod json;
@@ -139,3 +283,12 @@ pub fn config_delta(input: TokenStream) -> TokenStream {
         Err(e) => e.to_compile_error().into(),
     }
 }
+
+#[proc_macro_derive(QueryArgs, attributes(gel))]
+pub fn query_args(input: TokenStream) -> TokenStream {
+    let s = parse_macro_input!(input as syn::ItemStruct);
+    match args::derive_args(&s) {
+        Ok(stream) => stream.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
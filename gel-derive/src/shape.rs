@@ -3,46 +3,68 @@ use quote::quote;
 
 use crate::attrib::FieldAttrs;
 
-struct Field {
-    name: syn::Ident,
+pub struct Field {
+    pub name: syn::Ident,
     str_name: syn::LitStr,
     ty: syn::Type,
     attrs: FieldAttrs,
 }
 
+/// Code decoding named fields from the elements of a shape
+///
+/// This is shared by structures and enum variants. The code refers to the
+/// `decoder`, `sub_args`, `fields`, `order` and `slot` variables of
+/// `QueryableFields::decode_fields` and to `ctx`, `elements` and `order`
+/// of `QueryableFields::check_fields`.
+pub struct FieldsCode {
+    pub decoders: TokenStream,
+    pub checks: TokenStream,
+    pub construct_args: TokenStream,
+    pub args_ty: TokenStream,
+}
+
 pub fn derive_struct(s: &syn::ItemStruct) -> syn::Result<TokenStream> {
+    match &s.fields {
+        syn::Fields::Named(named) => derive_named(s, named),
+        syn::Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => derive_newtype(s, unnamed),
+        syn::Fields::Unnamed(unnamed) => derive_tuple(s, unnamed),
+        syn::Fields::Unit => Err(syn::Error::new_spanned(
+            s,
+            "can't derive Queryable for a unit struct",
+        )),
+    }
+}
+
+pub fn named_fields(named: &syn::FieldsNamed) -> syn::Result<Vec<Field>> {
+    let mut fields = Vec::with_capacity(named.named.len());
+    for field in &named.named {
+        let attrs = FieldAttrs::from_syn(&field.attrs)?;
+        check_attrs(field, &attrs)?;
+        let name = field.ident.clone().unwrap();
+        let str_name = attrs.rename.clone().unwrap_or_else(|| name.to_string());
+        fields.push(Field {
+            str_name: syn::LitStr::new(&str_name, name.span()),
+            name,
+            ty: field.ty.clone(),
+            attrs,
+        });
+    }
+    Ok(fields)
+}
+
+fn derive_named(s: &syn::ItemStruct, named: &syn::FieldsNamed) -> syn::Result<TokenStream> {
     let name = &s.ident;
     let decoder = syn::Ident::new("decoder", Span::mixed_site());
     let buf = syn::Ident::new("buf", Span::mixed_site());
     let nfields = syn::Ident::new("nfields", Span::mixed_site());
     let elements = syn::Ident::new("elements", Span::mixed_site());
+    let fields_ident = syn::Ident::new("fields", Span::mixed_site());
     let order = syn::Ident::new("order", Span::mixed_site());
+    let slot = syn::Ident::new("slot", Span::mixed_site());
     let sub_args = syn::Ident::new("sub_args", Span::mixed_site());
     let (impl_generics, ty_generics, _) = s.generics.split_for_impl();
-    let fields = match &s.fields {
-        syn::Fields::Named(named) => {
-            let mut fields = Vec::with_capacity(named.named.len());
-            for field in &named.named {
-                let attrs = FieldAttrs::from_syn(&field.attrs)?;
-                let name = field.ident.clone().unwrap();
-                fields.push(Field {
-                    str_name: syn::LitStr::new(&name.to_string(), name.span()),
-                    name,
-                    ty: field.ty.clone(),
-                    attrs,
-                });
-            }
-            fields
-        }
-        _ => {
-            return Err(syn::Error::new_spanned(
-                &s.fields,
-                "only named fields are supported",
-            ));
-        }
-    };
+    let fields = named_fields(named)?;
     let fieldname = fields.iter().map(|f| f.name.clone()).collect::<Vec<_>>();
-    let base_fields = fields.len();
     let type_id_block = Some(quote! {
         if #decoder.has_implicit_tid {
             #elements.skip_element()?;
@@ -82,30 +104,170 @@ pub fn derive_struct(s: &syn::ItemStruct) -> syn::Result<TokenStream> {
             idx += 1;
         }
     });
+    let FieldsCode {
+        decoders: field_decoders,
+        checks: field_checks,
+        construct_args: construct_sub_args,
+        args_ty,
+    } = fields_code(&fields);
+    let field_count = fields.len();
+
+    let expanded = quote! {
+        impl #impl_generics ::gel_protocol::queryable::QueryableFields
+            for #name #ty_generics {
+            type FieldArgs = (#args_ty);
+
+            // all fields may be skipped
+            #[allow(unused_variables)]
+            fn decode_fields(
+                #decoder: &::gel_protocol::queryable::Decoder,
+                #sub_args: &Self::FieldArgs,
+                #fields_ident: &[::std::option::Option<&[u8]>],
+                #order: &[usize],
+                #slot: &mut usize,
+            ) -> ::std::result::Result<Self, ::gel_protocol::errors::DecodeError> {
+                #field_decoders
+                ::std::result::Result::Ok(#name {
+                    #(
+                        #fieldname,
+                    )*
+                })
+            }
+            // all fields may be skipped
+            #[allow(unused_variables)]
+            fn check_fields(
+                ctx: &::gel_protocol::queryable::DescriptorContext,
+                elements: &mut ::std::collections::HashMap<
+                    &str,
+                    (usize, ::gel_protocol::descriptors::TypePos),
+                >,
+                order: &mut ::std::vec::Vec<usize>,
+            ) -> ::std::result::Result<Self::FieldArgs, ::gel_protocol::queryable::DescriptorMismatch>
+            {
+                #field_checks
+                ::std::result::Result::Ok((#construct_sub_args))
+            }
+        }
+
+        impl #impl_generics ::gel_protocol::queryable::Queryable
+            for #name #ty_generics {
+            type Args = (
+                ::std::vec::Vec<usize>,
+                <Self as ::gel_protocol::queryable::QueryableFields>::FieldArgs,
+            );
+
+            fn decode(
+                #decoder: &::gel_protocol::queryable::Decoder,
+                (#order, #sub_args): &Self::Args,
+                #buf: &[u8]
+            ) -> ::std::result::Result<Self, ::gel_protocol::errors::DecodeError> {
+                let #nfields = #order.len()
+                    + if #decoder.has_implicit_id { 1 } else { 0 }
+                    + if #decoder.has_implicit_tid { 1 } else { 0 }
+                    + if #decoder.has_implicit_tname { 1 } else { 0 };
+                let mut #elements =
+                    ::gel_protocol::serialization::decode::DecodeTupleLike
+                    ::new_object(#buf, #nfields)?;
+
+                #type_id_block
+                #type_name_block
+                #id_block
+                let #fields_ident = #elements.read_n(#order.len())?;
+                <Self as ::gel_protocol::queryable::QueryableFields>::decode_fields(
+                    #decoder, #sub_args, &#fields_ident, #order, &mut 0,
+                )
+            }
+            fn check_descriptor(
+                ctx: &::gel_protocol::queryable::DescriptorContext,
+                type_pos: ::gel_protocol::descriptors::TypePos
+            ) -> ::std::result::Result<Self::Args, ::gel_protocol::queryable::DescriptorMismatch>
+            {
+                use ::gel_protocol::descriptors::Descriptor::{ObjectShape, SQLRow};
+                use ::std::iter::Iterator;
+                let desc = ctx.get(type_pos)?;
+                let mut elements = ::std::collections::HashMap::with_capacity(#field_count);
+                match desc {
+                    ObjectShape(shape) => {
+                        // TODO(tailhook) cache shape.id somewhere
+                        let mut idx = 0;
+                        #type_id_check
+                        #type_name_check
+                        #id_check
+                        for (position, element) in shape.elements[idx..].iter().enumerate() {
+                            elements.insert(element.name.as_str(), (position, element.type_pos));
+                        }
+                    }
+                    // rows of SQL queries, columns are matched by name
+                    SQLRow(row) => {
+                        for (position, element) in row.elements.iter().enumerate() {
+                            elements.insert(element.name.as_str(), (position, element.type_pos));
+                        }
+                    }
+                    _ => {
+                        return ::std::result::Result::Err(ctx.wrong_type(desc, "str"))
+                    }
+                };
+
+                // elements are matched by name, so the shape may list them
+                // in any order; `order` maps fields to the positions
+                let total = elements.len();
+                let mut order = ::std::vec::Vec::with_capacity(total);
+                let sub_args =
+                    <Self as ::gel_protocol::queryable::QueryableFields>
+                    ::check_fields(ctx, &mut elements, &mut order)?;
+                if !elements.is_empty() {
+                    return ::std::result::Result::Err(ctx.field_number(order.len(), total));
+                }
+                ::std::result::Result::Ok((order, sub_args))
+            }
+        }
+    };
+    Ok(expanded)
+}
+
+pub fn fields_code(fields: &[Field]) -> FieldsCode {
+    let decoder = syn::Ident::new("decoder", Span::mixed_site());
+    let fields_ident = syn::Ident::new("fields", Span::mixed_site());
+    let order = syn::Ident::new("order", Span::mixed_site());
+    let slot = syn::Ident::new("slot", Span::mixed_site());
+    let sub_args = syn::Ident::new("sub_args", Span::mixed_site());
+    let next_buf = quote! {{
+        let position = #order[*#slot];
+        *#slot += 1;
+        #fields_ident[position]
+    }};
     let field_decoders = fields
         .iter()
         .enumerate()
         .map(|(index, field)| {
             let fieldname = &field.name;
+            let fieldtype = &field.ty;
 
             let index_lit = syn::LitInt::new(&index.to_string(), Span::mixed_site());
             let sub_arg = quote! { &#sub_args.#index_lit };
-            let buf = quote! { fields[#order[#index]].as_deref() };
 
-            if field.attrs.json {
+            if field.attrs.skip {
                 quote! {
-                    let #fieldname: ::gel_protocol::model::Json =
-                        <::gel_protocol::model::Json as
-                            ::gel_protocol::queryable::Queryable>
-                        ::decode_optional(#decoder, #sub_arg, #buf)?;
-                    let #fieldname = ::serde_json::from_str(#fieldname.as_ref())
-                        .map_err(::gel_protocol::errors::decode_error)?;
+                    let #fieldname = ::std::default::Default::default();
                 }
-            } else {
+            } else if field.attrs.flatten {
                 quote! {
                     let #fieldname =
-                        ::gel_protocol::queryable::Queryable
-                        ::decode_optional(#decoder, #sub_arg, #buf)?;
+                        <#fieldtype as ::gel_protocol::queryable::QueryableFields>
+                        ::decode_fields(#decoder, #sub_arg, #fields_ident, #order, #slot)?;
+                }
+            } else if field.attrs.default {
+                let value = decode_value(field, &decoder, &quote! { sub_arg }, &next_buf);
+                quote! {
+                    let #fieldname = match #sub_arg {
+                        ::std::option::Option::Some(sub_arg) => #value,
+                        ::std::option::Option::None => ::std::default::Default::default(),
+                    };
+                }
+            } else {
+                let value = decode_value(field, &decoder, &sub_arg, &next_buf);
+                quote! {
+                    let #fieldname = #value;
                 }
             }
         })
@@ -119,32 +281,51 @@ pub fn derive_struct(s: &syn::ItemStruct) -> syn::Result<TokenStream> {
                 &format!("field {}", field.str_name.value()),
                 field.str_name.span(),
             );
-            let get_element = quote! {
-                let ::std::option::Option::Some((position, el)) = elements.get(#name_str) else {
-                    return ::std::result::Result::Err(ctx.expected(#description_str));
-                };
-                order.push(*position);
-            };
 
             let fieldtype = &field.ty;
             let check_descriptor = if field.attrs.json {
                 quote! {
                     <::gel_protocol::model::Json as
                         ::gel_protocol::queryable::Queryable>
-                        ::check_descriptor(ctx, el.type_pos)?
+                        ::check_descriptor(ctx, element_pos)?
                 }
             } else {
                 quote! {
                     <#fieldtype as ::gel_protocol::queryable::Queryable>
-                        ::check_descriptor(ctx, el.type_pos)?
+                        ::check_descriptor(ctx, element_pos)?
                 }
             };
 
             let arg_ident = quote::format_ident!("arg_{field_index}");
 
-            quote! {
-                #get_element
-                let #arg_ident = #check_descriptor;
+            if field.attrs.skip {
+                quote! {
+                    let #arg_ident = ();
+                }
+            } else if field.attrs.flatten {
+                quote! {
+                    let #arg_ident =
+                        <#fieldtype as ::gel_protocol::queryable::QueryableFields>
+                        ::check_fields(ctx, elements, order)?;
+                }
+            } else if field.attrs.default {
+                quote! {
+                    let #arg_ident = match elements.remove(#name_str) {
+                        ::std::option::Option::Some((position, element_pos)) => {
+                            order.push(position);
+                            ::std::option::Option::Some(#check_descriptor)
+                        }
+                        ::std::option::Option::None => ::std::option::Option::None,
+                    };
+                }
+            } else {
+                quote! {
+                    let ::std::option::Option::Some((position, element_pos)) = elements.remove(#name_str) else {
+                        return ::std::result::Result::Err(ctx.expected(#description_str));
+                    };
+                    order.push(position);
+                    let #arg_ident = #check_descriptor;
+                }
             }
         })
         .collect::<TokenStream>();
@@ -159,81 +340,191 @@ pub fn derive_struct(s: &syn::ItemStruct) -> syn::Result<TokenStream> {
     let args_ty = fields
         .iter()
         .map(|field| {
-            if field.attrs.json {
-                quote! { (), }
+            let ty = &field.ty;
+            let args = if field.attrs.skip || field.attrs.json {
+                quote! { () }
+            } else if field.attrs.flatten {
+                quote! { <#ty as ::gel_protocol::queryable::QueryableFields>::FieldArgs }
+            } else {
+                quote! { <#ty as ::gel_protocol::queryable::Queryable>::Args }
+            };
+            if field.attrs.default {
+                quote! { ::std::option::Option<#args>, }
             } else {
-                let ty = &field.ty;
-                quote! { <#ty as ::gel_protocol::queryable::Queryable>::Args, }
+                quote! { #args, }
             }
         })
         .collect::<TokenStream>();
 
-    let field_count = fields.len();
+    FieldsCode {
+        decoders: field_decoders,
+        checks: field_checks,
+        construct_args: construct_sub_args,
+        args_ty,
+    }
+}
 
+/// Newtype structures are decoded transparently as their only field
+fn derive_newtype(s: &syn::ItemStruct, unnamed: &syn::FieldsUnnamed) -> syn::Result<TokenStream> {
+    let name = &s.ident;
+    let (impl_generics, ty_generics, _) = s.generics.split_for_impl();
+    let field = &unnamed.unnamed[0];
+    let attrs = FieldAttrs::from_syn(&field.attrs)?;
+    if attrs.json || attrs.rename.is_some() || attrs.default || attrs.skip || attrs.flatten {
+        return Err(syn::Error::new_spanned(
+            field,
+            "attributes are not supported for fields of newtype structs",
+        ));
+    }
+    let ty = &field.ty;
     let expanded = quote! {
         impl #impl_generics ::gel_protocol::queryable::Queryable
             for #name #ty_generics {
-            type Args = (::std::vec::Vec<usize>, (#args_ty));
+            type Args = <#ty as ::gel_protocol::queryable::Queryable>::Args;
+
+            fn decode(
+                decoder: &::gel_protocol::queryable::Decoder,
+                args: &Self::Args,
+                buf: &[u8],
+            ) -> ::std::result::Result<Self, ::gel_protocol::errors::DecodeError> {
+                <#ty as ::gel_protocol::queryable::Queryable>::decode(decoder, args, buf)
+                    .map(#name)
+            }
+            fn check_descriptor(
+                ctx: &::gel_protocol::queryable::DescriptorContext,
+                type_pos: ::gel_protocol::descriptors::TypePos,
+            ) -> ::std::result::Result<Self::Args, ::gel_protocol::queryable::DescriptorMismatch>
+            {
+                <#ty as ::gel_protocol::queryable::Queryable>::check_descriptor(ctx, type_pos)
+            }
+        }
+    };
+    Ok(expanded)
+}
+
+/// Tuple structures are decoded from tuples and named tuples by position
+fn derive_tuple(s: &syn::ItemStruct, unnamed: &syn::FieldsUnnamed) -> syn::Result<TokenStream> {
+    let name = &s.ident;
+    let decoder = syn::Ident::new("decoder", Span::mixed_site());
+    let args = syn::Ident::new("args", Span::mixed_site());
+    let elements = syn::Ident::new("elements", Span::mixed_site());
+    let (impl_generics, ty_generics, _) = s.generics.split_for_impl();
+    let mut decoders = Vec::with_capacity(unnamed.unnamed.len());
+    let mut checks = Vec::with_capacity(unnamed.unnamed.len());
+    let mut args_ty = Vec::with_capacity(unnamed.unnamed.len());
+    for (index, field) in unnamed.unnamed.iter().enumerate() {
+        let attrs = FieldAttrs::from_syn(&field.attrs)?;
+        if attrs.rename.is_some() || attrs.default || attrs.skip || attrs.flatten {
+            return Err(syn::Error::new_spanned(
+                field,
+                "only `json` attribute is supported for fields of tuple structs",
+            ));
+        }
+        let index_lit = syn::Index::from(index);
+        let ty = if attrs.json {
+            syn::parse_quote!(::gel_protocol::model::Json)
+        } else {
+            field.ty.clone()
+        };
+        let decode = quote! {
+            <#ty as ::gel_protocol::queryable::Queryable>
+                ::decode_optional(#decoder, &#args.#index_lit, #elements.read()?)?
+        };
+        decoders.push(if attrs.json {
+            quote! {{
+                let json = #decode;
+                ::serde_json::from_str(json.as_ref())
+                    .map_err(::gel_protocol::errors::decode_error)?
+            }}
+        } else {
+            decode
+        });
+        checks.push(quote! {
+            <#ty as ::gel_protocol::queryable::Queryable>
+                ::check_descriptor(ctx, element_types.next().unwrap())?
+        });
+        args_ty.push(quote! { <#ty as ::gel_protocol::queryable::Queryable>::Args });
+    }
+    let count = unnamed.unnamed.len();
+    let expanded = quote! {
+        impl #impl_generics ::gel_protocol::queryable::Queryable
+            for #name #ty_generics {
+            type Args = (#(#args_ty,)*);
 
             fn decode(
                 #decoder: &::gel_protocol::queryable::Decoder,
-                (#order, #sub_args): &Self::Args,
-                #buf: &[u8]
+                #args: &Self::Args,
+                buf: &[u8],
             ) -> ::std::result::Result<Self, ::gel_protocol::errors::DecodeError> {
-                let #nfields = #base_fields
-                    + if #decoder.has_implicit_id { 1 } else { 0 }
-                    + if #decoder.has_implicit_tid { 1 } else { 0 }
-                    + if #decoder.has_implicit_tname { 1 } else { 0 };
                 let mut #elements =
                     ::gel_protocol::serialization::decode::DecodeTupleLike
-                    ::new_object(#buf, #nfields)?;
-
-                #type_id_block
-                #type_name_block
-                #id_block
-                let fields = #elements.read_n(#field_count)?;
-                #field_decoders
-                ::std::result::Result::Ok(#name {
-                    #(
-                        #fieldname,
-                    )*
-                })
+                    ::new_tuple(buf, #count)?;
+                ::std::result::Result::Ok(#name(#(#decoders,)*))
             }
             fn check_descriptor(
                 ctx: &::gel_protocol::queryable::DescriptorContext,
-                type_pos: ::gel_protocol::descriptors::TypePos
+                type_pos: ::gel_protocol::descriptors::TypePos,
             ) -> ::std::result::Result<Self::Args, ::gel_protocol::queryable::DescriptorMismatch>
             {
-                use ::gel_protocol::descriptors::Descriptor::ObjectShape;
+                use ::gel_protocol::descriptors::Descriptor::{NamedTuple, Tuple};
+                use ::std::iter::Iterator;
                 let desc = ctx.get(type_pos)?;
-                let shape = match desc {
-                    ObjectShape(shape) => shape,
-                    _ => {
-                        return ::std::result::Result::Err(ctx.wrong_type(desc, "str"))
-                    }
+                let element_types = match desc {
+                    Tuple(tuple) => ::std::clone::Clone::clone(&tuple.element_types),
+                    // elements of named tuples are matched by position
+                    NamedTuple(tuple) => tuple.elements.iter().map(|e| e.type_pos).collect(),
+                    _ => return ::std::result::Result::Err(ctx.wrong_type(desc, "tuple")),
                 };
-
-                // TODO(tailhook) cache shape.id somewhere
-                let mut idx = 0;
-                #type_id_check
-                #type_name_check
-                #id_check
-                if(shape.elements.len() != #field_count) {
-                    return ::std::result::Result::Err(ctx.field_number(
-                        #field_count, shape.elements.len())
+                if element_types.len() != #count {
+                    return ::std::result::Result::Err(
+                        ctx.field_number(#count, element_types.len())
                     );
                 }
-
-                let mut elements = ::std::collections::HashMap::with_capacity(shape.elements.len());
-                use ::std::iter::Iterator;
-                for (position, element) in shape.elements.iter().enumerate() {
-                    elements.insert(element.name.as_str(), (position, element));
-                }
-                let mut order = ::std::vec::Vec::with_capacity(shape.elements.len());
-                #field_checks
-                ::std::result::Result::Ok((order, (#construct_sub_args)))
+                let mut element_types = ::std::iter::IntoIterator::into_iter(element_types);
+                ::std::result::Result::Ok((#(#checks,)*))
             }
         }
     };
     Ok(expanded)
 }
+
+/// Expression decoding a single shape element into the `field`
+fn decode_value(
+    field: &Field,
+    decoder: &syn::Ident,
+    sub_arg: &TokenStream,
+    buf: &TokenStream,
+) -> TokenStream {
+    if field.attrs.json {
+        quote! {{
+            let json: ::gel_protocol::model::Json =
+                <::gel_protocol::model::Json as
+                    ::gel_protocol::queryable::Queryable>
+                ::decode_optional(#decoder, #sub_arg, #buf)?;
+            ::serde_json::from_str(json.as_ref())
+                .map_err(::gel_protocol::errors::decode_error)?
+        }}
+    } else {
+        quote! {
+            ::gel_protocol::queryable::Queryable
+            ::decode_optional(#decoder, #sub_arg, #buf)?
+        }
+    }
+}
+
+fn check_attrs(field: &syn::Field, attrs: &FieldAttrs) -> syn::Result<()> {
+    let other = attrs.json || attrs.rename.is_some() || attrs.default;
+    if attrs.flatten && (other || attrs.skip) {
+        return Err(syn::Error::new_spanned(
+            field,
+            "`flatten` can't be combined with other attributes",
+        ));
+    }
+    if attrs.skip && other {
+        return Err(syn::Error::new_spanned(
+            field,
+            "`skip` can't be combined with other attributes",
+        ));
+    }
+    Ok(())
+}
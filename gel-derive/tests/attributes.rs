@@ -0,0 +1,136 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use gel_derive::Queryable;
+use gel_protocol::codec::STD_STR;
+use gel_protocol::common::{Cardinality, RawTypedesc};
+use gel_protocol::features::ProtocolVersion;
+use gel_protocol::model::Uuid;
+use gel_protocol::queryable::{Decoder, DescriptorMismatch, Queryable};
+
+#[derive(Queryable, Debug, PartialEq)]
+struct Renamed {
+    name: String,
+    #[gel(rename = "type")]
+    kind: String,
+}
+
+#[derive(Queryable, Debug, PartialEq)]
+struct WithDefault {
+    name: String,
+    #[gel(default)]
+    nick: Option<String>,
+    #[gel(skip)]
+    visits: u32,
+}
+
+#[derive(Queryable, Debug, PartialEq)]
+struct Name {
+    first_name: String,
+    last_name: String,
+}
+
+#[derive(Queryable, Debug, PartialEq)]
+struct User {
+    #[gel(flatten)]
+    name: Name,
+    email: String,
+}
+
+/// Output descriptor of a shape having `str` elements named `elements`
+fn shape(elements: &[&str]) -> RawTypedesc {
+    let mut buf = BytesMut::new();
+    buf.put_u8(0x02); // base scalar
+    buf.put_slice(STD_STR.as_bytes());
+    buf.put_u8(0x01); // object shape
+    buf.put_u128(1);
+    buf.put_u16(elements.len() as u16);
+    for name in elements {
+        buf.put_u32(0); // flags
+        buf.put_u8(Cardinality::One as u8);
+        buf.put_u32(name.len() as u32);
+        buf.put_slice(name.as_bytes());
+        buf.put_u16(0); // type_pos
+    }
+    RawTypedesc {
+        proto: ProtocolVersion::new(1, 0),
+        id: Uuid::from_u128(1),
+        data: buf.freeze(),
+    }
+}
+
+/// Object with `str` elements having `values`
+fn object(values: &[&str]) -> Bytes {
+    let mut buf = BytesMut::new();
+    buf.put_u32(values.len() as u32);
+    for value in values {
+        buf.put_u32(0); // reserved
+        buf.put_u32(value.len() as u32);
+        buf.put_slice(value.as_bytes());
+    }
+    buf.freeze()
+}
+
+fn decode<T: Queryable>(elements: &[&str], values: &[&str]) -> Result<T, DescriptorMismatch> {
+    let desc = shape(elements).decode().unwrap();
+    let args = T::check_descriptor(&desc.as_queryable_context(), desc.root_pos().unwrap())?;
+    Ok(T::decode(&Decoder::default(), &args, &object(values)).unwrap())
+}
+
+#[test]
+fn rename() {
+    assert_eq!(
+        decode::<Renamed>(&["type", "name"], &["admin", "John"]).unwrap(),
+        Renamed {
+            name: "John".into(),
+            kind: "admin".into(),
+        }
+    );
+    decode::<Renamed>(&["kind", "name"], &["admin", "John"]).unwrap_err();
+}
+
+#[test]
+fn default_and_skip() {
+    assert_eq!(
+        decode::<WithDefault>(&["name"], &["John"]).unwrap(),
+        WithDefault {
+            name: "John".into(),
+            nick: None,
+            visits: 0,
+        }
+    );
+    assert_eq!(
+        decode::<WithDefault>(&["nick", "name"], &["jd", "John"]).unwrap(),
+        WithDefault {
+            name: "John".into(),
+            nick: Some("jd".into()),
+            visits: 0,
+        }
+    );
+    // skipped fields are never matched to the shape
+    decode::<WithDefault>(&["name", "visits"], &["John", "1"]).unwrap_err();
+}
+
+#[test]
+fn flatten() {
+    assert_eq!(
+        decode::<User>(
+            &["last_name", "email", "first_name"],
+            &["Doe", "john@example.com", "John"],
+        )
+        .unwrap(),
+        User {
+            name: Name {
+                first_name: "John".into(),
+                last_name: "Doe".into(),
+            },
+            email: "john@example.com".into(),
+        }
+    );
+    decode::<User>(&["first_name", "email"], &["John", "john@example.com"]).unwrap_err();
+}
+
+#[test]
+fn unknown_element() {
+    let err =
+        decode::<Name>(&["first_name", "last_name", "age"], &["John", "Doe", "33"]).unwrap_err();
+    assert_eq!(err.to_string(), "expected 2 fields, got 3");
+}
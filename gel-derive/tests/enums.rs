@@ -1,4 +1,9 @@
+use bytes::{BufMut, Bytes, BytesMut};
 use gel_derive::Queryable;
+use gel_protocol::codec::STD_STR;
+use gel_protocol::common::{Cardinality, RawTypedesc};
+use gel_protocol::features::ProtocolVersion;
+use gel_protocol::model::Uuid;
 use gel_protocol::queryable::{Decoder, Queryable};
 
 #[derive(Queryable, Debug, PartialEq)]
@@ -25,3 +30,91 @@ fn enumeration() {
     );
     Status::decode(&dec, &(), &b"closed"[..]).unwrap_err();
 }
+
+#[derive(Queryable, Debug, PartialEq)]
+struct Show {
+    title: String,
+    seasons: String,
+}
+
+#[derive(Queryable, Debug, PartialEq)]
+enum Content {
+    #[gel(tname = "default::Movie")]
+    Movie {
+        title: String,
+        director: String,
+    },
+    Show(Show),
+    #[gel(tname = "default::Trailer")]
+    Trailer,
+}
+
+/// Shape of `SELECT Content { __tname__, title, [is Movie].director, [is Show].seasons }`
+fn content() -> RawTypedesc {
+    let elements = ["__tname__", "title", "director", "seasons"];
+    let mut buf = BytesMut::new();
+    buf.put_u8(0x02); // base scalar
+    buf.put_slice(STD_STR.as_bytes());
+    buf.put_u8(0x01); // object shape
+    buf.put_u128(1);
+    buf.put_u16(elements.len() as u16);
+    for name in elements {
+        buf.put_u32(0); // flags
+        buf.put_u8(Cardinality::AtMostOne as u8);
+        buf.put_u32(name.len() as u32);
+        buf.put_slice(name.as_bytes());
+        buf.put_u16(0); // type_pos
+    }
+    RawTypedesc {
+        proto: ProtocolVersion::new(1, 0),
+        id: Uuid::from_u128(1),
+        data: buf.freeze(),
+    }
+}
+
+fn object(values: &[Option<&str>]) -> Bytes {
+    let mut buf = BytesMut::new();
+    buf.put_u32(values.len() as u32);
+    for value in values {
+        buf.put_u32(0); // reserved
+        match value {
+            Some(value) => {
+                buf.put_u32(value.len() as u32);
+                buf.put_slice(value.as_bytes());
+            }
+            None => buf.put_i32(-1),
+        }
+    }
+    buf.freeze()
+}
+
+#[test]
+fn polymorphic() {
+    let desc = content().decode().unwrap();
+    let ctx = desc.as_queryable_context();
+    let args = Content::check_descriptor(&ctx, desc.root_pos().unwrap()).unwrap();
+    let dec = Decoder::default();
+    let movie = object(&[Some("default::Movie"), Some("Up"), Some("Pete"), None]);
+    assert_eq!(
+        Content::decode(&dec, &args, &movie).unwrap(),
+        Content::Movie {
+            title: "Up".into(),
+            director: "Pete".into(),
+        }
+    );
+    let show = object(&[Some("default::Show"), Some("Lost"), None, Some("6")]);
+    assert_eq!(
+        Content::decode(&dec, &args, &show).unwrap(),
+        Content::Show(Show {
+            title: "Lost".into(),
+            seasons: "6".into(),
+        })
+    );
+    let trailer = object(&[Some("default::Trailer"), Some("Up"), None, None]);
+    assert_eq!(
+        Content::decode(&dec, &args, &trailer).unwrap(),
+        Content::Trailer
+    );
+    let other = object(&[Some("default::Game"), Some("Doom"), None, None]);
+    Content::decode(&dec, &args, &other).unwrap_err();
+}
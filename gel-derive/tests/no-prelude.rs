@@ -7,3 +7,22 @@
 struct Test {
     field: i64,
 }
+
+#[derive(::gel_derive::Queryable)]
+#[allow(dead_code)]
+struct Pair(i64, ::std::string::String);
+
+#[derive(::gel_derive::Queryable)]
+#[allow(dead_code)]
+struct Id(i64);
+
+#[derive(::gel_derive::Queryable)]
+#[allow(dead_code)]
+enum Content {
+    Movie {
+        #[gel(default)]
+        field: i64,
+    },
+    Other(Test),
+    Unknown,
+}
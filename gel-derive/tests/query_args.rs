@@ -0,0 +1,94 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use gel_derive::QueryArgs;
+use gel_errors::{Error, MissingArgumentError, UnknownArgumentError};
+use gel_protocol::codec::STD_STR;
+use gel_protocol::common::{Cardinality, RawTypedesc};
+use gel_protocol::features::ProtocolVersion;
+use gel_protocol::model::Uuid;
+use gel_protocol::query_arg::{Encoder, QueryArgs};
+
+#[derive(QueryArgs)]
+struct User {
+    first_name: String,
+    #[gel(rename = "age")]
+    user_age: Option<String>,
+}
+
+/// Input descriptor of a query having `str` parameters named `params`
+fn input(params: &[&str]) -> RawTypedesc {
+    let mut buf = BytesMut::new();
+    buf.put_u8(0x02); // base scalar
+    buf.put_slice(STD_STR.as_bytes());
+    buf.put_u8(0x01); // object shape
+    buf.put_u128(1);
+    buf.put_u16(params.len() as u16);
+    for name in params {
+        buf.put_u32(0); // flags
+        buf.put_u8(Cardinality::AtMostOne as u8);
+        buf.put_u32(name.len() as u32);
+        buf.put_slice(name.as_bytes());
+        buf.put_u16(0); // type_pos
+    }
+    RawTypedesc {
+        proto: ProtocolVersion::new(1, 0),
+        id: Uuid::from_u128(1),
+        data: buf.freeze(),
+    }
+}
+
+fn encode(args: &impl QueryArgs, params: &[&str]) -> Result<Bytes, Error> {
+    let desc = input(params).decode().unwrap();
+    let mut buf = BytesMut::new();
+    args.encode(&mut Encoder::new(&desc.as_query_arg_context(), &mut buf))?;
+    Ok(buf.freeze())
+}
+
+#[test]
+fn encode_by_name() {
+    let user = User {
+        first_name: "John".into(),
+        user_age: Some("33".into()),
+    };
+    assert_eq!(
+        &encode(&user, &["age", "first_name"]).unwrap()[..],
+        b"\0\0\0\x02\
+          \0\0\0\0\0\0\0\x0233\
+          \0\0\0\0\0\0\0\x04John"
+    );
+}
+
+#[test]
+fn encode_none() {
+    let user = User {
+        first_name: "John".into(),
+        user_age: None,
+    };
+    assert_eq!(
+        &encode(&user, &["first_name", "age"]).unwrap()[..],
+        b"\0\0\0\x02\
+          \0\0\0\0\0\0\0\x04John\
+          \0\0\0\0\xFF\xFF\xFF\xFF"
+    );
+}
+
+#[test]
+fn missing_argument() {
+    let user = User {
+        first_name: "John".into(),
+        user_age: None,
+    };
+    let err = encode(&user, &["first_name", "age", "email"]).unwrap_err();
+    assert!(err.is::<MissingArgumentError>());
+    assert_eq!(err.initial_message(), Some("missing argument $email"));
+}
+
+#[test]
+fn unknown_argument() {
+    let user = User {
+        first_name: "John".into(),
+        user_age: None,
+    };
+    let err = encode(&user, &["first_name"]).unwrap_err();
+    assert!(err.is::<UnknownArgumentError>());
+    assert_eq!(err.initial_message(), Some("unexpected argument $age"));
+}
@@ -0,0 +1,77 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use gel_derive::Queryable;
+use gel_protocol::codec::STD_STR;
+use gel_protocol::common::RawTypedesc;
+use gel_protocol::features::ProtocolVersion;
+use gel_protocol::model::Uuid;
+use gel_protocol::queryable::{Decoder, Queryable};
+
+#[derive(Queryable, Debug, PartialEq)]
+struct UserId(String);
+
+#[derive(Queryable, Debug, PartialEq)]
+struct Pair(String, UserId);
+
+/// Descriptor of a tuple having two `str` elements, named if `names` are set
+fn tuple(names: Option<[&str; 2]>) -> RawTypedesc {
+    let mut buf = BytesMut::new();
+    buf.put_u8(0x02); // base scalar
+    buf.put_slice(STD_STR.as_bytes());
+    match names {
+        Some(names) => {
+            buf.put_u8(0x05); // named tuple
+            buf.put_u128(1);
+            buf.put_u16(2);
+            for name in names {
+                buf.put_u32(name.len() as u32);
+                buf.put_slice(name.as_bytes());
+                buf.put_u16(0); // type_pos
+            }
+        }
+        None => {
+            buf.put_u8(0x04); // tuple
+            buf.put_u128(1);
+            buf.put_u16(2);
+            buf.put_u16(0);
+            buf.put_u16(0);
+        }
+    }
+    RawTypedesc {
+        proto: ProtocolVersion::new(1, 0),
+        id: Uuid::from_u128(1),
+        data: buf.freeze(),
+    }
+}
+
+fn data(values: &[&str]) -> Bytes {
+    let mut buf = BytesMut::new();
+    buf.put_u32(values.len() as u32);
+    for value in values {
+        buf.put_u32(0); // reserved
+        buf.put_u32(value.len() as u32);
+        buf.put_slice(value.as_bytes());
+    }
+    buf.freeze()
+}
+
+#[test]
+fn newtype() {
+    let user = UserId::decode(&Decoder::default(), &(), b"user1").unwrap();
+    assert_eq!(user, UserId("user1".into()));
+}
+
+#[test]
+fn tuple_struct() {
+    for desc in [tuple(None), tuple(Some(["name", "id"]))] {
+        let desc = desc.decode().unwrap();
+        let ctx = desc.as_queryable_context();
+        let args = Pair::check_descriptor(&ctx, desc.root_pos().unwrap()).unwrap();
+        let pair = Pair::decode(&Decoder::default(), &args, &data(&["John", "user1"])).unwrap();
+        assert_eq!(pair, Pair("John".into(), UserId("user1".into())));
+    }
+}
+
+#[test]
+fn tuple_size() {
+    Pair::decode(&Decoder::default(), &((), ()), &data(&["John"])).unwrap_err();
+}
@@ -6,3 +6,11 @@ impl Field for QueryText {
     const NAME: &'static str = "source_code";
     type Value = String;
 }
+
+/// Position of the failed statement within a batch
+pub struct BatchIndex;
+
+impl Field for BatchIndex {
+    const NAME: &'static str = "batch_index";
+    type Value = usize;
+}
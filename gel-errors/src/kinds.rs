@@ -171,10 +171,13 @@ define_errors![
     (struct PasswordRequired, 0x0701FF00u32, 0x00000000),
     (struct ClientInconsistentError, 0xFFFF0000u32, 0x00000000),
     (struct ClientEncodingError, 0xFFFE0000u32, 0x00000000),
+    (struct ClientQueryTimeoutError, 0xFFFD0000u32, 0x00000000),
+    (struct ClientClosedError, 0xFFFC0000u32, 0x00000000),
     (struct ClientNoCredentialsError, 0xFF0101FFu32, 0x00000000),
     (struct NoCloudConfigFound, 0xFF0101FEu32, 0x00000000),
     (struct ClientConnectionEosError, 0xFF01FF00u32, 0x00000000),
     (struct NoResultExpected, 0xFF02FF00u32, 0x00000000),
     (struct DescriptorMismatch, 0xFF02FE00u32, 0x00000000),
+    (struct SessionStateLostError, 0xFF02FD00u32, 0x00000000),
     (struct UserError, 0xFE000000u32, 0x00000000),
 ];
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use bytes::{Bytes, BytesMut};
+use gel_auth::handshake::{ServerAuth, ServerAuthDrive, ServerAuthResponse};
+use gel_auth::{AuthType, CredentialData};
+use gel_errors::{AuthenticationError, ErrorKind, ProtocolError};
+use gel_protocol::client_message::{ClientMessage, Execute1, Parse};
+use gel_protocol::common::Capabilities;
+use gel_protocol::encoding::{Input, Output};
+use gel_protocol::features::ProtocolVersion;
+use gel_protocol::server_message::{Authentication, CommandComplete1};
+use gel_protocol::server_message::{Data, ErrorResponse, ErrorSeverity, ParameterStatus};
+use gel_protocol::server_message::{ReadyForCommand, ServerHandshake, ServerMessage};
+use gel_protocol::server_message::{StateDataDescription, TransactionState};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::watch;
+
+use crate::descriptors;
+use crate::reply::ReplyKind;
+use crate::{MockServer, Request, Shared};
+
+type BoxError = Box<dyn Error + Send + Sync>;
+
+struct Session<S> {
+    stream: S,
+    shared: Arc<Shared>,
+    proto: ProtocolVersion,
+    out_buf: BytesMut,
+    transaction_state: TransactionState,
+    /// An error has been sent, messages are skipped until the next `Sync`
+    skip_to_sync: bool,
+}
+
+pub(crate) async fn serve<S>(stream: S, shared: Arc<Shared>, mut shutdown: watch::Receiver<u64>)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    shutdown.mark_unchanged();
+    let session = Session {
+        stream,
+        shared,
+        proto: ProtocolVersion::current(),
+        out_buf: BytesMut::with_capacity(8192),
+        transaction_state: TransactionState::NotInTransaction,
+        skip_to_sync: false,
+    };
+    tokio::select! {
+        // Protocol errors and disconnects by the client just close the
+        // connection, as the client is the one under test
+        _ = session.run() => {}
+        _ = shutdown.changed() => {}
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Session<S> {
+    async fn run(mut self) -> Result<(), BoxError> {
+        self.handshake().await?;
+        loop {
+            match self.message().await? {
+                ClientMessage::Sync => {
+                    self.skip_to_sync = false;
+                    self.send(ServerMessage::ReadyForCommand(ReadyForCommand {
+                        headers: HashMap::new(),
+                        transaction_state: self.transaction_state,
+                    }))?;
+                    self.flush().await?;
+                }
+                ClientMessage::Flush => self.flush().await?,
+                ClientMessage::Terminate => {
+                    self.shared.terminations.fetch_add(1, Ordering::SeqCst);
+                    return Ok(());
+                }
+                _ if self.skip_to_sync => {}
+                ClientMessage::Parse(parse) => self.parse(parse)?,
+                ClientMessage::Execute1(execute) => {
+                    if !self.execute(execute).await? {
+                        return Ok(());
+                    }
+                }
+                msg => {
+                    let message = format!("unsupported message {msg:?}");
+                    self.error(ProtocolError::build().code(), message)?;
+                }
+            }
+        }
+    }
+
+    async fn handshake(&mut self) -> Result<(), BoxError> {
+        let ClientMessage::ClientHandshake(handshake) = self.message().await? else {
+            return Err("expected ClientHandshake".into());
+        };
+        let (major_ver, minor_ver) = self.proto.version_tuple();
+        if (handshake.major_ver, handshake.minor_ver) != (major_ver, minor_ver) {
+            self.send(ServerMessage::ServerHandshake(ServerHandshake {
+                major_ver,
+                minor_ver,
+                extensions: HashMap::new(),
+            }))?;
+        }
+
+        let user = handshake.params.get("user").cloned().unwrap_or_default();
+        let credentials = if user == MockServer::USER {
+            CredentialData::Plain(MockServer::PASSWORD.into())
+        } else {
+            CredentialData::Deny
+        };
+        let mut auth = ServerAuth::new(user, AuthType::ScramSha256, credentials);
+        let mut response = auth.drive(ServerAuthDrive::Initial);
+        loop {
+            let data = match response {
+                ServerAuthResponse::Initial(..) => {
+                    self.send(ServerMessage::Authentication(Authentication::Sasl {
+                        methods: vec!["SCRAM-SHA-256".into()],
+                    }))?;
+                    self.flush().await?;
+                    match self.message().await? {
+                        ClientMessage::AuthenticationSaslInitialResponse(msg) => msg.data,
+                        _ => return Err("expected SASL initial response".into()),
+                    }
+                }
+                ServerAuthResponse::Continue(data) => {
+                    self.send(ServerMessage::Authentication(
+                        Authentication::SaslContinue { data: data.into() },
+                    ))?;
+                    self.flush().await?;
+                    match self.message().await? {
+                        ClientMessage::AuthenticationSaslResponse(msg) => msg.data,
+                        _ => return Err("expected SASL response".into()),
+                    }
+                }
+                ServerAuthResponse::Complete(data) => {
+                    self.send(ServerMessage::Authentication(Authentication::SaslFinal {
+                        data: data.into(),
+                    }))?;
+                    self.send(ServerMessage::Authentication(Authentication::Ok))?;
+                    break;
+                }
+                ServerAuthResponse::Error(e) => {
+                    self.send(error_response(
+                        AuthenticationError::build().code(),
+                        format!("authentication failed: {e}"),
+                    ))?;
+                    self.flush().await?;
+                    return Err(e.into());
+                }
+            };
+            response = auth.drive(ServerAuthDrive::Message(AuthType::ScramSha256, &data));
+        }
+
+        let concurrency = self.shared.pool_concurrency.load(Ordering::SeqCst);
+        self.send(ServerMessage::ParameterStatus(ParameterStatus {
+            proto: self.proto.clone(),
+            name: Bytes::from_static(b"suggested_pool_concurrency"),
+            value: concurrency.to_string().into(),
+        }))?;
+        self.send(ServerMessage::StateDataDescription(StateDataDescription {
+            typedesc: descriptors::state(),
+        }))?;
+        self.send(ServerMessage::ReadyForCommand(ReadyForCommand {
+            headers: HashMap::new(),
+            transaction_state: self.transaction_state,
+        }))?;
+        self.flush().await
+    }
+
+    fn parse(&mut self, parse: Parse) -> Result<(), BoxError> {
+        let reply = self.shared.peek(&parse.command_text);
+        self.send(ServerMessage::CommandDataDescription1(reply.description()))
+    }
+
+    /// Returns `false` if the connection must be closed
+    async fn execute(&mut self, execute: Execute1) -> Result<bool, BoxError> {
+        let query = execute.command_text;
+        let module = match descriptors::decode_module(&execute.state) {
+            Ok(module) => module,
+            Err(message) => {
+                self.error(ProtocolError::build().code(), message)?;
+                return Ok(true);
+            }
+        };
+        let reply = self.shared.execute(Request {
+            query: query.clone(),
+            arguments: execute.arguments,
+            module,
+        });
+        if let Some(delay) = reply.delay {
+            self.flush().await?;
+            tokio::time::sleep(delay).await;
+        }
+        let state = reply.module.as_deref().map(descriptors::encode_state);
+        let description = reply.description();
+        let rows = match &reply.kind {
+            ReplyKind::Ok => Vec::new(),
+            ReplyKind::Rows { ty, rows } => match crate::Reply::encode_rows(ty, rows) {
+                Ok(rows) => rows,
+                Err(message) => {
+                    self.error(ProtocolError::build().code(), message)?;
+                    return Ok(true);
+                }
+            },
+            ReplyKind::Error { code, message } => {
+                self.error(*code, message.clone())?;
+                return Ok(true);
+            }
+            ReplyKind::Disconnect => return Ok(false),
+        };
+        if execute.output_typedesc_id != description.output.id {
+            self.send(ServerMessage::CommandDataDescription1(description))?;
+        }
+        for row in rows {
+            self.send(ServerMessage::Data(Data { data: vec![row] }))?;
+        }
+        self.send(ServerMessage::CommandComplete1(CommandComplete1 {
+            annotations: HashMap::new(),
+            capabilities: Capabilities::empty(),
+            status_data: status(&query).into(),
+            state,
+        }))?;
+        self.transaction_state = match self.transaction_state {
+            _ if query.starts_with("START TRANSACTION") => TransactionState::InTransaction,
+            _ if query.starts_with("ROLLBACK TO SAVEPOINT") => TransactionState::InTransaction,
+            _ if query == "COMMIT" || query == "ROLLBACK" => TransactionState::NotInTransaction,
+            state => state,
+        };
+        Ok(true)
+    }
+
+    fn error(&mut self, code: u32, message: String) -> Result<(), BoxError> {
+        self.skip_to_sync = true;
+        if self.transaction_state == TransactionState::InTransaction {
+            self.transaction_state = TransactionState::InFailedTransaction;
+        }
+        self.send(error_response(code, message))
+    }
+
+    fn send(&mut self, msg: ServerMessage) -> Result<(), BoxError> {
+        msg.encode(&mut Output::new(&self.proto, &mut self.out_buf))?;
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), BoxError> {
+        self.stream.write_all_buf(&mut self.out_buf).await?;
+        self.stream.flush().await?;
+        Ok(())
+    }
+
+    async fn message(&mut self) -> Result<ClientMessage, BoxError> {
+        let mut header = [0u8; 5];
+        self.stream.read_exact(&mut header).await?;
+        let len = u32::from_be_bytes(header[1..5].try_into().unwrap()) as usize;
+        if len < 4 {
+            return Err("invalid message length".into());
+        }
+        let mut frame = BytesMut::zeroed(len + 1);
+        frame[..5].copy_from_slice(&header);
+        self.stream.read_exact(&mut frame[5..]).await?;
+        let msg = ClientMessage::decode(&mut Input::new(self.proto.clone(), frame.freeze()))?;
+        Ok(msg)
+    }
+}
+
+fn error_response(code: u32, message: String) -> ServerMessage {
+    ServerMessage::ErrorResponse(ErrorResponse {
+        severity: ErrorSeverity::Error,
+        code,
+        message,
+        attributes: HashMap::new(),
+    })
+}
+
+/// Status reported on completion of the query, e.g. `SELECT`
+fn status(query: &str) -> String {
+    match query.split_whitespace().next() {
+        Some("START") => "START TRANSACTION".into(),
+        Some(word) => word.to_uppercase(),
+        None => "OK".into(),
+    }
+}
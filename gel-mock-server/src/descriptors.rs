@@ -0,0 +1,185 @@
+//! Encoders for the (protocol 2.0+) type descriptors sent by the server
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use gel_protocol::codec::STD_STR;
+use gel_protocol::common::{Cardinality, RawTypedesc, State};
+use gel_protocol::features::ProtocolVersion;
+use gel_protocol::model::Uuid;
+
+const STATE_ID: Uuid = Uuid::from_u128(0x4d6f636b_5374_6174_6500_000000000001);
+const ALIAS_TUPLE_ID: Uuid = Uuid::from_u128(0x4d6f636b_5374_6174_6500_000000000002);
+const ALIASES_ID: Uuid = Uuid::from_u128(0x4d6f636b_5374_6174_6500_000000000003);
+const CONFIG_ID: Uuid = Uuid::from_u128(0x4d6f636b_5374_6174_6500_000000000004);
+const GLOBALS_ID: Uuid = Uuid::from_u128(0x4d6f636b_5374_6174_6500_000000000005);
+
+fn put_str(buf: &mut BytesMut, value: &str) {
+    buf.put_u32(value.len() as u32);
+    buf.put_slice(value.as_bytes());
+}
+
+fn put_descriptor(buf: &mut BytesMut, tag: u8, id: Uuid, body: impl FnOnce(&mut BytesMut)) {
+    let mut desc = BytesMut::new();
+    desc.put_u8(tag);
+    desc.put_slice(id.as_bytes());
+    body(&mut desc);
+    buf.put_u32(desc.len() as u32);
+    buf.put_slice(&desc);
+}
+
+fn put_scalar(buf: &mut BytesMut, id: Uuid, name: &str) {
+    put_descriptor(buf, 0x03, id, |buf| {
+        put_str(buf, name);
+        buf.put_u8(1); // schema_defined
+        buf.put_u16(0); // ancestors
+    });
+}
+
+fn put_input_shape(buf: &mut BytesMut, id: Uuid, elements: &[(&str, u16)]) {
+    put_descriptor(buf, 0x08, id, |buf| {
+        buf.put_u16(elements.len() as u16);
+        for (name, type_pos) in elements {
+            buf.put_u32(0); // flags
+            buf.put_u8(Cardinality::AtMostOne as u8);
+            put_str(buf, name);
+            buf.put_u16(*type_pos);
+        }
+    });
+}
+
+/// Descriptor of a single base scalar type
+pub(crate) fn scalar(id: Uuid, name: &str) -> RawTypedesc {
+    let mut buf = BytesMut::new();
+    put_scalar(&mut buf, id, name);
+    RawTypedesc {
+        proto: ProtocolVersion::current(),
+        id,
+        data: buf.freeze(),
+    }
+}
+
+/// Descriptor of a query without arguments or without a result
+pub(crate) fn empty() -> RawTypedesc {
+    RawTypedesc {
+        proto: ProtocolVersion::current(),
+        id: Uuid::from_u128(0),
+        data: Bytes::new(),
+    }
+}
+
+/// State descriptor of a schema having no config settings and no globals
+///
+/// Only the current module and module aliases can be set by clients.
+pub(crate) fn state() -> RawTypedesc {
+    let mut buf = BytesMut::new();
+    put_scalar(&mut buf, STD_STR, "std::str");
+    put_descriptor(&mut buf, 0x04, ALIAS_TUPLE_ID, |buf| {
+        put_str(buf, "tuple<std::str, std::str>");
+        buf.put_u8(0); // schema_defined
+        buf.put_u16(0); // ancestors
+        buf.put_u16(2);
+        buf.put_u16(0);
+        buf.put_u16(0);
+    });
+    put_descriptor(&mut buf, 0x06, ALIASES_ID, |buf| {
+        put_str(buf, "array<tuple<std::str, std::str>>");
+        buf.put_u8(0); // schema_defined
+        buf.put_u16(0); // ancestors
+        buf.put_u16(1); // element type
+        buf.put_u16(1); // dimensions
+        buf.put_i32(-1);
+    });
+    put_input_shape(&mut buf, CONFIG_ID, &[]);
+    put_input_shape(&mut buf, GLOBALS_ID, &[]);
+    put_input_shape(
+        &mut buf,
+        STATE_ID,
+        &[("module", 0), ("aliases", 2), ("config", 3), ("globals", 4)],
+    );
+    RawTypedesc {
+        proto: ProtocolVersion::current(),
+        id: STATE_ID,
+        data: buf.freeze(),
+    }
+}
+
+/// Session state with `module` as the current module, encoded using the
+/// [`state`] descriptor
+///
+/// Other elements of the state are left unset.
+pub(crate) fn encode_state(module: &str) -> State {
+    let mut buf = BytesMut::new();
+    buf.put_u32(1); // number of elements
+    buf.put_u32(0); // index of `module`
+    put_str(&mut buf, module);
+    State {
+        typedesc_id: STATE_ID,
+        data: buf.freeze(),
+    }
+}
+
+/// Current module in the session state sent by the client
+///
+/// Returns `None` if the module is not set in the state.
+pub(crate) fn decode_module(state: &State) -> Result<Option<String>, String> {
+    if state.typedesc_id == Uuid::from_u128(0) {
+        return Ok(None);
+    }
+    if state.typedesc_id != STATE_ID {
+        return Err("state doesn't match state descriptor".into());
+    }
+    let underflow = || "unexpected end of state data".to_string();
+    let mut buf = &state.data[..];
+    if buf.remaining() < 4 {
+        return Err(underflow());
+    }
+    let count = buf.get_u32();
+    for _ in 0..count {
+        if buf.remaining() < 8 {
+            return Err(underflow());
+        }
+        let index = buf.get_u32();
+        let Ok(len) = usize::try_from(buf.get_i32()) else {
+            continue; // explicit empty set
+        };
+        if buf.remaining() < len {
+            return Err(underflow());
+        }
+        let (data, rest) = buf.split_at(len);
+        if index == 0 {
+            let module = std::str::from_utf8(data).map_err(|e| e.to_string())?;
+            return Ok(Some(module.into()));
+        }
+        buf = rest;
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use gel_protocol::codec::STD_INT64;
+    use gel_protocol::descriptors::Descriptor;
+
+    #[test]
+    fn state() {
+        let desc = super::state().decode().unwrap();
+        assert_eq!(desc.descriptors().len(), 6);
+        assert!(matches!(
+            desc.root(),
+            Some(Descriptor::InputShape(shape)) if shape.elements.len() == 4
+        ));
+    }
+
+    #[test]
+    fn state_roundtrip() {
+        let state = super::encode_state("foo");
+        assert_eq!(
+            super::decode_module(&state).unwrap().as_deref(),
+            Some("foo")
+        );
+    }
+
+    #[test]
+    fn scalar() {
+        let desc = super::scalar(STD_INT64, "std::int64").decode().unwrap();
+        assert!(desc.build_codec().is_ok());
+    }
+}
@@ -0,0 +1,238 @@
+/*!
+An in-process mock of the Gel server for testing clients without a database.
+
+[`MockServer`] listens on a local TCP port with TLS, authenticates clients
+using SCRAM-SHA-256 and answers queries with [replies](Reply) scripted by the
+test. This allows testing retries, transactions and connection pool
+behavior of `gel-tokio` hermetically.
+
+```rust,no_run
+# async fn test() -> std::io::Result<()> {
+use gel_errors::TransactionConflictError;
+use gel_mock_server::{MockServer, Reply, Type};
+use gel_protocol::value::Value;
+
+let server = MockServer::start().await?;
+// The first attempt fails with a retryable error, the next ones succeed
+server.on("SELECT 1", Reply::error::<TransactionConflictError>("conflict"));
+server.on("SELECT 1", Reply::rows(Type::INT64, [Value::Int64(1)]));
+// Connect a client to `server.address()` using `MockServer::USER`,
+// `MockServer::PASSWORD` and `MockServer::CA_CERTIFICATE`.
+# Ok(())
+# }
+```
+
+Queries are matched by their exact text. Replies registered for the same
+query are used in order, the last one is reused for all subsequent
+executions. Queries without scripted replies (including `START
+TRANSACTION`, `COMMIT` and `ROLLBACK` sent by the client itself) complete
+successfully without returning any data.
+
+The mock has no schema: queries can't have arguments (arguments sent by the
+client are recorded though, see [`MockServer::requests`]) and the session
+state can't contain config settings or globals. Replies can change the
+current module of the session though, see [`Reply::with_module`].
+*/
+
+mod connection;
+mod descriptors;
+mod reply;
+
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use futures::StreamExt;
+use gel_stream::{Acceptor, LocalAddress, ResolvedTarget, TlsAlpn, TlsClientCertVerify, TlsKey};
+use gel_stream::{TlsServerParameterProvider, TlsServerParameters};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+pub use reply::{Reply, Type};
+
+// The server uses the test certificates of `gel-stream`
+const SERVER_CERTIFICATE: &[u8] = include_bytes!("../../gel-stream/tests/certs/server.cert.pem");
+const SERVER_KEY: &[u8] = include_bytes!("../../gel-stream/tests/certs/server.key.pem");
+
+/// A query executed by a client
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Request {
+    /// Text of the query
+    pub query: String,
+    /// Encoded arguments of the query
+    pub arguments: Bytes,
+    /// Current module in the session state sent with the query
+    pub module: Option<String>,
+}
+
+/// Mock Gel server running in the background of the current tokio runtime
+///
+/// The server is stopped and all its connections are closed when the value
+/// is dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    address: SocketAddr,
+    shared: Arc<Shared>,
+    shutdown: watch::Sender<u64>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Shared {
+    replies: Mutex<HashMap<String, VecDeque<Reply>>>,
+    requests: Mutex<Vec<Request>>,
+    connections: AtomicUsize,
+    pub(crate) terminations: AtomicUsize,
+    pub(crate) pool_concurrency: AtomicUsize,
+}
+
+impl MockServer {
+    /// The only user accepted by the server
+    pub const USER: &'static str = "admin";
+    /// Password of the [`USER`](Self::USER)
+    pub const PASSWORD: &'static str = "mock-password";
+    /// Certificate of the CA that has signed the certificate of the server
+    ///
+    /// The server certificate is issued for `localhost`.
+    pub const CA_CERTIFICATE: &'static str =
+        include_str!("../../gel-stream/tests/certs/ca.cert.pem");
+
+    /// Start the server on a random port of the loopback interface
+    pub async fn start() -> io::Result<MockServer> {
+        let acceptor =
+            Acceptor::new_tcp_tls(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)), tls_provider()?);
+        let mut listener = acceptor.bind().await.map_err(io::Error::other)?;
+        let address = match listener.local_address()? {
+            ResolvedTarget::SocketAddr(address) => address,
+            target => {
+                return Err(io::Error::other(format!(
+                    "unexpected listening address {target:?}"
+                )))
+            }
+        };
+        let shared = Arc::new(Shared {
+            replies: Mutex::new(HashMap::new()),
+            requests: Mutex::new(Vec::new()),
+            connections: AtomicUsize::new(0),
+            terminations: AtomicUsize::new(0),
+            pool_concurrency: AtomicUsize::new(10),
+        });
+        let (shutdown, _) = watch::channel(0);
+        let task = tokio::spawn({
+            let shared = shared.clone();
+            let shutdown = shutdown.clone();
+            async move {
+                while let Some(conn) = listener.next().await {
+                    // Failed TLS handshakes only affect the client
+                    let Ok(conn) = conn else { continue };
+                    shared.connections.fetch_add(1, Ordering::SeqCst);
+                    tokio::spawn(connection::serve(
+                        conn,
+                        shared.clone(),
+                        shutdown.subscribe(),
+                    ));
+                }
+            }
+        });
+        Ok(MockServer {
+            address,
+            shared,
+            shutdown,
+            task,
+        })
+    }
+    /// Address the server is listening on
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+    /// Add a reply for the `query`
+    ///
+    /// See [crate-level documentation](crate) for how replies are matched.
+    pub fn on(&self, query: impl Into<String>, reply: Reply) -> &Self {
+        self.shared
+            .replies
+            .lock()
+            .unwrap()
+            .entry(query.into())
+            .or_default()
+            .push_back(reply);
+        self
+    }
+    /// Set the `suggested_pool_concurrency` reported to new connections
+    ///
+    /// Defaults to `10`.
+    pub fn set_pool_concurrency(&self, value: usize) {
+        self.shared.pool_concurrency.store(value, Ordering::SeqCst);
+    }
+    /// Queries executed so far, in order of execution
+    pub fn requests(&self) -> Vec<Request> {
+        self.shared.requests.lock().unwrap().clone()
+    }
+    /// Texts of the queries executed so far, in order of execution
+    pub fn queries(&self) -> Vec<String> {
+        self.requests().into_iter().map(|r| r.query).collect()
+    }
+    /// Number of connections accepted so far
+    pub fn connections(&self) -> usize {
+        self.shared.connections.load(Ordering::SeqCst)
+    }
+    /// Number of connections closed by the client with a `Terminate` message
+    pub fn terminations(&self) -> usize {
+        self.shared.terminations.load(Ordering::SeqCst)
+    }
+    /// Close all currently open connections
+    ///
+    /// Clients notice it only when they use the connection next time.
+    pub fn disconnect_all(&self) {
+        self.shutdown.send_modify(|generation| *generation += 1);
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+        self.disconnect_all();
+    }
+}
+
+impl Shared {
+    /// Returns the reply for the next execution of the `query`
+    pub(crate) fn peek(&self, query: &str) -> Reply {
+        self.replies
+            .lock()
+            .unwrap()
+            .get(query)
+            .and_then(|queue| queue.front().cloned())
+            .unwrap_or_else(Reply::ok)
+    }
+    /// Records execution of the query and returns its reply
+    pub(crate) fn execute(&self, request: Request) -> Reply {
+        let query = request.query.clone();
+        self.requests.lock().unwrap().push(request);
+        let mut replies = self.replies.lock().unwrap();
+        match replies.get_mut(&query) {
+            Some(queue) if queue.len() > 1 => queue.pop_front().unwrap(),
+            Some(queue) => queue.front().cloned().unwrap_or_else(Reply::ok),
+            None => Reply::ok(),
+        }
+    }
+}
+
+fn tls_provider() -> io::Result<TlsServerParameterProvider> {
+    let cert = rustls_pemfile::certs(&mut &SERVER_CERTIFICATE[..])
+        .next()
+        .ok_or_else(|| io::Error::other("no server certificate"))??;
+    let key = rustls_pemfile::private_key(&mut &SERVER_KEY[..])?
+        .ok_or_else(|| io::Error::other("no server key"))?;
+    Ok(TlsServerParameterProvider::new(TlsServerParameters {
+        server_certificate: TlsKey::new(key, cert),
+        client_cert_verify: TlsClientCertVerify::Ignore,
+        min_protocol_version: None,
+        max_protocol_version: None,
+        alpn: TlsAlpn::new_str(&["edgedb-binary", "gel-binary"]),
+    }))
+}
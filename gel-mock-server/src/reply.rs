@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bytes::{Bytes, BytesMut};
+use gel_errors::ErrorKind;
+use gel_protocol::codec;
+use gel_protocol::common::{Capabilities, Cardinality};
+use gel_protocol::model::Uuid;
+use gel_protocol::server_message::CommandDataDescription1;
+use gel_protocol::value::Value;
+
+use crate::descriptors;
+
+/// Type of the values returned by a scripted query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Type {
+    id: Uuid,
+    name: &'static str,
+}
+
+/// Scripted reply of the [`MockServer`](crate::MockServer) to a query
+///
+/// Replies are registered using [`MockServer::on`](crate::MockServer::on).
+#[derive(Debug, Clone)]
+pub struct Reply {
+    pub(crate) kind: ReplyKind,
+    pub(crate) delay: Option<Duration>,
+    pub(crate) module: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum ReplyKind {
+    Ok,
+    Rows { ty: Type, rows: Vec<Value> },
+    Error { code: u32, message: String },
+    Disconnect,
+}
+
+impl Type {
+    pub const UUID: Type = Type::scalar(codec::STD_UUID, "std::uuid");
+    pub const STR: Type = Type::scalar(codec::STD_STR, "std::str");
+    pub const BYTES: Type = Type::scalar(codec::STD_BYTES, "std::bytes");
+    pub const INT16: Type = Type::scalar(codec::STD_INT16, "std::int16");
+    pub const INT32: Type = Type::scalar(codec::STD_INT32, "std::int32");
+    pub const INT64: Type = Type::scalar(codec::STD_INT64, "std::int64");
+    pub const FLOAT32: Type = Type::scalar(codec::STD_FLOAT32, "std::float32");
+    pub const FLOAT64: Type = Type::scalar(codec::STD_FLOAT64, "std::float64");
+    pub const BOOL: Type = Type::scalar(codec::STD_BOOL, "std::bool");
+    pub const DATETIME: Type = Type::scalar(codec::STD_DATETIME, "std::datetime");
+    pub const DURATION: Type = Type::scalar(codec::STD_DURATION, "std::duration");
+    pub const JSON: Type = Type::scalar(codec::STD_JSON, "std::json");
+
+    const fn scalar(id: Uuid, name: &'static str) -> Type {
+        Type { id, name }
+    }
+}
+
+impl Reply {
+    /// Successful completion of a command that returns no data
+    pub fn ok() -> Reply {
+        ReplyKind::Ok.into()
+    }
+    /// Successful completion returning `rows` of type `ty`
+    pub fn rows(ty: Type, rows: impl IntoIterator<Item = Value>) -> Reply {
+        ReplyKind::Rows {
+            ty,
+            rows: rows.into_iter().collect(),
+        }
+        .into()
+    }
+    /// Error of kind `K` (e.g. [`TransactionConflictError`]) with `message`
+    ///
+    /// [`TransactionConflictError`]: gel_errors::TransactionConflictError
+    pub fn error<K: ErrorKind>(message: impl Into<String>) -> Reply {
+        ReplyKind::Error {
+            code: K::build().code(),
+            message: message.into(),
+        }
+        .into()
+    }
+    /// Close the connection without replying
+    pub fn disconnect() -> Reply {
+        ReplyKind::Disconnect.into()
+    }
+    /// Wait for `delay` before replying (or disconnecting)
+    pub fn after(mut self, delay: Duration) -> Reply {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Report `module` as the current module of the session on completion
+    ///
+    /// This is what the server does on `SET MODULE`. The rest of the
+    /// session state is reported as empty.
+    pub fn with_module(mut self, module: impl Into<String>) -> Reply {
+        self.module = Some(module.into());
+        self
+    }
+
+    pub(crate) fn description(&self) -> CommandDataDescription1 {
+        let (result_cardinality, output) = match &self.kind {
+            ReplyKind::Rows { ty, .. } => (Cardinality::Many, descriptors::scalar(ty.id, ty.name)),
+            _ => (Cardinality::NoResult, descriptors::empty()),
+        };
+        CommandDataDescription1 {
+            annotations: HashMap::new(),
+            capabilities: Capabilities::empty(),
+            result_cardinality,
+            input: descriptors::empty(),
+            output,
+        }
+    }
+
+    pub(crate) fn encode_rows(ty: &Type, rows: &[Value]) -> Result<Vec<Bytes>, String> {
+        let codec = descriptors::scalar(ty.id, ty.name)
+            .decode()
+            .map_err(|e| e.to_string())?
+            .build_codec()
+            .map_err(|e| e.to_string())?;
+        rows.iter()
+            .map(|row| {
+                let mut buf = BytesMut::new();
+                codec.encode(&mut buf, row).map_err(|e| e.to_string())?;
+                Ok(buf.freeze())
+            })
+            .collect()
+    }
+}
+
+impl From<ReplyKind> for Reply {
+    fn from(kind: ReplyKind) -> Reply {
+        Reply {
+            kind,
+            delay: None,
+            module: None,
+        }
+    }
+}
@@ -272,7 +272,7 @@ pub struct Range {
 
 #[derive(Debug)]
 pub struct MultiRange {
-    element: Arc<dyn Codec>,
+    element: Range,
 }
 
 #[derive(Debug)]
@@ -362,9 +362,9 @@ impl<'a> CodecBuilder<'a> {
                     element: self.build(d.type_pos)?,
                 })),
                 D::MultiRange(d) => Ok(Arc::new(MultiRange {
-                    element: Arc::new(Range {
+                    element: Range {
                         element: self.build(d.type_pos)?,
-                    }),
+                    },
                 })),
                 D::Enumeration(d) => Ok(Arc::new(Enum {
                     members: d.members.iter().map(|x| x[..].into()).collect(),
@@ -1444,7 +1444,19 @@ impl Codec for Vector {
 }
 
 impl Codec for Range {
-    fn decode(&self, mut buf: &[u8]) -> Result<Value, DecodeError> {
+    fn decode(&self, buf: &[u8]) -> Result<Value, DecodeError> {
+        self.decode_range(buf).map(Value::Range)
+    }
+    fn encode(&self, buf: &mut BytesMut, val: &Value) -> Result<(), EncodeError> {
+        match val {
+            Value::Range(rng) => self.encode_range(buf, rng),
+            _ => Err(errors::invalid_value(type_name::<Self>(), val)),
+        }
+    }
+}
+
+impl Range {
+    fn decode_range(&self, mut buf: &[u8]) -> Result<model::Range<Box<Value>>, DecodeError> {
         ensure!(buf.remaining() >= 1, errors::Underflow);
         let flags = buf.get_u8() as usize;
 
@@ -1467,20 +1479,20 @@ impl Codec for Range {
             None
         };
 
-        Ok(Value::Range(model::Range {
+        Ok(model::Range {
             lower,
             upper,
             inc_lower,
             inc_upper,
             empty,
-        }))
+        })
     }
-    fn encode(&self, buf: &mut BytesMut, val: &Value) -> Result<(), EncodeError> {
-        let rng = match val {
-            Value::Range(rng) => rng,
-            _ => Err(errors::invalid_value(type_name::<Self>(), val))?,
-        };
 
+    fn encode_range(
+        &self,
+        buf: &mut BytesMut,
+        rng: &model::Range<Box<Value>>,
+    ) -> Result<(), EncodeError> {
         let flags = if rng.empty {
             range::EMPTY
         } else {
@@ -1535,13 +1547,23 @@ impl Codec for Range {
 impl Codec for MultiRange {
     fn decode(&self, buf: &[u8]) -> Result<Value, DecodeError> {
         let elements = DecodeArrayLike::new_tuple_header(buf)?;
-        let items = decode_array_like(elements, &*self.element)?;
-        Ok(Value::Array(items))
+        let ranges = elements
+            .map(|item| self.element.decode_range(item?))
+            .collect::<Result<_, DecodeError>>()?;
+        Ok(Value::MultiRange(model::MultiRange { ranges }))
     }
 
     fn encode(&self, buf: &mut BytesMut, val: &Value) -> Result<(), EncodeError> {
+        // arrays of ranges are accepted for backwards compatibility
         let items = match val {
-            Value::Array(items) => items,
+            Value::MultiRange(rng) => rng.ranges.iter().collect::<Vec<_>>(),
+            Value::Array(items) => items
+                .iter()
+                .map(|item| match item {
+                    Value::Range(rng) => Ok(rng),
+                    _ => Err(errors::invalid_value(type_name::<Self>(), item)),
+                })
+                .collect::<Result<_, _>>()?,
             _ => Err(errors::invalid_value(type_name::<Self>(), val))?,
         };
         buf.reserve(4);
@@ -1550,7 +1572,7 @@ impl Codec for MultiRange {
             buf.reserve(4);
             let pos = buf.len();
             buf.put_u32(0); // replaced after serializing a value
-            self.element.encode(buf, item)?;
+            self.element.encode_range(buf, item)?;
             let len = buf.len() - pos - 4;
             buf[pos..pos + 4].copy_from_slice(
                 &u32::try_from(len)
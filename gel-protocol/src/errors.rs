@@ -93,6 +93,11 @@ pub enum DecodeError {
     InvalidJsonFormat { backtrace: Backtrace },
     #[snafu(display("enum value returned is not in type descriptor"))]
     ExtraEnumValue { backtrace: Backtrace },
+    #[snafu(display("object of type {type_name} doesn't match any enum variant"))]
+    ExtraTypeName {
+        backtrace: Backtrace,
+        type_name: String,
+    },
     #[snafu(display("too may descriptors ({})", index))]
     TooManyDescriptors { backtrace: Backtrace, index: usize },
     #[snafu(display("invalid index in input shape ({})", index))]
@@ -116,6 +121,16 @@ pub enum DecodeError {
     },
     #[snafu(display("invalid type operation value"))]
     InvalidTypeOperation { backtrace: Backtrace },
+    #[snafu(display("decimal is out of range: {reason}"))]
+    DecimalOutOfRange {
+        backtrace: Backtrace,
+        reason: &'static str,
+    },
+    #[snafu(display("invalid EWKB geometry: {reason}"))]
+    InvalidGeometry {
+        backtrace: Backtrace,
+        reason: &'static str,
+    },
 }
 
 #[derive(Snafu, Debug)]
@@ -53,6 +53,7 @@ pub enum Value {
     Array(Vec<Value>),
     Enum(EnumValue),
     Range(Range<Box<Value>>),
+    MultiRange(MultiRange<Box<Value>>),
 }
 ```
 */
@@ -8,12 +8,15 @@ mod vector;
 
 pub(crate) mod range;
 
+#[cfg(feature = "with-postgis")]
+pub mod postgis;
+
 pub use self::bignum::{BigInt, Decimal};
 pub use self::json::Json;
 pub use self::time::{DateDuration, RelativeDuration};
 pub use self::time::{Datetime, Duration, LocalDate, LocalDatetime, LocalTime};
 pub use memory::ConfigMemory;
-pub use range::Range;
+pub use range::{MultiRange, Range};
 pub use uuid::Uuid;
 pub use vector::Vector;
 pub(crate) use vector::VectorRef;
@@ -38,6 +41,22 @@ impl From<std::num::TryFromIntError> for OutOfRangeError {
     }
 }
 
+/// Error converting Gel `BigInt` or `Decimal` to/from `rust_decimal::Decimal`.
+#[cfg(feature = "rust_decimal")]
+#[derive(Debug, PartialEq)]
+pub struct DecimalOutOfRangeError {
+    pub(crate) reason: &'static str,
+}
+
+#[cfg(feature = "rust_decimal")]
+impl std::error::Error for DecimalOutOfRangeError {}
+#[cfg(feature = "rust_decimal")]
+impl fmt::Display for DecimalOutOfRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "decimal is out of range: {}", self.reason)
+    }
+}
+
 /// Error parsing string into Gel Duration type.
 #[derive(Debug, PartialEq)]
 pub struct ParseDurationError {
@@ -80,3 +99,39 @@ impl ParseDurationError {
         self
     }
 }
+
+/// Error parsing string into Gel date or time type.
+#[derive(Debug, PartialEq)]
+pub struct ParseDatetimeError {
+    pub(crate) message: String,
+    pub(crate) pos: usize,
+}
+
+impl std::error::Error for ParseDatetimeError {}
+impl fmt::Display for ParseDatetimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        format!(
+            "Error parsing input at position {}: {}",
+            self.pos, self.message,
+        )
+        .fmt(f)
+    }
+}
+
+/// Error parsing string into Gel `BigInt` or `Decimal` type.
+#[derive(Debug, PartialEq)]
+pub struct ParseNumberError {
+    pub(crate) message: String,
+    pub(crate) pos: usize,
+}
+
+impl std::error::Error for ParseNumberError {}
+impl fmt::Display for ParseNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        format!(
+            "Error parsing number at position {}: {}",
+            self.pos, self.message,
+        )
+        .fmt(f)
+    }
+}
@@ -5,6 +5,10 @@ mod num_bigint_interop;
 
 #[cfg(feature = "bigdecimal")]
 mod bigdecimal_interop;
+mod parse;
+
+#[cfg(feature = "rust_decimal")]
+mod rust_decimal_interop;
 
 /// Virtually unlimited precision integer.
 ///
@@ -50,6 +54,9 @@ impl BigInt {
             self.digits.remove(0);
             self.weight -= 1;
         }
+        if self.digits.is_empty() {
+            self.weight = 0;
+        }
         self
     }
 
@@ -171,7 +178,7 @@ impl Decimal {
         &self.digits
     }
 
-    #[allow(dead_code)] // isn't used when BigDecimal is disabled
+    #[allow(dead_code)] // isn't used when BigDecimal and rust_decimal are disabled
     fn normalize(mut self) -> Decimal {
         while let Some(0) = self.digits.last() {
             self.digits.pop();
@@ -180,6 +187,9 @@ impl Decimal {
             self.digits.remove(0);
             self.weight -= 1;
         }
+        if self.digits.is_empty() {
+            self.weight = 0;
+        }
         self
     }
 }
@@ -206,13 +216,13 @@ impl std::fmt::Display for Decimal {
             }
         }
 
-        // trailing zeros of the integer part
-        for _ in 0..(self.weight - index + 1) {
-            f.write_str("0000")?;
-        }
-
         if index == 0 {
             write!(f, "0")?;
+        } else {
+            // trailing zeros of the integer part
+            for _ in 0..(self.weight - index + 1) {
+                f.write_str("0000")?;
+            }
         }
 
         // dot
@@ -222,8 +232,9 @@ impl std::fmt::Display for Decimal {
         let mut decimals = u16::max(self.decimal_digits, 1);
         if index == 0 && self.weight < 0 {
             for _ in 0..(-1 - self.weight) {
-                f.write_str("0000")?;
-                decimals -= 4;
+                let consumed = u16::min(4, decimals);
+                f.write_str(&"0000"[..consumed as usize])?;
+                decimals -= consumed;
             }
         }
 
@@ -332,6 +343,15 @@ mod test {
         assert_eq!(BigInt::from(i64::MIN).digits, &[922, 3372, 368, 5477, 5808]);
     }
 
+    #[test]
+    fn big_int_zero() {
+        assert_eq!(BigInt::from(0u32).weight, 0);
+        assert!(BigInt::from(0u32).digits.is_empty());
+        assert_eq!(BigInt::from(0u64).weight, 0);
+        assert!(BigInt::from(0u64).digits.is_empty());
+        assert_eq!(BigInt::from(0u32), BigInt::from(0i64));
+    }
+
     #[test]
     fn bigint_display() {
         let cases = [0, 1, -1, 1_0000, -1_0000, 1_2345_6789, i64::MAX, i64::MIN];
@@ -417,5 +437,27 @@ mod test {
             .to_string(),
             "0.000000000000000000001"
         );
+
+        assert_eq!(
+            Decimal {
+                negative: false,
+                weight: 0,
+                decimal_digits: 0,
+                digits: vec![],
+            }
+            .to_string(),
+            "0.0"
+        );
+
+        assert_eq!(
+            Decimal {
+                negative: false,
+                weight: -2,
+                decimal_digits: 2,
+                digits: vec![],
+            }
+            .to_string(),
+            "0.00"
+        );
     }
 }
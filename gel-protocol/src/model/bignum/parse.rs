@@ -0,0 +1,141 @@
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use super::{BigInt, Decimal};
+use crate::model::ParseNumberError;
+
+struct Number<'a> {
+    negative: bool,
+    integer: &'a str,
+    fraction: &'a str,
+    exponent: i32,
+}
+
+fn error(message: &str, pos: usize) -> ParseNumberError {
+    ParseNumberError {
+        message: message.into(),
+        pos,
+    }
+}
+
+fn digits(s: &str, pos: usize) -> &str {
+    let len = s[pos..].bytes().take_while(u8::is_ascii_digit).count();
+    &s[pos..pos + len]
+}
+
+fn split(s: &str, allow_fraction: bool) -> Result<Number<'_>, ParseNumberError> {
+    let (negative, mut pos) = match s.as_bytes().first() {
+        Some(b'-') => (true, 1),
+        Some(b'+') => (false, 1),
+        _ => (false, 0),
+    };
+    let integer = digits(s, pos);
+    pos += integer.len();
+    let mut fraction = "";
+    let mut exponent = 0;
+    if allow_fraction {
+        if s[pos..].starts_with('.') {
+            fraction = digits(s, pos + 1);
+            pos += 1 + fraction.len();
+        }
+        if integer.is_empty() && fraction.is_empty() {
+            return Err(error("expected digits", pos));
+        }
+        if s[pos..].starts_with(['e', 'E']) {
+            pos += 1;
+            let exp_start = pos;
+            if s[pos..].starts_with(['+', '-']) {
+                pos += 1;
+            }
+            pos += digits(s, pos).len();
+            exponent = s[exp_start..pos]
+                .parse()
+                .map_err(|_| error("invalid exponent", exp_start))?;
+        }
+    } else if integer.is_empty() {
+        return Err(error("expected digits", pos));
+    }
+    if pos != s.len() {
+        return Err(error("unexpected character", pos));
+    }
+    Ok(Number {
+        negative,
+        integer,
+        fraction,
+        exponent,
+    })
+}
+
+/// Splits decimal digits into normalized base-10000 groups
+///
+/// `point` is the position of the decimal point in `digits`, which may be
+/// outside of the string. Returns the groups and the weight of the first
+/// group.
+fn to_groups(digits: &[u8], point: i64) -> Result<(Vec<u16>, i16), ParseNumberError> {
+    let pad = (4 - point.rem_euclid(4)) % 4;
+    let mut weight = (point + pad) / 4 - 1;
+    let mut groups = Vec::with_capacity(digits.len() / 4 + 2);
+    let mut group = 0;
+    let mut len = pad;
+    for &c in digits {
+        group = group * 10 + u16::from(c - b'0');
+        len += 1;
+        if len == 4 {
+            if group != 0 || !groups.is_empty() {
+                groups.push(group);
+            } else {
+                weight -= 1;
+            }
+            group = 0;
+            len = 0;
+        }
+    }
+    if len > 0 {
+        groups.push(group * 10_u16.pow(4 - len as u32));
+    }
+    while let Some(0) = groups.last() {
+        groups.pop();
+    }
+    if groups.is_empty() {
+        return Ok((groups, 0));
+    }
+    let weight = i16::try_from(weight).map_err(|_| error("value is out of range", 0))?;
+    Ok((groups, weight))
+}
+
+/// Parses integer in decimal notation, e.g. `-12345`
+impl FromStr for BigInt {
+    type Err = ParseNumberError;
+    fn from_str(s: &str) -> Result<BigInt, ParseNumberError> {
+        let number = split(s, false)?;
+        let (digits, weight) = to_groups(number.integer.as_bytes(), number.integer.len() as i64)?;
+        Ok(BigInt {
+            negative: number.negative && !digits.is_empty(),
+            weight,
+            digits,
+        })
+    }
+}
+
+/// Parses decimal number, e.g. `-12.3400` or `1.5e-10`
+///
+/// Trailing zeros after the decimal point are preserved as the display scale.
+impl FromStr for Decimal {
+    type Err = ParseNumberError;
+    fn from_str(s: &str) -> Result<Decimal, ParseNumberError> {
+        let number = split(s, true)?;
+        let digits = [number.integer.as_bytes(), number.fraction.as_bytes()].concat();
+        let exponent = i64::from(number.exponent);
+        let point = number.integer.len() as i64 + exponent;
+        let decimal_digits = number.fraction.len() as i64 - exponent;
+        let decimal_digits = u16::try_from(decimal_digits.max(0))
+            .map_err(|_| error("too many digits after the decimal point", 0))?;
+        let (digits, weight) = to_groups(&digits, point)?;
+        Ok(Decimal {
+            negative: number.negative && !digits.is_empty(),
+            weight,
+            decimal_digits,
+            digits,
+        })
+    }
+}
@@ -0,0 +1,298 @@
+use super::{BigInt, Decimal};
+use crate::model::DecimalOutOfRangeError;
+use std::convert::TryFrom;
+
+/// Maximum mantissa of `rust_decimal::Decimal`, which is 96 bits
+const MAX_MANTISSA: u128 = (1 << 96) - 1;
+/// Maximum scale of `rust_decimal::Decimal`
+const MAX_SCALE: u32 = 28;
+
+const TOO_BIG: &str = "value exceeds 96 bits of precision";
+
+/// Converts base-10000 digits into the integer they represent, starting at
+/// `weight` and ending at 10000^`last_weight`
+fn digits_to_u128(digits: &[u16], weight: i16, last_weight: i16) -> Option<u128> {
+    let mut result: u128 = 0;
+    for pos in (last_weight..=weight).rev() {
+        let digit = usize::try_from(weight - pos)
+            .ok()
+            .and_then(|i| digits.get(i))
+            .copied()
+            .unwrap_or(0);
+        result = result.checked_mul(10000)?.checked_add(digit.into())?;
+    }
+    Some(result)
+}
+
+impl Decimal {
+    /// Converts into `rust_decimal::Decimal` describing why that fails
+    ///
+    /// Keeps the display scale where it fits, dropping trailing zeros
+    /// otherwise.
+    pub(crate) fn to_rust_decimal(&self) -> Result<rust_decimal::Decimal, &'static str> {
+        if self.digits.is_empty() {
+            let scale = u32::from(self.decimal_digits).min(MAX_SCALE);
+            return Ok(rust_decimal::Decimal::new(0, scale));
+        }
+        let last_weight = self.weight - self.digits.len() as i16 + 1;
+        // fractional digits stored in the groups
+        let stored_scale = u32::try_from(-4 * i32::from(last_weight.min(0))).unwrap();
+        let mut mantissa =
+            digits_to_u128(&self.digits, self.weight, last_weight.min(0)).ok_or(TOO_BIG)?;
+        let mut scale = stored_scale;
+        let target_scale = u32::from(self.decimal_digits);
+        while scale > target_scale && mantissa % 10 == 0 {
+            mantissa /= 10;
+            scale -= 1;
+        }
+        if scale > target_scale {
+            // not possible for values received from the server
+            return Err("value has more digits than its scale");
+        }
+        while scale < target_scale.min(MAX_SCALE) {
+            match mantissa.checked_mul(10).filter(|&m| m <= MAX_MANTISSA) {
+                Some(m) => mantissa = m,
+                None => break,
+            }
+            scale += 1;
+        }
+        while scale > MAX_SCALE && mantissa % 10 == 0 {
+            mantissa /= 10;
+            scale -= 1;
+        }
+        if scale > MAX_SCALE {
+            return Err("value exceeds 28 digits of scale");
+        }
+        if mantissa > MAX_MANTISSA {
+            return Err(TOO_BIG);
+        }
+        let mantissa = i128::try_from(mantissa).unwrap();
+        let mantissa = if self.negative { -mantissa } else { mantissa };
+        Ok(rust_decimal::Decimal::from_i128_with_scale(mantissa, scale))
+    }
+}
+
+impl TryFrom<Decimal> for rust_decimal::Decimal {
+    type Error = DecimalOutOfRangeError;
+    fn try_from(v: Decimal) -> Result<rust_decimal::Decimal, Self::Error> {
+        rust_decimal::Decimal::try_from(&v)
+    }
+}
+
+impl TryFrom<&Decimal> for rust_decimal::Decimal {
+    type Error = DecimalOutOfRangeError;
+    fn try_from(v: &Decimal) -> Result<rust_decimal::Decimal, Self::Error> {
+        v.to_rust_decimal()
+            .map_err(|reason| DecimalOutOfRangeError { reason })
+    }
+}
+
+impl From<rust_decimal::Decimal> for Decimal {
+    fn from(v: rust_decimal::Decimal) -> Decimal {
+        (&v).into()
+    }
+}
+
+impl From<&rust_decimal::Decimal> for Decimal {
+    fn from(v: &rust_decimal::Decimal) -> Decimal {
+        let scale = v.scale();
+        // align decimal point to the base-10000 digit boundary
+        let pad = (4 - scale % 4) % 4;
+        let mut val = v.mantissa().unsigned_abs() * 10_u128.pow(pad);
+        let mut digits = Vec::new();
+        while val != 0 {
+            digits.push((val % 10000) as u16);
+            val /= 10000;
+        }
+        digits.reverse();
+        Decimal {
+            negative: v.is_sign_negative() && !v.is_zero(),
+            weight: digits.len() as i16 - ((scale + pad) / 4) as i16 - 1,
+            decimal_digits: scale as u16,
+            digits,
+        }
+        .normalize()
+    }
+}
+
+impl TryFrom<BigInt> for rust_decimal::Decimal {
+    type Error = DecimalOutOfRangeError;
+    fn try_from(v: BigInt) -> Result<rust_decimal::Decimal, Self::Error> {
+        rust_decimal::Decimal::try_from(&v)
+    }
+}
+
+impl TryFrom<&BigInt> for rust_decimal::Decimal {
+    type Error = DecimalOutOfRangeError;
+    fn try_from(v: &BigInt) -> Result<rust_decimal::Decimal, Self::Error> {
+        let last_weight = v.weight - v.digits.len() as i16 + 1;
+        let val = digits_to_u128(&v.digits, v.weight, last_weight.min(0))
+            .filter(|&val| val <= MAX_MANTISSA)
+            .ok_or(DecimalOutOfRangeError { reason: TOO_BIG })?;
+        let val = i128::try_from(val).unwrap();
+        Ok(rust_decimal::Decimal::from_i128_with_scale(
+            if v.negative { -val } else { val },
+            0,
+        ))
+    }
+}
+
+impl TryFrom<rust_decimal::Decimal> for BigInt {
+    type Error = DecimalOutOfRangeError;
+    fn try_from(v: rust_decimal::Decimal) -> Result<BigInt, Self::Error> {
+        BigInt::try_from(&v)
+    }
+}
+
+/// Fails if the value has a non-zero fractional part
+impl TryFrom<&rust_decimal::Decimal> for BigInt {
+    type Error = DecimalOutOfRangeError;
+    fn try_from(v: &rust_decimal::Decimal) -> Result<BigInt, Self::Error> {
+        if !v.fract().is_zero() {
+            return Err(DecimalOutOfRangeError {
+                reason: "value has a fractional part",
+            });
+        }
+        let dec = Decimal::from(v.trunc());
+        Ok(BigInt {
+            negative: dec.negative,
+            weight: dec.weight,
+            digits: dec.digits,
+        }
+        .normalize())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test_helpers::gen_i64;
+    use super::{BigInt, Decimal};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use std::convert::TryFrom;
+    use std::str::FromStr;
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str(s).unwrap()
+    }
+
+    fn rdec(s: &str) -> rust_decimal::Decimal {
+        rust_decimal::Decimal::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn decimal_conversion() {
+        let x = Decimal::from(rdec("42.00"));
+        assert_eq!(x.weight, 0);
+        assert_eq!(x.decimal_digits, 2);
+        assert_eq!(x.digits, &[42]);
+        let x = Decimal::from(rdec("-0.07"));
+        assert!(x.negative);
+        assert_eq!(x.weight, -1);
+        assert_eq!(x.decimal_digits, 2);
+        assert_eq!(x.digits, &[700]);
+        let x = Decimal::from(rdec("12345678.901234567"));
+        assert_eq!(x.weight, 1);
+        assert_eq!(x.decimal_digits, 9);
+        assert_eq!(x.digits, &[1234, 5678, 9012, 3456, 7000]);
+        let x = Decimal::from(rust_decimal::Decimal::MAX);
+        assert_eq!(x.weight, 7);
+        assert_eq!(x.decimal_digits, 0);
+        assert_eq!(x.to_string(), "79228162514264337593543950335.0");
+        let x = Decimal::from(rdec("0.0000000000000000000000000001"));
+        assert_eq!(x.weight, -7);
+        assert_eq!(x.decimal_digits, 28);
+        assert_eq!(x.digits, &[1]);
+        let x = Decimal::from(rdec("0.000"));
+        assert_eq!(x, dec("0.000"));
+    }
+
+    #[test]
+    fn decimal_roundtrip() {
+        for s in [
+            "42.00",
+            "-42.07",
+            "0",
+            "0.000",
+            "1e20",
+            "79228162514264337593543950335",
+            "-79228162514264337593543950335",
+            "7.9228162514264337593543950335",
+            "0.0000000000000000000000000001",
+            "12345678.901234567",
+        ] {
+            let value = dec(s);
+            let converted = rust_decimal::Decimal::try_from(&value).unwrap();
+            assert_eq!(converted.scale(), u32::from(value.decimal_digits));
+            assert_eq!(Decimal::from(converted), value, "{}", s);
+        }
+
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..10000 {
+            let mantissa = gen_i64(&mut rng);
+            let scale = rng.gen_range(0..=28);
+            let value = rust_decimal::Decimal::new(mantissa, scale);
+            let converted = rust_decimal::Decimal::try_from(Decimal::from(value)).unwrap();
+            assert_eq!(converted, value);
+            assert_eq!(converted.scale(), value.scale());
+        }
+    }
+
+    #[test]
+    fn decimal_out_of_range() {
+        assert_eq!(
+            dec("79228162514264337593543950336").to_rust_decimal(),
+            Err("value exceeds 96 bits of precision")
+        );
+        assert_eq!(
+            dec("1e100").to_rust_decimal(),
+            Err("value exceeds 96 bits of precision")
+        );
+        assert_eq!(
+            dec("0.00000000000000000000000000001").to_rust_decimal(),
+            Err("value exceeds 28 digits of scale")
+        );
+        assert_eq!(
+            rust_decimal::Decimal::try_from(dec("1e100"))
+                .unwrap_err()
+                .to_string(),
+            "decimal is out of range: value exceeds 96 bits of precision"
+        );
+        // trailing zeros beyond maximum scale are dropped
+        assert_eq!(
+            dec("1.000000000000000000000000000000").to_rust_decimal(),
+            Ok(rdec("1.0000000000000000000000000000"))
+        );
+        // as well as ones that don't fit into the mantissa
+        assert_eq!(
+            dec("79228162514264337593543950335.00").to_rust_decimal(),
+            Ok(rust_decimal::Decimal::MAX)
+        );
+    }
+
+    #[test]
+    fn bigint_conversion() {
+        let x = BigInt::try_from(rdec("-30000.000")).unwrap();
+        assert_eq!(x, BigInt::from(-30000));
+        assert_eq!(BigInt::try_from(rdec("0.00")).unwrap(), BigInt::from(0));
+        assert_eq!(
+            BigInt::try_from(rdec("1.5")).unwrap_err().to_string(),
+            "decimal is out of range: value has a fractional part"
+        );
+
+        let x = BigInt::from_str("-79228162514264337593543950335").unwrap();
+        assert_eq!(
+            rust_decimal::Decimal::try_from(&x).unwrap(),
+            rust_decimal::Decimal::MIN
+        );
+        let x = BigInt::from_str("79228162514264337593543950336").unwrap();
+        assert_eq!(
+            rust_decimal::Decimal::try_from(&x).unwrap_err().to_string(),
+            "decimal is out of range: value exceeds 96 bits of precision"
+        );
+        let x = BigInt::from(1_000_000_000_000_i64);
+        assert_eq!(
+            rust_decimal::Decimal::try_from(&x).unwrap(),
+            rdec("1000000000000")
+        );
+    }
+}
@@ -0,0 +1,893 @@
+//! Typed PostGIS geometries
+//!
+//! Values of `ext::postgis::geometry` and `ext::postgis::geography` are
+//! transferred as [EWKB](https://postgis.net/docs/using_postgis_dbmanagement.html#EWKB_EWKT),
+//! this module contains types those values are decoded to, along with
+//! conversions to/from WKT text (via [`Display`](fmt::Display) and
+//! [`FromStr`]) and to/from [geo-types](https://docs.rs/geo-types)
+//! (`with-geo-types` feature).
+use std::fmt;
+use std::str::FromStr;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use snafu::ensure;
+
+use crate::errors::{self, DecodeError};
+
+const WKB_Z: u32 = 0x8000_0000;
+const WKB_M: u32 = 0x4000_0000;
+const WKB_SRID: u32 = 0x2000_0000;
+
+const POINT: u32 = 1;
+const LINE_STRING: u32 = 2;
+const POLYGON: u32 = 3;
+const MULTI_POINT: u32 = 4;
+const MULTI_LINE_STRING: u32 = 5;
+const MULTI_POLYGON: u32 = 6;
+const GEOMETRY_COLLECTION: u32 = 7;
+
+/// Maximum number of nested collections accepted when decoding and parsing
+const MAX_DEPTH: usize = 64;
+
+/// A geometry along with its spatial reference system
+#[derive(Clone, Debug, PartialEq)]
+pub struct Geometry {
+    /// Spatial reference system identifier (e.g. `4326` for WGS 84)
+    pub srid: Option<u32>,
+    pub shape: Shape,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    Point(Point),
+    LineString(LineString),
+    Polygon(Polygon),
+    MultiPoint(Vec<Point>),
+    MultiLineString(Vec<LineString>),
+    MultiPolygon(Vec<Polygon>),
+    GeometryCollection(Vec<Shape>),
+}
+
+/// A point, optionally having Z (elevation) and M (measure) coordinates
+///
+/// Empty point (`POINT EMPTY`) has `NaN` for both `x` and `y`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+    pub z: Option<f64>,
+    pub m: Option<f64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LineString(pub Vec<Point>);
+
+/// Polygon as a list of rings, the first one is the exterior ring and the
+/// rest are holes
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Polygon(pub Vec<LineString>);
+
+/// Error parsing WKT text into a [`Geometry`]
+#[derive(Debug, PartialEq)]
+pub struct ParseGeometryError {
+    pub(crate) message: String,
+    pub(crate) pos: usize,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Dims {
+    z: bool,
+    m: bool,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Point {
+        Point {
+            x,
+            y,
+            z: None,
+            m: None,
+        }
+    }
+    pub fn empty() -> Point {
+        Point::new(f64::NAN, f64::NAN)
+    }
+    pub fn with_z(self, z: f64) -> Point {
+        Point { z: Some(z), ..self }
+    }
+    pub fn with_m(self, m: f64) -> Point {
+        Point { m: Some(m), ..self }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.x.is_nan() && self.y.is_nan()
+    }
+}
+
+impl Geometry {
+    pub fn new(shape: impl Into<Shape>) -> Geometry {
+        Geometry {
+            srid: None,
+            shape: shape.into(),
+        }
+    }
+    pub fn with_srid(self, srid: u32) -> Geometry {
+        Geometry {
+            srid: Some(srid),
+            ..self
+        }
+    }
+    /// Decode geometry from (E)WKB
+    pub fn from_ewkb(mut buf: &[u8]) -> Result<Geometry, DecodeError> {
+        let mut srid = None;
+        let shape = read_shape(&mut buf, &mut srid, 0)?;
+        ensure!(buf.is_empty(), errors::ExtraData);
+        Ok(Geometry { srid, shape })
+    }
+    /// Encode geometry as EWKB
+    ///
+    /// Coordinates are written as Z (or M) if any point of the geometry
+    /// has one, missing coordinates of other points are written as `NaN`.
+    pub fn to_ewkb(&self) -> Bytes {
+        let mut buf = BytesMut::new();
+        write_shape(&mut buf, &self.shape, self.srid, self.shape.dims());
+        buf.freeze()
+    }
+}
+
+impl Shape {
+    fn type_code(&self) -> u32 {
+        match self {
+            Shape::Point(_) => POINT,
+            Shape::LineString(_) => LINE_STRING,
+            Shape::Polygon(_) => POLYGON,
+            Shape::MultiPoint(_) => MULTI_POINT,
+            Shape::MultiLineString(_) => MULTI_LINE_STRING,
+            Shape::MultiPolygon(_) => MULTI_POLYGON,
+            Shape::GeometryCollection(_) => GEOMETRY_COLLECTION,
+        }
+    }
+    fn tag(&self) -> &'static str {
+        match self {
+            Shape::Point(_) => "POINT",
+            Shape::LineString(_) => "LINESTRING",
+            Shape::Polygon(_) => "POLYGON",
+            Shape::MultiPoint(_) => "MULTIPOINT",
+            Shape::MultiLineString(_) => "MULTILINESTRING",
+            Shape::MultiPolygon(_) => "MULTIPOLYGON",
+            Shape::GeometryCollection(_) => "GEOMETRYCOLLECTION",
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Shape::Point(p) => p.is_empty(),
+            Shape::LineString(l) => l.0.is_empty(),
+            Shape::Polygon(p) => p.0.is_empty(),
+            Shape::MultiPoint(v) => v.is_empty(),
+            Shape::MultiLineString(v) => v.is_empty(),
+            Shape::MultiPolygon(v) => v.is_empty(),
+            Shape::GeometryCollection(v) => v.is_empty(),
+        }
+    }
+    fn dims(&self) -> Dims {
+        let mut dims = Dims::default();
+        self.visit_points(&mut |p| {
+            dims.z |= p.z.is_some();
+            dims.m |= p.m.is_some();
+        });
+        dims
+    }
+    fn visit_points(&self, f: &mut impl FnMut(&Point)) {
+        match self {
+            Shape::Point(p) => f(p),
+            Shape::LineString(l) => l.0.iter().for_each(f),
+            Shape::Polygon(p) => p.0.iter().flat_map(|r| &r.0).for_each(f),
+            Shape::MultiPoint(v) => v.iter().for_each(f),
+            Shape::MultiLineString(v) => v.iter().flat_map(|l| &l.0).for_each(f),
+            Shape::MultiPolygon(v) => v.iter().flat_map(|p| &p.0).flat_map(|r| &r.0).for_each(f),
+            Shape::GeometryCollection(v) => v.iter().for_each(|s| s.visit_points(f)),
+        }
+    }
+}
+
+impl From<Point> for Shape {
+    fn from(value: Point) -> Shape {
+        Shape::Point(value)
+    }
+}
+
+impl From<LineString> for Shape {
+    fn from(value: LineString) -> Shape {
+        Shape::LineString(value)
+    }
+}
+
+impl From<Polygon> for Shape {
+    fn from(value: Polygon) -> Shape {
+        Shape::Polygon(value)
+    }
+}
+
+impl From<Shape> for Geometry {
+    fn from(shape: Shape) -> Geometry {
+        Geometry { srid: None, shape }
+    }
+}
+
+fn invalid(reason: &'static str) -> DecodeError {
+    errors::InvalidGeometry { reason }.build()
+}
+
+fn read_u32(buf: &mut &[u8], little_endian: bool) -> Result<u32, DecodeError> {
+    ensure!(buf.remaining() >= 4, errors::Underflow);
+    Ok(if little_endian {
+        buf.get_u32_le()
+    } else {
+        buf.get_u32()
+    })
+}
+
+fn read_f64(buf: &mut &[u8], little_endian: bool) -> Result<f64, DecodeError> {
+    ensure!(buf.remaining() >= 8, errors::Underflow);
+    Ok(if little_endian {
+        buf.get_f64_le()
+    } else {
+        buf.get_f64()
+    })
+}
+
+fn read_point(buf: &mut &[u8], little_endian: bool, dims: Dims) -> Result<Point, DecodeError> {
+    let x = read_f64(buf, little_endian)?;
+    let y = read_f64(buf, little_endian)?;
+    let z = dims.z.then(|| read_f64(buf, little_endian)).transpose()?;
+    let m = dims.m.then(|| read_f64(buf, little_endian)).transpose()?;
+    Ok(Point { x, y, z, m })
+}
+
+/// Reads number of items, each taking at least `min_size` bytes
+fn read_count(buf: &mut &[u8], little_endian: bool, min_size: usize) -> Result<usize, DecodeError> {
+    let count = read_u32(buf, little_endian)? as usize;
+    ensure!(
+        buf.remaining() >= count.saturating_mul(min_size),
+        errors::Underflow
+    );
+    Ok(count)
+}
+
+fn read_points(
+    buf: &mut &[u8],
+    little_endian: bool,
+    dims: Dims,
+) -> Result<LineString, DecodeError> {
+    let count = read_count(buf, little_endian, 16)?;
+    (0..count)
+        .map(|_| read_point(buf, little_endian, dims))
+        .collect::<Result<_, _>>()
+        .map(LineString)
+}
+
+fn read_polygon(buf: &mut &[u8], little_endian: bool, dims: Dims) -> Result<Polygon, DecodeError> {
+    let count = read_count(buf, little_endian, 4)?;
+    (0..count)
+        .map(|_| read_points(buf, little_endian, dims))
+        .collect::<Result<_, _>>()
+        .map(Polygon)
+}
+
+fn read_shape(buf: &mut &[u8], srid: &mut Option<u32>, depth: usize) -> Result<Shape, DecodeError> {
+    ensure!(buf.remaining() >= 1, errors::Underflow);
+    let little_endian = match buf.get_u8() {
+        0 => false,
+        1 => true,
+        _ => return Err(invalid("invalid byte order")),
+    };
+    let code = read_u32(buf, little_endian)?;
+    if code & WKB_SRID != 0 {
+        *srid = Some(read_u32(buf, little_endian)?);
+    }
+    let mut dims = Dims {
+        z: code & WKB_Z != 0,
+        m: code & WKB_M != 0,
+    };
+    // ISO WKB encodes dimensions as 1000, 2000 and 3000 added to the type
+    let kind = code & 0x0FFF_FFFF;
+    match kind / 1000 {
+        0 => {}
+        1 => dims.z = true,
+        2 => dims.m = true,
+        3 => {
+            dims.z = true;
+            dims.m = true;
+        }
+        _ => return Err(invalid("unsupported geometry type")),
+    }
+    let shape = match kind % 1000 {
+        POINT => Shape::Point(read_point(buf, little_endian, dims)?),
+        LINE_STRING => Shape::LineString(read_points(buf, little_endian, dims)?),
+        POLYGON => Shape::Polygon(read_polygon(buf, little_endian, dims)?),
+        MULTI_POINT => Shape::MultiPoint(read_elements(buf, little_endian, depth, |s| match s {
+            Shape::Point(p) => Ok(p),
+            _ => Err(invalid("multipoint contains non-point element")),
+        })?),
+        MULTI_LINE_STRING => {
+            Shape::MultiLineString(read_elements(buf, little_endian, depth, |s| match s {
+                Shape::LineString(l) => Ok(l),
+                _ => Err(invalid("multilinestring contains non-linestring element")),
+            })?)
+        }
+        MULTI_POLYGON => {
+            Shape::MultiPolygon(read_elements(buf, little_endian, depth, |s| match s {
+                Shape::Polygon(p) => Ok(p),
+                _ => Err(invalid("multipolygon contains non-polygon element")),
+            })?)
+        }
+        GEOMETRY_COLLECTION => {
+            Shape::GeometryCollection(read_elements(buf, little_endian, depth, Ok)?)
+        }
+        _ => return Err(invalid("unsupported geometry type")),
+    };
+    Ok(shape)
+}
+
+fn read_elements<T>(
+    buf: &mut &[u8],
+    little_endian: bool,
+    depth: usize,
+    convert: impl Fn(Shape) -> Result<T, DecodeError>,
+) -> Result<Vec<T>, DecodeError> {
+    if depth >= MAX_DEPTH {
+        return Err(invalid("geometry is nested too deeply"));
+    }
+    let count = read_count(buf, little_endian, 5)?;
+    (0..count)
+        // SRID of elements, if any, is the same as of the whole geometry
+        .map(|_| read_shape(buf, &mut None, depth + 1).and_then(&convert))
+        .collect()
+}
+
+fn write_point(buf: &mut BytesMut, point: &Point, dims: Dims) {
+    buf.reserve(32);
+    buf.put_f64_le(point.x);
+    buf.put_f64_le(point.y);
+    if dims.z {
+        buf.put_f64_le(point.z.unwrap_or(f64::NAN));
+    }
+    if dims.m {
+        buf.put_f64_le(point.m.unwrap_or(f64::NAN));
+    }
+}
+
+fn write_points(buf: &mut BytesMut, line: &LineString, dims: Dims) {
+    buf.reserve(4);
+    buf.put_u32_le(line.0.len() as u32);
+    for point in &line.0 {
+        write_point(buf, point, dims);
+    }
+}
+
+fn write_header(buf: &mut BytesMut, type_code: u32, srid: Option<u32>, dims: Dims) {
+    let mut code = type_code;
+    if dims.z {
+        code |= WKB_Z;
+    }
+    if dims.m {
+        code |= WKB_M;
+    }
+    if srid.is_some() {
+        code |= WKB_SRID;
+    }
+    buf.reserve(9);
+    buf.put_u8(1); // little endian
+    buf.put_u32_le(code);
+    if let Some(srid) = srid {
+        buf.put_u32_le(srid);
+    }
+}
+
+fn write_polygon(buf: &mut BytesMut, polygon: &Polygon, dims: Dims) {
+    buf.reserve(4);
+    buf.put_u32_le(polygon.0.len() as u32);
+    for ring in &polygon.0 {
+        write_points(buf, ring, dims);
+    }
+}
+
+fn write_shape(buf: &mut BytesMut, shape: &Shape, srid: Option<u32>, dims: Dims) {
+    write_header(buf, shape.type_code(), srid, dims);
+    match shape {
+        Shape::Point(p) => write_point(buf, p, dims),
+        Shape::LineString(l) => write_points(buf, l, dims),
+        Shape::Polygon(p) => write_polygon(buf, p, dims),
+        Shape::MultiPoint(v) => {
+            buf.reserve(4);
+            buf.put_u32_le(v.len() as u32);
+            for p in v {
+                write_header(buf, POINT, None, dims);
+                write_point(buf, p, dims);
+            }
+        }
+        Shape::MultiLineString(v) => {
+            buf.reserve(4);
+            buf.put_u32_le(v.len() as u32);
+            for l in v {
+                write_header(buf, LINE_STRING, None, dims);
+                write_points(buf, l, dims);
+            }
+        }
+        Shape::MultiPolygon(v) => {
+            buf.reserve(4);
+            buf.put_u32_le(v.len() as u32);
+            for p in v {
+                write_header(buf, POLYGON, None, dims);
+                write_polygon(buf, p, dims);
+            }
+        }
+        Shape::GeometryCollection(v) => {
+            buf.reserve(4);
+            buf.put_u32_le(v.len() as u32);
+            for s in v {
+                write_shape(buf, s, None, dims);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Geometry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(srid) = self.srid {
+            write!(f, "SRID={srid};")?;
+        }
+        write_wkt(f, &self.shape, self.shape.dims())
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_wkt(f, self, self.dims())
+    }
+}
+
+fn write_wkt(f: &mut fmt::Formatter<'_>, shape: &Shape, dims: Dims) -> fmt::Result {
+    f.write_str(shape.tag())?;
+    match (dims.z, dims.m) {
+        (false, false) => {}
+        (true, false) => f.write_str(" Z")?,
+        (false, true) => f.write_str(" M")?,
+        (true, true) => f.write_str(" ZM")?,
+    }
+    if shape.is_empty() {
+        return f.write_str(" EMPTY");
+    }
+    if dims.z || dims.m {
+        f.write_str(" ")?;
+    }
+    match shape {
+        Shape::Point(p) => write_wkt_point(f, p, dims),
+        Shape::LineString(l) => write_wkt_points(f, l, dims),
+        Shape::Polygon(p) => write_wkt_polygon(f, p, dims),
+        Shape::MultiPoint(v) => write_wkt_list(f, v, |f, p| write_wkt_point(f, p, dims)),
+        Shape::MultiLineString(v) => write_wkt_list(f, v, |f, l| write_wkt_points(f, l, dims)),
+        Shape::MultiPolygon(v) => write_wkt_list(f, v, |f, p| write_wkt_polygon(f, p, dims)),
+        Shape::GeometryCollection(v) => write_wkt_list(f, v, |f, s| write_wkt(f, s, dims)),
+    }
+}
+
+fn write_wkt_list<T>(
+    f: &mut fmt::Formatter<'_>,
+    items: &[T],
+    mut write_item: impl FnMut(&mut fmt::Formatter<'_>, &T) -> fmt::Result,
+) -> fmt::Result {
+    f.write_str("(")?;
+    for (idx, item) in items.iter().enumerate() {
+        if idx > 0 {
+            f.write_str(",")?;
+        }
+        write_item(f, item)?;
+    }
+    f.write_str(")")
+}
+
+fn write_wkt_point(f: &mut fmt::Formatter<'_>, point: &Point, dims: Dims) -> fmt::Result {
+    if point.is_empty() {
+        return f.write_str("EMPTY");
+    }
+    f.write_str("(")?;
+    write_wkt_coords(f, point, dims)?;
+    f.write_str(")")
+}
+
+fn write_wkt_coords(f: &mut fmt::Formatter<'_>, point: &Point, dims: Dims) -> fmt::Result {
+    write!(f, "{} {}", point.x, point.y)?;
+    if dims.z {
+        write!(f, " {}", point.z.unwrap_or(f64::NAN))?;
+    }
+    if dims.m {
+        write!(f, " {}", point.m.unwrap_or(f64::NAN))?;
+    }
+    Ok(())
+}
+
+fn write_wkt_points(f: &mut fmt::Formatter<'_>, line: &LineString, dims: Dims) -> fmt::Result {
+    if line.0.is_empty() {
+        return f.write_str("EMPTY");
+    }
+    write_wkt_list(f, &line.0, |f, p| write_wkt_coords(f, p, dims))
+}
+
+fn write_wkt_polygon(f: &mut fmt::Formatter<'_>, polygon: &Polygon, dims: Dims) -> fmt::Result {
+    if polygon.0.is_empty() {
+        return f.write_str("EMPTY");
+    }
+    write_wkt_list(f, &polygon.0, |f, l| write_wkt_points(f, l, dims))
+}
+
+impl std::error::Error for ParseGeometryError {}
+impl fmt::Display for ParseGeometryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        format!(
+            "Error parsing geometry at position {}: {}",
+            self.pos, self.message,
+        )
+        .fmt(f)
+    }
+}
+
+/// Parses WKT, or EWKT having `SRID=<srid>;` prefix
+impl FromStr for Geometry {
+    type Err = ParseGeometryError;
+    fn from_str(s: &str) -> Result<Geometry, ParseGeometryError> {
+        let mut parser = WktParser {
+            src: s,
+            pos: 0,
+            depth: 0,
+        };
+        parser.skip_whitespace();
+        let mut srid = None;
+        if parser
+            .rest()
+            .get(..5)
+            .is_some_and(|s| s.eq_ignore_ascii_case("SRID="))
+        {
+            parser.pos += 5;
+            let end = parser
+                .rest()
+                .find(';')
+                .ok_or_else(|| parser.error("expected `;`"))?;
+            let value = parser.rest()[..end].trim();
+            srid = Some(value.parse().map_err(|_| parser.error("invalid SRID"))?);
+            parser.pos += end + 1;
+        }
+        let shape = parser.shape()?;
+        parser.skip_whitespace();
+        if !parser.rest().is_empty() {
+            return Err(parser.error("unexpected trailing characters"));
+        }
+        Ok(Geometry { srid, shape })
+    }
+}
+
+struct WktParser<'a> {
+    src: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl WktParser<'_> {
+    fn rest(&self) -> &str {
+        &self.src[self.pos..]
+    }
+    fn error(&self, message: &str) -> ParseGeometryError {
+        ParseGeometryError {
+            message: message.into(),
+            pos: self.pos,
+        }
+    }
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+    fn expect(&mut self, c: char) -> Result<(), ParseGeometryError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{c}`")))
+        }
+    }
+    fn peek_word(&mut self) -> &str {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        &rest[..len]
+    }
+    fn eat_empty(&mut self) -> bool {
+        if self.peek_word().eq_ignore_ascii_case("EMPTY") {
+            self.pos += "EMPTY".len();
+            true
+        } else {
+            false
+        }
+    }
+    /// Parses `(` items separated by comma `)` or `EMPTY`
+    fn list<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<T, ParseGeometryError>,
+    ) -> Result<Vec<T>, ParseGeometryError> {
+        if self.eat_empty() {
+            return Ok(Vec::new());
+        }
+        self.expect('(')?;
+        let mut items = vec![item(self)?];
+        while self.eat(',') {
+            items.push(item(self)?);
+        }
+        self.expect(')')?;
+        Ok(items)
+    }
+    fn number(&mut self) -> Result<Option<f64>, ParseGeometryError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| c.is_whitespace() || c == ',' || c == ')')
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Ok(None);
+        }
+        let value = rest[..len]
+            .parse()
+            .map_err(|_| self.error("invalid number"))?;
+        self.pos += len;
+        Ok(Some(value))
+    }
+    fn coords(&mut self, dims: Option<Dims>) -> Result<Point, ParseGeometryError> {
+        let mut values = Vec::with_capacity(4);
+        while let Some(value) = self.number()? {
+            if values.len() == 4 {
+                return Err(self.error("too many coordinates"));
+            }
+            values.push(value);
+        }
+        let dims = dims.unwrap_or(Dims {
+            z: values.len() >= 3,
+            m: values.len() == 4,
+        });
+        let point = match (&values[..], dims.z, dims.m) {
+            (&[x, y], false, false) => Point::new(x, y),
+            (&[x, y, z], true, false) => Point::new(x, y).with_z(z),
+            (&[x, y, m], false, true) => Point::new(x, y).with_m(m),
+            (&[x, y, z, m], true, true) => Point::new(x, y).with_z(z).with_m(m),
+            _ => return Err(self.error("wrong number of coordinates")),
+        };
+        Ok(point)
+    }
+    fn points(&mut self, dims: Option<Dims>) -> Result<LineString, ParseGeometryError> {
+        self.list(|p| p.coords(dims)).map(LineString)
+    }
+    fn polygon(&mut self, dims: Option<Dims>) -> Result<Polygon, ParseGeometryError> {
+        self.list(|p| p.points(dims)).map(Polygon)
+    }
+    fn shape(&mut self) -> Result<Shape, ParseGeometryError> {
+        let start = self.pos;
+        let word = self.peek_word().to_ascii_uppercase();
+        self.pos += word.len();
+        // EWKT uses suffix without space: `POINTM(1 2 3)`
+        let (tag, mut dims) = if let Some(tag) = word.strip_suffix("ZM") {
+            (tag, Some(Dims { z: true, m: true }))
+        } else if let Some(tag) = word.strip_suffix('M') {
+            (tag, Some(Dims { z: false, m: true }))
+        } else if let Some(tag) = word.strip_suffix('Z') {
+            (tag, Some(Dims { z: true, m: false }))
+        } else {
+            (&word[..], None)
+        };
+        if !is_tag(tag) {
+            self.pos = start;
+            return Err(self.error("unknown geometry type"));
+        }
+        if dims.is_none() {
+            dims = match &self.peek_word().to_ascii_uppercase()[..] {
+                "Z" => Some(Dims { z: true, m: false }),
+                "M" => Some(Dims { z: false, m: true }),
+                "ZM" => Some(Dims { z: true, m: true }),
+                _ => None,
+            };
+            if let Some(dims) = dims {
+                self.pos += dims.z as usize + dims.m as usize;
+            }
+        }
+        let shape = match tag {
+            "POINT" => {
+                if self.eat_empty() {
+                    Shape::Point(Point::empty())
+                } else {
+                    self.expect('(')?;
+                    let point = self.coords(dims)?;
+                    self.expect(')')?;
+                    Shape::Point(point)
+                }
+            }
+            "LINESTRING" => Shape::LineString(self.points(dims)?),
+            "POLYGON" => Shape::Polygon(self.polygon(dims)?),
+            "MULTIPOINT" => Shape::MultiPoint(self.list(|p| {
+                // both `MULTIPOINT(1 2, 3 4)` and `MULTIPOINT((1 2), (3 4))`
+                // are used
+                if p.eat_empty() {
+                    Ok(Point::empty())
+                } else if p.eat('(') {
+                    let point = p.coords(dims)?;
+                    p.expect(')')?;
+                    Ok(point)
+                } else {
+                    p.coords(dims)
+                }
+            })?),
+            "MULTILINESTRING" => Shape::MultiLineString(self.list(|p| p.points(dims))?),
+            "MULTIPOLYGON" => Shape::MultiPolygon(self.list(|p| p.polygon(dims))?),
+            "GEOMETRYCOLLECTION" => {
+                if self.depth >= MAX_DEPTH {
+                    return Err(self.error("geometry is nested too deeply"));
+                }
+                self.depth += 1;
+                let shapes = self.list(|p| p.shape())?;
+                self.depth -= 1;
+                Shape::GeometryCollection(shapes)
+            }
+            _ => unreachable!(),
+        };
+        Ok(shape)
+    }
+}
+
+fn is_tag(word: &str) -> bool {
+    matches!(
+        word,
+        "POINT"
+            | "LINESTRING"
+            | "POLYGON"
+            | "MULTIPOINT"
+            | "MULTILINESTRING"
+            | "MULTIPOLYGON"
+            | "GEOMETRYCOLLECTION"
+    )
+}
+
+#[cfg(feature = "geo-types")]
+mod geo_interop {
+    use super::{Geometry, LineString, Point, Polygon, Shape};
+
+    impl From<geo_types::Coord<f64>> for Point {
+        fn from(value: geo_types::Coord<f64>) -> Point {
+            Point::new(value.x, value.y)
+        }
+    }
+
+    impl From<geo_types::Point<f64>> for Point {
+        fn from(value: geo_types::Point<f64>) -> Point {
+            value.0.into()
+        }
+    }
+
+    /// Z and M coordinates are dropped
+    impl From<Point> for geo_types::Coord<f64> {
+        fn from(value: Point) -> geo_types::Coord<f64> {
+            geo_types::Coord {
+                x: value.x,
+                y: value.y,
+            }
+        }
+    }
+
+    /// Z and M coordinates are dropped
+    impl From<Point> for geo_types::Point<f64> {
+        fn from(value: Point) -> geo_types::Point<f64> {
+            geo_types::Point(value.into())
+        }
+    }
+
+    impl From<geo_types::LineString<f64>> for LineString {
+        fn from(value: geo_types::LineString<f64>) -> LineString {
+            LineString(value.0.into_iter().map(Point::from).collect())
+        }
+    }
+
+    impl From<LineString> for geo_types::LineString<f64> {
+        fn from(value: LineString) -> geo_types::LineString<f64> {
+            geo_types::LineString(value.0.into_iter().map(Into::into).collect())
+        }
+    }
+
+    impl From<geo_types::Polygon<f64>> for Polygon {
+        fn from(value: geo_types::Polygon<f64>) -> Polygon {
+            let (exterior, interiors) = value.into_inner();
+            if exterior.0.is_empty() && interiors.is_empty() {
+                return Polygon(Vec::new());
+            }
+            Polygon(
+                std::iter::once(exterior)
+                    .chain(interiors)
+                    .map(LineString::from)
+                    .collect(),
+            )
+        }
+    }
+
+    impl From<Polygon> for geo_types::Polygon<f64> {
+        fn from(value: Polygon) -> geo_types::Polygon<f64> {
+            let mut rings = value.0.into_iter().map(geo_types::LineString::from);
+            let exterior = rings
+                .next()
+                .unwrap_or_else(|| geo_types::LineString(Vec::new()));
+            geo_types::Polygon::new(exterior, rings.collect())
+        }
+    }
+
+    impl From<geo_types::Geometry<f64>> for Shape {
+        fn from(value: geo_types::Geometry<f64>) -> Shape {
+            use geo_types::Geometry as G;
+            match value {
+                G::Point(p) => Shape::Point(p.into()),
+                G::Line(l) => Shape::LineString(LineString(vec![l.start.into(), l.end.into()])),
+                G::LineString(l) => Shape::LineString(l.into()),
+                G::Polygon(p) => Shape::Polygon(p.into()),
+                G::MultiPoint(v) => Shape::MultiPoint(v.0.into_iter().map(Into::into).collect()),
+                G::MultiLineString(v) => {
+                    Shape::MultiLineString(v.0.into_iter().map(Into::into).collect())
+                }
+                G::MultiPolygon(v) => {
+                    Shape::MultiPolygon(v.0.into_iter().map(Into::into).collect())
+                }
+                G::GeometryCollection(v) => {
+                    Shape::GeometryCollection(v.0.into_iter().map(Into::into).collect())
+                }
+                G::Rect(r) => Shape::Polygon(r.to_polygon().into()),
+                G::Triangle(t) => Shape::Polygon(t.to_polygon().into()),
+            }
+        }
+    }
+
+    /// Z and M coordinates are dropped
+    impl From<Shape> for geo_types::Geometry<f64> {
+        fn from(value: Shape) -> geo_types::Geometry<f64> {
+            use geo_types::Geometry as G;
+            match value {
+                Shape::Point(p) => G::Point(p.into()),
+                Shape::LineString(l) => G::LineString(l.into()),
+                Shape::Polygon(p) => G::Polygon(p.into()),
+                Shape::MultiPoint(v) => G::MultiPoint(geo_types::MultiPoint(
+                    v.into_iter().map(Into::into).collect(),
+                )),
+                Shape::MultiLineString(v) => G::MultiLineString(geo_types::MultiLineString(
+                    v.into_iter().map(Into::into).collect(),
+                )),
+                Shape::MultiPolygon(v) => G::MultiPolygon(geo_types::MultiPolygon(
+                    v.into_iter().map(Into::into).collect(),
+                )),
+                Shape::GeometryCollection(v) => G::GeometryCollection(
+                    geo_types::GeometryCollection(v.into_iter().map(Into::into).collect()),
+                ),
+            }
+        }
+    }
+
+    impl From<geo_types::Geometry<f64>> for Geometry {
+        fn from(value: geo_types::Geometry<f64>) -> Geometry {
+            Geometry::new(Shape::from(value))
+        }
+    }
+
+    /// SRID, Z and M coordinates are dropped
+    impl From<Geometry> for geo_types::Geometry<f64> {
+        fn from(value: Geometry) -> geo_types::Geometry<f64> {
+            value.shape.into()
+        }
+    }
+}
@@ -1,3 +1,7 @@
+use std::convert::TryFrom;
+use std::iter::FromIterator;
+
+use crate::model::OutOfRangeError;
 use crate::value::Value;
 
 pub(crate) const EMPTY: usize = 0x01;
@@ -28,12 +32,106 @@ impl<T> From<std::ops::Range<T>> for Range<T> {
     }
 }
 
+impl<T> From<std::ops::RangeInclusive<T>> for Range<T> {
+    fn from(src: std::ops::RangeInclusive<T>) -> Range<T> {
+        let (start, end) = src.into_inner();
+        Range {
+            lower: Some(start),
+            upper: Some(end),
+            inc_lower: true,
+            inc_upper: true,
+            empty: false,
+        }
+    }
+}
+
+impl<T> From<std::ops::RangeFrom<T>> for Range<T> {
+    fn from(src: std::ops::RangeFrom<T>) -> Range<T> {
+        Range {
+            lower: Some(src.start),
+            upper: None,
+            inc_lower: true,
+            inc_upper: false,
+            empty: false,
+        }
+    }
+}
+
+impl<T> From<std::ops::RangeTo<T>> for Range<T> {
+    fn from(src: std::ops::RangeTo<T>) -> Range<T> {
+        Range {
+            lower: None,
+            upper: Some(src.end),
+            inc_lower: false,
+            inc_upper: false,
+            empty: false,
+        }
+    }
+}
+
+impl<T> From<std::ops::RangeToInclusive<T>> for Range<T> {
+    fn from(src: std::ops::RangeToInclusive<T>) -> Range<T> {
+        Range {
+            lower: None,
+            upper: Some(src.end),
+            inc_lower: false,
+            inc_upper: true,
+            empty: false,
+        }
+    }
+}
+
 impl<T: Into<Value>> From<std::ops::Range<T>> for Value {
     fn from(src: std::ops::Range<T>) -> Value {
         Range::from(src).into_value()
     }
 }
 
+impl<T: Into<Value>> From<std::ops::RangeInclusive<T>> for Value {
+    fn from(src: std::ops::RangeInclusive<T>) -> Value {
+        Range::from(src).into_value()
+    }
+}
+
+/// Only non-empty ranges with both bounds, inclusive lower and exclusive
+/// upper one, can be converted.
+impl<T> TryFrom<Range<T>> for std::ops::Range<T> {
+    type Error = OutOfRangeError;
+    fn try_from(src: Range<T>) -> Result<std::ops::Range<T>, OutOfRangeError> {
+        match src {
+            Range {
+                lower: Some(start),
+                upper: Some(end),
+                inc_lower: true,
+                inc_upper: false,
+                empty: false,
+            } => Ok(start..end),
+            _ => Err(OutOfRangeError),
+        }
+    }
+}
+
+/// Only non-empty ranges with both bounds inclusive can be converted.
+///
+/// Note: the database normalizes ranges of discrete types (`int32`,
+/// `int64`, `cal::local_date`) to have exclusive upper bound, so they have
+/// to be converted to [`std::ops::Range`].
+impl<T> TryFrom<Range<T>> for std::ops::RangeInclusive<T> {
+    type Error = OutOfRangeError;
+    fn try_from(src: Range<T>) -> Result<std::ops::RangeInclusive<T>, OutOfRangeError> {
+        match src {
+            Range {
+                lower: Some(start),
+                upper: Some(end),
+                inc_lower: true,
+                inc_upper: true,
+                empty: false,
+            } => Ok(start..=end),
+            _ => Err(OutOfRangeError),
+        }
+    }
+}
+
 impl<T> Range<T> {
     /// Constructor of the empty range
     pub fn empty() -> Range<T> {
@@ -73,3 +171,95 @@ impl<T: Into<Value>> Range<T> {
         })
     }
 }
+
+/// A set of non-overlapping ranges
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultiRange<T> {
+    pub(crate) ranges: Vec<Range<T>>,
+}
+
+impl<T> MultiRange<T> {
+    /// Constructor of the multirange
+    ///
+    /// Ranges are normalized (sorted and merged) by the database, so the
+    /// multirange received back might differ from the one sent.
+    pub fn new(ranges: Vec<Range<T>>) -> MultiRange<T> {
+        MultiRange { ranges }
+    }
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+    pub fn into_ranges(self) -> Vec<Range<T>> {
+        self.ranges
+    }
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+    pub fn iter(&self) -> std::slice::Iter<'_, Range<T>> {
+        self.ranges.iter()
+    }
+}
+
+impl<T> Default for MultiRange<T> {
+    fn default() -> MultiRange<T> {
+        MultiRange { ranges: Vec::new() }
+    }
+}
+
+impl<T> From<Vec<Range<T>>> for MultiRange<T> {
+    fn from(ranges: Vec<Range<T>>) -> MultiRange<T> {
+        MultiRange { ranges }
+    }
+}
+
+impl<T> From<MultiRange<T>> for Vec<Range<T>> {
+    fn from(src: MultiRange<T>) -> Vec<Range<T>> {
+        src.ranges
+    }
+}
+
+impl<T> FromIterator<Range<T>> for MultiRange<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> MultiRange<T> {
+        MultiRange {
+            ranges: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> IntoIterator for MultiRange<T> {
+    type Item = Range<T>;
+    type IntoIter = std::vec::IntoIter<Range<T>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.ranges.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a MultiRange<T> {
+    type Item = &'a Range<T>;
+    type IntoIter = std::slice::Iter<'a, Range<T>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.ranges.iter()
+    }
+}
+
+impl<T: Into<Value>> MultiRange<T> {
+    pub fn into_value(self) -> Value {
+        Value::MultiRange(MultiRange {
+            ranges: self
+                .ranges
+                .into_iter()
+                .map(|rng| Range {
+                    lower: rng.lower.map(|v| Box::new(v.into())),
+                    upper: rng.upper.map(|v| Box::new(v.into())),
+                    inc_lower: rng.inc_lower,
+                    inc_upper: rng.inc_upper,
+                    empty: rng.empty,
+                })
+                .collect(),
+        })
+    }
+}
@@ -4,6 +4,8 @@ use std::fmt::{self, Debug, Display};
 use std::str::FromStr;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+mod parse;
+
 /// A span of time.
 ///
 /// Precision: microseconds.
@@ -1495,6 +1497,10 @@ impl Display for RelativeDuration {
             let seconds = minutes % 60_000_000;
             if seconds.abs() >= 1_000_000 {
                 write!(f, "{}", seconds / 1_000_000)?;
+            } else if seconds < 0 {
+                write!(f, "-0")?;
+            } else if seconds > 0 {
+                write!(f, "0")?;
             }
             let micros = seconds % 1_000_000;
             if micros.abs() > 0 {
@@ -1549,6 +1555,10 @@ fn relative_duration_display() {
     assert_eq!(dur.to_string(), "PT1M");
     let dur = RelativeDuration::from_secs(1);
     assert_eq!(dur.to_string(), "PT1S");
+    let dur = RelativeDuration::from_millis(400);
+    assert_eq!(dur.to_string(), "PT0.4S");
+    let dur = RelativeDuration::from_millis(-400);
+    assert_eq!(dur.to_string(), "PT-0.4S");
 }
 
 impl DateDuration {
@@ -1627,13 +1637,27 @@ impl Display for DateDuration {
 }
 
 fn nanos_to_micros(nanos: i64) -> i64 {
-    // round to the nearest even
-    let mut micros = nanos / 1000;
-    let remainder = nanos % 1000;
-    if remainder == 500 && micros % 2 == 1 || remainder > 500 {
-        micros += 1;
+    let micros = nanos / 1000;
+    micros + rounding_adjustment(micros, nanos % 1000)
+}
+
+/// Returns what to add to `micros` to account for `remainder` nanoseconds
+/// of the same sign
+fn rounding_adjustment(micros: i64, remainder: i64) -> i64 {
+    // round to the nearest even, symmetrically around zero
+    if remainder.abs() == 500 && micros % 2 != 0 || remainder.abs() > 500 {
+        remainder.signum()
+    } else {
+        0
     }
-    micros
+}
+
+#[cfg(any(feature = "time", feature = "jiff"))]
+fn secs_to_micros(secs: i64, subsec_nanos: i64) -> Result<i64, OutOfRangeError> {
+    // `subsec_nanos` must have the same sign as `secs` (or be non-negative)
+    secs.checked_mul(1_000_000)
+        .and_then(|x| x.checked_add(nanos_to_micros(subsec_nanos)))
+        .ok_or(OutOfRangeError)
 }
 
 #[cfg(feature = "chrono")]
@@ -1911,3 +1935,706 @@ mod chrono_interop {
         }
     }
 }
+
+#[cfg(feature = "time")]
+mod time_interop {
+    use super::*;
+    use time::{OffsetDateTime, PrimitiveDateTime};
+
+    /// Julian day number of 2000-01-01
+    const POSTGRES_EPOCH_JULIAN_DAY: i32 = 2_451_545;
+
+    fn unix_micros_to_local(micros: i64) -> Result<LocalDatetime, OutOfRangeError> {
+        let micros = micros
+            .checked_add(Datetime::UNIX_EPOCH.micros)
+            .ok_or(OutOfRangeError)?;
+        if !(LocalDatetime::MIN.micros..=LocalDatetime::MAX.micros).contains(&micros) {
+            return Err(OutOfRangeError);
+        }
+        Ok(LocalDatetime { micros })
+    }
+
+    impl From<&Datetime> for OffsetDateTime {
+        fn from(value: &Datetime) -> OffsetDateTime {
+            let nanos = i128::from(value.to_unix_micros()) * 1000;
+            OffsetDateTime::from_unix_timestamp_nanos(nanos)
+                .expect("OffsetDateTime range is bigger than Datetime")
+        }
+    }
+
+    impl TryFrom<&OffsetDateTime> for Datetime {
+        type Error = OutOfRangeError;
+        fn try_from(value: &OffsetDateTime) -> Result<Datetime, Self::Error> {
+            let micros = secs_to_micros(value.unix_timestamp(), value.nanosecond().into())?;
+            Datetime::try_from_unix_micros(micros)
+        }
+    }
+
+    impl From<&LocalDatetime> for PrimitiveDateTime {
+        fn from(value: &LocalDatetime) -> PrimitiveDateTime {
+            let utc = OffsetDateTime::from(value.to_utc());
+            PrimitiveDateTime::new(utc.date(), utc.time())
+        }
+    }
+
+    impl TryFrom<&PrimitiveDateTime> for LocalDatetime {
+        type Error = OutOfRangeError;
+        fn try_from(value: &PrimitiveDateTime) -> Result<LocalDatetime, Self::Error> {
+            let utc = value.assume_utc();
+            unix_micros_to_local(secs_to_micros(
+                utc.unix_timestamp(),
+                utc.nanosecond().into(),
+            )?)
+        }
+    }
+
+    impl From<&LocalDate> for time::Date {
+        fn from(value: &LocalDate) -> time::Date {
+            time::Date::from_julian_day(value.days + POSTGRES_EPOCH_JULIAN_DAY)
+                .expect("time::Date range is bigger than LocalDate")
+        }
+    }
+
+    impl TryFrom<&time::Date> for LocalDate {
+        type Error = OutOfRangeError;
+        fn try_from(value: &time::Date) -> Result<LocalDate, Self::Error> {
+            LocalDate::try_from_days(value.to_julian_day() - POSTGRES_EPOCH_JULIAN_DAY)
+        }
+    }
+
+    impl From<&LocalTime> for time::Time {
+        fn from(value: &LocalTime) -> time::Time {
+            let (hour, minute, second, microsecond) = value.to_hmsu();
+            time::Time::from_hms_micro(hour, minute, second, microsecond)
+                .expect("LocalTime and time::Time have equal range")
+        }
+    }
+
+    impl From<&time::Time> for LocalTime {
+        fn from(value: &time::Time) -> LocalTime {
+            let (hour, minute, second, nanosecond) = value.as_hms_nano();
+            let secs = u64::from(hour) * 3600 + u64::from(minute) * 60 + u64::from(second);
+            let mut micros = secs * 1_000_000 + nanos_to_micros(nanosecond.into()) as u64;
+
+            if micros >= MICROS_PER_DAY {
+                // this is only possible due to rounding:
+                // >= 23:59:59.999999500
+                micros -= MICROS_PER_DAY;
+            }
+
+            LocalTime { micros }
+        }
+    }
+
+    impl From<&Duration> for time::Duration {
+        fn from(value: &Duration) -> time::Duration {
+            time::Duration::microseconds(value.micros)
+        }
+    }
+
+    impl TryFrom<&time::Duration> for Duration {
+        type Error = OutOfRangeError;
+        fn try_from(value: &time::Duration) -> Result<Duration, Self::Error> {
+            let micros = secs_to_micros(value.whole_seconds(), value.subsec_nanoseconds().into())?;
+            Ok(Duration { micros })
+        }
+    }
+
+    /// Months have no fixed length, so only durations without months can
+    /// be converted. Days are converted as 24 hours.
+    impl TryFrom<&RelativeDuration> for time::Duration {
+        type Error = OutOfRangeError;
+        fn try_from(value: &RelativeDuration) -> Result<time::Duration, Self::Error> {
+            if value.months != 0 {
+                return Err(OutOfRangeError);
+            }
+            time::Duration::days(value.days.into())
+                .checked_add(time::Duration::microseconds(value.micros))
+                .ok_or(OutOfRangeError)
+        }
+    }
+
+    impl TryFrom<&time::Duration> for RelativeDuration {
+        type Error = OutOfRangeError;
+        fn try_from(value: &time::Duration) -> Result<RelativeDuration, Self::Error> {
+            let Duration { micros } = value.try_into()?;
+            Ok(RelativeDuration {
+                micros,
+                days: 0,
+                months: 0,
+            })
+        }
+    }
+
+    /// Months have no fixed length, so only durations without months can
+    /// be converted. Days are converted as 24 hours.
+    impl TryFrom<&DateDuration> for time::Duration {
+        type Error = OutOfRangeError;
+        fn try_from(value: &DateDuration) -> Result<time::Duration, Self::Error> {
+            if value.months != 0 {
+                return Err(OutOfRangeError);
+            }
+            Ok(time::Duration::days(value.days.into()))
+        }
+    }
+
+    /// Only a whole number of days can be converted.
+    impl TryFrom<&time::Duration> for DateDuration {
+        type Error = OutOfRangeError;
+        fn try_from(value: &time::Duration) -> Result<DateDuration, Self::Error> {
+            if *value != time::Duration::days(value.whole_days()) {
+                return Err(OutOfRangeError);
+            }
+            Ok(DateDuration {
+                days: value.whole_days().try_into().map_err(|_| OutOfRangeError)?,
+                months: 0,
+            })
+        }
+    }
+
+    impl From<Datetime> for OffsetDateTime {
+        fn from(value: Datetime) -> OffsetDateTime {
+            (&value).into()
+        }
+    }
+
+    impl TryFrom<OffsetDateTime> for Datetime {
+        type Error = OutOfRangeError;
+        fn try_from(value: OffsetDateTime) -> Result<Datetime, Self::Error> {
+            std::convert::TryFrom::try_from(&value)
+        }
+    }
+
+    impl From<LocalDatetime> for PrimitiveDateTime {
+        fn from(value: LocalDatetime) -> PrimitiveDateTime {
+            (&value).into()
+        }
+    }
+
+    impl TryFrom<PrimitiveDateTime> for LocalDatetime {
+        type Error = OutOfRangeError;
+        fn try_from(value: PrimitiveDateTime) -> Result<LocalDatetime, Self::Error> {
+            std::convert::TryFrom::try_from(&value)
+        }
+    }
+
+    impl From<LocalDate> for time::Date {
+        fn from(value: LocalDate) -> time::Date {
+            (&value).into()
+        }
+    }
+
+    impl TryFrom<time::Date> for LocalDate {
+        type Error = OutOfRangeError;
+        fn try_from(value: time::Date) -> Result<LocalDate, Self::Error> {
+            std::convert::TryFrom::try_from(&value)
+        }
+    }
+
+    impl From<LocalTime> for time::Time {
+        fn from(value: LocalTime) -> time::Time {
+            (&value).into()
+        }
+    }
+
+    impl From<time::Time> for LocalTime {
+        fn from(value: time::Time) -> LocalTime {
+            From::from(&value)
+        }
+    }
+
+    impl From<Duration> for time::Duration {
+        fn from(value: Duration) -> time::Duration {
+            (&value).into()
+        }
+    }
+
+    impl TryFrom<time::Duration> for Duration {
+        type Error = OutOfRangeError;
+        fn try_from(value: time::Duration) -> Result<Duration, Self::Error> {
+            std::convert::TryFrom::try_from(&value)
+        }
+    }
+
+    impl TryFrom<RelativeDuration> for time::Duration {
+        type Error = OutOfRangeError;
+        fn try_from(value: RelativeDuration) -> Result<time::Duration, Self::Error> {
+            std::convert::TryFrom::try_from(&value)
+        }
+    }
+
+    impl TryFrom<time::Duration> for RelativeDuration {
+        type Error = OutOfRangeError;
+        fn try_from(value: time::Duration) -> Result<RelativeDuration, Self::Error> {
+            std::convert::TryFrom::try_from(&value)
+        }
+    }
+
+    impl TryFrom<DateDuration> for time::Duration {
+        type Error = OutOfRangeError;
+        fn try_from(value: DateDuration) -> Result<time::Duration, Self::Error> {
+            std::convert::TryFrom::try_from(&value)
+        }
+    }
+
+    impl TryFrom<time::Duration> for DateDuration {
+        type Error = OutOfRangeError;
+        fn try_from(value: time::Duration) -> Result<DateDuration, Self::Error> {
+            std::convert::TryFrom::try_from(&value)
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use crate::model::time::test::{test_times, valid_test_dates};
+
+        #[test]
+        fn time_roundtrips() {
+            for (y, m, d) in valid_test_dates() {
+                let date = LocalDate::from_ymd(y, m, d);
+                let converted = time::Date::from(date);
+                assert_eq!(
+                    (
+                        converted.year(),
+                        u8::from(converted.month()),
+                        converted.day()
+                    ),
+                    (y, m, d)
+                );
+                assert_eq!(LocalDate::try_from(converted), Ok(date));
+
+                for micros in test_times() {
+                    let time_of_day = LocalTime::from_micros(micros);
+                    let converted = time::Time::from(time_of_day);
+                    assert_eq!(
+                        (
+                            converted.hour(),
+                            converted.minute(),
+                            converted.second(),
+                            converted.microsecond()
+                        ),
+                        time_of_day.to_hmsu()
+                    );
+                    assert_eq!(LocalTime::from(converted), time_of_day);
+
+                    let local = LocalDatetime::new(date, time_of_day);
+                    let converted = PrimitiveDateTime::from(local);
+                    assert_eq!(converted.date(), time::Date::from(date));
+                    assert_eq!(converted.time(), time::Time::from(time_of_day));
+                    assert_eq!(LocalDatetime::try_from(converted), Ok(local));
+
+                    let utc = local.to_utc();
+                    let converted = OffsetDateTime::from(utc);
+                    assert_eq!(converted, converted.to_offset(time::UtcOffset::UTC));
+                    assert_eq!(Datetime::try_from(converted), Ok(utc));
+                }
+            }
+        }
+
+        #[test]
+        fn out_of_range() {
+            let date = time::Date::from_calendar_date(0, time::Month::December, 31).unwrap();
+            assert!(LocalDate::try_from(date).is_err());
+            assert!(LocalDatetime::try_from(date.midnight()).is_err());
+            assert!(Datetime::try_from(date.midnight().assume_utc()).is_err());
+            let offset = time::UtcOffset::from_hms(1, 0, 0).unwrap();
+            let date = time::Date::from_calendar_date(1, time::Month::January, 1).unwrap();
+            assert!(LocalDatetime::try_from(date.midnight()).is_ok());
+            assert!(Datetime::try_from(date.midnight().assume_offset(offset)).is_err());
+            assert!(Duration::try_from(time::Duration::MAX).is_err());
+        }
+    }
+}
+
+#[cfg(feature = "jiff")]
+mod jiff_interop {
+    use super::*;
+    use jiff::civil;
+    use jiff::{SignedDuration, Span, Timestamp};
+
+    fn time_micros(value: &civil::Time) -> u64 {
+        let secs = value.hour() as u64 * 3600 + value.minute() as u64 * 60 + value.second() as u64;
+        // may be equal to MICROS_PER_DAY due to rounding
+        secs * 1_000_000 + nanos_to_micros(value.subsec_nanosecond().into()) as u64
+    }
+
+    fn same_sign(values: &[i64]) -> bool {
+        !(values.iter().any(|&x| x > 0) && values.iter().any(|&x| x < 0))
+    }
+
+    fn span_months_and_days(value: &Span) -> Result<(i32, i32), OutOfRangeError> {
+        let months = i32::from(value.get_years())
+            .checked_mul(12)
+            .and_then(|x| x.checked_add(value.get_months()))
+            .ok_or(OutOfRangeError)?;
+        let days = value
+            .get_weeks()
+            .checked_mul(7)
+            .and_then(|x| x.checked_add(value.get_days()))
+            .ok_or(OutOfRangeError)?;
+        Ok((months, days))
+    }
+
+    fn span_micros(value: &Span) -> Result<i64, OutOfRangeError> {
+        let nanos = value.get_nanoseconds();
+        i64::from(value.get_hours())
+            .checked_mul(MICROS_PER_HOUR)
+            .and_then(|x| x.checked_add(value.get_minutes().checked_mul(MICROS_PER_MINUTE)?))
+            .and_then(|x| x.checked_add(value.get_seconds().checked_mul(MICROS_PER_SECOND)?))
+            .and_then(|x| x.checked_add(value.get_milliseconds().checked_mul(MICROS_PER_MS)?))
+            .and_then(|x| x.checked_add(value.get_microseconds()))
+            .and_then(|x| x.checked_add(nanos / 1000))
+            .and_then(|x| x.checked_add(rounding_adjustment(x, nanos % 1000)))
+            .ok_or(OutOfRangeError)
+    }
+
+    fn to_span(months: i32, days: i32, micros: i64) -> Result<Span, OutOfRangeError> {
+        // all units of a span share the same sign
+        if !same_sign(&[months.into(), days.into(), micros]) {
+            return Err(OutOfRangeError);
+        }
+        Span::new()
+            .try_months(months)
+            .and_then(|x| x.try_days(days))
+            .and_then(|x| x.try_seconds(micros / MICROS_PER_SECOND))
+            .and_then(|x| x.try_microseconds(micros % MICROS_PER_SECOND))
+            .map_err(|_| OutOfRangeError)
+    }
+
+    /// Fails for datetimes after `9999-12-30T22:00:00Z`, which [`Timestamp`]
+    /// can't represent.
+    impl TryFrom<&Datetime> for Timestamp {
+        type Error = OutOfRangeError;
+        fn try_from(value: &Datetime) -> Result<Timestamp, Self::Error> {
+            Timestamp::from_microsecond(value.to_unix_micros()).map_err(|_| OutOfRangeError)
+        }
+    }
+
+    impl TryFrom<&Timestamp> for Datetime {
+        type Error = OutOfRangeError;
+        fn try_from(value: &Timestamp) -> Result<Datetime, Self::Error> {
+            let micros = secs_to_micros(value.as_second(), value.subsec_nanosecond().into())?;
+            Datetime::try_from_unix_micros(micros)
+        }
+    }
+
+    impl From<&LocalDatetime> for civil::DateTime {
+        fn from(value: &LocalDatetime) -> civil::DateTime {
+            civil::DateTime::from_parts(value.date().into(), value.time().into())
+        }
+    }
+
+    impl TryFrom<&civil::DateTime> for LocalDatetime {
+        type Error = OutOfRangeError;
+        fn try_from(value: &civil::DateTime) -> Result<LocalDatetime, Self::Error> {
+            let date = LocalDate::try_from(value.date())?;
+            let micros =
+                i64::from(date.days) * MICROS_PER_DAY as i64 + time_micros(&value.time()) as i64;
+            if !(LocalDatetime::MIN.micros..=LocalDatetime::MAX.micros).contains(&micros) {
+                return Err(OutOfRangeError);
+            }
+            Ok(LocalDatetime { micros })
+        }
+    }
+
+    impl From<&LocalDate> for civil::Date {
+        fn from(value: &LocalDate) -> civil::Date {
+            let (year, month, day) = value.to_ymd();
+            civil::Date::new(year as i16, month as i8, day as i8)
+                .expect("civil::Date range is bigger than LocalDate")
+        }
+    }
+
+    impl TryFrom<&civil::Date> for LocalDate {
+        type Error = OutOfRangeError;
+        fn try_from(value: &civil::Date) -> Result<LocalDate, Self::Error> {
+            LocalDate::try_from_ymd(value.year().into(), value.month() as u8, value.day() as u8)
+        }
+    }
+
+    impl From<&LocalTime> for civil::Time {
+        fn from(value: &LocalTime) -> civil::Time {
+            let (hour, minute, second, microsecond) = value.to_hmsu();
+            civil::Time::new(
+                hour as i8,
+                minute as i8,
+                second as i8,
+                microsecond as i32 * 1000,
+            )
+            .expect("LocalTime and civil::Time have equal range")
+        }
+    }
+
+    impl From<&civil::Time> for LocalTime {
+        fn from(value: &civil::Time) -> LocalTime {
+            let mut micros = time_micros(value);
+
+            if micros >= MICROS_PER_DAY {
+                // this is only possible due to rounding:
+                // >= 23:59:59.999999500
+                micros -= MICROS_PER_DAY;
+            }
+
+            LocalTime { micros }
+        }
+    }
+
+    impl From<&Duration> for SignedDuration {
+        fn from(value: &Duration) -> SignedDuration {
+            SignedDuration::from_micros(value.micros)
+        }
+    }
+
+    impl TryFrom<&SignedDuration> for Duration {
+        type Error = OutOfRangeError;
+        fn try_from(value: &SignedDuration) -> Result<Duration, Self::Error> {
+            let micros = secs_to_micros(value.as_secs(), value.subsec_nanos().into())?;
+            Ok(Duration { micros })
+        }
+    }
+
+    /// Fails if the components have different signs, as all units of a
+    /// [`Span`] share a single sign.
+    impl TryFrom<&RelativeDuration> for Span {
+        type Error = OutOfRangeError;
+        fn try_from(value: &RelativeDuration) -> Result<Span, Self::Error> {
+            to_span(value.months, value.days, value.micros)
+        }
+    }
+
+    /// Years and weeks are converted to months and days respectively.
+    impl TryFrom<&Span> for RelativeDuration {
+        type Error = OutOfRangeError;
+        fn try_from(value: &Span) -> Result<RelativeDuration, Self::Error> {
+            let (months, days) = span_months_and_days(value)?;
+            Ok(RelativeDuration {
+                micros: span_micros(value)?,
+                days,
+                months,
+            })
+        }
+    }
+
+    /// Fails if the components have different signs, as all units of a
+    /// [`Span`] share a single sign.
+    impl TryFrom<&DateDuration> for Span {
+        type Error = OutOfRangeError;
+        fn try_from(value: &DateDuration) -> Result<Span, Self::Error> {
+            to_span(value.months, value.days, 0)
+        }
+    }
+
+    /// Fails if the span has any units smaller than days.
+    impl TryFrom<&Span> for DateDuration {
+        type Error = OutOfRangeError;
+        fn try_from(value: &Span) -> Result<DateDuration, Self::Error> {
+            if span_micros(value)? != 0 {
+                return Err(OutOfRangeError);
+            }
+            let (months, days) = span_months_and_days(value)?;
+            Ok(DateDuration { days, months })
+        }
+    }
+
+    impl TryFrom<Datetime> for Timestamp {
+        type Error = OutOfRangeError;
+        fn try_from(value: Datetime) -> Result<Timestamp, Self::Error> {
+            std::convert::TryFrom::try_from(&value)
+        }
+    }
+
+    impl TryFrom<Timestamp> for Datetime {
+        type Error = OutOfRangeError;
+        fn try_from(value: Timestamp) -> Result<Datetime, Self::Error> {
+            std::convert::TryFrom::try_from(&value)
+        }
+    }
+
+    impl From<LocalDatetime> for civil::DateTime {
+        fn from(value: LocalDatetime) -> civil::DateTime {
+            (&value).into()
+        }
+    }
+
+    impl TryFrom<civil::DateTime> for LocalDatetime {
+        type Error = OutOfRangeError;
+        fn try_from(value: civil::DateTime) -> Result<LocalDatetime, Self::Error> {
+            std::convert::TryFrom::try_from(&value)
+        }
+    }
+
+    impl From<LocalDate> for civil::Date {
+        fn from(value: LocalDate) -> civil::Date {
+            (&value).into()
+        }
+    }
+
+    impl TryFrom<civil::Date> for LocalDate {
+        type Error = OutOfRangeError;
+        fn try_from(value: civil::Date) -> Result<LocalDate, Self::Error> {
+            std::convert::TryFrom::try_from(&value)
+        }
+    }
+
+    impl From<LocalTime> for civil::Time {
+        fn from(value: LocalTime) -> civil::Time {
+            (&value).into()
+        }
+    }
+
+    impl From<civil::Time> for LocalTime {
+        fn from(value: civil::Time) -> LocalTime {
+            From::from(&value)
+        }
+    }
+
+    impl From<Duration> for SignedDuration {
+        fn from(value: Duration) -> SignedDuration {
+            (&value).into()
+        }
+    }
+
+    impl TryFrom<SignedDuration> for Duration {
+        type Error = OutOfRangeError;
+        fn try_from(value: SignedDuration) -> Result<Duration, Self::Error> {
+            std::convert::TryFrom::try_from(&value)
+        }
+    }
+
+    impl TryFrom<RelativeDuration> for Span {
+        type Error = OutOfRangeError;
+        fn try_from(value: RelativeDuration) -> Result<Span, Self::Error> {
+            std::convert::TryFrom::try_from(&value)
+        }
+    }
+
+    impl TryFrom<Span> for RelativeDuration {
+        type Error = OutOfRangeError;
+        fn try_from(value: Span) -> Result<RelativeDuration, Self::Error> {
+            std::convert::TryFrom::try_from(&value)
+        }
+    }
+
+    impl TryFrom<DateDuration> for Span {
+        type Error = OutOfRangeError;
+        fn try_from(value: DateDuration) -> Result<Span, Self::Error> {
+            std::convert::TryFrom::try_from(&value)
+        }
+    }
+
+    impl TryFrom<Span> for DateDuration {
+        type Error = OutOfRangeError;
+        fn try_from(value: Span) -> Result<DateDuration, Self::Error> {
+            std::convert::TryFrom::try_from(&value)
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use crate::model::time::test::{test_times, valid_test_dates};
+
+        #[test]
+        fn jiff_roundtrips() {
+            for (y, m, d) in valid_test_dates() {
+                let date = LocalDate::from_ymd(y, m, d);
+                let converted = civil::Date::from(date);
+                assert_eq!(
+                    (
+                        converted.year().into(),
+                        converted.month() as u8,
+                        converted.day() as u8
+                    ),
+                    (y, m, d)
+                );
+                assert_eq!(LocalDate::try_from(converted), Ok(date));
+
+                for micros in test_times() {
+                    let time = LocalTime::from_micros(micros);
+                    let converted = civil::Time::from(time);
+                    let (hour, minute, second, microsecond) = time.to_hmsu();
+                    assert_eq!(
+                        (
+                            converted.hour(),
+                            converted.minute(),
+                            converted.second(),
+                            converted.subsec_nanosecond()
+                        ),
+                        (
+                            hour as i8,
+                            minute as i8,
+                            second as i8,
+                            microsecond as i32 * 1000
+                        )
+                    );
+                    assert_eq!(LocalTime::from(converted), time);
+
+                    let local = LocalDatetime::new(date, time);
+                    let converted = civil::DateTime::from(local);
+                    assert_eq!(converted.date(), civil::Date::from(date));
+                    assert_eq!(converted.time(), civil::Time::from(time));
+                    assert_eq!(LocalDatetime::try_from(converted), Ok(local));
+
+                    let utc = local.to_utc();
+                    if let Ok(converted) = Timestamp::try_from(utc) {
+                        assert_eq!(converted.as_microsecond(), utc.to_unix_micros());
+                        assert_eq!(Datetime::try_from(converted), Ok(utc));
+                    } else {
+                        assert!(utc.to_unix_micros() > Timestamp::MAX.as_microsecond());
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn durations() {
+            let span = Span::new().years(1).months(2).weeks(1).days(3).hours(4);
+            let span = span.minutes(5).seconds(6).milliseconds(7).microseconds(8);
+            let span = span.nanoseconds(8_500);
+            let rel = RelativeDuration::try_from(span).unwrap();
+            assert_eq!(rel.months, 14);
+            assert_eq!(rel.days, 10);
+            assert_eq!(rel.micros, 14_706_007_016);
+            assert!(DateDuration::try_from(span).is_err());
+
+            let rel = RelativeDuration {
+                micros: -1_500_000,
+                days: -2,
+                months: -3,
+            };
+            let span = Span::try_from(rel).unwrap();
+            assert_eq!(
+                span.fieldwise(),
+                Span::new()
+                    .months(-3)
+                    .days(-2)
+                    .seconds(-1)
+                    .microseconds(-500_000)
+            );
+            assert_eq!(RelativeDuration::try_from(span), Ok(rel));
+            let mixed = RelativeDuration {
+                micros: 1,
+                days: 0,
+                months: -1,
+            };
+            assert!(Span::try_from(mixed).is_err());
+
+            let date = DateDuration::from_years(2) + DateDuration::from_days(3);
+            let span = Span::try_from(date).unwrap();
+            assert_eq!(span.fieldwise(), Span::new().months(24).days(3));
+            assert_eq!(DateDuration::try_from(span), Ok(date));
+
+            let dur = SignedDuration::new(-3, -1_500);
+            assert_eq!(
+                Duration::try_from(dur),
+                Ok(Duration::from_micros(-3_000_002))
+            );
+            assert_eq!(
+                SignedDuration::from(Duration::from_micros(-3_000_002)),
+                SignedDuration::new(-3, -2_000)
+            );
+            assert!(Duration::try_from(SignedDuration::MAX).is_err());
+        }
+    }
+}
@@ -0,0 +1,516 @@
+//! Parsing of ISO 8601 dates, times and durations
+//!
+//! Accepts the formats the server accepts as well as the output of the
+//! `Display` and `Debug` implementations of the respective types.
+
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use super::{DateDuration, Datetime, LocalDate, LocalDatetime, LocalTime, RelativeDuration};
+use super::{MICROS_PER_DAY, MICROS_PER_HOUR, MICROS_PER_MINUTE, MICROS_PER_MS, MICROS_PER_SECOND};
+use crate::model::{ParseDatetimeError, ParseDurationError};
+
+struct Error {
+    message: &'static str,
+    pos: usize,
+}
+
+impl From<Error> for ParseDatetimeError {
+    fn from(e: Error) -> ParseDatetimeError {
+        ParseDatetimeError {
+            message: e.message.into(),
+            pos: e.pos,
+        }
+    }
+}
+
+impl From<Error> for ParseDurationError {
+    fn from(e: Error) -> ParseDurationError {
+        ParseDurationError::new(e.message).pos(e.pos)
+    }
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str) -> Parser<'a> {
+        Parser { src, pos: 0 }
+    }
+
+    fn error(&self, message: &'static str) -> Error {
+        Error {
+            message,
+            pos: self.pos,
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.as_bytes().get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        if self.peek().map(|x| x.to_ascii_uppercase()) == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_sign(&mut self) -> bool {
+        if self.eat(b'-') {
+            true
+        } else {
+            self.eat(b'+');
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn digits(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest.bytes().take_while(u8::is_ascii_digit).count();
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn number<T: FromStr>(&mut self, len: usize, what: &'static str) -> Result<T, Error> {
+        let start = self.pos;
+        let digits = self.rest().get(..len).unwrap_or("");
+        if digits.len() != len || !digits.bytes().all(|c| c.is_ascii_digit()) {
+            return Err(self.error(what));
+        }
+        self.pos += len;
+        digits.parse().map_err(|_| Error {
+            message: what,
+            pos: start,
+        })
+    }
+
+    fn integer(&mut self) -> Result<i64, Error> {
+        let start = self.pos;
+        let digits = self.digits();
+        if digits.is_empty() {
+            return Err(self.error("expected digits"));
+        }
+        digits.parse().map_err(|_| Error {
+            message: "value is out of range",
+            pos: start,
+        })
+    }
+
+    fn expect(&mut self, c: u8, message: &'static str) -> Result<(), Error> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    fn expect_end(&self) -> Result<(), Error> {
+        if self.pos == self.src.len() {
+            Ok(())
+        } else {
+            Err(self.error("unexpected trailing characters"))
+        }
+    }
+
+    /// Parses digits after the decimal point into microseconds
+    ///
+    /// Rounds to the nearest even microsecond.
+    fn fraction(&mut self) -> Result<i64, Error> {
+        let digits = self.digits();
+        if digits.is_empty() {
+            return Err(self.error("expected fractional digits"));
+        }
+        let mut micros = 0;
+        let mut remainder = 0;
+        for (i, c) in digits.bytes().take(9).enumerate() {
+            let digit = i64::from(c - b'0');
+            if i < 6 {
+                micros += digit * 10_i64.pow(5 - i as u32);
+            } else {
+                remainder += digit * 10_i64.pow(8 - i as u32);
+            }
+        }
+        if remainder == 500 && digits.bytes().skip(9).any(|c| c != b'0') {
+            // more than half after all
+            remainder += 1;
+        }
+        if remainder > 500 || remainder == 500 && micros % 2 == 1 {
+            micros += 1;
+        }
+        Ok(micros)
+    }
+
+    fn date(&mut self) -> Result<LocalDate, Error> {
+        let start = self.pos;
+        let year_negative = self.eat_sign();
+        let year_digits = self.digits();
+        if year_digits.len() < 4 {
+            self.pos = start;
+            return Err(self.error("expected 4-digit year"));
+        }
+        self.expect(b'-', "expected `-`")?;
+        let month = self.number(2, "expected 2-digit month")?;
+        self.expect(b'-', "expected `-`")?;
+        let day = self.number(2, "expected 2-digit day")?;
+        year_digits
+            .parse::<i32>()
+            .ok()
+            .filter(|_| !year_negative)
+            .and_then(|year| LocalDate::try_from_ymd(year, month, day).ok())
+            .ok_or(Error {
+                message: "invalid date or date is out of range",
+                pos: start,
+            })
+    }
+
+    /// Returns microseconds since midnight, which may be a full day due to
+    /// rounding
+    fn time(&mut self) -> Result<u64, Error> {
+        let hour: u64 = self.number(2, "expected 2-digit hour")?;
+        if hour > 23 {
+            return Err(self.error("hour value out of range"));
+        }
+        self.expect(b':', "expected `:`")?;
+        let minute: u64 = self.number(2, "expected 2-digit minute")?;
+        if minute > 59 {
+            return Err(self.error("minute value out of range"));
+        }
+        let mut second = 0;
+        let mut micros = 0;
+        if self.eat(b':') {
+            second = self.number(2, "expected 2-digit second")?;
+            if second > 59 {
+                return Err(self.error("second value out of range"));
+            }
+            if self.eat(b'.') || self.eat(b',') {
+                micros = self.fraction()? as u64;
+            }
+        }
+        Ok((hour * 3600 + minute * 60 + second) * 1_000_000 + micros)
+    }
+
+    /// Returns offset from UTC in seconds
+    fn offset(&mut self) -> Result<i64, Error> {
+        self.skip_whitespace();
+        if self.eat(b'Z') {
+            return Ok(0);
+        }
+        if self
+            .rest()
+            .get(..3)
+            .is_some_and(|s| s.eq_ignore_ascii_case("UTC"))
+        {
+            self.pos += 3;
+            return Ok(0);
+        }
+        let negative = match self.peek() {
+            Some(b'+') => false,
+            Some(b'-') => true,
+            _ => return Err(self.error("expected timezone offset")),
+        };
+        self.pos += 1;
+        let hours: i64 = self.number(2, "expected 2-digit hour offset")?;
+        let mut minutes: i64 = 0;
+        if self.eat(b':') || self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            minutes = self.number(2, "expected 2-digit minute offset")?;
+        }
+        if hours > 15 || minutes > 59 {
+            return Err(self.error("timezone offset out of range"));
+        }
+        let offset = hours * 3600 + minutes * 60;
+        Ok(if negative { -offset } else { offset })
+    }
+
+    /// Parses date and time, returning microseconds since 2000-01-01 without
+    /// checking the range
+    fn local_datetime(&mut self) -> Result<i64, Error> {
+        let date = self.date()?;
+        if !(self.eat(b'T') || self.eat(b' ')) {
+            return Err(self.error("expected `T` or space"));
+        }
+        let time = self.time()?;
+        Ok(i64::from(date.days) * MICROS_PER_DAY as i64 + time as i64)
+    }
+
+    /// Parses a signed number of units, with optional fraction
+    ///
+    /// Returns the integer value and the fractional part in microseconds,
+    /// which has the same sign as the integer value.
+    fn component(&mut self, negative: bool) -> Result<(i64, i64), Error> {
+        let negative = self.eat_sign() != negative;
+        let start = self.pos;
+        let value = if self.peek() == Some(b'.') {
+            0
+        } else {
+            self.integer()?
+        };
+        let fraction = if self.eat(b'.') || self.eat(b',') {
+            self.fraction()?
+        } else {
+            0
+        };
+        if self.pos == start {
+            return Err(self.error("expected number"));
+        }
+        if negative {
+            Ok((-value, -fraction))
+        } else {
+            Ok((value, fraction))
+        }
+    }
+
+    fn relative_duration(&mut self) -> Result<RelativeDuration, Error> {
+        let mut acc = DurationAccumulator::default();
+        let negative = self.eat_sign();
+        if self.eat(b'P') {
+            let mut time = false;
+            let mut empty = true;
+            while self.pos < self.src.len() {
+                if !time && self.eat(b'T') {
+                    time = true;
+                    continue;
+                }
+                let start = self.pos;
+                let (value, fraction) = self.component(negative)?;
+                let unit = match (self.peek().map(|c| c.to_ascii_uppercase()), time) {
+                    (Some(b'Y'), false) => Unit::Year,
+                    (Some(b'M'), false) => Unit::Month,
+                    (Some(b'W'), false) => Unit::Week,
+                    (Some(b'D'), false) => Unit::Day,
+                    (Some(b'H'), true) => Unit::Hour,
+                    (Some(b'M'), true) => Unit::Minute,
+                    (Some(b'S'), true) => Unit::Second,
+                    _ => return Err(self.error("expected unit")),
+                };
+                self.pos += 1;
+                acc.add(unit, value, fraction).map_err(|message| Error {
+                    message,
+                    pos: start,
+                })?;
+                empty = false;
+            }
+            if empty {
+                return Err(self.error("expected at least one component"));
+            }
+        } else {
+            // PostgreSQL format: `1 year 2 months -3 hours`
+            self.pos = 0;
+            self.skip_whitespace();
+            if self.pos == self.src.len() {
+                return Err(self.error("expected number"));
+            }
+            while self.pos < self.src.len() {
+                let start = self.pos;
+                let (value, fraction) = self.component(false)?;
+                self.skip_whitespace();
+                let unit_start = self.pos;
+                let unit_len = self
+                    .rest()
+                    .bytes()
+                    .take_while(u8::is_ascii_alphabetic)
+                    .count();
+                self.pos += unit_len;
+                let unit = Unit::from_name(&self.src[unit_start..self.pos]).ok_or(Error {
+                    message: "unknown unit",
+                    pos: unit_start,
+                })?;
+                acc.add(unit, value, fraction).map_err(|message| Error {
+                    message,
+                    pos: start,
+                })?;
+                self.skip_whitespace();
+            }
+        }
+        Ok(acc.finish())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Unit {
+    Year,
+    Month,
+    Week,
+    Day,
+    Hour,
+    Minute,
+    Second,
+    Millisecond,
+    Microsecond,
+}
+
+impl Unit {
+    fn from_name(name: &str) -> Option<Unit> {
+        let unit = match name.to_lowercase().as_str() {
+            "y" | "year" | "years" => Unit::Year,
+            "mon" | "mons" | "month" | "months" => Unit::Month,
+            "w" | "week" | "weeks" => Unit::Week,
+            "d" | "day" | "days" => Unit::Day,
+            "h" | "hr" | "hrs" | "hour" | "hours" => Unit::Hour,
+            "m" | "min" | "mins" | "minute" | "minutes" => Unit::Minute,
+            "s" | "sec" | "secs" | "second" | "seconds" => Unit::Second,
+            "ms" | "millisecond" | "milliseconds" => Unit::Millisecond,
+            "us" | "microsecond" | "microseconds" => Unit::Microsecond,
+            _ => return None,
+        };
+        Some(unit)
+    }
+}
+
+#[derive(Default)]
+struct DurationAccumulator {
+    months: i32,
+    days: i32,
+    micros: i64,
+    seen: Vec<Unit>,
+}
+
+impl DurationAccumulator {
+    fn add(&mut self, unit: Unit, value: i64, fraction: i64) -> Result<(), &'static str> {
+        const OUT_OF_RANGE: &str = "value is out of range";
+        if self.seen.contains(&unit) {
+            return Err("specified more than once");
+        }
+        self.seen.push(unit);
+        if fraction != 0 && unit != Unit::Second {
+            return Err("fractional values are only allowed for seconds");
+        }
+        let (months, days, micros) = match unit {
+            Unit::Year => (value.checked_mul(12).ok_or(OUT_OF_RANGE)?, 0, Some(0)),
+            Unit::Month => (value, 0, Some(0)),
+            Unit::Week => (0, value.checked_mul(7).ok_or(OUT_OF_RANGE)?, Some(0)),
+            Unit::Day => (0, value, Some(0)),
+            Unit::Hour => (0, 0, value.checked_mul(MICROS_PER_HOUR)),
+            Unit::Minute => (0, 0, value.checked_mul(MICROS_PER_MINUTE)),
+            Unit::Second => (
+                0,
+                0,
+                value
+                    .checked_mul(MICROS_PER_SECOND)
+                    .and_then(|x| x.checked_add(fraction)),
+            ),
+            Unit::Millisecond => (0, 0, value.checked_mul(MICROS_PER_MS)),
+            Unit::Microsecond => (0, 0, Some(value)),
+        };
+        self.months = i32::try_from(months)
+            .ok()
+            .and_then(|x| self.months.checked_add(x))
+            .ok_or(OUT_OF_RANGE)?;
+        self.days = i32::try_from(days)
+            .ok()
+            .and_then(|x| self.days.checked_add(x))
+            .ok_or(OUT_OF_RANGE)?;
+        self.micros = micros
+            .and_then(|x| self.micros.checked_add(x))
+            .ok_or(OUT_OF_RANGE)?;
+        Ok(())
+    }
+
+    fn finish(self) -> RelativeDuration {
+        RelativeDuration {
+            micros: self.micros,
+            days: self.days,
+            months: self.months,
+        }
+    }
+}
+
+/// Parses ISO 8601 datetime with a mandatory timezone offset, e.g.
+/// `2024-01-02T03:04:05.678Z`, `2024-01-02 03:04:05+02:00` or
+/// `2024-01-02 03:04:05.678 UTC`
+impl FromStr for Datetime {
+    type Err = ParseDatetimeError;
+    fn from_str(s: &str) -> Result<Datetime, ParseDatetimeError> {
+        let mut parser = Parser::new(s);
+        let local = parser.local_datetime()?;
+        let offset = parser.offset()?;
+        parser.expect_end()?;
+        let micros = local - offset * MICROS_PER_SECOND;
+        Datetime::from_postgres_micros(micros).map_err(|_| ParseDatetimeError {
+            message: "datetime is out of range".into(),
+            pos: 0,
+        })
+    }
+}
+
+/// Parses ISO 8601 datetime without timezone, e.g. `2024-01-02T03:04:05.678`
+/// or `2024-01-02 03:04:05`
+impl FromStr for LocalDatetime {
+    type Err = ParseDatetimeError;
+    fn from_str(s: &str) -> Result<LocalDatetime, ParseDatetimeError> {
+        let mut parser = Parser::new(s);
+        let micros = parser.local_datetime()?;
+        parser.expect_end()?;
+        LocalDatetime::from_postgres_micros(micros).map_err(|_| ParseDatetimeError {
+            message: "datetime is out of range".into(),
+            pos: 0,
+        })
+    }
+}
+
+/// Parses ISO 8601 date, e.g. `2024-01-02`
+impl FromStr for LocalDate {
+    type Err = ParseDatetimeError;
+    fn from_str(s: &str) -> Result<LocalDate, ParseDatetimeError> {
+        let mut parser = Parser::new(s);
+        let date = parser.date()?;
+        parser.expect_end()?;
+        Ok(date)
+    }
+}
+
+/// Parses ISO 8601 time, e.g. `03:04`, `03:04:05` or `03:04:05.678`
+impl FromStr for LocalTime {
+    type Err = ParseDatetimeError;
+    fn from_str(s: &str) -> Result<LocalTime, ParseDatetimeError> {
+        let mut parser = Parser::new(s);
+        let micros = parser.time()?;
+        parser.expect_end()?;
+        // only a full day is possible due to rounding: >= 23:59:59.9999995
+        Ok(LocalTime {
+            micros: micros % MICROS_PER_DAY,
+        })
+    }
+}
+
+/// Parses ISO 8601 duration, e.g. `P1Y2M3DT4H5M6.7S`, or PostgreSQL
+/// interval format, e.g. `1 year 2 months 3 days 4 hours`
+impl FromStr for RelativeDuration {
+    type Err = ParseDurationError;
+    fn from_str(s: &str) -> Result<RelativeDuration, ParseDurationError> {
+        Ok(Parser::new(s).relative_duration()?)
+    }
+}
+
+/// Parses ISO 8601 duration, e.g. `P1Y2M3D`, or PostgreSQL interval format,
+/// e.g. `1 year 2 months 3 days`
+impl FromStr for DateDuration {
+    type Err = ParseDurationError;
+    fn from_str(s: &str) -> Result<DateDuration, ParseDurationError> {
+        if s == "PT0D" {
+            // the way `Display` formats empty duration
+            return Ok(DateDuration { days: 0, months: 0 });
+        }
+        let duration = Parser::new(s).relative_duration()?;
+        if duration.micros != 0 {
+            return Err(ParseDurationError::new("time units are not allowed"));
+        }
+        Ok(DateDuration {
+            days: duration.days,
+            months: duration.months,
+        })
+    }
+}
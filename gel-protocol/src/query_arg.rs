@@ -2,6 +2,7 @@
 Contains the [QueryArg] and [QueryArgs] traits.
 */
 
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::convert::{TryFrom, TryInto};
 use std::ops::Deref;
 use std::sync::Arc;
@@ -12,7 +13,8 @@ use uuid::Uuid;
 
 use gel_errors::ParameterTypeMismatchError;
 use gel_errors::{ClientEncodingError, DescriptorMismatch, ProtocolError};
-use gel_errors::{Error, ErrorKind, InvalidReferenceError};
+use gel_errors::{ErrorKind, InvalidReferenceError};
+use gel_errors::{MissingArgumentError, UnknownArgumentError};
 
 use crate::codec::{self, build_codec, Codec};
 use crate::descriptors::TypePos;
@@ -22,6 +24,9 @@ use crate::features::ProtocolVersion;
 use crate::model::range;
 use crate::value::Value;
 
+/// Error returned when encoding arguments
+pub use gel_errors::Error;
+
 pub struct Encoder<'a> {
     pub ctx: &'a DescriptorContext<'a>,
     pub buf: &'a mut BytesMut,
@@ -79,6 +84,56 @@ impl<'a> Encoder<'a> {
 
         Ok(())
     }
+    /// Encode named arguments, used by `#[derive(QueryArgs)]`
+    ///
+    /// Arguments are matched to the query parameters by their `names`, and
+    /// `encode` is called for each parameter, in the order expected by the
+    /// server, with the index of the argument in `names`.
+    pub fn named_args(
+        &mut self,
+        names: &[&str],
+        mut encode: impl FnMut(&mut Encoder, usize, TypePos) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let Some(root_pos) = self.ctx.root_pos else {
+            if let Some(name) = names.first() {
+                return Err(UnknownArgumentError::with_message(format!(
+                    "unexpected argument ${name}, the query has no arguments"
+                )));
+            }
+            return Ok(());
+        };
+        let desc = self.ctx.get(root_pos)?;
+        let Descriptor::ObjectShape(desc) = desc else {
+            return Err(self.ctx.wrong_type(desc, "object"));
+        };
+        let mut indices = Vec::with_capacity(desc.elements.len());
+        for el in &desc.elements {
+            let Some(index) = names.iter().position(|name| *name == el.name) else {
+                return Err(MissingArgumentError::with_message(format!(
+                    "missing argument ${}",
+                    el.name
+                )));
+            };
+            indices.push((index, el.type_pos));
+        }
+        if let Some(name) = names
+            .iter()
+            .find(|name| !desc.elements.iter().any(|el| el.name == **name))
+        {
+            return Err(UnknownArgumentError::with_message(format!(
+                "unexpected argument ${name}"
+            )));
+        }
+
+        self.buf.reserve(4 + 8 * indices.len());
+        self.buf.put_u32(indices.len() as u32);
+        for (index, type_pos) in indices {
+            self.buf.reserve(8);
+            self.buf.put_u32(0);
+            encode(self, index, type_pos)?;
+        }
+        Ok(())
+    }
 }
 
 impl DescriptorContext<'_> {
@@ -202,6 +257,7 @@ impl QueryArg for Value {
             Array(v) => v.encode_slot(enc)?,
             Enum(v) => v.encode_slot(enc)?,
             Range(v) => v.encode_slot(enc)?,
+            MultiRange(v) => v.encode_slot(enc)?,
             Vector(v) => crate::model::VectorRef(v).encode_slot(enc)?,
             PostGisGeometry(v) => v.encode_slot(enc)?,
             PostGisGeography(v) => v.encode_slot(enc)?,
@@ -247,6 +303,8 @@ impl QueryArg for Value {
                 let val = val.deref();
                 check_enum(val, &members)
             }
+            (Value::Range(val), Descriptor::Range(_)) => val.check_descriptor(ctx, pos),
+            (Value::MultiRange(val), Descriptor::MultiRange(_)) => val.check_descriptor(ctx, pos),
             (Vector(_), BaseScalar(d)) if d.id == codec::PGVECTOR_VECTOR => Ok(()),
             (PostGisGeometry(_), BaseScalar(d)) if d.id == codec::POSTGIS_GEOMETRY => Ok(()),
             (PostGisGeography(_), BaseScalar(d)) if d.id == codec::POSTGIS_GEOGRAPHY => Ok(()),
@@ -331,76 +389,119 @@ impl<T: ScalarArg> QueryArg for Option<T> {
     }
 }
 
-impl<T: ScalarArg> QueryArg for Vec<T> {
-    fn encode_slot(&self, enc: &mut Encoder) -> Result<(), Error> {
-        enc.buf.reserve(8);
-        enc.length_prefixed(|enc| {
-            if self.is_empty() {
-                enc.buf.reserve(12);
-                enc.buf.put_u32(0); // ndims
-                enc.buf.put_u32(0); // reserved0
-                enc.buf.put_u32(0); // reserved1
-                return Ok(());
-            }
-            enc.buf.reserve(20);
-            enc.buf.put_u32(1); // ndims
+/// Encodes `items` as an array, each item is encoded using `encode`
+fn encode_array<I: ExactSizeIterator>(
+    enc: &mut Encoder,
+    items: I,
+    mut encode: impl FnMut(&mut Encoder, I::Item) -> Result<(), Error>,
+) -> Result<(), Error> {
+    enc.buf.reserve(8);
+    enc.length_prefixed(|enc| {
+        if items.len() == 0 {
+            enc.buf.reserve(12);
+            enc.buf.put_u32(0); // ndims
             enc.buf.put_u32(0); // reserved0
             enc.buf.put_u32(0); // reserved1
-            enc.buf.put_u32(
-                self.len()
-                    .try_into()
-                    .map_err(|_| ClientEncodingError::with_message("array is too long"))?,
-            );
-            enc.buf.put_u32(1); // lower
-            for item in self {
-                enc.length_prefixed(|enc| item.encode(enc))?;
-            }
-            Ok(())
-        })
-    }
-    fn check_descriptor(&self, ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
-        let desc = ctx.get(pos)?;
-        if let Descriptor::Array(arr) = desc {
-            T::check_descriptor(ctx, arr.type_pos)
-        } else {
-            Err(ctx.wrong_type(desc, "array"))
+            return Ok(());
         }
-    }
-    fn to_value(&self) -> Result<Value, Error> {
-        Ok(Value::Array(
-            self.iter()
-                .map(|v| v.to_value())
-                .collect::<Result<_, _>>()?,
-        ))
+        enc.buf.reserve(20);
+        enc.buf.put_u32(1); // ndims
+        enc.buf.put_u32(0); // reserved0
+        enc.buf.put_u32(0); // reserved1
+        enc.buf.put_u32(
+            items
+                .len()
+                .try_into()
+                .map_err(|_| ClientEncodingError::with_message("array is too long"))?,
+        );
+        enc.buf.put_u32(1); // lower
+        for item in items {
+            enc.length_prefixed(|enc| encode(enc, item))?;
+        }
+        Ok(())
+    })
+}
+
+/// Returns the type of elements of the array at `pos`
+fn array_element(ctx: &DescriptorContext, pos: TypePos) -> Result<TypePos, Error> {
+    let desc = ctx.get(pos)?;
+    if let Descriptor::Array(arr) = desc {
+        Ok(arr.type_pos)
+    } else {
+        Err(ctx.wrong_type(desc, "array"))
     }
 }
 
-impl QueryArg for Vec<Value> {
-    fn encode_slot(&self, enc: &mut Encoder) -> Result<(), Error> {
-        enc.buf.reserve(8);
-        enc.length_prefixed(|enc| {
-            if self.is_empty() {
-                enc.buf.reserve(12);
-                enc.buf.put_u32(0); // ndims
-                enc.buf.put_u32(0); // reserved0
-                enc.buf.put_u32(0); // reserved1
-                return Ok(());
+macro_rules! implement_array {
+    ([$($generics:tt)*] $collection:ty) => {
+        impl<$($generics)*> QueryArg for $collection {
+            fn encode_slot(&self, enc: &mut Encoder) -> Result<(), Error> {
+                encode_array(enc, self.iter(), |enc, item| item.encode(enc))
             }
-            enc.buf.reserve(20);
-            enc.buf.put_u32(1); // ndims
-            enc.buf.put_u32(0); // reserved0
-            enc.buf.put_u32(0); // reserved1
-            enc.buf.put_u32(
-                self.len()
-                    .try_into()
-                    .map_err(|_| ClientEncodingError::with_message("array is too long"))?,
-            );
-            enc.buf.put_u32(1); // lower
-            for item in self {
-                enc.length_prefixed(|enc| item.encode(enc))?;
+            fn check_descriptor(&self, ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+                T::check_descriptor(ctx, array_element(ctx, pos)?)
             }
-            Ok(())
-        })
+            fn to_value(&self) -> Result<Value, Error> {
+                Ok(Value::Array(
+                    self.iter()
+                        .map(|v| v.to_value())
+                        .collect::<Result<_, _>>()?,
+                ))
+            }
+        }
+    };
+}
+
+implement_array!([T: ScalarArg] Vec<T>);
+implement_array!([T: ScalarArg] VecDeque<T>);
+implement_array!([T: ScalarArg] Box<[T]>);
+implement_array!([T: ScalarArg] Arc<[T]>);
+implement_array!([T: ScalarArg] BTreeSet<T>);
+implement_array!([T: ScalarArg, S: Send + Sync] HashSet<T, S>);
+
+/// Maps are encoded as arrays of tuples of keys and values
+macro_rules! implement_map {
+    ([$($generics:tt)*] $map:ty) => {
+        impl<$($generics)*> QueryArg for $map {
+            fn encode_slot(&self, enc: &mut Encoder) -> Result<(), Error> {
+                encode_array(enc, self.iter(), |enc, (key, value)| {
+                    enc.buf.reserve(4);
+                    enc.buf.put_u32(2);
+                    enc.buf.reserve(4);
+                    enc.buf.put_u32(0); // reserved
+                    enc.length_prefixed(|enc| key.encode(enc))?;
+                    enc.buf.reserve(4);
+                    enc.buf.put_u32(0); // reserved
+                    enc.length_prefixed(|enc| value.encode(enc))
+                })
+            }
+            fn check_descriptor(&self, ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+                let desc = ctx.get(array_element(ctx, pos)?)?;
+                match desc {
+                    Descriptor::Tuple(tuple) if tuple.element_types.len() == 2 => {
+                        K::check_descriptor(ctx, tuple.element_types[0])?;
+                        V::check_descriptor(ctx, tuple.element_types[1])
+                    }
+                    _ => Err(ctx.wrong_type(desc, "tuple of key and value")),
+                }
+            }
+            fn to_value(&self) -> Result<Value, Error> {
+                Ok(Value::Array(
+                    self.iter()
+                        .map(|(k, v)| Ok(Value::Tuple(vec![k.to_value()?, v.to_value()?])))
+                        .collect::<Result<_, Error>>()?,
+                ))
+            }
+        }
+    };
+}
+
+implement_map!([K: ScalarArg, V: ScalarArg] BTreeMap<K, V>);
+implement_map!([K: ScalarArg, V: ScalarArg, S: Send + Sync] HashMap<K, V, S>);
+
+impl QueryArg for Vec<Value> {
+    fn encode_slot(&self, enc: &mut Encoder) -> Result<(), Error> {
+        encode_array(enc, self.iter(), |enc, item| item.encode(enc))
     }
     fn check_descriptor(&self, ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
         let desc = ctx.get(pos)?;
@@ -445,11 +546,11 @@ impl QueryArg for range::Range<Box<Value>> {
             encoder.buf.put_u8(flags as u8);
 
             if let Some(lower) = &self.lower {
-                encoder.length_prefixed(|encoder| lower.encode(encoder))?
+                lower.encode_slot(encoder)?;
             }
 
             if let Some(upper) = &self.upper {
-                encoder.length_prefixed(|encoder| upper.encode(encoder))?;
+                upper.encode_slot(encoder)?;
             }
             Ok(())
         })
@@ -457,15 +558,7 @@ impl QueryArg for range::Range<Box<Value>> {
     fn check_descriptor(&self, ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
         let desc = ctx.get(pos)?;
         if let Descriptor::Range(rng) = desc {
-            self.lower
-                .as_ref()
-                .map(|v| v.check_descriptor(ctx, rng.type_pos))
-                .transpose()?;
-            self.upper
-                .as_ref()
-                .map(|v| v.check_descriptor(ctx, rng.type_pos))
-                .transpose()?;
-            Ok(())
+            check_range_bounds(self, ctx, rng.type_pos)
         } else {
             Err(ctx.wrong_type(desc, "range"))
         }
@@ -475,6 +568,51 @@ impl QueryArg for range::Range<Box<Value>> {
     }
 }
 
+fn check_range_bounds(
+    rng: &range::Range<Box<Value>>,
+    ctx: &DescriptorContext,
+    element: TypePos,
+) -> Result<(), Error> {
+    rng.lower
+        .as_ref()
+        .map(|v| v.check_descriptor(ctx, element))
+        .transpose()?;
+    rng.upper
+        .as_ref()
+        .map(|v| v.check_descriptor(ctx, element))
+        .transpose()?;
+    Ok(())
+}
+
+impl QueryArg for range::MultiRange<Box<Value>> {
+    fn encode_slot(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        encoder.length_prefixed(|encoder| {
+            let len = u32::try_from(self.ranges.len())
+                .map_err(|_| ClientEncodingError::with_message("multirange is too long"))?;
+            encoder.buf.reserve(4);
+            encoder.buf.put_u32(len);
+            for rng in &self.ranges {
+                rng.encode_slot(encoder)?;
+            }
+            Ok(())
+        })
+    }
+    fn check_descriptor(&self, ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        let desc = ctx.get(pos)?;
+        if let Descriptor::MultiRange(rng) = desc {
+            for item in &self.ranges {
+                check_range_bounds(item, ctx, rng.type_pos)?;
+            }
+            Ok(())
+        } else {
+            Err(ctx.wrong_type(desc, "multirange"))
+        }
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        Ok(Value::MultiRange(self.clone()))
+    }
+}
+
 macro_rules! implement_tuple {
     ( $count:expr, $($name:ident,)+ ) => {
         impl<$($name:QueryArg),+> QueryArgs for ($($name,)+) {
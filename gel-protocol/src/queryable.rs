@@ -2,6 +2,7 @@
 Contains the [Queryable] trait.
 */
 use snafu::{ensure, Snafu};
+use std::collections::HashMap;
 use std::default::Default;
 use std::sync::Arc;
 
@@ -39,6 +40,55 @@ pub trait Queryable: Sized {
     ) -> Result<Self::Args, DescriptorMismatch>;
 }
 
+/// Fields of a structure matched against elements of a shape by name
+///
+/// This is an implementation detail of `#[derive(Queryable)]`, which
+/// implements it for structures with named fields. Fields marked
+/// `#[gel(flatten)]` are decoded using this trait from the elements of the
+/// enclosing shape.
+#[doc(hidden)]
+pub trait QueryableFields: Sized {
+    type FieldArgs;
+
+    /// Decode fields, `order` holds positions of the elements in `fields`,
+    /// starting at `*slot`, in the order they were looked up by
+    /// [`check_fields`](QueryableFields::check_fields)
+    fn decode_fields(
+        decoder: &Decoder,
+        args: &Self::FieldArgs,
+        fields: &[Option<&[u8]>],
+        order: &[usize],
+        slot: &mut usize,
+    ) -> Result<Self, DecodeError>;
+    /// Take elements of the fields out of `elements` (mapping names to
+    /// positions and types) and push their positions to `order`
+    fn check_fields(
+        ctx: &DescriptorContext,
+        elements: &mut HashMap<&str, (usize, TypePos)>,
+        order: &mut Vec<usize>,
+    ) -> Result<Self::FieldArgs, DescriptorMismatch>;
+}
+
+/// Data returned by [Queryable::check_descriptor] of maps
+///
+/// Maps are decoded either from arrays (or sets) of 2-tuples of keys and
+/// values, or from objects and named tuples whose element names are keys.
+pub struct MapArgs<K: Queryable, V: Queryable> {
+    pub(crate) kind: MapKind<K, V>,
+}
+
+pub(crate) enum MapKind<K: Queryable, V: Queryable> {
+    Pairs {
+        key: K::Args,
+        value: V::Args,
+    },
+    Elements {
+        tuple: bool,
+        /// `None` for implicit elements of objects, which are skipped
+        elements: Vec<Option<(String, V::Args)>>,
+    },
+}
+
 #[derive(Snafu, Debug)]
 #[non_exhaustive]
 pub enum DescriptorMismatch {
@@ -4,6 +4,12 @@ mod raw_scalar;
 
 #[cfg(feature = "chrono")]
 mod chrono;
+#[cfg(feature = "jiff")]
+mod jiff;
+#[cfg(feature = "with-postgis")]
+mod postgis;
+#[cfg(feature = "time")]
+mod time;
 
 pub(crate) use self::raw_composite::DecodeArrayLike;
 pub(crate) use self::raw_composite::DecodeRange;
@@ -1,5 +1,15 @@
+use std::convert::TryFrom;
+
+use gel_errors::{ClientEncodingError, Error, ErrorKind};
+
+use crate::codec;
+use crate::descriptors::TypePos;
 use crate::errors::DecodeError;
-use crate::serialization::decode::raw_scalar::RawCodec;
+use crate::model::{Datetime, LocalDate, LocalDatetime, LocalTime};
+use crate::query_arg::{DescriptorContext, Encoder, ScalarArg};
+use crate::serialization::decode::queryable::scalars::DecodeScalar;
+use crate::serialization::decode::raw_scalar::{check_scalar, RawCodec};
+use crate::value::Value;
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 
 impl<'t> RawCodec<'t> for DateTime<Utc> {
@@ -25,3 +35,70 @@ impl<'t> RawCodec<'t> for NaiveTime {
         crate::model::LocalTime::decode(buf).map(Into::into)
     }
 }
+
+impl ScalarArg for DateTime<Utc> {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        let val = Datetime::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize DateTime value")
+        })?;
+        codec::encode_datetime(encoder.buf, &val).map_err(ClientEncodingError::with_source)
+    }
+    fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        check_scalar(ctx, pos, Self::uuid(), Self::typename())
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        let val = Datetime::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize DateTime value")
+        })?;
+        Ok(Value::Datetime(val))
+    }
+}
+
+impl ScalarArg for NaiveDateTime {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        let val = LocalDatetime::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize NaiveDateTime value")
+        })?;
+        codec::encode_local_datetime(encoder.buf, &val).map_err(ClientEncodingError::with_source)
+    }
+    fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        check_scalar(ctx, pos, Self::uuid(), Self::typename())
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        let val = LocalDatetime::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize NaiveDateTime value")
+        })?;
+        Ok(Value::LocalDatetime(val))
+    }
+}
+
+impl ScalarArg for NaiveDate {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        let val = LocalDate::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize NaiveDate value")
+        })?;
+        codec::encode_local_date(encoder.buf, &val).map_err(ClientEncodingError::with_source)
+    }
+    fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        check_scalar(ctx, pos, Self::uuid(), Self::typename())
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        let val = LocalDate::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize NaiveDate value")
+        })?;
+        Ok(Value::LocalDate(val))
+    }
+}
+
+impl ScalarArg for NaiveTime {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        codec::encode_local_time(encoder.buf, &LocalTime::from(self))
+            .map_err(ClientEncodingError::with_source)
+    }
+    fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        check_scalar(ctx, pos, Self::uuid(), Self::typename())
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        Ok(Value::LocalTime(LocalTime::from(self)))
+    }
+}
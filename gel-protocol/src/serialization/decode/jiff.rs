@@ -0,0 +1,155 @@
+use std::convert::TryFrom;
+
+use gel_errors::{ClientEncodingError, Error, ErrorKind};
+
+use crate::codec;
+use crate::descriptors::TypePos;
+use crate::errors::{self, DecodeError};
+use crate::model::{Datetime, Duration, LocalDate, LocalDatetime, LocalTime, RelativeDuration};
+use crate::query_arg::{DescriptorContext, Encoder, ScalarArg};
+use crate::serialization::decode::queryable::scalars::DecodeScalar;
+use crate::serialization::decode::raw_scalar::{check_scalar, RawCodec};
+use crate::value::Value;
+use jiff::civil::{Date, DateTime, Time};
+use jiff::{SignedDuration, Span, Timestamp};
+
+impl<'t> RawCodec<'t> for Timestamp {
+    fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        let val = Datetime::decode(buf)?;
+        Timestamp::try_from(val).map_err(|_| errors::InvalidDate.build())
+    }
+}
+
+impl<'t> RawCodec<'t> for DateTime {
+    fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        LocalDatetime::decode(buf).map(Into::into)
+    }
+}
+
+impl<'t> RawCodec<'t> for Date {
+    fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        LocalDate::decode(buf).map(Into::into)
+    }
+}
+
+impl<'t> RawCodec<'t> for Time {
+    fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        LocalTime::decode(buf).map(Into::into)
+    }
+}
+
+impl<'t> RawCodec<'t> for SignedDuration {
+    fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        Duration::decode(buf).map(Into::into)
+    }
+}
+
+impl<'t> RawCodec<'t> for Span {
+    fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        let val = RelativeDuration::decode(buf)?;
+        Span::try_from(val).map_err(|_| errors::InvalidDate.build())
+    }
+}
+
+impl ScalarArg for Timestamp {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        let val = Datetime::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize Timestamp value")
+        })?;
+        codec::encode_datetime(encoder.buf, &val).map_err(ClientEncodingError::with_source)
+    }
+    fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        check_scalar(ctx, pos, Self::uuid(), Self::typename())
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        let val = Datetime::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize Timestamp value")
+        })?;
+        Ok(Value::Datetime(val))
+    }
+}
+
+impl ScalarArg for DateTime {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        let val = LocalDatetime::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize DateTime value")
+        })?;
+        codec::encode_local_datetime(encoder.buf, &val).map_err(ClientEncodingError::with_source)
+    }
+    fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        check_scalar(ctx, pos, Self::uuid(), Self::typename())
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        let val = LocalDatetime::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize DateTime value")
+        })?;
+        Ok(Value::LocalDatetime(val))
+    }
+}
+
+impl ScalarArg for Date {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        let val = LocalDate::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize Date value")
+        })?;
+        codec::encode_local_date(encoder.buf, &val).map_err(ClientEncodingError::with_source)
+    }
+    fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        check_scalar(ctx, pos, Self::uuid(), Self::typename())
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        let val = LocalDate::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize Date value")
+        })?;
+        Ok(Value::LocalDate(val))
+    }
+}
+
+impl ScalarArg for Time {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        codec::encode_local_time(encoder.buf, &LocalTime::from(self))
+            .map_err(ClientEncodingError::with_source)
+    }
+    fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        check_scalar(ctx, pos, Self::uuid(), Self::typename())
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        Ok(Value::LocalTime(LocalTime::from(self)))
+    }
+}
+
+impl ScalarArg for SignedDuration {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        let val = Duration::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize SignedDuration value")
+        })?;
+        codec::encode_duration(encoder.buf, &val).map_err(ClientEncodingError::with_source)
+    }
+    fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        check_scalar(ctx, pos, Self::uuid(), Self::typename())
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        let val = Duration::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize SignedDuration value")
+        })?;
+        Ok(Value::Duration(val))
+    }
+}
+
+impl ScalarArg for Span {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        let val = RelativeDuration::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize Span value")
+        })?;
+        codec::encode_relative_duration(encoder.buf, &val).map_err(ClientEncodingError::with_source)
+    }
+    fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        check_scalar(ctx, pos, Self::uuid(), Self::typename())
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        let val = RelativeDuration::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize Span value")
+        })?;
+        Ok(Value::RelativeDuration(val))
+    }
+}
@@ -0,0 +1,48 @@
+use bytes::BufMut;
+use gel_errors::Error;
+
+use crate::codec;
+use crate::descriptors::TypePos;
+use crate::errors::DecodeError;
+use crate::model::postgis::Geometry;
+use crate::query_arg::{self, Encoder, ScalarArg};
+use crate::queryable::{self, Decoder, DescriptorMismatch, Queryable};
+use crate::serialization::decode::queryable::scalars;
+use crate::serialization::decode::raw_scalar;
+use crate::value::Value;
+
+const TYPE_NAME: &str = "ext::postgis::geometry or ext::postgis::geography";
+
+/// Both `geometry` and `geography` are encoded as EWKB
+impl Queryable for Geometry {
+    type Args = ();
+
+    fn decode(_decoder: &Decoder, _args: &(), buf: &[u8]) -> Result<Self, DecodeError> {
+        Geometry::from_ewkb(buf)
+    }
+
+    fn check_descriptor(
+        ctx: &queryable::DescriptorContext,
+        type_pos: TypePos,
+    ) -> Result<(), DescriptorMismatch> {
+        scalars::check_scalar(ctx, type_pos, codec::POSTGIS_GEOMETRY, TYPE_NAME)
+            .or_else(|_| scalars::check_scalar(ctx, type_pos, codec::POSTGIS_GEOGRAPHY, TYPE_NAME))
+    }
+}
+
+/// [`to_value`](ScalarArg::to_value) has no access to the descriptor, so it
+/// always returns [`Value::PostGisGeometry`]. Use [`Value::PostGisGeography`]
+/// explicitly where a dynamically typed `geography` is needed.
+impl ScalarArg for Geometry {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        encoder.buf.put(self.to_ewkb());
+        Ok(())
+    }
+    fn check_descriptor(ctx: &query_arg::DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        raw_scalar::check_scalar(ctx, pos, codec::POSTGIS_GEOMETRY, TYPE_NAME)
+            .or_else(|_| raw_scalar::check_scalar(ctx, pos, codec::POSTGIS_GEOGRAPHY, TYPE_NAME))
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        Ok(Value::PostGisGeometry(self.to_ewkb()))
+    }
+}
@@ -1,3 +1,4 @@
 pub(crate) mod collections;
+pub(crate) mod ranges;
 pub(crate) mod scalars;
 pub(crate) mod tuples;
@@ -1,9 +1,15 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::hash::{BuildHasher, Hash};
+use std::iter::FromIterator;
+use std::sync::Arc;
+
+use crate::codec;
 use crate::descriptors::{Descriptor, TypePos};
 use crate::errors::DecodeError;
-use crate::queryable::DescriptorMismatch;
 use crate::queryable::{Decoder, DescriptorContext, Queryable};
-use crate::serialization::decode::DecodeArrayLike;
-use std::iter::FromIterator;
+use crate::queryable::{DescriptorMismatch, MapArgs, MapKind};
+use crate::serialization::decode::queryable::scalars::DecodeScalar;
+use crate::serialization::decode::{DecodeArrayLike, DecodeTupleLike, RawCodec};
 
 impl<T: Queryable> Queryable for Option<T> {
     type Args = T::Args;
@@ -30,64 +36,237 @@ impl<T: Queryable> Queryable for Option<T> {
 
 struct Collection<T>(T);
 
-impl<T: IntoIterator + FromIterator<<T as IntoIterator>::Item>> Collection<T>
-where
-    <T as IntoIterator>::Item: Queryable,
-{
-    fn decode(
+impl<T: Queryable> Collection<T> {
+    fn decode<C: FromIterator<T>>(
         decoder: &Decoder,
-        args: &<<T as IntoIterator>::Item as Queryable>::Args,
+        args: &T::Args,
         buf: &[u8],
-    ) -> Result<T, DecodeError> {
+    ) -> Result<C, DecodeError> {
         let elements = DecodeArrayLike::new_collection(buf)?;
-        let elements = elements.map(|e| <T as IntoIterator>::Item::decode(decoder, args, e?));
-        elements.collect::<Result<T, DecodeError>>()
+        let elements = elements.map(|e| T::decode(decoder, args, e?));
+        elements.collect::<Result<C, DecodeError>>()
     }
 
-    fn decode_optional(
+    fn decode_optional<C: FromIterator<T>>(
         decoder: &Decoder,
-        args: &<<T as IntoIterator>::Item as Queryable>::Args,
+        args: &T::Args,
         buf: Option<&[u8]>,
-    ) -> Result<T, DecodeError> {
+    ) -> Result<C, DecodeError> {
         match buf {
             Some(buf) => Self::decode(decoder, args, buf),
-            None => Ok(T::from_iter(std::iter::empty())),
+            None => Ok(C::from_iter(std::iter::empty())),
         }
     }
 
     fn check_descriptor(
         ctx: &DescriptorContext,
         type_pos: TypePos,
-    ) -> Result<<<T as IntoIterator>::Item as Queryable>::Args, DescriptorMismatch> {
+    ) -> Result<T::Args, DescriptorMismatch> {
         let desc = ctx.get(type_pos)?;
         let element_type_pos = match desc {
             Descriptor::Set(desc) => desc.type_pos,
             Descriptor::Array(desc) => desc.type_pos,
             _ => return Err(ctx.wrong_type(desc, "array or set")),
         };
-        <T as IntoIterator>::Item::check_descriptor(ctx, element_type_pos)
+        T::check_descriptor(ctx, element_type_pos)
     }
 }
 
-impl<T: Queryable> Queryable for Vec<T> {
-    type Args = T::Args;
+macro_rules! implement_collection {
+    ([$($generics:tt)*] $collection:ty) => {
+        impl<$($generics)*> Queryable for $collection {
+            type Args = T::Args;
+
+            fn decode(decoder: &Decoder, args: &T::Args, buf: &[u8]) -> Result<Self, DecodeError> {
+                Collection::<T>::decode(decoder, args, buf)
+            }
+
+            fn decode_optional(
+                decoder: &Decoder,
+                args: &T::Args,
+                buf: Option<&[u8]>,
+            ) -> Result<Self, DecodeError> {
+                Collection::<T>::decode_optional(decoder, args, buf)
+            }
+
+            fn check_descriptor(
+                ctx: &DescriptorContext,
+                type_pos: TypePos,
+            ) -> Result<T::Args, DescriptorMismatch> {
+                Collection::<T>::check_descriptor(ctx, type_pos)
+            }
+        }
+    };
+}
+
+implement_collection!([T: Queryable] Vec<T>);
+implement_collection!([T: Queryable] VecDeque<T>);
+implement_collection!([T: Queryable] Box<[T]>);
+implement_collection!([T: Queryable] Arc<[T]>);
+implement_collection!([T: Queryable + Ord] BTreeSet<T>);
+implement_collection!([T: Queryable + Eq + Hash, S: BuildHasher + Default] HashSet<T, S>);
 
-    fn decode(decoder: &Decoder, args: &T::Args, buf: &[u8]) -> Result<Self, DecodeError> {
-        Collection::<Vec<T>>::decode(decoder, args, buf)
+struct Map<K, V>(K, V);
+
+impl<K: DecodeScalar, V: Queryable> Map<K, V> {
+    fn decode<C: FromIterator<(K, V)>>(
+        decoder: &Decoder,
+        args: &MapArgs<K, V>,
+        buf: &[u8],
+    ) -> Result<C, DecodeError> {
+        match &args.kind {
+            MapKind::Pairs { key, value } => {
+                let elements = DecodeArrayLike::new_collection(buf)?;
+                elements
+                    .map(|e| {
+                        let mut pair = DecodeTupleLike::new_tuple(e?, 2)?;
+                        Ok((
+                            K::decode_optional(decoder, key, pair.read()?)?,
+                            V::decode_optional(decoder, value, pair.read()?)?,
+                        ))
+                    })
+                    .collect()
+            }
+            MapKind::Elements { tuple, elements } => {
+                let mut values = if *tuple {
+                    DecodeTupleLike::new_tuple(buf, elements.len())?
+                } else {
+                    DecodeTupleLike::new_object(buf, elements.len())?
+                };
+                let mut map = Vec::with_capacity(elements.len());
+                for element in elements {
+                    let buf = values.read()?;
+                    if let Some((name, args)) = element {
+                        map.push((
+                            <K as RawCodec>::decode(name.as_bytes())?,
+                            V::decode_optional(decoder, args, buf)?,
+                        ));
+                    }
+                }
+                Ok(map.into_iter().collect())
+            }
+        }
     }
 
-    fn decode_optional(
+    fn decode_optional<C: FromIterator<(K, V)>>(
         decoder: &Decoder,
-        args: &T::Args,
+        args: &MapArgs<K, V>,
         buf: Option<&[u8]>,
-    ) -> Result<Self, DecodeError> {
-        Collection::<Vec<T>>::decode_optional(decoder, args, buf)
+    ) -> Result<C, DecodeError> {
+        match buf {
+            Some(buf) => Self::decode(decoder, args, buf),
+            None => Ok(C::from_iter(std::iter::empty())),
+        }
     }
 
     fn check_descriptor(
         ctx: &DescriptorContext,
         type_pos: TypePos,
-    ) -> Result<T::Args, DescriptorMismatch> {
-        Collection::<Vec<T>>::check_descriptor(ctx, type_pos)
+    ) -> Result<MapArgs<K, V>, DescriptorMismatch> {
+        let desc = ctx.get(type_pos)?;
+        let kind = match desc {
+            Descriptor::Set(desc) => Self::check_pair(ctx, desc.type_pos)?,
+            Descriptor::Array(desc) => Self::check_pair(ctx, desc.type_pos)?,
+            Descriptor::ObjectShape(shape) => MapKind::Elements {
+                tuple: false,
+                elements: Self::check_elements(
+                    ctx,
+                    shape
+                        .elements
+                        .iter()
+                        .map(|e| (!e.flag_implicit).then_some((&e.name, e.type_pos))),
+                )?,
+            },
+            Descriptor::SQLRow(row) => MapKind::Elements {
+                tuple: false,
+                elements: Self::check_elements(
+                    ctx,
+                    row.elements.iter().map(|e| Some((&e.name, e.type_pos))),
+                )?,
+            },
+            Descriptor::NamedTuple(tuple) => MapKind::Elements {
+                tuple: true,
+                elements: Self::check_elements(
+                    ctx,
+                    tuple.elements.iter().map(|e| Some((&e.name, e.type_pos))),
+                )?,
+            },
+            _ => return Err(ctx.wrong_type(desc, "array of tuples, object or named tuple")),
+        };
+        Ok(MapArgs { kind })
+    }
+
+    fn check_pair(
+        ctx: &DescriptorContext,
+        type_pos: TypePos,
+    ) -> Result<MapKind<K, V>, DescriptorMismatch> {
+        let desc = ctx.get(type_pos)?;
+        let (key, value) = match desc {
+            Descriptor::Tuple(tuple) if tuple.element_types.len() == 2 => {
+                (tuple.element_types[0], tuple.element_types[1])
+            }
+            Descriptor::NamedTuple(tuple) if tuple.elements.len() == 2 => {
+                (tuple.elements[0].type_pos, tuple.elements[1].type_pos)
+            }
+            _ => return Err(ctx.wrong_type(desc, "tuple of key and value")),
+        };
+        Ok(MapKind::Pairs {
+            key: K::check_descriptor(ctx, key)?,
+            value: V::check_descriptor(ctx, value)?,
+        })
+    }
+
+    fn check_elements<'a>(
+        ctx: &DescriptorContext,
+        elements: impl Iterator<Item = Option<(&'a String, TypePos)>>,
+    ) -> Result<Vec<Option<(String, V::Args)>>, DescriptorMismatch> {
+        // element names are keys of the map
+        if K::uuid() != codec::STD_STR {
+            return Err(DescriptorMismatch::WrongType {
+                unexpected: "element names of type std::str".into(),
+                expected: K::typename().into(),
+            });
+        }
+        elements
+            .map(|element| {
+                element
+                    .map(|(name, type_pos)| Ok((name.clone(), V::check_descriptor(ctx, type_pos)?)))
+                    .transpose()
+            })
+            .collect()
     }
 }
+
+macro_rules! implement_map {
+    ([$($generics:tt)*] $map:ty) => {
+        impl<$($generics)*> Queryable for $map {
+            type Args = MapArgs<K, V>;
+
+            fn decode(
+                decoder: &Decoder,
+                args: &MapArgs<K, V>,
+                buf: &[u8],
+            ) -> Result<Self, DecodeError> {
+                Map::<K, V>::decode(decoder, args, buf)
+            }
+
+            fn decode_optional(
+                decoder: &Decoder,
+                args: &MapArgs<K, V>,
+                buf: Option<&[u8]>,
+            ) -> Result<Self, DecodeError> {
+                Map::<K, V>::decode_optional(decoder, args, buf)
+            }
+
+            fn check_descriptor(
+                ctx: &DescriptorContext,
+                type_pos: TypePos,
+            ) -> Result<MapArgs<K, V>, DescriptorMismatch> {
+                Map::<K, V>::check_descriptor(ctx, type_pos)
+            }
+        }
+    };
+}
+
+implement_map!([K: DecodeScalar + Ord, V: Queryable] BTreeMap<K, V>);
+implement_map!([K: DecodeScalar + Eq + Hash, V: Queryable, S: BuildHasher + Default] HashMap<K, V, S>);
@@ -0,0 +1,76 @@
+use bytes::Buf;
+use snafu::ensure;
+
+use crate::descriptors::{Descriptor, TypePos};
+use crate::errors::{self, DecodeError};
+use crate::model::range::{self, MultiRange, Range};
+use crate::queryable::{Decoder, DescriptorContext, DescriptorMismatch, Queryable};
+use crate::serialization::decode::{DecodeArrayLike, DecodeRange};
+
+impl<T: Queryable> Queryable for Range<T> {
+    type Args = T::Args;
+
+    fn decode(decoder: &Decoder, args: &Self::Args, mut buf: &[u8]) -> Result<Self, DecodeError> {
+        ensure!(buf.remaining() >= 1, errors::Underflow);
+        let flags = buf.get_u8() as usize;
+
+        let empty = (flags & range::EMPTY) != 0;
+        let has_lower = (flags & (range::EMPTY | range::LB_INF)) == 0;
+        let has_upper = (flags & (range::EMPTY | range::UB_INF)) == 0;
+
+        let mut elements = DecodeRange::new(buf)?;
+        let lower = if has_lower {
+            Some(T::decode(decoder, args, elements.read()?)?)
+        } else {
+            None
+        };
+        let upper = if has_upper {
+            Some(T::decode(decoder, args, elements.read()?)?)
+        } else {
+            None
+        };
+
+        Ok(Range {
+            lower,
+            upper,
+            inc_lower: (flags & range::LB_INC) != 0,
+            inc_upper: (flags & range::UB_INC) != 0,
+            empty,
+        })
+    }
+
+    fn check_descriptor(
+        ctx: &DescriptorContext,
+        type_pos: TypePos,
+    ) -> Result<Self::Args, DescriptorMismatch> {
+        let desc = ctx.get(type_pos)?;
+        match desc {
+            Descriptor::Range(rng) => T::check_descriptor(ctx, rng.type_pos),
+            _ => Err(ctx.wrong_type(desc, "range")),
+        }
+    }
+}
+
+impl<T: Queryable> Queryable for MultiRange<T> {
+    type Args = T::Args;
+
+    fn decode(decoder: &Decoder, args: &Self::Args, buf: &[u8]) -> Result<Self, DecodeError> {
+        let elements = DecodeArrayLike::new_tuple_header(buf)?;
+        let ranges = elements
+            .map(|e| Range::<T>::decode(decoder, args, e?))
+            .collect::<Result<_, DecodeError>>()?;
+        Ok(MultiRange { ranges })
+    }
+
+    fn check_descriptor(
+        ctx: &DescriptorContext,
+        type_pos: TypePos,
+    ) -> Result<Self::Args, DescriptorMismatch> {
+        let desc = ctx.get(type_pos)?;
+        match desc {
+            // type of the element, not the range
+            Descriptor::MultiRange(rng) => T::check_descriptor(ctx, rng.type_pos),
+            _ => Err(ctx.wrong_type(desc, "multirange")),
+        }
+    }
+}
@@ -189,6 +189,16 @@ impl DecodeScalar for bigdecimal::BigDecimal {
     }
 }
 
+#[cfg(feature = "rust_decimal")]
+impl DecodeScalar for rust_decimal::Decimal {
+    fn uuid() -> Uuid {
+        codec::STD_DECIMAL
+    }
+    fn typename() -> &'static str {
+        "std::decimal"
+    }
+}
+
 impl DecodeScalar for LocalDatetime {
     fn uuid() -> Uuid {
         codec::CAL_LOCAL_DATETIME
@@ -208,6 +218,26 @@ impl DecodeScalar for chrono::NaiveDateTime {
     }
 }
 
+#[cfg(feature = "time")]
+impl DecodeScalar for time::PrimitiveDateTime {
+    fn uuid() -> Uuid {
+        codec::CAL_LOCAL_DATETIME
+    }
+    fn typename() -> &'static str {
+        "cal::local_datetime"
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl DecodeScalar for jiff::civil::DateTime {
+    fn uuid() -> Uuid {
+        codec::CAL_LOCAL_DATETIME
+    }
+    fn typename() -> &'static str {
+        "cal::local_datetime"
+    }
+}
+
 impl DecodeScalar for LocalDate {
     fn uuid() -> Uuid {
         codec::CAL_LOCAL_DATE
@@ -227,6 +257,26 @@ impl DecodeScalar for chrono::NaiveDate {
     }
 }
 
+#[cfg(feature = "time")]
+impl DecodeScalar for time::Date {
+    fn uuid() -> Uuid {
+        codec::CAL_LOCAL_DATE
+    }
+    fn typename() -> &'static str {
+        "cal::local_date"
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl DecodeScalar for jiff::civil::Date {
+    fn uuid() -> Uuid {
+        codec::CAL_LOCAL_DATE
+    }
+    fn typename() -> &'static str {
+        "cal::local_date"
+    }
+}
+
 impl DecodeScalar for LocalTime {
     fn uuid() -> Uuid {
         codec::CAL_LOCAL_TIME
@@ -246,6 +296,26 @@ impl DecodeScalar for chrono::NaiveTime {
     }
 }
 
+#[cfg(feature = "time")]
+impl DecodeScalar for time::Time {
+    fn uuid() -> Uuid {
+        codec::CAL_LOCAL_TIME
+    }
+    fn typename() -> &'static str {
+        "cal::local_time"
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl DecodeScalar for jiff::civil::Time {
+    fn uuid() -> Uuid {
+        codec::CAL_LOCAL_TIME
+    }
+    fn typename() -> &'static str {
+        "cal::local_time"
+    }
+}
+
 impl DecodeScalar for Duration {
     fn uuid() -> Uuid {
         codec::STD_DURATION
@@ -255,6 +325,26 @@ impl DecodeScalar for Duration {
     }
 }
 
+#[cfg(feature = "time")]
+impl DecodeScalar for time::Duration {
+    fn uuid() -> Uuid {
+        codec::STD_DURATION
+    }
+    fn typename() -> &'static str {
+        "std::duration"
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl DecodeScalar for jiff::SignedDuration {
+    fn uuid() -> Uuid {
+        codec::STD_DURATION
+    }
+    fn typename() -> &'static str {
+        "std::duration"
+    }
+}
+
 impl DecodeScalar for RelativeDuration {
     fn uuid() -> Uuid {
         codec::CAL_RELATIVE_DURATION
@@ -264,6 +354,16 @@ impl DecodeScalar for RelativeDuration {
     }
 }
 
+#[cfg(feature = "jiff")]
+impl DecodeScalar for jiff::Span {
+    fn uuid() -> Uuid {
+        codec::CAL_RELATIVE_DURATION
+    }
+    fn typename() -> &'static str {
+        "cal::relative_duration"
+    }
+}
+
 impl DecodeScalar for SystemTime {
     fn uuid() -> Uuid {
         codec::STD_DATETIME
@@ -292,6 +392,26 @@ impl DecodeScalar for chrono::DateTime<chrono::Utc> {
     }
 }
 
+#[cfg(feature = "time")]
+impl DecodeScalar for time::OffsetDateTime {
+    fn uuid() -> Uuid {
+        codec::STD_DATETIME
+    }
+    fn typename() -> &'static str {
+        "std::datetime"
+    }
+}
+
+#[cfg(feature = "jiff")]
+impl DecodeScalar for jiff::Timestamp {
+    fn uuid() -> Uuid {
+        codec::STD_DATETIME
+    }
+    fn typename() -> &'static str {
+        "std::datetime"
+    }
+}
+
 impl DecodeScalar for ConfigMemory {
     fn uuid() -> Uuid {
         codec::CFG_MEMORY
@@ -1,4 +1,4 @@
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 use std::mem::size_of;
 use std::str;
 use std::time::SystemTime;
@@ -12,7 +12,7 @@ use crate::descriptors::{Descriptor, TypePos};
 use crate::errors::{self, DecodeError};
 use crate::model::{range, Vector, VectorRef};
 use crate::model::{BigInt, Decimal};
-use crate::model::{ConfigMemory, Range};
+use crate::model::{ConfigMemory, MultiRange, Range};
 use crate::model::{DateDuration, RelativeDuration};
 use crate::model::{Datetime, Duration, LocalDate, LocalDatetime, LocalTime};
 use crate::model::{Json, Uuid};
@@ -41,7 +41,7 @@ impl<'t> RawCodec<'t> for String {
     }
 }
 
-fn check_scalar(
+pub(super) fn check_scalar(
     ctx: &DescriptorContext,
     type_pos: TypePos,
     type_id: Uuid,
@@ -375,6 +375,15 @@ impl<'t> RawCodec<'t> for bigdecimal::BigDecimal {
     }
 }
 
+#[cfg(feature = "rust_decimal")]
+impl<'t> RawCodec<'t> for rust_decimal::Decimal {
+    fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        let dec: Decimal = RawCodec::decode(buf)?;
+        dec.to_rust_decimal()
+            .map_err(|reason| errors::DecimalOutOfRange { reason }.build())
+    }
+}
+
 impl ScalarArg for Decimal {
     fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
         codec::encode_decimal(encoder.buf, self).map_err(ClientEncodingError::with_source)
@@ -415,6 +424,19 @@ impl ScalarArg for bigdecimal::BigDecimal {
     }
 }
 
+#[cfg(feature = "rust_decimal")]
+impl ScalarArg for rust_decimal::Decimal {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        codec::encode_decimal(encoder.buf, &self.into()).map_err(ClientEncodingError::with_source)
+    }
+    fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        check_scalar(ctx, pos, Self::uuid(), Self::typename())
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        Ok(Value::Decimal(self.into()))
+    }
+}
+
 impl<'t> RawCodec<'t> for BigInt {
     fn decode(mut buf: &[u8]) -> Result<Self, DecodeError> {
         ensure!(buf.remaining() >= 8, errors::Underflow);
@@ -717,21 +739,54 @@ impl<T: ScalarArg + Clone> ScalarArg for Range<T> {
         }
     }
     fn to_value(&self) -> Result<Value, Error> {
-        Ok(Value::Range(Range {
-            lower: self
-                .lower
-                .as_ref()
-                .map(|v| v.to_value().map(Box::new))
-                .transpose()?,
-            upper: self
-                .upper
-                .as_ref()
-                .map(|v| v.to_value().map(Box::new))
-                .transpose()?,
-            inc_lower: self.inc_lower,
-            inc_upper: self.inc_upper,
-            empty: self.empty,
-        }))
+        range_to_value(self).map(Value::Range)
+    }
+}
+
+fn range_to_value<T: ScalarArg>(rng: &Range<T>) -> Result<Range<Box<Value>>, Error> {
+    Ok(Range {
+        lower: rng
+            .lower
+            .as_ref()
+            .map(|v| v.to_value().map(Box::new))
+            .transpose()?,
+        upper: rng
+            .upper
+            .as_ref()
+            .map(|v| v.to_value().map(Box::new))
+            .transpose()?,
+        inc_lower: rng.inc_lower,
+        inc_upper: rng.inc_upper,
+        empty: rng.empty,
+    })
+}
+
+impl<T: ScalarArg + Clone> ScalarArg for MultiRange<T> {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        let len = u32::try_from(self.ranges.len())
+            .map_err(|_| ClientEncodingError::with_message("multirange is too long"))?;
+        encoder.buf.reserve(4);
+        encoder.buf.put_u32(len);
+        for rng in &self.ranges {
+            encoder.length_prefixed(|encoder| rng.encode(encoder))?;
+        }
+        Ok(())
+    }
+    fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        let desc = ctx.get(pos)?;
+        if let Descriptor::MultiRange(rng) = desc {
+            T::check_descriptor(ctx, rng.type_pos)
+        } else {
+            Err(ctx.wrong_type(desc, "multirange"))
+        }
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        let ranges = self
+            .ranges
+            .iter()
+            .map(range_to_value)
+            .collect::<Result<_, _>>()?;
+        Ok(Value::MultiRange(MultiRange { ranges }))
     }
 }
 
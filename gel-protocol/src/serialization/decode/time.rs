@@ -0,0 +1,128 @@
+use std::convert::TryFrom;
+
+use gel_errors::{ClientEncodingError, Error, ErrorKind};
+
+use crate::codec;
+use crate::descriptors::TypePos;
+use crate::errors::DecodeError;
+use crate::model::{Datetime, Duration, LocalDate, LocalDatetime, LocalTime};
+use crate::query_arg::{DescriptorContext, Encoder, ScalarArg};
+use crate::serialization::decode::queryable::scalars::DecodeScalar;
+use crate::serialization::decode::raw_scalar::{check_scalar, RawCodec};
+use crate::value::Value;
+use time::{Date, OffsetDateTime, PrimitiveDateTime, Time};
+
+impl<'t> RawCodec<'t> for OffsetDateTime {
+    fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        Datetime::decode(buf).map(Into::into)
+    }
+}
+
+impl<'t> RawCodec<'t> for PrimitiveDateTime {
+    fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        LocalDatetime::decode(buf).map(Into::into)
+    }
+}
+
+impl<'t> RawCodec<'t> for Date {
+    fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        LocalDate::decode(buf).map(Into::into)
+    }
+}
+
+impl<'t> RawCodec<'t> for Time {
+    fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        LocalTime::decode(buf).map(Into::into)
+    }
+}
+
+impl<'t> RawCodec<'t> for time::Duration {
+    fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        Duration::decode(buf).map(Into::into)
+    }
+}
+
+impl ScalarArg for OffsetDateTime {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        let val = Datetime::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize OffsetDateTime value")
+        })?;
+        codec::encode_datetime(encoder.buf, &val).map_err(ClientEncodingError::with_source)
+    }
+    fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        check_scalar(ctx, pos, Self::uuid(), Self::typename())
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        let val = Datetime::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize OffsetDateTime value")
+        })?;
+        Ok(Value::Datetime(val))
+    }
+}
+
+impl ScalarArg for PrimitiveDateTime {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        let val = LocalDatetime::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize PrimitiveDateTime value")
+        })?;
+        codec::encode_local_datetime(encoder.buf, &val).map_err(ClientEncodingError::with_source)
+    }
+    fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        check_scalar(ctx, pos, Self::uuid(), Self::typename())
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        let val = LocalDatetime::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize PrimitiveDateTime value")
+        })?;
+        Ok(Value::LocalDatetime(val))
+    }
+}
+
+impl ScalarArg for Date {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        let val = LocalDate::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize Date value")
+        })?;
+        codec::encode_local_date(encoder.buf, &val).map_err(ClientEncodingError::with_source)
+    }
+    fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        check_scalar(ctx, pos, Self::uuid(), Self::typename())
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        let val = LocalDate::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize Date value")
+        })?;
+        Ok(Value::LocalDate(val))
+    }
+}
+
+impl ScalarArg for Time {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        codec::encode_local_time(encoder.buf, &LocalTime::from(self))
+            .map_err(ClientEncodingError::with_source)
+    }
+    fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        check_scalar(ctx, pos, Self::uuid(), Self::typename())
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        Ok(Value::LocalTime(LocalTime::from(self)))
+    }
+}
+
+impl ScalarArg for time::Duration {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        let val = Duration::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize Duration value")
+        })?;
+        codec::encode_duration(encoder.buf, &val).map_err(ClientEncodingError::with_source)
+    }
+    fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        check_scalar(ctx, pos, Self::uuid(), Self::typename())
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        let val = Duration::try_from(self).map_err(|e| {
+            ClientEncodingError::with_source(e).context("cannot serialize Duration value")
+        })?;
+        Ok(Value::Duration(val))
+    }
+}
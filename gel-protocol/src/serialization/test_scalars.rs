@@ -179,6 +179,39 @@ fn decimal() {
     );
 }
 
+#[test]
+#[cfg(feature = "rust_decimal")]
+fn rust_decimal() {
+    use crate::errors::DecodeError;
+    use rust_decimal::Decimal;
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str(s).expect("rust_decimal")
+    }
+
+    encoding_eq!(dec("42.00"), b"\0\x01\0\0\0\0\0\x02\0*");
+    encoding_eq!(
+        dec("12345678.901234567"),
+        b"\0\x05\0\x01\0\0\0\t\x04\xd2\x16.#4\r\x80\x1bX"
+    );
+    encoding_eq!(dec("-0.07"), b"\0\x01\xff\xff\x40\0\0\x02\x02\xbc");
+    encoding_eq!(Decimal::ZERO, b"\0\0\0\0\0\0\0\0");
+
+    // 1e100
+    let err = <Decimal as RawCodec>::decode(b"\0\x01\0\x19\0\0\0\0\0\x01").unwrap_err();
+    assert!(matches!(err, DecodeError::DecimalOutOfRange { .. }));
+    assert_eq!(
+        err.to_string(),
+        "decimal is out of range: value exceeds 96 bits of precision"
+    );
+    // 1e-32
+    let err = <Decimal as RawCodec>::decode(b"\0\x01\xff\xf8\0\0\0\x20\0\x01").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "decimal is out of range: value exceeds 28 digits of scale"
+    );
+}
+
 #[test]
 #[cfg(feature = "num-bigint")]
 fn bigint() {
@@ -6,10 +6,13 @@ use crate::codec::{
     InputObjectShape, InputShapeElement, NamedTupleShape, ObjectShape, SQLRowShape,
 };
 use crate::common::Cardinality;
-use crate::model::{BigInt, ConfigMemory, Decimal, Range, Uuid};
+use crate::model::{BigInt, ConfigMemory, Decimal, MultiRange, Range, Uuid};
 use crate::model::{DateDuration, Json, RelativeDuration};
 use crate::model::{Datetime, Duration, LocalDate, LocalDatetime, LocalTime};
 
+#[cfg(feature = "with-serde")]
+mod json;
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Value {
     Nothing,
@@ -52,6 +55,7 @@ pub enum Value {
     Vector(Vec<f32>),
     Enum(EnumValue),
     Range(Range<Box<Value>>),
+    MultiRange(MultiRange<Box<Value>>),
     PostGisGeometry(bytes::Bytes),
     PostGisGeography(bytes::Bytes),
     PostGisBox2d(bytes::Bytes),
@@ -91,6 +95,7 @@ impl Value {
             Nothing => "nothing",
             Object { .. } => "object",
             Range { .. } => "range",
+            MultiRange { .. } => "multirange",
             RelativeDuration(..) => "cal::relative_duration",
             Set(..) => "set",
             SparseObject { .. } => "sparse_object",
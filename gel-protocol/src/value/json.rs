@@ -0,0 +1,704 @@
+//! JSON conversions of [Value] using the representation of the server
+//!
+//! Values serialize to the same structure that `<json>` casts produce:
+//! objects are keyed by shape element names (with link properties prefixed
+//! by `@`), tuples, sets and arrays are sequences, temporal types and
+//! durations are ISO 8601 strings, and `bigint` and `decimal` are numbers
+//! of arbitrary precision.
+//!
+//! Since JSON lacks type information, deserializing requires a type
+//! descriptor.
+//!
+//! Only `serde_json` is supported, as `std::json`, `std::bigint` and
+//! `std::decimal` values are (de)serialized as raw JSON. So instead of
+//! implementing serde traits for [Value], the conversions are exposed as
+//! [Value::to_json] and [Value::from_json].
+use std::fmt;
+use std::str::FromStr;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+
+use crate::codec::{self, InputObjectShape, NamedTupleShape, ObjectShape, SQLRowShape};
+use crate::descriptors::{Descriptor, TypePos, Typedesc};
+use crate::model::{ConfigMemory, Datetime, Json, LocalDatetime, LocalTime, Range, Uuid};
+use crate::model::{DateDuration, Duration, MultiRange, RelativeDuration};
+use crate::value::{SparseObject, Value};
+
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    BASE64.decode(text).ok()
+}
+
+fn hex_encode(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hex_decode(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Formats time like PostgreSQL does: without trailing zeros in fraction
+fn trim_fraction(mut text: String) -> String {
+    if text.contains('.') {
+        text.truncate(text.trim_end_matches('0').trim_end_matches('.').len());
+    }
+    text
+}
+
+fn local_datetime_json(value: &LocalDatetime) -> String {
+    trim_fraction(format!("{:?}", value))
+}
+
+fn local_time_json(value: &LocalTime) -> String {
+    trim_fraction(value.to_string())
+}
+
+fn datetime_json(value: &Datetime) -> String {
+    let local = LocalDatetime {
+        micros: value.micros,
+    };
+    local_datetime_json(&local) + "+00:00"
+}
+
+fn duration_json(value: &Duration) -> String {
+    RelativeDuration {
+        micros: value.micros,
+        days: 0,
+        months: 0,
+    }
+    .to_string()
+}
+
+fn date_duration_json(value: &DateDuration) -> String {
+    // unlike `Display`, uses the same zero value as other durations
+    RelativeDuration {
+        micros: 0,
+        days: value.days,
+        months: value.months,
+    }
+    .to_string()
+}
+
+fn serialize_raw<S: Serializer>(serializer: S, text: String) -> Result<S::Ok, S::Error> {
+    use serde::ser::Error;
+
+    RawValue::from_string(text)
+        .map_err(S::Error::custom)?
+        .serialize(serializer)
+}
+
+fn serialize_float<S: Serializer>(serializer: S, value: f64) -> Result<S::Ok, S::Error> {
+    if value.is_nan() {
+        serializer.serialize_str("NaN")
+    } else if value.is_infinite() {
+        serializer.serialize_str(if value > 0.0 { "Infinity" } else { "-Infinity" })
+    } else {
+        serializer.serialize_f64(value)
+    }
+}
+
+struct RangeRef<'a>(&'a Range<Box<Value>>);
+
+impl Serialize for RangeRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let range = self.0;
+        if range.empty {
+            let mut map = serializer.serialize_map(Some(1))?;
+            map.serialize_entry("empty", &true)?;
+            return map.end();
+        }
+        let mut map = serializer.serialize_map(Some(4))?;
+        map.serialize_entry("lower", &range.lower.as_deref().map(JsonValue))?;
+        map.serialize_entry("inc_lower", &range.inc_lower)?;
+        map.serialize_entry("upper", &range.upper.as_deref().map(JsonValue))?;
+        map.serialize_entry("inc_upper", &range.inc_upper)?;
+        map.end()
+    }
+}
+
+/// Serializes [Value] the way `<json>` casts do
+struct JsonValue<'a>(&'a Value);
+
+impl Serialize for JsonValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use Value::*;
+        match self.0 {
+            Nothing => serializer.serialize_none(),
+            Uuid(v) => serializer.collect_str(v),
+            Str(v) => serializer.serialize_str(v),
+            Bytes(v) => serializer.serialize_str(&BASE64.encode(v)),
+            Int16(v) => serializer.serialize_i16(*v),
+            Int32(v) => serializer.serialize_i32(*v),
+            Int64(v) => serializer.serialize_i64(*v),
+            Float32(v) if v.is_finite() => serializer.serialize_f32(*v),
+            Float32(v) => serialize_float(serializer, f64::from(*v)),
+            Float64(v) => serialize_float(serializer, *v),
+            BigInt(v) => serialize_raw(serializer, v.to_string()),
+            Decimal(v) => {
+                let text = v.to_string();
+                if v.decimal_digits == 0 {
+                    // `Display` outputs at least one digit after the dot
+                    let len = text.strip_suffix(".0").unwrap_or(&text).len();
+                    serialize_raw(serializer, text[..len].to_string())
+                } else {
+                    serialize_raw(serializer, text)
+                }
+            }
+            ConfigMemory(v) => serializer.collect_str(v),
+            Bool(v) => serializer.serialize_bool(*v),
+            Datetime(v) => serializer.serialize_str(&datetime_json(v)),
+            LocalDatetime(v) => serializer.serialize_str(&local_datetime_json(v)),
+            LocalDate(v) => serializer.collect_str(v),
+            LocalTime(v) => serializer.serialize_str(&local_time_json(v)),
+            Duration(v) => serializer.serialize_str(&duration_json(v)),
+            RelativeDuration(v) => serializer.collect_str(v),
+            DateDuration(v) => serializer.serialize_str(&date_duration_json(v)),
+            Json(v) => serialize_raw(serializer, v.to_string()),
+            Set(items) | Array(items) | Tuple(items) => {
+                serializer.collect_seq(items.iter().map(JsonValue))
+            }
+            Object { shape, fields } => {
+                let mut map = serializer.serialize_map(None)?;
+                for (element, field) in shape.elements.iter().zip(fields) {
+                    if element.flag_implicit {
+                        continue;
+                    }
+                    let field = field.as_ref().map(JsonValue);
+                    if element.flag_link_property {
+                        map.serialize_entry(&format!("@{}", element.name), &field)?;
+                    } else {
+                        map.serialize_entry(&element.name, &field)?;
+                    }
+                }
+                map.end()
+            }
+            SparseObject(object) => serializer.collect_map(
+                object
+                    .pairs()
+                    .map(|(name, field)| (name, field.map(JsonValue))),
+            ),
+            NamedTuple { shape, fields } => serializer.collect_map(
+                shape
+                    .elements
+                    .iter()
+                    .map(|element| &element.name)
+                    .zip(fields.iter().map(JsonValue)),
+            ),
+            SQLRow { shape, fields } => serializer.collect_map(
+                shape
+                    .elements
+                    .iter()
+                    .map(|element| &element.name)
+                    .zip(fields.iter().map(|field| field.as_ref().map(JsonValue))),
+            ),
+            Vector(items) => serializer.collect_seq(items),
+            Enum(v) => serializer.serialize_str(v),
+            Range(range) => RangeRef(range).serialize(serializer),
+            MultiRange(ranges) => serializer.collect_seq(ranges.iter().map(RangeRef)),
+            PostGisGeometry(v) | PostGisGeography(v) | PostGisBox2d(v) | PostGisBox3d(v) => {
+                serializer.serialize_str(&hex_encode(v))
+            }
+        }
+    }
+}
+
+/// Deserializes [Value] of the type described by type descriptors
+#[derive(Clone, Copy, Debug)]
+struct ValueSeed<'a> {
+    pos: Option<TypePos>,
+    descriptors: &'a [Descriptor],
+}
+
+impl<'a> ValueSeed<'a> {
+    /// Create a seed for the type at `root_pos` (`None` means no data)
+    fn new(root_pos: Option<TypePos>, descriptors: &'a [Descriptor]) -> ValueSeed<'a> {
+        ValueSeed {
+            pos: root_pos,
+            descriptors,
+        }
+    }
+
+    fn at(self, pos: TypePos) -> ValueSeed<'a> {
+        ValueSeed {
+            pos: Some(pos),
+            descriptors: self.descriptors,
+        }
+    }
+}
+
+impl Value {
+    /// Serialize into JSON of the same structure as `<json>` casts produce
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(&JsonValue(self))
+    }
+
+    /// Parse JSON into the value of the type described by `typedesc`
+    pub fn from_json(typedesc: &Typedesc, json: &str) -> Result<Value, serde_json::Error> {
+        Value::from_json_with_descriptors(typedesc.root_pos(), typedesc.descriptors(), json)
+    }
+
+    /// Parse JSON into the value of the type at `root_pos` of `descriptors`
+    /// (`None` means no data)
+    pub fn from_json_with_descriptors(
+        root_pos: Option<TypePos>,
+        descriptors: &[Descriptor],
+        json: &str,
+    ) -> Result<Value, serde_json::Error> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let value = ValueSeed::new(root_pos, descriptors).deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(value)
+    }
+}
+
+fn parse<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+    D: Deserializer<'de>,
+{
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(de::Error::custom)
+}
+
+fn raw<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(Box::<RawValue>::deserialize(deserializer)?
+        .get()
+        .to_string())
+}
+
+fn parse_number<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+    D: Deserializer<'de>,
+{
+    let text = raw(deserializer)?;
+    let number = match serde_json::from_str::<String>(&text) {
+        Ok(string) => string,
+        Err(_) => text,
+    };
+    number.parse().map_err(de::Error::custom)
+}
+
+fn parse_memory(text: &str) -> Option<ConfigMemory> {
+    let digits = text.bytes().take_while(u8::is_ascii_digit).count();
+    let value: i64 = text[..digits].parse().ok()?;
+    let multiplier: i64 = match &text[digits..] {
+        "B" => 1,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        "TiB" => 1 << 40,
+        "PiB" => 1 << 50,
+        _ => return None,
+    };
+    value.checked_mul(multiplier).map(ConfigMemory)
+}
+
+fn bytes_from<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    decode: fn(&str) -> Option<Vec<u8>>,
+) -> Result<bytes::Bytes, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    decode(&text)
+        .map(bytes::Bytes::from)
+        .ok_or_else(|| de::Error::invalid_value(de::Unexpected::Str(&text), &"encoded bytes"))
+}
+
+struct FloatVisitor;
+
+impl Visitor<'_> for FloatVisitor {
+    type Value = f64;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a number, \"NaN\", \"Infinity\" or \"-Infinity\"")
+    }
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<f64, E> {
+        Ok(v)
+    }
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<f64, E> {
+        Ok(v as f64)
+    }
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<f64, E> {
+        Ok(v as f64)
+    }
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<f64, E> {
+        match v {
+            "NaN" => Ok(f64::NAN),
+            "Infinity" => Ok(f64::INFINITY),
+            "-Infinity" => Ok(f64::NEG_INFINITY),
+            _ => Err(E::invalid_value(de::Unexpected::Str(v), &self)),
+        }
+    }
+}
+
+fn deserialize_scalar<'de, D: Deserializer<'de>>(
+    id: &Uuid,
+    deserializer: D,
+) -> Result<Value, D::Error> {
+    use de::Error;
+
+    let value = match *id {
+        codec::STD_UUID => Value::Uuid(parse(deserializer)?),
+        codec::STD_STR => Value::Str(String::deserialize(deserializer)?),
+        codec::STD_BYTES => Value::Bytes(bytes_from(deserializer, base64_decode)?),
+        codec::STD_INT16 => Value::Int16(i16::deserialize(deserializer)?),
+        codec::STD_INT32 => Value::Int32(i32::deserialize(deserializer)?),
+        codec::STD_INT64 => Value::Int64(i64::deserialize(deserializer)?),
+        codec::STD_FLOAT32 => Value::Float32(deserializer.deserialize_any(FloatVisitor)? as f32),
+        codec::STD_FLOAT64 => Value::Float64(deserializer.deserialize_any(FloatVisitor)?),
+        codec::STD_DECIMAL => Value::Decimal(parse_number(deserializer)?),
+        codec::STD_BIGINT => Value::BigInt(parse_number(deserializer)?),
+        codec::STD_BOOL => Value::Bool(bool::deserialize(deserializer)?),
+        codec::STD_DATETIME | codec::STD_PG_TIMESTAMPTZ => Value::Datetime(parse(deserializer)?),
+        codec::CAL_LOCAL_DATETIME | codec::STD_PG_TIMESTAMP => {
+            Value::LocalDatetime(parse(deserializer)?)
+        }
+        codec::CAL_LOCAL_DATE | codec::STD_PG_DATE => Value::LocalDate(parse(deserializer)?),
+        codec::CAL_LOCAL_TIME => Value::LocalTime(parse(deserializer)?),
+        codec::STD_DURATION => {
+            let duration: RelativeDuration = parse(deserializer)?;
+            if duration.months != 0 || duration.days != 0 {
+                return Err(D::Error::custom(
+                    "duration cannot contain days, months or years",
+                ));
+            }
+            Value::Duration(Duration::from_micros(duration.micros))
+        }
+        codec::CAL_RELATIVE_DURATION | codec::STD_PG_INTERVAL => {
+            Value::RelativeDuration(parse(deserializer)?)
+        }
+        codec::CAL_DATE_DURATION => Value::DateDuration(parse(deserializer)?),
+        codec::STD_JSON | codec::STD_PG_JSON => {
+            Value::Json(Json::new_unchecked(raw(deserializer)?))
+        }
+        codec::CFG_MEMORY => {
+            let text = String::deserialize(deserializer)?;
+            Value::ConfigMemory(parse_memory(&text).ok_or_else(|| {
+                D::Error::invalid_value(de::Unexpected::Str(&text), &"memory size")
+            })?)
+        }
+        codec::PGVECTOR_VECTOR => Value::Vector(Vec::deserialize(deserializer)?),
+        codec::POSTGIS_GEOMETRY => Value::PostGisGeometry(bytes_from(deserializer, hex_decode)?),
+        codec::POSTGIS_GEOGRAPHY => Value::PostGisGeography(bytes_from(deserializer, hex_decode)?),
+        codec::POSTGIS_BOX_2D => Value::PostGisBox2d(bytes_from(deserializer, hex_decode)?),
+        codec::POSTGIS_BOX_3D => Value::PostGisBox3d(bytes_from(deserializer, hex_decode)?),
+        _ => {
+            return Err(D::Error::custom(format_args!(
+                "cannot deserialize scalar type {}",
+                id
+            )))
+        }
+    };
+    Ok(value)
+}
+
+/// Deserializes `null` into `None`
+struct OptionalSeed<'a>(ValueSeed<'a>);
+
+impl<'de> DeserializeSeed<'de> for OptionalSeed<'_> {
+    type Value = Option<Value>;
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_option(self)
+    }
+}
+
+impl<'de> Visitor<'de> for OptionalSeed<'_> {
+    type Value = Option<Value>;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an optional value")
+    }
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        self.0.deserialize(deserializer).map(Some)
+    }
+}
+
+/// Deserializes a sequence of elements of the same type
+struct ElementsVisitor<'a>(ValueSeed<'a>);
+
+impl<'de> Visitor<'de> for ElementsVisitor<'_> {
+    type Value = Vec<Value>;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array")
+    }
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(item) = seq.next_element_seed(self.0)? {
+            items.push(item);
+        }
+        Ok(items)
+    }
+}
+
+struct TupleVisitor<'a> {
+    seed: ValueSeed<'a>,
+    elements: &'a [TypePos],
+}
+
+impl<'de> Visitor<'de> for TupleVisitor<'_> {
+    type Value = Vec<Value>;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an array of {} elements", self.elements.len())
+    }
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut items = Vec::with_capacity(self.elements.len());
+        for (index, &pos) in self.elements.iter().enumerate() {
+            match seq.next_element_seed(self.seed.at(pos))? {
+                Some(item) => items.push(item),
+                None => return Err(de::Error::invalid_length(index, &self)),
+            }
+        }
+        if seq.next_element::<IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(self.elements.len() + 1, &self));
+        }
+        Ok(items)
+    }
+}
+
+/// Deserializes a map of named fields
+///
+/// Returns `None` for missing fields and `Some(None)` for `null` ones.
+struct FieldsVisitor<'a> {
+    seed: ValueSeed<'a>,
+    fields: Vec<(String, TypePos)>,
+}
+
+impl<'de> Visitor<'de> for FieldsVisitor<'_> {
+    type Value = Vec<Option<Option<Value>>>;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an object")
+    }
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut result = vec![None; self.fields.len()];
+        while let Some(key) = map.next_key::<String>()? {
+            let Some(index) = self.fields.iter().position(|(name, _)| *name == key) else {
+                return Err(de::Error::custom(format_args!("unknown field `{}`", key)));
+            };
+            if result[index].is_some() {
+                return Err(de::Error::custom(format_args!("duplicate field `{}`", key)));
+            }
+            let pos = self.fields[index].1;
+            result[index] = Some(map.next_value_seed(OptionalSeed(self.seed.at(pos)))?);
+        }
+        Ok(result)
+    }
+}
+
+struct RangeVisitor<'a>(ValueSeed<'a>);
+
+impl<'de> Visitor<'de> for RangeVisitor<'_> {
+    type Value = Range<Box<Value>>;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a range object")
+    }
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut empty = false;
+        let mut range = Range {
+            lower: None,
+            upper: None,
+            inc_lower: false,
+            inc_upper: false,
+            empty: false,
+        };
+        while let Some(key) = map.next_key::<String>()? {
+            match &key[..] {
+                "empty" => empty = map.next_value()?,
+                "lower" => range.lower = map.next_value_seed(OptionalSeed(self.0))?.map(Box::new),
+                "upper" => range.upper = map.next_value_seed(OptionalSeed(self.0))?.map(Box::new),
+                "inc_lower" => range.inc_lower = map.next_value()?,
+                "inc_upper" => range.inc_upper = map.next_value()?,
+                _ => {
+                    return Err(de::Error::unknown_field(
+                        &key,
+                        &["lower", "upper", "inc_lower", "inc_upper", "empty"],
+                    ))
+                }
+            }
+        }
+        if empty {
+            return Ok(Range::empty());
+        }
+        Ok(range)
+    }
+}
+
+struct MultiRangeVisitor<'a>(ValueSeed<'a>);
+
+impl<'de> Visitor<'de> for MultiRangeVisitor<'_> {
+    type Value = MultiRange<Box<Value>>;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of ranges")
+    }
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut ranges = Vec::new();
+        while let Some(range) = seq.next_element_seed(RangeSeed(self.0))? {
+            ranges.push(range);
+        }
+        Ok(MultiRange::new(ranges))
+    }
+}
+
+struct RangeSeed<'a>(ValueSeed<'a>);
+
+impl<'de> DeserializeSeed<'de> for RangeSeed<'_> {
+    type Value = Range<Box<Value>>;
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(RangeVisitor(self.0))
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for ValueSeed<'_> {
+    type Value = Value;
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        use de::Error;
+        use Descriptor as T;
+
+        let Some(pos) = self.pos else {
+            <()>::deserialize(deserializer)?;
+            return Ok(Value::Nothing);
+        };
+        let descriptor = self.descriptors.get(pos.0 as usize).ok_or_else(|| {
+            D::Error::custom(format_args!("invalid type descriptor position {}", pos.0))
+        })?;
+        match descriptor {
+            T::BaseScalar(d) => deserialize_scalar(&d.id, deserializer),
+            T::Scalar(d) => match d.base_type_pos {
+                Some(base) => self.at(base).deserialize(deserializer),
+                None => deserialize_scalar(&d.id, deserializer),
+            },
+            T::Set(d) => deserializer
+                .deserialize_seq(ElementsVisitor(self.at(d.type_pos)))
+                .map(Value::Set),
+            T::Array(d) => deserializer
+                .deserialize_seq(ElementsVisitor(self.at(d.type_pos)))
+                .map(Value::Array),
+            T::Tuple(d) => deserializer
+                .deserialize_seq(TupleVisitor {
+                    seed: self,
+                    elements: &d.element_types,
+                })
+                .map(Value::Tuple),
+            T::NamedTuple(d) => {
+                let fields = deserializer.deserialize_map(FieldsVisitor {
+                    seed: self,
+                    fields: d
+                        .elements
+                        .iter()
+                        .map(|el| (el.name.clone(), el.type_pos))
+                        .collect(),
+                })?;
+                let fields = fields
+                    .into_iter()
+                    .zip(&d.elements)
+                    .map(|(field, el)| {
+                        field.flatten().ok_or_else(|| {
+                            D::Error::custom(format_args!("missing field `{}`", el.name))
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(Value::NamedTuple {
+                    shape: NamedTupleShape::from(&d.elements[..]),
+                    fields,
+                })
+            }
+            T::ObjectShape(d) => {
+                let fields = deserializer.deserialize_map(FieldsVisitor {
+                    seed: self,
+                    fields: d
+                        .elements
+                        .iter()
+                        .map(|el| {
+                            if el.flag_link_property {
+                                (format!("@{}", el.name), el.type_pos)
+                            } else {
+                                (el.name.clone(), el.type_pos)
+                            }
+                        })
+                        .collect(),
+                })?;
+                Ok(Value::Object {
+                    shape: ObjectShape::from(&d.elements[..]),
+                    fields: fields.into_iter().map(Option::flatten).collect(),
+                })
+            }
+            T::SQLRow(d) => {
+                let fields = deserializer.deserialize_map(FieldsVisitor {
+                    seed: self,
+                    fields: d
+                        .elements
+                        .iter()
+                        .map(|el| (el.name.clone(), el.type_pos))
+                        .collect(),
+                })?;
+                Ok(Value::SQLRow {
+                    shape: SQLRowShape::from(&d.elements[..]),
+                    fields: fields.into_iter().map(Option::flatten).collect(),
+                })
+            }
+            T::InputShape(d) => {
+                let fields = deserializer.deserialize_map(FieldsVisitor {
+                    seed: self,
+                    fields: d
+                        .elements
+                        .iter()
+                        .map(|el| (el.name.clone(), el.type_pos))
+                        .collect(),
+                })?;
+                Ok(Value::SparseObject(SparseObject {
+                    shape: InputObjectShape::from(&d.elements[..]),
+                    fields,
+                }))
+            }
+            T::Enumeration(d) => {
+                let member = String::deserialize(deserializer)?;
+                if !d.members.contains(&member) {
+                    return Err(D::Error::custom(format_args!(
+                        "`{}` is not a member of the enum",
+                        member
+                    )));
+                }
+                Ok(Value::Enum(member[..].into()))
+            }
+            T::Range(d) => RangeSeed(self.at(d.type_pos))
+                .deserialize(deserializer)
+                .map(Value::Range),
+            T::MultiRange(d) => deserializer
+                .deserialize_seq(MultiRangeVisitor(self.at(d.type_pos)))
+                .map(Value::MultiRange),
+            T::Object(_) | T::Compound(_) | T::TypeAnnotation(_) => Err(D::Error::custom(
+                format_args!("cannot deserialize {:?}", descriptor.id()),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{hex_decode, hex_encode};
+
+    #[test]
+    fn hex() {
+        assert_eq!(hex_encode(b"\x01\xab\xff"), "01abff");
+        assert_eq!(hex_decode("01abff").as_deref(), Some(&b"\x01\xab\xff"[..]));
+        assert_eq!(hex_decode("01ABFF").as_deref(), Some(&b"\x01\xab\xff"[..]));
+        assert_eq!(hex_decode("01a"), None);
+        assert_eq!(hex_decode("0g"), None);
+    }
+}
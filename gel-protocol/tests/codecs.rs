@@ -22,6 +22,7 @@ use gel_protocol::descriptors::{ObjectShapeDescriptor, ShapeElement};
 use gel_protocol::features::ProtocolVersion;
 use gel_protocol::model::{Datetime, Json, RelativeDuration};
 use gel_protocol::model::{Duration, LocalDate, LocalTime};
+use gel_protocol::model::{MultiRange, Range};
 use gel_protocol::server_message::StateDataDescription;
 use gel_protocol::value::{SparseObject, Value};
 use uuid::Uuid;
@@ -1190,15 +1191,17 @@ fn multi_range() -> Result<(), Box<dyn Error>> {
         ],
     )?;
 
+    let bytes = b"\0\0\0\x01\0\0\0\x19\x02\0\0\0\x08\0\0\0\0\0\0\0\x07\0\0\0\x08\0\0\0\0\0\0\0'";
     encoding_eq!(
         &codec,
-        b"\0\0\0\x01\0\0\0\x19\x02\0\0\0\x08\0\0\0\0\0\0\0\x07\0\0\0\x08\0\0\0\0\0\0\0'",
-        Value::Array(vec![std::ops::Range {
-            start: 7i64,
-            end: 39
-        }
-        .into()])
+        bytes,
+        MultiRange::from(vec![Range::from(7i64..39)]).into_value()
     );
+
+    // arrays of ranges are still accepted for encoding
+    let mut buf = bytes::BytesMut::new();
+    codec.encode(&mut buf, &Value::Array(vec![(7i64..39).into()]))?;
+    assert_eq!(&buf[..], bytes);
     Ok(())
 }
 
@@ -0,0 +1,82 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+use bytes::{BufMut, Bytes, BytesMut};
+use gel_protocol::codec::{STD_INT64, STD_STR};
+use gel_protocol::descriptors::Typedesc;
+use gel_protocol::queryable::{Decoder, Queryable};
+
+use common::{decode, roundtrip, str_elements, Descriptors};
+
+mod common;
+
+/// Descriptors: `str`, `int64`, `tuple<str, int64>`, `array<tuple<str, int64>>`,
+/// `tuple<name: str, email: str>`
+fn typedesc() -> Typedesc {
+    Descriptors::new()
+        .scalars(&[STD_STR, STD_INT64])
+        .tuple(&[0, 1])
+        .array(2)
+        .named_tuple(&[("name", 0), ("email", 0)])
+        .build()
+}
+
+fn int_array(items: &[i64]) -> Bytes {
+    let mut buf = BytesMut::new();
+    buf.put_u32(1); // ndims
+    buf.put_u32(0);
+    buf.put_u32(0);
+    buf.put_u32(items.len() as u32);
+    buf.put_u32(1); // lower
+    for item in items {
+        buf.put_u32(8);
+        buf.put_i64(*item);
+    }
+    buf.freeze()
+}
+
+#[test]
+fn sets_and_arrays() {
+    let buf = int_array(&[3, 1, 2, 1]);
+    let dec = Decoder::default();
+    assert_eq!(
+        HashSet::<i64>::decode(&dec, &(), &buf).unwrap(),
+        HashSet::from([1, 2, 3])
+    );
+    assert_eq!(
+        BTreeSet::<i64>::decode(&dec, &(), &buf).unwrap(),
+        BTreeSet::from([1, 2, 3])
+    );
+    assert_eq!(
+        VecDeque::<i64>::decode(&dec, &(), &buf).unwrap(),
+        VecDeque::from([3, 1, 2, 1])
+    );
+    assert_eq!(
+        &*Box::<[i64]>::decode(&dec, &(), &buf).unwrap(),
+        &[3, 1, 2, 1]
+    );
+    assert_eq!(
+        &*Arc::<[i64]>::decode(&dec, &(), &buf).unwrap(),
+        &[3, 1, 2, 1]
+    );
+}
+
+#[test]
+fn map_from_pairs() {
+    let map = BTreeMap::from([("a".to_string(), 1_i64), ("b".to_string(), 2)]);
+    let decoded: HashMap<String, i64> = roundtrip(&typedesc(), 3, &map);
+    assert_eq!(decoded, map.into_iter().collect::<HashMap<_, _>>());
+}
+
+#[test]
+fn map_from_named_tuple() {
+    let buf = str_elements(&["John", "john@example.com"]);
+    let decoded: BTreeMap<String, String> = decode(&typedesc(), 4, &buf);
+    assert_eq!(
+        decoded,
+        BTreeMap::from([
+            ("name".into(), "John".into()),
+            ("email".into(), "john@example.com".into()),
+        ])
+    );
+}
@@ -0,0 +1,147 @@
+//! Fixtures shared by the `Queryable` and `QueryArg` tests
+#![allow(dead_code)]
+
+use bytes::{BufMut, Bytes, BytesMut};
+use gel_protocol::codec;
+use gel_protocol::common::RawTypedesc;
+use gel_protocol::descriptors::{TypePos, Typedesc};
+use gel_protocol::features::ProtocolVersion;
+use gel_protocol::model::Uuid;
+use gel_protocol::query_arg::{Encoder, QueryArg};
+use gel_protocol::queryable::{Decoder, Queryable};
+
+/// Writes type descriptors in the wire format
+///
+/// Descriptors refer to each other by position, the last descriptor
+/// written becomes the root one.
+#[derive(Default)]
+pub struct Descriptors {
+    buf: BytesMut,
+    count: u16,
+    root: Uuid,
+}
+
+impl Descriptors {
+    pub fn new() -> Descriptors {
+        Descriptors::default()
+    }
+    /// Base scalars with the well-known `ids`
+    pub fn scalars(mut self, ids: &[Uuid]) -> Descriptors {
+        for id in ids {
+            self.buf.put_u8(0x02);
+            self.buf.put_slice(id.as_bytes());
+            self.push(*id);
+        }
+        self
+    }
+    pub fn tuple(mut self, elements: &[u16]) -> Descriptors {
+        self.buf.put_u8(0x04);
+        self.put_id();
+        self.buf.put_u16(elements.len() as u16);
+        for type_pos in elements {
+            self.buf.put_u16(*type_pos);
+        }
+        self
+    }
+    pub fn named_tuple(mut self, elements: &[(&str, u16)]) -> Descriptors {
+        self.buf.put_u8(0x05);
+        self.put_id();
+        self.buf.put_u16(elements.len() as u16);
+        for (name, type_pos) in elements {
+            self.put_str(name);
+            self.buf.put_u16(*type_pos);
+        }
+        self
+    }
+    /// One-dimensional array of unlimited size
+    pub fn array(mut self, element: u16) -> Descriptors {
+        self.buf.put_u8(0x06);
+        self.put_id();
+        self.buf.put_u16(element);
+        self.buf.put_u16(1); // dimensions
+        self.buf.put_i32(-1);
+        self
+    }
+    pub fn range(mut self, element: u16) -> Descriptors {
+        self.buf.put_u8(0x09);
+        self.put_id();
+        self.buf.put_u16(element);
+        self
+    }
+    pub fn multi_range(mut self, element: u16) -> Descriptors {
+        self.buf.put_u8(0x0C);
+        self.put_id();
+        self.buf.put_u16(element);
+        self
+    }
+    pub fn build(self) -> Typedesc {
+        RawTypedesc {
+            proto: ProtocolVersion::new(1, 0),
+            id: self.root,
+            data: self.buf.freeze(),
+        }
+        .decode()
+        .unwrap()
+    }
+    fn put_id(&mut self) {
+        // ids of non-scalar descriptors don't matter, only need to be unique
+        let id = Uuid::from_u128(u128::from(self.count) + 1);
+        self.buf.put_slice(id.as_bytes());
+        self.push(id);
+    }
+    fn put_str(&mut self, value: &str) {
+        self.buf.put_u32(value.len() as u32);
+        self.buf.put_slice(value.as_bytes());
+    }
+    fn push(&mut self, id: Uuid) {
+        self.count += 1;
+        self.root = id;
+    }
+}
+
+/// Encoded tuple or object having `str` elements with `values`
+pub fn str_elements(values: &[&str]) -> Bytes {
+    let mut buf = BytesMut::new();
+    buf.put_u32(values.len() as u32);
+    for value in values {
+        buf.put_u32(0); // reserved
+        buf.put_u32(value.len() as u32);
+        buf.put_slice(value.as_bytes());
+    }
+    buf.freeze()
+}
+
+/// Decodes `buf` as `T` with the descriptor at `type_pos`
+pub fn decode<T: Queryable>(desc: &Typedesc, type_pos: u16, buf: &[u8]) -> T {
+    let ctx = desc.as_queryable_context();
+    let args = T::check_descriptor(&ctx, TypePos(type_pos)).unwrap();
+    T::decode(&Decoder::default(), &args, buf).unwrap()
+}
+
+/// Encodes `value` as a query argument and decodes it back as `T`
+pub fn roundtrip<A: QueryArg, T: Queryable>(desc: &Typedesc, type_pos: u16, value: &A) -> T {
+    let mut buf = BytesMut::new();
+    let ctx = desc.as_query_arg_context();
+    let mut enc = Encoder::new(&ctx, &mut buf);
+    value.check_descriptor(enc.ctx, TypePos(type_pos)).unwrap();
+    value.encode_slot(&mut enc).unwrap();
+    // skip the length of the argument
+    decode(desc, type_pos, &buf[4..])
+}
+
+/// Descriptors: `std::datetime`, `cal::local_datetime`, `cal::local_date`,
+/// `cal::local_time`, `std::duration`, `cal::relative_duration`,
+/// `cal::date_duration`
+pub fn datetime_typedesc() -> Typedesc {
+    Descriptors::new()
+        .scalars(&[
+            codec::STD_DATETIME,
+            codec::CAL_LOCAL_DATETIME,
+            codec::CAL_LOCAL_DATE,
+            codec::CAL_LOCAL_TIME,
+            codec::STD_DURATION,
+            codec::CAL_RELATIVE_DURATION,
+            codec::CAL_DATE_DURATION,
+        ])
+        .build()
+}
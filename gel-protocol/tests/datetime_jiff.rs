@@ -0,0 +1,525 @@
+mod common;
+
+#[cfg(feature = "jiff")]
+mod jiff {
+
+    use std::convert::{TryFrom, TryInto};
+
+    use bytes::{Buf, BytesMut};
+    use gel_protocol::codec::{self, Codec};
+    use gel_protocol::descriptors::TypePos;
+    use gel_protocol::model::{DateDuration, Datetime, Duration, LocalDate, LocalDatetime};
+    use gel_protocol::model::{LocalTime, RelativeDuration};
+    use gel_protocol::queryable::Queryable;
+    use gel_protocol::value::Value;
+    use jiff::civil::{Date, DateTime, Time};
+    use jiff::{SignedDuration, Span, Timestamp};
+    use test_case::test_case;
+
+    use crate::common::{datetime_typedesc, roundtrip};
+
+    // ========
+    // Datetime
+    // ========
+
+    // Minimum and Maximum
+    // -------------------
+    #[test_case(
+        /*input*/ "9999-12-30T22:00:00Z",
+        // Note: `Timestamp` can't represent anything later
+        /*micros*/ 252455522400000000,
+        /*formatted*/ "9999-12-30T22:00:00Z"
+        ; "maximum"
+    )]
+    #[test_case(
+        /*input*/ "0001-01-01T00:00:00.000000Z",
+        /*micros*/ -63082281600000000,
+        /*formatted*/ "0001-01-01T00:00:00Z"
+        ; "minimum"
+    )]
+    // Rounding in Various Ranges
+    // --------------------------
+    #[test_case(
+        /*input*/ "1814-03-09T01:02:03.000005500Z",
+        /*micros*/ -5863791476999994,
+        /*formatted*/ "1814-03-09T01:02:03.000006Z"
+        ; "negative unix timestamp, round up"
+    )]
+    #[test_case(
+        /*input*/ "1814-03-09T01:02:03.000005501Z",
+        /*micros*/ -5863791476999994,
+        /*formatted*/ "1814-03-09T01:02:03.000006Z"
+        ; "negative unix timestamp, 5501"
+    )]
+    #[test_case(
+        /*input*/ "1814-03-09T01:02:03.000005499Z",
+        /*micros*/ -5863791476999995,
+        /*formatted*/ "1814-03-09T01:02:03.000005Z"
+        ; "negative unix timestamp, 5499"
+    )]
+    #[test_case(
+        /*input*/ "1856-08-27T01:02:03.000004500Z",
+        /*micros*/ -4523554676999996,
+        /*formatted*/ "1856-08-27T01:02:03.000004Z"
+        ; "negative unix timestamp, round down"
+    )]
+    #[test_case(
+        /*input*/ "1856-08-27T01:02:03.000004501Z",
+        /*micros*/ -4523554676999995,
+        /*formatted*/ "1856-08-27T01:02:03.000005Z"
+        ; "negative unix timestamp, 4501"
+    )]
+    #[test_case(
+        /*input*/ "1856-08-27T01:02:03.000004499Z",
+        /*micros*/ -4523554676999996,
+        /*formatted*/ "1856-08-27T01:02:03.000004Z"
+        ; "negative unix timestamp, 4499"
+    )]
+    #[test_case(
+        /*input*/ "1969-12-31T23:59:59.999999500Z",
+        /*micros*/ -946684800000000,
+        /*formatted*/ "1970-01-01T00:00:00Z"
+        ; "unix timestamp to zero"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000009500Z",
+        /*micros*/ -78620276999990,
+        /*formatted*/ "1997-07-05T01:02:03.000010Z"
+        ; "negative postgres timestamp, round up"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000009500Z",
+        /*micros*/ -78620276999990,
+        /*formatted*/ "1997-07-05T01:02:03.000010Z"
+        ; "negative postgres timestamp, 9501"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000009499Z",
+        /*micros*/ -78620276999991,
+        /*formatted*/ "1997-07-05T01:02:03.000009Z"
+        ; "negative postgres timestamp, 9499"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000000500Z",
+        /*micros*/ -78620277000000,
+        /*formatted*/ "1997-07-05T01:02:03Z"
+        ; "negative postgres timestamp, round down"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000000501Z",
+        /*micros*/ -78620276999999,
+        /*formatted*/ "1997-07-05T01:02:03.000001Z"
+        ; "negative postgres timestamp, 501"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000000499Z",
+        /*micros*/ -78620277000000,
+        /*formatted*/ "1997-07-05T01:02:03Z"
+        ; "negative postgres timestamp, 499"
+    )]
+    #[test_case(
+        /*input*/ "1999-12-31T23:59:59.999999500Z",
+        /*micros*/ 0,
+        /*formatted*/ "2000-01-01T00:00:00Z"
+        ; "postgres timestamp to zero"
+    )]
+    #[test_case(
+        /*input*/ "2014-02-27T00:00:00.000001500Z",
+        /*micros*/ 446774400000002,
+        /*formatted*/ "2014-02-27T00:00:00.000002Z"
+        ; "positive timestamp, round up"
+    )]
+    #[test_case(
+        /*input*/ "2014-02-27T00:00:00.000001501Z",
+        /*micros*/ 446774400000002,
+        /*formatted*/ "2014-02-27T00:00:00.000002Z"
+        ; "positive timestamp, 1501"
+    )]
+    #[test_case(
+        /*input*/ "2014-02-27T00:00:00.000001499Z",
+        /*micros*/ 446774400000001,
+        /*formatted*/ "2014-02-27T00:00:00.000001Z"
+        ; "positive timestamp, 1499"
+    )]
+    #[test_case(
+        /*input*/ "2022-02-24T05:43:03.000002500Z",
+        /*micros*/ 698996583000002,
+        /*formatted*/ "2022-02-24T05:43:03.000002Z"
+        ; "positive timestamp, round down"
+    )]
+    #[test_case(
+        /*input*/ "2022-02-24T05:43:03.000002501Z",
+        /*micros*/ 698996583000003,
+        /*formatted*/ "2022-02-24T05:43:03.000003Z"
+        ; "positive timestamp, 2501"
+    )]
+    #[test_case(
+        /*input*/ "2022-02-24T05:43:03.000002499Z",
+        /*micros*/ 698996583000002,
+        /*formatted*/ "2022-02-24T05:43:03.000002Z"
+        ; "positive timestamp, 2499"
+    )]
+    fn datetime(input: &str, micros: i64, formatted: &str) {
+        let jiff: Timestamp = input.parse().unwrap();
+        let edgedb: Datetime = jiff.try_into().unwrap();
+        assert_eq!(format!("{:?}", edgedb), formatted);
+
+        let mut buf = BytesMut::new();
+        let val = Value::Datetime(edgedb);
+        codec::Datetime.encode(&mut buf, &val).unwrap();
+        let serialized_micros = buf.get_i64();
+
+        assert_eq!(serialized_micros, micros);
+
+        let rev = Timestamp::try_from(edgedb).unwrap();
+        assert_eq!(rev, formatted.parse().unwrap());
+    }
+
+    // ==============
+    // Local Datetime
+    // ==============
+
+    // Minimum and Maximum
+    // -------------------
+    #[test_case(
+        /*input*/ "9999-12-31T23:59:59.999999499",
+        // Note: Can't round up here, so   --^
+        /*micros*/ 252455615999999999,
+        /*formatted*/ "9999-12-31T23:59:59.999999"
+        ; "maximum"
+    )]
+    #[test_case(
+        /*input*/ "0001-01-01T00:00:00.000000",
+        /*micros*/ -63082281600000000,
+        /*formatted*/ "0001-01-01T00:00:00"
+        ; "minimum"
+    )]
+    // Rounding in Various Ranges
+    // --------------------------
+    #[test_case(
+        /*input*/ "1814-03-09T01:02:03.000005500",
+        /*micros*/ -5863791476999994,
+        /*formatted*/ "1814-03-09T01:02:03.000006"
+        ; "negative unix timestamp, round up"
+    )]
+    #[test_case(
+        /*input*/ "1814-03-09T01:02:03.000005501",
+        /*micros*/ -5863791476999994,
+        /*formatted*/ "1814-03-09T01:02:03.000006"
+        ; "negative unix timestamp, 5501"
+    )]
+    #[test_case(
+        /*input*/ "1814-03-09T01:02:03.000005499",
+        /*micros*/ -5863791476999995,
+        /*formatted*/ "1814-03-09T01:02:03.000005"
+        ; "negative unix timestamp, 5499"
+    )]
+    #[test_case(
+        /*input*/ "1856-08-27T01:02:03.000004500",
+        /*micros*/ -4523554676999996,
+        /*formatted*/ "1856-08-27T01:02:03.000004"
+        ; "negative unix timestamp, round down"
+    )]
+    #[test_case(
+        /*input*/ "1856-08-27T01:02:03.000004501",
+        /*micros*/ -4523554676999995,
+        /*formatted*/ "1856-08-27T01:02:03.000005"
+        ; "negative unix timestamp, 4501"
+    )]
+    #[test_case(
+        /*input*/ "1856-08-27T01:02:03.000004499",
+        /*micros*/ -4523554676999996,
+        /*formatted*/ "1856-08-27T01:02:03.000004"
+        ; "negative unix timestamp, 4499"
+    )]
+    #[test_case(
+        /*input*/ "1969-12-31T23:59:59.999999500",
+        /*micros*/ -946684800000000,
+        /*formatted*/ "1970-01-01T00:00:00"
+        ; "unix timestamp to zero"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000009500",
+        /*micros*/ -78620276999990,
+        /*formatted*/ "1997-07-05T01:02:03.000010"
+        ; "negative postgres timestamp, round up"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000009500",
+        /*micros*/ -78620276999990,
+        /*formatted*/ "1997-07-05T01:02:03.000010"
+        ; "negative postgres timestamp, 9501"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000009499",
+        /*micros*/ -78620276999991,
+        /*formatted*/ "1997-07-05T01:02:03.000009"
+        ; "negative postgres timestamp, 9499"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000000500",
+        /*micros*/ -78620277000000,
+        /*formatted*/ "1997-07-05T01:02:03"
+        ; "negative postgres timestamp, round down"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000000501",
+        /*micros*/ -78620276999999,
+        /*formatted*/ "1997-07-05T01:02:03.000001"
+        ; "negative postgres timestamp, 501"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000000499",
+        /*micros*/ -78620277000000,
+        /*formatted*/ "1997-07-05T01:02:03"
+        ; "negative postgres timestamp, 499"
+    )]
+    #[test_case(
+        /*input*/ "1999-12-31T23:59:59.999999500",
+        /*micros*/ 0,
+        /*formatted*/ "2000-01-01T00:00:00"
+        ; "postgres timestamp to zero"
+    )]
+    #[test_case(
+        /*input*/ "2014-02-27T00:00:00.000001500",
+        /*micros*/ 446774400000002,
+        /*formatted*/ "2014-02-27T00:00:00.000002"
+        ; "positive timestamp, round up"
+    )]
+    #[test_case(
+        /*input*/ "2014-02-27T00:00:00.000001501",
+        /*micros*/ 446774400000002,
+        /*formatted*/ "2014-02-27T00:00:00.000002"
+        ; "positive timestamp, 1501"
+    )]
+    #[test_case(
+        /*input*/ "2014-02-27T00:00:00.000001499",
+        /*micros*/ 446774400000001,
+        /*formatted*/ "2014-02-27T00:00:00.000001"
+        ; "positive timestamp, 1499"
+    )]
+    #[test_case(
+        /*input*/ "2022-02-24T05:43:03.000002500",
+        /*micros*/ 698996583000002,
+        /*formatted*/ "2022-02-24T05:43:03.000002"
+        ; "positive timestamp, round down"
+    )]
+    #[test_case(
+        /*input*/ "2022-02-24T05:43:03.000002501",
+        /*micros*/ 698996583000003,
+        /*formatted*/ "2022-02-24T05:43:03.000003"
+        ; "positive timestamp, 2501"
+    )]
+    #[test_case(
+        /*input*/ "2022-02-24T05:43:03.000002499",
+        /*micros*/ 698996583000002,
+        /*formatted*/ "2022-02-24T05:43:03.000002"
+        ; "positive timestamp, 2499"
+    )]
+    fn local_datetime(input: &str, micros: i64, formatted: &str) {
+        let jiff: DateTime = input.parse().unwrap();
+        let edgedb: LocalDatetime = jiff.try_into().unwrap();
+        assert_eq!(format!("{:?}", edgedb), formatted);
+
+        let mut buf = BytesMut::new();
+        let val = Value::LocalDatetime(edgedb);
+        codec::LocalDatetime.encode(&mut buf, &val).unwrap();
+        let serialized_micros = buf.get_i64();
+
+        assert_eq!(serialized_micros, micros);
+
+        let rev = DateTime::from(edgedb);
+        assert_eq!(rev, formatted.parse().unwrap());
+    }
+
+    // ==========
+    // Local Time
+    // ==========
+    #[test_case(
+        /*input*/ "23:59:59.999999500",
+        // Note: Can't round up here, so   --^
+        /*micros*/ 0,
+        /*formatted*/ "00:00:00"
+        ; "wraparound"
+    )]
+    #[test_case(
+        /*input*/ "00:00:00.000000",
+        /*micros*/ 0,
+        /*formatted*/ "00:00:00"
+        ; "minimum"
+    )]
+    #[test_case(
+        /*input*/ "23:59:59.999999",
+        /*micros*/ 86399999999,
+        /*formatted*/ "23:59:59.999999"
+        ; "maximum"
+    )]
+    #[test_case(
+        /*input*/ "01:02:03.000005500",
+        /*micros*/ 3723000006,
+        /*formatted*/ "01:02:03.000006"
+        ; "round up"
+    )]
+    #[test_case(
+        /*input*/ "01:02:03.000005501",
+        /*micros*/ 3723000006,
+        /*formatted*/ "01:02:03.000006"
+        ; "5501"
+    )]
+    #[test_case(
+        /*input*/ "01:02:03.000005499",
+        /*micros*/ 3723000005,
+        /*formatted*/ "01:02:03.000005"
+        ; "5499"
+    )]
+    #[test_case(
+        /*input*/ "01:02:03.000004500",
+        /*micros*/ 3723000004,
+        /*formatted*/ "01:02:03.000004"
+        ; "round down"
+    )]
+    #[test_case(
+        /*input*/ "01:02:03.000004501",
+        /*micros*/ 3723000005,
+        /*formatted*/ "01:02:03.000005"
+        ; "4501"
+    )]
+    #[test_case(
+        /*input*/ "01:02:03.000004499",
+        /*micros*/ 3723000004,
+        /*formatted*/ "01:02:03.000004"
+        ; "4499"
+    )]
+    fn local_time(input: &str, micros: i64, formatted: &str) {
+        let jiff: Time = input.parse().unwrap();
+        let edgedb: LocalTime = jiff.into();
+        assert_eq!(format!("{:?}", edgedb), formatted);
+
+        let mut buf = BytesMut::new();
+        let val = Value::LocalTime(edgedb);
+        codec::LocalTime.encode(&mut buf, &val).unwrap();
+        let serialized_micros = buf.get_i64();
+
+        assert_eq!(serialized_micros, micros);
+
+        let rev = Time::from(edgedb);
+        assert_eq!(rev, formatted.parse().unwrap());
+    }
+
+    // ==========
+    // Local Date
+    // ==========
+    #[test_case("0001-01-01", -730119 ; "minimum")]
+    #[test_case("9999-12-31", 2921939 ; "maximum")]
+    #[test_case("1970-01-01", -10957 ; "unix epoch")]
+    #[test_case("2000-01-01", 0 ; "postgres epoch")]
+    #[test_case("2024-02-29", 8825 ; "leap day")]
+    fn local_date(input: &str, days: i32) {
+        let jiff: Date = input.parse().unwrap();
+        let edgedb: LocalDate = jiff.try_into().unwrap();
+        assert_eq!(edgedb.to_days(), days);
+        assert_eq!(Date::from(edgedb), jiff);
+    }
+
+    #[test]
+    fn out_of_range() {
+        let date: Date = "0000-12-31".parse().unwrap();
+        assert!(LocalDate::try_from(date).is_err());
+        let datetime: DateTime = "0000-12-31T23:59:59.999999".parse().unwrap();
+        assert!(LocalDatetime::try_from(datetime).is_err());
+        let datetime: DateTime = "9999-12-31T23:59:59.999999500".parse().unwrap();
+        assert!(LocalDatetime::try_from(datetime).is_err());
+        let timestamp: Timestamp = "-000001-01-01T00:00:00Z".parse().unwrap();
+        assert!(Datetime::try_from(timestamp).is_err());
+        let datetime: DateTime = "9999-12-31T23:00:00".parse().unwrap();
+        let edgedb = LocalDatetime::try_from(datetime).unwrap().to_utc();
+        assert!(Timestamp::try_from(edgedb).is_err());
+    }
+
+    // ========
+    // Duration
+    // ========
+    #[test_case(0, 0, 0 ; "zero")]
+    #[test_case(1, 500, 1_000_000 ; "round down")]
+    #[test_case(1, 1_500, 1_000_002 ; "round up")]
+    #[test_case(1, 1_499, 1_000_001 ; "1499")]
+    #[test_case(-1, -500, -1_000_000 ; "negative, round down")]
+    #[test_case(-1, -1_500, -1_000_002 ; "negative, round up")]
+    #[test_case(-1, -1_501, -1_000_002 ; "negative, 1501")]
+    fn duration(secs: i64, nanos: i32, micros: i64) {
+        let jiff = SignedDuration::new(secs, nanos);
+        let edgedb: Duration = jiff.try_into().unwrap();
+        assert_eq!(edgedb.to_micros(), micros);
+        assert_eq!(
+            SignedDuration::from(edgedb),
+            SignedDuration::from_micros(micros)
+        );
+    }
+
+    // =================
+    // Relative Duration
+    // =================
+    #[test_case("P1Y2M3DT4H5M6.000007S", 14, 3, 14_706_000_007 ; "all units")]
+    #[test_case("P2W", 0, 14, 0 ; "weeks")]
+    #[test_case("-P1MT1.0000015S", -1, 0, -1_000_002 ; "negative, round up")]
+    #[test_case("PT0.0000025S", 0, 0, 2 ; "round down")]
+    #[test_case("PT0S", 0, 0, 0 ; "zero")]
+    fn relative_duration(input: &str, months: i32, days: i32, micros: i64) {
+        let jiff: Span = input.parse().unwrap();
+        let edgedb: RelativeDuration = jiff.try_into().unwrap();
+        let expected = RelativeDuration::from_months(months)
+            + RelativeDuration::from_days(days)
+            + RelativeDuration::from_micros(micros);
+        assert_eq!(edgedb, expected);
+
+        let rev = Span::try_from(edgedb).unwrap();
+        assert_eq!(RelativeDuration::try_from(rev), Ok(edgedb));
+    }
+
+    #[test]
+    fn calendar_durations() {
+        assert!(Duration::try_from(SignedDuration::MAX).is_err());
+
+        let mixed = RelativeDuration::from_months(1) + RelativeDuration::from_days(-1);
+        assert!(Span::try_from(mixed).is_err());
+
+        let date = DateDuration::from_years(1) + DateDuration::from_days(3);
+        let span = Span::try_from(date).unwrap();
+        assert_eq!(span.fieldwise(), Span::new().months(12).days(3));
+        assert_eq!(DateDuration::try_from(span), Ok(date));
+        let span: Span = "P1Y2W".parse().unwrap();
+        assert_eq!(
+            DateDuration::try_from(span),
+            Ok(DateDuration::from_months(12) + DateDuration::from_days(14))
+        );
+        assert!(DateDuration::try_from(Span::new().days(1).hours(1)).is_err());
+    }
+
+    #[test]
+    fn query_args() {
+        let desc = datetime_typedesc();
+        let jiff: Timestamp = "2022-02-24T05:43:03.000002501Z".parse().unwrap();
+        let decoded: Datetime = roundtrip(&desc, 0, &jiff);
+        assert_eq!(decoded, Datetime::try_from(jiff).unwrap());
+        let decoded: Timestamp = roundtrip(&desc, 0, &decoded);
+        assert_eq!(decoded, "2022-02-24T05:43:03.000003Z".parse().unwrap());
+
+        let jiff: DateTime = "2022-02-24T05:43:03".parse().unwrap();
+        let decoded: DateTime = roundtrip(&desc, 1, &jiff);
+        assert_eq!(decoded, jiff);
+        let decoded: Date = roundtrip(&desc, 2, &jiff.date());
+        assert_eq!(decoded, jiff.date());
+        let decoded: Time = roundtrip(&desc, 3, &jiff.time());
+        assert_eq!(decoded, jiff.time());
+        let decoded: SignedDuration = roundtrip(&desc, 4, &SignedDuration::from_mins(-90));
+        assert_eq!(decoded, SignedDuration::from_mins(-90));
+        let span = Span::new().months(3).days(2).microseconds(1);
+        let decoded: Span = roundtrip(&desc, 5, &span);
+        assert_eq!(decoded.fieldwise(), span);
+
+        let ctx = desc.as_queryable_context();
+        <Timestamp as Queryable>::check_descriptor(&ctx, TypePos(1)).unwrap_err();
+        <DateTime as Queryable>::check_descriptor(&ctx, TypePos(0)).unwrap_err();
+        <Span as Queryable>::check_descriptor(&ctx, TypePos(6)).unwrap_err();
+    }
+}
@@ -0,0 +1,516 @@
+mod common;
+
+#[cfg(feature = "time")]
+mod time {
+
+    use std::convert::{TryFrom, TryInto};
+
+    use bytes::{Buf, BytesMut};
+    use gel_protocol::codec::{self, Codec};
+    use gel_protocol::descriptors::TypePos;
+    use gel_protocol::model::{DateDuration, Datetime, Duration, LocalDate, LocalDatetime};
+    use gel_protocol::model::{LocalTime, RelativeDuration};
+    use gel_protocol::queryable::Queryable;
+    use gel_protocol::value::Value;
+    use test_case::test_case;
+    use time::format_description::well_known::{Iso8601, Rfc3339};
+    use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time};
+
+    use crate::common::{datetime_typedesc, roundtrip};
+
+    fn parse_time(input: &str) -> Time {
+        let datetime = format!("2000-01-01T{}", input);
+        PrimitiveDateTime::parse(&datetime, &Iso8601::DEFAULT)
+            .unwrap()
+            .time()
+    }
+
+    // ========
+    // Datetime
+    // ========
+
+    // Minimum and Maximum
+    // -------------------
+    #[test_case(
+        /*input*/ "9999-12-31T23:59:59.999999499Z",
+        // Note: Can't round up here, so   --^
+        /*micros*/ 252455615999999999,
+        /*formatted*/ "9999-12-31T23:59:59.999999Z"
+        ; "maximum"
+    )]
+    #[test_case(
+        /*input*/ "0001-01-01T00:00:00.000000Z",
+        /*micros*/ -63082281600000000,
+        /*formatted*/ "0001-01-01T00:00:00Z"
+        ; "minimum"
+    )]
+    // Rounding in Various Ranges
+    // --------------------------
+    #[test_case(
+        /*input*/ "1814-03-09T01:02:03.000005500Z",
+        /*micros*/ -5863791476999994,
+        /*formatted*/ "1814-03-09T01:02:03.000006Z"
+        ; "negative unix timestamp, round up"
+    )]
+    #[test_case(
+        /*input*/ "1814-03-09T01:02:03.000005501Z",
+        /*micros*/ -5863791476999994,
+        /*formatted*/ "1814-03-09T01:02:03.000006Z"
+        ; "negative unix timestamp, 5501"
+    )]
+    #[test_case(
+        /*input*/ "1814-03-09T01:02:03.000005499Z",
+        /*micros*/ -5863791476999995,
+        /*formatted*/ "1814-03-09T01:02:03.000005Z"
+        ; "negative unix timestamp, 5499"
+    )]
+    #[test_case(
+        /*input*/ "1856-08-27T01:02:03.000004500Z",
+        /*micros*/ -4523554676999996,
+        /*formatted*/ "1856-08-27T01:02:03.000004Z"
+        ; "negative unix timestamp, round down"
+    )]
+    #[test_case(
+        /*input*/ "1856-08-27T01:02:03.000004501Z",
+        /*micros*/ -4523554676999995,
+        /*formatted*/ "1856-08-27T01:02:03.000005Z"
+        ; "negative unix timestamp, 4501"
+    )]
+    #[test_case(
+        /*input*/ "1856-08-27T01:02:03.000004499Z",
+        /*micros*/ -4523554676999996,
+        /*formatted*/ "1856-08-27T01:02:03.000004Z"
+        ; "negative unix timestamp, 4499"
+    )]
+    #[test_case(
+        /*input*/ "1969-12-31T23:59:59.999999500Z",
+        /*micros*/ -946684800000000,
+        /*formatted*/ "1970-01-01T00:00:00Z"
+        ; "unix timestamp to zero"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000009500Z",
+        /*micros*/ -78620276999990,
+        /*formatted*/ "1997-07-05T01:02:03.000010Z"
+        ; "negative postgres timestamp, round up"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000009500Z",
+        /*micros*/ -78620276999990,
+        /*formatted*/ "1997-07-05T01:02:03.000010Z"
+        ; "negative postgres timestamp, 9501"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000009499Z",
+        /*micros*/ -78620276999991,
+        /*formatted*/ "1997-07-05T01:02:03.000009Z"
+        ; "negative postgres timestamp, 9499"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000000500Z",
+        /*micros*/ -78620277000000,
+        /*formatted*/ "1997-07-05T01:02:03Z"
+        ; "negative postgres timestamp, round down"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000000501Z",
+        /*micros*/ -78620276999999,
+        /*formatted*/ "1997-07-05T01:02:03.000001Z"
+        ; "negative postgres timestamp, 501"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000000499Z",
+        /*micros*/ -78620277000000,
+        /*formatted*/ "1997-07-05T01:02:03Z"
+        ; "negative postgres timestamp, 499"
+    )]
+    #[test_case(
+        /*input*/ "1999-12-31T23:59:59.999999500Z",
+        /*micros*/ 0,
+        /*formatted*/ "2000-01-01T00:00:00Z"
+        ; "postgres timestamp to zero"
+    )]
+    #[test_case(
+        /*input*/ "2014-02-27T00:00:00.000001500Z",
+        /*micros*/ 446774400000002,
+        /*formatted*/ "2014-02-27T00:00:00.000002Z"
+        ; "positive timestamp, round up"
+    )]
+    #[test_case(
+        /*input*/ "2014-02-27T00:00:00.000001501Z",
+        /*micros*/ 446774400000002,
+        /*formatted*/ "2014-02-27T00:00:00.000002Z"
+        ; "positive timestamp, 1501"
+    )]
+    #[test_case(
+        /*input*/ "2014-02-27T00:00:00.000001499Z",
+        /*micros*/ 446774400000001,
+        /*formatted*/ "2014-02-27T00:00:00.000001Z"
+        ; "positive timestamp, 1499"
+    )]
+    #[test_case(
+        /*input*/ "2022-02-24T05:43:03.000002500Z",
+        /*micros*/ 698996583000002,
+        /*formatted*/ "2022-02-24T05:43:03.000002Z"
+        ; "positive timestamp, round down"
+    )]
+    #[test_case(
+        /*input*/ "2022-02-24T05:43:03.000002501Z",
+        /*micros*/ 698996583000003,
+        /*formatted*/ "2022-02-24T05:43:03.000003Z"
+        ; "positive timestamp, 2501"
+    )]
+    #[test_case(
+        /*input*/ "2022-02-24T05:43:03.000002499Z",
+        /*micros*/ 698996583000002,
+        /*formatted*/ "2022-02-24T05:43:03.000002Z"
+        ; "positive timestamp, 2499"
+    )]
+    fn datetime(input: &str, micros: i64, formatted: &str) {
+        let time = OffsetDateTime::parse(input, &Rfc3339).unwrap();
+        let edgedb: Datetime = time.try_into().unwrap();
+        assert_eq!(format!("{:?}", edgedb), formatted);
+
+        let mut buf = BytesMut::new();
+        let val = Value::Datetime(edgedb);
+        codec::Datetime.encode(&mut buf, &val).unwrap();
+        let serialized_micros = buf.get_i64();
+
+        assert_eq!(serialized_micros, micros);
+
+        let rev = OffsetDateTime::from(edgedb);
+        assert_eq!(rev, OffsetDateTime::parse(formatted, &Rfc3339).unwrap());
+    }
+
+    // ==============
+    // Local Datetime
+    // ==============
+
+    // Minimum and Maximum
+    // -------------------
+    #[test_case(
+        /*input*/ "9999-12-31T23:59:59.999999499",
+        // Note: Can't round up here, so   --^
+        /*micros*/ 252455615999999999,
+        /*formatted*/ "9999-12-31T23:59:59.999999"
+        ; "maximum"
+    )]
+    #[test_case(
+        /*input*/ "0001-01-01T00:00:00.000000",
+        /*micros*/ -63082281600000000,
+        /*formatted*/ "0001-01-01T00:00:00"
+        ; "minimum"
+    )]
+    // Rounding in Various Ranges
+    // --------------------------
+    #[test_case(
+        /*input*/ "1814-03-09T01:02:03.000005500",
+        /*micros*/ -5863791476999994,
+        /*formatted*/ "1814-03-09T01:02:03.000006"
+        ; "negative unix timestamp, round up"
+    )]
+    #[test_case(
+        /*input*/ "1814-03-09T01:02:03.000005501",
+        /*micros*/ -5863791476999994,
+        /*formatted*/ "1814-03-09T01:02:03.000006"
+        ; "negative unix timestamp, 5501"
+    )]
+    #[test_case(
+        /*input*/ "1814-03-09T01:02:03.000005499",
+        /*micros*/ -5863791476999995,
+        /*formatted*/ "1814-03-09T01:02:03.000005"
+        ; "negative unix timestamp, 5499"
+    )]
+    #[test_case(
+        /*input*/ "1856-08-27T01:02:03.000004500",
+        /*micros*/ -4523554676999996,
+        /*formatted*/ "1856-08-27T01:02:03.000004"
+        ; "negative unix timestamp, round down"
+    )]
+    #[test_case(
+        /*input*/ "1856-08-27T01:02:03.000004501",
+        /*micros*/ -4523554676999995,
+        /*formatted*/ "1856-08-27T01:02:03.000005"
+        ; "negative unix timestamp, 4501"
+    )]
+    #[test_case(
+        /*input*/ "1856-08-27T01:02:03.000004499",
+        /*micros*/ -4523554676999996,
+        /*formatted*/ "1856-08-27T01:02:03.000004"
+        ; "negative unix timestamp, 4499"
+    )]
+    #[test_case(
+        /*input*/ "1969-12-31T23:59:59.999999500",
+        /*micros*/ -946684800000000,
+        /*formatted*/ "1970-01-01T00:00:00"
+        ; "unix timestamp to zero"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000009500",
+        /*micros*/ -78620276999990,
+        /*formatted*/ "1997-07-05T01:02:03.000010"
+        ; "negative postgres timestamp, round up"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000009500",
+        /*micros*/ -78620276999990,
+        /*formatted*/ "1997-07-05T01:02:03.000010"
+        ; "negative postgres timestamp, 9501"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000009499",
+        /*micros*/ -78620276999991,
+        /*formatted*/ "1997-07-05T01:02:03.000009"
+        ; "negative postgres timestamp, 9499"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000000500",
+        /*micros*/ -78620277000000,
+        /*formatted*/ "1997-07-05T01:02:03"
+        ; "negative postgres timestamp, round down"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000000501",
+        /*micros*/ -78620276999999,
+        /*formatted*/ "1997-07-05T01:02:03.000001"
+        ; "negative postgres timestamp, 501"
+    )]
+    #[test_case(
+        /*input*/ "1997-07-05T01:02:03.000000499",
+        /*micros*/ -78620277000000,
+        /*formatted*/ "1997-07-05T01:02:03"
+        ; "negative postgres timestamp, 499"
+    )]
+    #[test_case(
+        /*input*/ "1999-12-31T23:59:59.999999500",
+        /*micros*/ 0,
+        /*formatted*/ "2000-01-01T00:00:00"
+        ; "postgres timestamp to zero"
+    )]
+    #[test_case(
+        /*input*/ "2014-02-27T00:00:00.000001500",
+        /*micros*/ 446774400000002,
+        /*formatted*/ "2014-02-27T00:00:00.000002"
+        ; "positive timestamp, round up"
+    )]
+    #[test_case(
+        /*input*/ "2014-02-27T00:00:00.000001501",
+        /*micros*/ 446774400000002,
+        /*formatted*/ "2014-02-27T00:00:00.000002"
+        ; "positive timestamp, 1501"
+    )]
+    #[test_case(
+        /*input*/ "2014-02-27T00:00:00.000001499",
+        /*micros*/ 446774400000001,
+        /*formatted*/ "2014-02-27T00:00:00.000001"
+        ; "positive timestamp, 1499"
+    )]
+    #[test_case(
+        /*input*/ "2022-02-24T05:43:03.000002500",
+        /*micros*/ 698996583000002,
+        /*formatted*/ "2022-02-24T05:43:03.000002"
+        ; "positive timestamp, round down"
+    )]
+    #[test_case(
+        /*input*/ "2022-02-24T05:43:03.000002501",
+        /*micros*/ 698996583000003,
+        /*formatted*/ "2022-02-24T05:43:03.000003"
+        ; "positive timestamp, 2501"
+    )]
+    #[test_case(
+        /*input*/ "2022-02-24T05:43:03.000002499",
+        /*micros*/ 698996583000002,
+        /*formatted*/ "2022-02-24T05:43:03.000002"
+        ; "positive timestamp, 2499"
+    )]
+    fn local_datetime(input: &str, micros: i64, formatted: &str) {
+        let time = PrimitiveDateTime::parse(input, &Iso8601::DEFAULT).unwrap();
+        let edgedb: LocalDatetime = time.try_into().unwrap();
+        assert_eq!(format!("{:?}", edgedb), formatted);
+
+        let mut buf = BytesMut::new();
+        let val = Value::LocalDatetime(edgedb);
+        codec::LocalDatetime.encode(&mut buf, &val).unwrap();
+        let serialized_micros = buf.get_i64();
+
+        assert_eq!(serialized_micros, micros);
+
+        let rev = PrimitiveDateTime::from(edgedb);
+        assert_eq!(
+            rev,
+            PrimitiveDateTime::parse(formatted, &Iso8601::DEFAULT).unwrap()
+        );
+    }
+
+    // ==========
+    // Local Time
+    // ==========
+    #[test_case(
+        /*input*/ "23:59:59.999999500",
+        // Note: Can't round up here, so   --^
+        /*micros*/ 0,
+        /*formatted*/ "00:00:00"
+        ; "wraparound"
+    )]
+    #[test_case(
+        /*input*/ "00:00:00.000000",
+        /*micros*/ 0,
+        /*formatted*/ "00:00:00"
+        ; "minimum"
+    )]
+    #[test_case(
+        /*input*/ "23:59:59.999999",
+        /*micros*/ 86399999999,
+        /*formatted*/ "23:59:59.999999"
+        ; "maximum"
+    )]
+    #[test_case(
+        /*input*/ "01:02:03.000005500",
+        /*micros*/ 3723000006,
+        /*formatted*/ "01:02:03.000006"
+        ; "round up"
+    )]
+    #[test_case(
+        /*input*/ "01:02:03.000005501",
+        /*micros*/ 3723000006,
+        /*formatted*/ "01:02:03.000006"
+        ; "5501"
+    )]
+    #[test_case(
+        /*input*/ "01:02:03.000005499",
+        /*micros*/ 3723000005,
+        /*formatted*/ "01:02:03.000005"
+        ; "5499"
+    )]
+    #[test_case(
+        /*input*/ "01:02:03.000004500",
+        /*micros*/ 3723000004,
+        /*formatted*/ "01:02:03.000004"
+        ; "round down"
+    )]
+    #[test_case(
+        /*input*/ "01:02:03.000004501",
+        /*micros*/ 3723000005,
+        /*formatted*/ "01:02:03.000005"
+        ; "4501"
+    )]
+    #[test_case(
+        /*input*/ "01:02:03.000004499",
+        /*micros*/ 3723000004,
+        /*formatted*/ "01:02:03.000004"
+        ; "4499"
+    )]
+    fn local_time(input: &str, micros: i64, formatted: &str) {
+        let time = parse_time(input);
+        let edgedb: LocalTime = time.into();
+        assert_eq!(format!("{:?}", edgedb), formatted);
+
+        let mut buf = BytesMut::new();
+        let val = Value::LocalTime(edgedb);
+        codec::LocalTime.encode(&mut buf, &val).unwrap();
+        let serialized_micros = buf.get_i64();
+
+        assert_eq!(serialized_micros, micros);
+
+        let rev = Time::from(edgedb);
+        assert_eq!(rev, parse_time(formatted));
+    }
+
+    // ==========
+    // Local Date
+    // ==========
+    #[test_case(1, Month::January, 1, -730119 ; "minimum")]
+    #[test_case(9999, Month::December, 31, 2921939 ; "maximum")]
+    #[test_case(1970, Month::January, 1, -10957 ; "unix epoch")]
+    #[test_case(2000, Month::January, 1, 0 ; "postgres epoch")]
+    #[test_case(2024, Month::February, 29, 8825 ; "leap day")]
+    fn local_date(year: i32, month: Month, day: u8, days: i32) {
+        let time = Date::from_calendar_date(year, month, day).unwrap();
+        let edgedb: LocalDate = time.try_into().unwrap();
+        assert_eq!(edgedb.to_days(), days);
+        assert_eq!(Date::from(edgedb), time);
+    }
+
+    #[test]
+    fn out_of_range() {
+        let date = Date::from_calendar_date(0, Month::December, 31).unwrap();
+        assert!(LocalDate::try_from(date).is_err());
+        let date = Date::from_calendar_date(10000, Month::January, 1);
+        if let Ok(date) = date {
+            // only with the `large-dates` feature of `time`
+            assert!(LocalDate::try_from(date).is_err());
+        }
+        let time = PrimitiveDateTime::parse("0000-12-31T23:59:59.999999", &Iso8601::DEFAULT);
+        assert!(LocalDatetime::try_from(time.unwrap()).is_err());
+        let time = OffsetDateTime::parse("9999-12-31T23:59:59.999999500Z", &Rfc3339);
+        assert!(Datetime::try_from(time.unwrap()).is_err());
+        let time = OffsetDateTime::parse("9999-12-31T22:00:00-02:00", &Rfc3339);
+        assert!(Datetime::try_from(time.unwrap()).is_err());
+    }
+
+    // ========
+    // Duration
+    // ========
+    #[test_case(0, 0, 0 ; "zero")]
+    #[test_case(1, 500, 1_000_000 ; "round down")]
+    #[test_case(1, 1_500, 1_000_002 ; "round up")]
+    #[test_case(1, 1_499, 1_000_001 ; "1499")]
+    #[test_case(-1, -500, -1_000_000 ; "negative, round down")]
+    #[test_case(-1, -1_500, -1_000_002 ; "negative, round up")]
+    #[test_case(-1, -1_501, -1_000_002 ; "negative, 1501")]
+    fn duration(secs: i64, nanos: i32, micros: i64) {
+        let time = time::Duration::new(secs, nanos);
+        let edgedb: Duration = time.try_into().unwrap();
+        assert_eq!(edgedb.to_micros(), micros);
+        assert_eq!(
+            time::Duration::from(edgedb),
+            time::Duration::microseconds(micros)
+        );
+    }
+
+    #[test]
+    fn calendar_durations() {
+        assert!(Duration::try_from(time::Duration::MAX).is_err());
+
+        let time = time::Duration::days(2) + time::Duration::nanoseconds(1_500);
+        let rel = RelativeDuration::try_from(time).unwrap();
+        assert_eq!(rel, RelativeDuration::from_micros(172_800_000_002));
+        let rel = RelativeDuration::from_days(2) + RelativeDuration::from_micros(2);
+        assert_eq!(
+            time::Duration::try_from(rel),
+            Ok(time::Duration::microseconds(172_800_000_002))
+        );
+        assert!(time::Duration::try_from(RelativeDuration::from_months(1)).is_err());
+
+        let date = DateDuration::try_from(time::Duration::days(-3)).unwrap();
+        assert_eq!(date, DateDuration::from_days(-3));
+        assert_eq!(time::Duration::try_from(date), Ok(time::Duration::days(-3)));
+        assert!(DateDuration::try_from(time::Duration::hours(1)).is_err());
+        assert!(time::Duration::try_from(DateDuration::from_years(1)).is_err());
+    }
+
+    #[test]
+    fn query_args() {
+        let desc = datetime_typedesc();
+        let time = OffsetDateTime::parse("2022-02-24T05:43:03.000002501Z", &Rfc3339).unwrap();
+        let decoded: Datetime = roundtrip(&desc, 0, &time);
+        assert_eq!(decoded, Datetime::try_from(time).unwrap());
+        let decoded: OffsetDateTime = roundtrip(&desc, 0, &decoded);
+        assert_eq!(decoded, time.replace_nanosecond(3_000).unwrap());
+
+        let time = PrimitiveDateTime::parse("2022-02-24T05:43:03", &Iso8601::DEFAULT).unwrap();
+        let decoded: PrimitiveDateTime = roundtrip(&desc, 1, &time);
+        assert_eq!(decoded, time);
+        let decoded: Date = roundtrip(&desc, 2, &time.date());
+        assert_eq!(decoded, time.date());
+        let decoded: Time = roundtrip(&desc, 3, &time.time());
+        assert_eq!(decoded, time.time());
+        let decoded: time::Duration = roundtrip(&desc, 4, &time::Duration::minutes(-90));
+        assert_eq!(decoded, time::Duration::minutes(-90));
+
+        let ctx = desc.as_queryable_context();
+        <OffsetDateTime as Queryable>::check_descriptor(&ctx, TypePos(1)).unwrap_err();
+        <PrimitiveDateTime as Queryable>::check_descriptor(&ctx, TypePos(0)).unwrap_err();
+        <time::Duration as Queryable>::check_descriptor(&ctx, TypePos(5)).unwrap_err();
+    }
+}
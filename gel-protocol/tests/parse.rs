@@ -0,0 +1,418 @@
+use std::str::FromStr;
+
+use bytes::{Buf, BytesMut};
+use gel_protocol::codec::{self, Codec};
+use gel_protocol::model::{BigInt, Decimal};
+use gel_protocol::model::{DateDuration, RelativeDuration};
+use gel_protocol::model::{Datetime, LocalDate, LocalDatetime, LocalTime};
+use gel_protocol::value::Value;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use test_case::test_case;
+
+fn encode(codec: impl Codec, value: Value) -> BytesMut {
+    let mut buf = BytesMut::new();
+    codec.encode(&mut buf, &value).unwrap();
+    buf
+}
+
+// ========
+// Datetime
+// ========
+
+#[test_case(
+    /*input*/ "9999-12-31T23:59:59.999999499Z",
+    // Note: Can't round up here, so   --^
+    /*micros*/ 252455615999999999,
+    /*formatted*/ "9999-12-31 23:59:59.999999 UTC"
+    ; "maximum"
+)]
+#[test_case(
+    /*input*/ "0001-01-01T00:00:00Z",
+    /*micros*/ -63082281600000000,
+    /*formatted*/ "0001-01-01 00:00:00 UTC"
+    ; "minimum"
+)]
+#[test_case(
+    /*input*/ "1997-07-05T01:02:03.000009500Z",
+    /*micros*/ -78620276999990,
+    /*formatted*/ "1997-07-05 01:02:03.000010 UTC"
+    ; "negative postgres timestamp, round up"
+)]
+#[test_case(
+    /*input*/ "1997-07-05T01:02:03.000009499Z",
+    /*micros*/ -78620276999991,
+    /*formatted*/ "1997-07-05 01:02:03.000009 UTC"
+    ; "negative postgres timestamp, 9499"
+)]
+#[test_case(
+    /*input*/ "1997-07-05T01:02:03.000000500Z",
+    /*micros*/ -78620277000000,
+    /*formatted*/ "1997-07-05 01:02:03 UTC"
+    ; "negative postgres timestamp, round down"
+)]
+#[test_case(
+    /*input*/ "1997-07-05T01:02:03.0000005000001Z",
+    /*micros*/ -78620276999999,
+    /*formatted*/ "1997-07-05 01:02:03.000001 UTC"
+    ; "negative postgres timestamp, above half in far digits"
+)]
+#[test_case(
+    /*input*/ "1999-12-31T23:59:59.999999500Z",
+    /*micros*/ 0,
+    /*formatted*/ "2000-01-01 00:00:00 UTC"
+    ; "postgres timestamp to zero"
+)]
+#[test_case(
+    /*input*/ "2022-02-24T05:43:03.000002500Z",
+    /*micros*/ 698996583000002,
+    /*formatted*/ "2022-02-24 05:43:03.000002 UTC"
+    ; "positive timestamp, round down"
+)]
+#[test_case(
+    /*input*/ "2022-02-24T05:43:03.000002501Z",
+    /*micros*/ 698996583000003,
+    /*formatted*/ "2022-02-24 05:43:03.000003 UTC"
+    ; "positive timestamp, 2501"
+)]
+#[test_case(
+    /*input*/ "2022-02-24 08:43:03.5+03:00",
+    /*micros*/ 698996583500000,
+    /*formatted*/ "2022-02-24 05:43:03.500 UTC"
+    ; "positive offset"
+)]
+#[test_case(
+    /*input*/ "2022-02-23T23:13:03-0630",
+    /*micros*/ 698996583000000,
+    /*formatted*/ "2022-02-24 05:43:03 UTC"
+    ; "negative offset without colon"
+)]
+#[test_case(
+    /*input*/ "2022-02-24 05:43 UTC",
+    /*micros*/ 698996580000000,
+    /*formatted*/ "2022-02-24 05:43:00 UTC"
+    ; "display format without seconds"
+)]
+fn datetime(input: &str, micros: i64, formatted: &str) {
+    let parsed = Datetime::from_str(input).unwrap();
+    let mut buf = encode(codec::Datetime, Value::Datetime(parsed));
+    assert_eq!(buf.get_i64(), micros);
+    assert_eq!(parsed.to_string(), formatted);
+    assert_eq!(Datetime::from_str(formatted).unwrap(), parsed);
+    assert_eq!(
+        Datetime::from_str(&format!("{:?}", parsed)).unwrap(),
+        parsed
+    );
+}
+
+#[test_case("2022-02-24T05:43:03" ; "missing offset")]
+#[test_case("2022-02-24T05:43:03+24:00" ; "offset out of range")]
+#[test_case("2022-02-30T05:43:03Z" ; "invalid day")]
+#[test_case("2022-02-24T24:00:00Z" ; "invalid hour")]
+#[test_case("2022-2-24T05:43:03Z" ; "single digit month")]
+#[test_case("2022-02-24T05:43:03.Z" ; "empty fraction")]
+#[test_case("2022-02-24T05:43:03Z " ; "trailing space")]
+#[test_case("9999-12-31T23:59:59.9999995Z" ; "rounded out of range")]
+#[test_case("0001-01-01T00:00:00+01:00" ; "before minimum")]
+#[test_case("" ; "empty")]
+#[test_case("2022-02-24T05:43:03UTé" ; "non-ascii offset")]
+#[test_case("2022-02-24T05:43:03 éé" ; "non-ascii after whitespace")]
+#[test_case("２０２２-02-24T05:43:03Z" ; "non-ascii digits")]
+fn datetime_invalid(input: &str) {
+    Datetime::from_str(input).unwrap_err();
+}
+
+#[test_case(
+    /*input*/ "2039-02-13T23:31:30.123456",
+    /*micros*/ 1234567890123456,
+    /*formatted*/ "2039-02-13 23:31:30.123456"
+    ; "debug format"
+)]
+#[test_case(
+    /*input*/ "1999-12-31 23:59:59.9999995",
+    /*micros*/ 0,
+    /*formatted*/ "2000-01-01 00:00:00"
+    ; "round up to next day"
+)]
+#[test_case(
+    /*input*/ "0001-01-01 00:00",
+    /*micros*/ -63082281600000000,
+    /*formatted*/ "0001-01-01 00:00:00"
+    ; "minimum"
+)]
+fn local_datetime(input: &str, micros: i64, formatted: &str) {
+    let parsed = LocalDatetime::from_str(input).unwrap();
+    let mut buf = encode(codec::LocalDatetime, Value::LocalDatetime(parsed));
+    assert_eq!(buf.get_i64(), micros);
+    assert_eq!(parsed.to_string(), formatted);
+    assert_eq!(LocalDatetime::from_str(formatted).unwrap(), parsed);
+}
+
+#[test_case("2039-02-13T23:31:30Z" ; "offset")]
+#[test_case("2039-02-13 23:31:30 UTC" ; "utc")]
+#[test_case("2039-02-13" ; "date only")]
+#[test_case("2039-02-13T23:31:30é" ; "non-ascii suffix")]
+fn local_datetime_invalid(input: &str) {
+    LocalDatetime::from_str(input).unwrap_err();
+}
+
+#[test_case("2000-01-01", 0 ; "epoch")]
+#[test_case("0001-01-01", -730119 ; "minimum")]
+#[test_case("9999-12-31", 2921939 ; "maximum")]
+#[test_case("2024-02-29", 8825 ; "leap day")]
+fn local_date(input: &str, days: i32) {
+    let parsed = LocalDate::from_str(input).unwrap();
+    let mut buf = encode(codec::LocalDate, Value::LocalDate(parsed));
+    assert_eq!(buf.get_i32(), days);
+    assert_eq!(parsed.to_string(), input);
+}
+
+#[test_case("2023-02-29" ; "not a leap year")]
+#[test_case("0000-12-31" ; "year zero")]
+#[test_case("10000-01-01" ; "five digit year")]
+#[test_case("2024-13-01" ; "month out of range")]
+#[test_case("2024-01-01T00:00" ; "with time")]
+#[test_case("2024-01-0é" ; "non-ascii day")]
+fn local_date_invalid(input: &str) {
+    LocalDate::from_str(input).unwrap_err();
+}
+
+#[test_case("00:00:00", 0, "00:00:00" ; "midnight")]
+#[test_case("12:34", 45240000000, "12:34:00" ; "no seconds")]
+#[test_case("12:34:56.789", 45296789000, "12:34:56.789" ; "millis")]
+#[test_case("12:34:56.000001", 45296000001, "12:34:56.000001" ; "micros")]
+#[test_case("12:34:56.0000025", 45296000002, "12:34:56.000002" ; "half even down")]
+#[test_case("12:34:56.0000035", 45296000004, "12:34:56.000004" ; "half even up")]
+#[test_case("23:59:59.9999995", 0, "00:00:00" ; "wrap around")]
+fn local_time(input: &str, micros: i64, formatted: &str) {
+    let parsed = LocalTime::from_str(input).unwrap();
+    let mut buf = encode(codec::LocalTime, Value::LocalTime(parsed));
+    assert_eq!(buf.get_i64(), micros);
+    assert_eq!(parsed.to_string(), formatted);
+}
+
+#[test_case("24:00:00" ; "hour out of range")]
+#[test_case("12:60:00" ; "minute out of range")]
+#[test_case("12:00:60" ; "second out of range")]
+#[test_case("12" ; "hour only")]
+#[test_case("12:00:00Z" ; "offset")]
+#[test_case("12:00:0é" ; "non-ascii second")]
+fn local_time_invalid(input: &str) {
+    LocalTime::from_str(input).unwrap_err();
+}
+
+// =========
+// Durations
+// =========
+
+#[test_case("P1Y2M3DT4H5M6.7S", 14706700000, 3, 14, "P1Y2M3DT4H5M6.7S" ; "all units")]
+#[test_case("P1Y2M3DT4H", 14400000000, 3, 14, "P1Y2M3DT4H" ; "request example")]
+#[test_case("P6Y8M-16DT52H5M7.6S", 187507600000, -16, 80, "P6Y8M-16DT52H5M7.6S" ; "mixed signs")]
+#[test_case("-P1Y2M", 0, 0, -14, "P-1Y-2M" ; "negated")]
+#[test_case("P2W", 0, 14, 0, "P14D" ; "weeks")]
+#[test_case("PT0.4S", 400000, 0, 0, "PT0.4S" ; "fraction only")]
+#[test_case("PT-.4S", -400000, 0, 0, "PT-0.4S" ; "negative fraction only")]
+#[test_case("PT0S", 0, 0, 0, "PT0S" ; "zero")]
+#[test_case("PT36H", 129600000000, 0, 0, "PT36H" ; "hours are not days")]
+#[test_case("1 year 2 months -3 days 4 hours", 14400000000, -3, 14, "P1Y2M-3DT4H" ; "pg format")]
+#[test_case("3 mins 5.5 secs 7 ms 9 us", 185507009, 0, 0, "PT3M5.507009S" ; "pg format short units")]
+fn relative_duration(input: &str, micros: i64, days: i32, months: i32, formatted: &str) {
+    let parsed = RelativeDuration::from_str(input).unwrap();
+    let mut buf = encode(codec::RelativeDuration, Value::RelativeDuration(parsed));
+    assert_eq!(buf.get_i64(), micros);
+    assert_eq!(buf.get_i32(), days);
+    assert_eq!(buf.get_i32(), months);
+    assert_eq!(parsed.to_string(), formatted);
+    assert_eq!(RelativeDuration::from_str(formatted).unwrap(), parsed);
+}
+
+#[test_case("P" ; "no components")]
+#[test_case("P1H" ; "time unit before T")]
+#[test_case("PT1D" ; "date unit after T")]
+#[test_case("P1Y1Y" ; "repeated unit")]
+#[test_case("P1.5Y" ; "fractional years")]
+#[test_case("P178956971Y" ; "years out of range")]
+#[test_case("1 fortnight" ; "unknown unit")]
+#[test_case("" ; "empty")]
+#[test_case("PT1é" ; "non-ascii unit")]
+#[test_case("1 hé" ; "non-ascii pg unit")]
+fn relative_duration_invalid(input: &str) {
+    RelativeDuration::from_str(input).unwrap_err();
+}
+
+#[test_case("P1Y2M3D", 3, 14, "P1Y2M3D" ; "all units")]
+#[test_case("P-16D", -16, 0, "P-16D" ; "negative days")]
+#[test_case("PT0D", 0, 0, "PT0D" ; "zero")]
+#[test_case("P0D", 0, 0, "PT0D" ; "zero days")]
+#[test_case("2 years 1 week", 7, 24, "P2Y7D" ; "pg format")]
+fn date_duration(input: &str, days: i32, months: i32, formatted: &str) {
+    let parsed = DateDuration::from_str(input).unwrap();
+    let mut buf = encode(codec::DateDuration, Value::DateDuration(parsed));
+    assert_eq!(buf.get_i64(), 0);
+    assert_eq!(buf.get_i32(), days);
+    assert_eq!(buf.get_i32(), months);
+    assert_eq!(parsed.to_string(), formatted);
+    assert_eq!(DateDuration::from_str(formatted).unwrap(), parsed);
+}
+
+#[test_case("P1DT1H" ; "hours")]
+#[test_case("1 day 1 second" ; "pg format seconds")]
+#[test_case("P1é" ; "non-ascii unit")]
+fn date_duration_invalid(input: &str) {
+    DateDuration::from_str(input).unwrap_err();
+}
+
+// =======
+// Numbers
+// =======
+
+#[test_case("42", b"\0\x01\0\0\0\0\0\0\0*", "42" ; "small")]
+#[test_case("+30000", b"\0\x01\0\x01\0\0\0\0\0\x03", "30000" ; "plus sign")]
+#[test_case("-15000", b"\0\x02\0\x01@\0\0\0\0\x01\x13\x88", "-15000" ; "negative")]
+#[test_case("1000000000000000000000", b"\0\x01\0\x05\0\0\0\0\0\n", "1000000000000000000000" ; "big")]
+#[test_case("-000", b"\0\0\0\0\0\0\0\0", "0" ; "negative zero")]
+fn bigint(input: &str, bytes: &[u8], formatted: &str) {
+    let parsed = BigInt::from_str(input).unwrap();
+    let buf = encode(codec::BigInt, Value::BigInt(parsed.clone()));
+    assert_eq!(&buf[..], bytes);
+    assert_eq!(parsed.to_string(), formatted);
+}
+
+#[test_case("42.00", b"\0\x01\0\0\0\0\0\x02\0*", "42.00" ; "trailing zeros")]
+#[test_case(
+    "12345678.901234567",
+    b"\0\x05\0\x01\0\0\0\t\x04\xd2\x16.#4\r\x80\x1bX",
+    "12345678.901234567"
+    ; "fraction"
+)]
+#[test_case("1e10", b"\0\x01\0\x02\0\0\0\0\0d", "10000000000.0" ; "exponent")]
+#[test_case(
+    "-7033672342206924902e26",
+    b"\0\x06\0\x0b@\0\0\0\0\x07\x01P\x1cB\x08\x9e$!\0\xc8",
+    "-703367234220692490200000000000000000000000000.0"
+    ; "negative with exponent"
+)]
+#[test_case("-12.3400", b"\0\x02\0\0@\0\0\x04\0\x0c\x0d\x48", "-12.3400" ; "request example")]
+#[test_case(".00001", b"\0\x01\xff\xfe\0\0\0\x05\x03\xe8", "0.00001" ; "no integer part")]
+#[test_case("0.000", b"\0\0\0\0\0\0\0\x03", "0.000" ; "zero")]
+fn decimal(input: &str, bytes: &[u8], formatted: &str) {
+    let parsed = Decimal::from_str(input).unwrap();
+    let buf = encode(codec::Decimal, Value::Decimal(parsed.clone()));
+    assert_eq!(&buf[..], bytes);
+    assert_eq!(parsed.to_string(), formatted);
+}
+
+#[test_case("" ; "empty")]
+#[test_case("-" ; "sign only")]
+#[test_case("." ; "dot only")]
+#[test_case("1e" ; "empty exponent")]
+#[test_case("1.2.3" ; "two dots")]
+#[test_case("1 " ; "trailing space")]
+#[test_case("1e400000" ; "out of range")]
+#[test_case("1.é" ; "non-ascii fraction")]
+#[test_case("١٢" ; "non-ascii digits")]
+fn decimal_invalid(input: &str) {
+    Decimal::from_str(input).unwrap_err();
+}
+
+#[test_case("1.5" ; "fraction")]
+#[test_case("1e5" ; "exponent")]
+#[test_case("0x10" ; "hex")]
+#[test_case("1é" ; "non-ascii suffix")]
+fn bigint_invalid(input: &str) {
+    BigInt::from_str(input).unwrap_err();
+}
+
+// ==========
+// Roundtrips
+// ==========
+
+#[test]
+fn datetime_roundtrip() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let min = Datetime::MIN.to_unix_micros();
+    let max = Datetime::MAX.to_unix_micros();
+    for _ in 0..10000 {
+        let value = Datetime::from_unix_micros(rng.gen_range(min..=max));
+        assert_eq!(Datetime::from_str(&value.to_string()).unwrap(), value);
+        assert_eq!(Datetime::from_str(&format!("{:?}", value)).unwrap(), value);
+    }
+}
+
+#[test]
+fn local_datetime_roundtrip() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let min = LocalDatetime::MIN.to_utc().to_unix_micros();
+    let max = LocalDatetime::MAX.to_utc().to_unix_micros();
+    for _ in 0..10000 {
+        let value = LocalDatetime::from(Datetime::from_unix_micros(rng.gen_range(min..=max)));
+        assert_eq!(LocalDatetime::from_str(&value.to_string()).unwrap(), value);
+        assert_eq!(
+            LocalDatetime::from_str(&format!("{:?}", value)).unwrap(),
+            value
+        );
+        assert_eq!(
+            LocalDate::from_str(&value.date().to_string()).unwrap(),
+            value.date()
+        );
+        assert_eq!(
+            LocalTime::from_str(&value.time().to_string()).unwrap(),
+            value.time()
+        );
+    }
+}
+
+#[test]
+fn relative_duration_roundtrip() {
+    let mut rng = StdRng::seed_from_u64(3);
+    for _ in 0..10000 {
+        let value = RelativeDuration::from_months(rng.gen())
+            + RelativeDuration::from_days(rng.gen())
+            + RelativeDuration::from_micros(rng.gen());
+        assert_eq!(
+            RelativeDuration::from_str(&value.to_string()).unwrap(),
+            value
+        );
+        let value = DateDuration::from_months(rng.gen()) + DateDuration::from_days(rng.gen());
+        assert_eq!(DateDuration::from_str(&value.to_string()).unwrap(), value);
+    }
+}
+
+fn gen_digits<T: Rng>(rng: &mut T, max_len: usize) -> String {
+    let len = rng.gen_range(0..=max_len);
+    (0..len).map(|_| rng.gen_range('0'..='9')).collect()
+}
+
+#[test]
+fn bigint_roundtrip() {
+    let mut rng = StdRng::seed_from_u64(4);
+    for _ in 0..10000 {
+        let value: i64 = rng.gen::<i64>() >> rng.gen_range(0..64);
+        assert_eq!(BigInt::from_str(&value.to_string()).unwrap(), value.into());
+        let text = format!("1{}", gen_digits(&mut rng, 60));
+        assert_eq!(BigInt::from_str(&text).unwrap().to_string(), text);
+    }
+}
+
+#[test]
+fn decimal_roundtrip() {
+    let mut rng = StdRng::seed_from_u64(5);
+    for _ in 0..10000 {
+        let text = format!(
+            "{}{}.{}",
+            if rng.gen() { "-" } else { "" },
+            gen_digits(&mut rng, 40),
+            gen_digits(&mut rng, 40),
+        );
+        let Ok(value) = Decimal::from_str(&text) else {
+            assert_eq!(text.trim_start_matches('-'), ".");
+            continue;
+        };
+        let formatted = value.to_string();
+        let reparsed = Decimal::from_str(&formatted).unwrap();
+        assert_eq!(reparsed.to_string(), formatted);
+        if value.decimal_digits() > 0 {
+            // otherwise `Display` adds `.0`
+            assert_eq!(reparsed, value, "{}", text);
+        }
+    }
+}
@@ -0,0 +1,188 @@
+#![cfg(feature = "with-postgis")]
+
+use std::str::FromStr;
+
+use bytes::{BufMut, BytesMut};
+use gel_protocol::codec::{POSTGIS_GEOGRAPHY, POSTGIS_GEOMETRY};
+use gel_protocol::descriptors::Typedesc;
+use gel_protocol::model::postgis::{Geometry, LineString, Point, Polygon};
+use gel_protocol::query_arg::ScalarArg;
+use gel_protocol::value::Value;
+use test_case::test_case;
+
+use common::{roundtrip, Descriptors};
+
+mod common;
+
+/// Descriptors: `ext::postgis::geometry`, `ext::postgis::geography`
+fn typedesc() -> Typedesc {
+    Descriptors::new()
+        .scalars(&[POSTGIS_GEOMETRY, POSTGIS_GEOGRAPHY])
+        .build()
+}
+
+#[test]
+fn point() {
+    // little endian point (2 1)
+    let data = b"\
+        \x01\
+        \x01\x00\x00\x00\
+        \x00\x00\x00\x00\x00\x00\x00\x40\
+        \x00\x00\x00\x00\x00\x00\xF0\x3F\
+    ";
+    let geometry = Geometry::from_ewkb(data).unwrap();
+    assert_eq!(geometry, Geometry::new(Point::new(2.0, 1.0)));
+    assert_eq!(&geometry.to_ewkb()[..], &data[..]);
+    assert_eq!(geometry.to_string(), "POINT(2 1)");
+}
+
+#[test]
+fn big_endian_with_srid() {
+    // big endian `SRID=4326;POINT Z (1 2 3)`
+    let mut buf = BytesMut::new();
+    buf.put_u8(0);
+    buf.put_u32(0xA000_0001);
+    buf.put_u32(4326);
+    buf.put_f64(1.0);
+    buf.put_f64(2.0);
+    buf.put_f64(3.0);
+    let geometry = Geometry::from_ewkb(&buf).unwrap();
+    assert_eq!(
+        geometry,
+        Geometry::new(Point::new(1.0, 2.0).with_z(3.0)).with_srid(4326)
+    );
+    assert_eq!(geometry.to_string(), "SRID=4326;POINT Z (1 2 3)");
+    assert_eq!(Geometry::from_ewkb(&geometry.to_ewkb()).unwrap(), geometry);
+}
+
+#[test]
+fn invalid_ewkb() {
+    Geometry::from_ewkb(b"\x01\x01\x00\x00\x00").unwrap_err();
+    Geometry::from_ewkb(b"\x02\x01\x00\x00\x00").unwrap_err();
+    Geometry::from_ewkb(b"\x01\x09\x00\x00\x00").unwrap_err();
+    // huge number of points
+    Geometry::from_ewkb(b"\x01\x02\x00\x00\x00\xff\xff\xff\xff").unwrap_err();
+}
+
+#[test_case("POINT EMPTY")]
+#[test_case("POINT M (1 2 4)")]
+#[test_case("POINT ZM (1 2 3 4)")]
+#[test_case("LINESTRING(0 0,1 1.5,-2 3)")]
+#[test_case("POLYGON((0 0,4 0,4 4,0 0),(1 1,2 1,2 2,1 1))")]
+#[test_case("MULTIPOINT((1 2),EMPTY)")]
+#[test_case("MULTILINESTRING((0 0,1 1),(2 2,3 3))")]
+#[test_case("MULTIPOLYGON(((0 0,1 0,1 1,0 0)),EMPTY)")]
+#[test_case("GEOMETRYCOLLECTION(POINT(1 2),LINESTRING(0 0,1 1))")]
+#[test_case("GEOMETRYCOLLECTION EMPTY")]
+#[test_case("SRID=4326;MULTIPOLYGON Z (((0 0 1,1 0 1,1 1 1,0 0 1)))")]
+fn wkt_roundtrip(wkt: &str) {
+    let geometry = Geometry::from_str(wkt).unwrap();
+    assert_eq!(geometry.to_string(), wkt);
+    let decoded = Geometry::from_ewkb(&geometry.to_ewkb()).unwrap();
+    assert_eq!(decoded.to_string(), wkt);
+}
+
+#[test_case("point(1 2)", "POINT(1 2)")]
+#[test_case("POINT (1 2 3)", "POINT Z (1 2 3)")]
+#[test_case("POINTM(1 2 3)", "POINT M (1 2 3)")]
+#[test_case("MULTIPOINT (1 2, 3 4)", "MULTIPOINT((1 2),(3 4))")]
+#[test_case(
+    " SRID=3857; LINESTRING ( 1 2 , 3 4 ) ",
+    "SRID=3857;LINESTRING(1 2,3 4)"
+)]
+fn wkt_normalize(input: &str, output: &str) {
+    assert_eq!(Geometry::from_str(input).unwrap().to_string(), output);
+}
+
+#[test_case("CIRCLE(1 2)")]
+#[test_case("POINT(1)")]
+#[test_case("POINT(1 2")]
+#[test_case("POINT Z (1 2)")]
+#[test_case("POINT(1 2 3 4 5)")]
+#[test_case("POINT(1 x)")]
+#[test_case("POINT(1 2) extra")]
+#[test_case("SRID=x;POINT(1 2)")]
+#[test_case("SRIDé;POINT(1 2)")]
+#[test_case("é")]
+fn wkt_invalid(input: &str) {
+    Geometry::from_str(input).unwrap_err();
+}
+
+fn nested_wkt(depth: usize) -> String {
+    let mut wkt = "GEOMETRYCOLLECTION(".repeat(depth);
+    wkt.push_str("POINT(1 2)");
+    wkt.push_str(&")".repeat(depth));
+    wkt
+}
+
+fn nested_ewkb(depth: usize) -> Vec<u8> {
+    let mut ewkb = b"\x01\x07\x00\x00\x00\x01\x00\x00\x00".repeat(depth);
+    ewkb.extend(b"\x01\x01\x00\x00\x00");
+    ewkb.extend(1f64.to_le_bytes());
+    ewkb.extend(2f64.to_le_bytes());
+    ewkb
+}
+
+#[test]
+fn nesting_limit() {
+    let geometry = Geometry::from_str(&nested_wkt(64)).unwrap();
+    assert_eq!(Geometry::from_ewkb(&nested_ewkb(64)).unwrap(), geometry);
+
+    let err = Geometry::from_str(&nested_wkt(65)).unwrap_err();
+    assert!(err.to_string().contains("nested too deeply"), "{}", err);
+    Geometry::from_str(&nested_wkt(100_000)).unwrap_err();
+    let err = Geometry::from_ewkb(&nested_ewkb(65)).unwrap_err();
+    assert!(err.to_string().contains("nested too deeply"), "{}", err);
+    Geometry::from_ewkb(&nested_ewkb(100_000)).unwrap_err();
+}
+
+#[test]
+fn query_arg_and_queryable() {
+    let geometry = Geometry::new(Polygon(vec![LineString(vec![
+        Point::new(0.0, 0.0),
+        Point::new(1.0, 0.0),
+        Point::new(1.0, 1.0),
+        Point::new(0.0, 0.0),
+    ])]))
+    .with_srid(4326);
+    let desc = typedesc();
+    for type_pos in [0, 1] {
+        let decoded: Geometry = roundtrip(&desc, type_pos, &geometry);
+        assert_eq!(decoded, geometry);
+    }
+}
+
+#[test]
+fn to_value_is_geometry() {
+    let geometry = Geometry::new(Point::new(1.0, 2.0));
+    // even if the argument is `geography`, there is no descriptor to tell
+    assert_eq!(
+        ScalarArg::to_value(&geometry).unwrap(),
+        Value::PostGisGeometry(geometry.to_ewkb())
+    );
+}
+
+#[cfg(feature = "geo-types")]
+#[test]
+fn geo_types() {
+    let polygon = geo_types::Polygon::new(
+        geo_types::LineString::from(vec![(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 0.0)]),
+        vec![],
+    );
+    let geometry = Geometry::from(geo_types::Geometry::from(polygon.clone()));
+    assert_eq!(geometry.to_string(), "POLYGON((0 0,2 0,2 2,0 0))");
+    assert_eq!(
+        geo_types::Geometry::from(geometry),
+        geo_types::Geometry::Polygon(polygon)
+    );
+
+    let point: geo_types::Point = Point::new(1.0, 2.0).with_z(3.0).into();
+    assert_eq!(point, geo_types::Point::new(1.0, 2.0));
+    use gel_protocol::model::postgis::Shape;
+
+    let shape = Shape::from(geo_types::Geometry::Rect(geo_types::Rect::new(
+        (0.0, 0.0),
+        (1.0, 1.0),
+    )));
+    assert!(matches!(shape, Shape::Polygon(_)));
+}
@@ -0,0 +1,80 @@
+use std::convert::TryFrom;
+
+use gel_protocol::codec::{CAL_LOCAL_DATE, STD_INT64};
+use gel_protocol::descriptors::{TypePos, Typedesc};
+use gel_protocol::model::{MultiRange, Range};
+use gel_protocol::queryable::Queryable;
+
+use common::{roundtrip, Descriptors};
+
+mod common;
+
+/// Descriptors: `int64`, `range<int64>`, `multirange<int64>`,
+/// `cal::local_date`, `range<cal::local_date>`
+fn typedesc() -> Typedesc {
+    Descriptors::new()
+        .scalars(&[STD_INT64])
+        .range(0)
+        .multi_range(0)
+        .scalars(&[CAL_LOCAL_DATE])
+        .range(3)
+        .build()
+}
+
+#[test]
+fn range() {
+    let desc = typedesc();
+    let decoded: Range<i64> = roundtrip(&desc, 1, &Range::from(7i64..39));
+    assert_eq!(decoded, Range::from(7..39));
+    let decoded: Range<i64> = roundtrip(&desc, 1, &Range::from(7i64..));
+    assert_eq!(decoded, Range::from(7..));
+    assert_eq!(decoded.upper(), None);
+    let decoded: Range<i64> = roundtrip(&desc, 1, &Range::<i64>::empty());
+    assert!(decoded.is_empty());
+    let decoded: Range<i64> = roundtrip(&desc, 1, &Range::from(7i64..39).into_value());
+    assert_eq!(decoded, Range::from(7..39));
+
+    let ctx = desc.as_queryable_context();
+    <Range<i64> as Queryable>::check_descriptor(&ctx, TypePos(3)).unwrap_err();
+    <Range<String> as Queryable>::check_descriptor(&ctx, TypePos(1)).unwrap_err();
+}
+
+#[test]
+fn multi_range() {
+    let desc = typedesc();
+    let value = MultiRange::from(vec![Range::from(1i64..3), Range::from(7i64..)]);
+    let decoded: MultiRange<i64> = roundtrip(&desc, 2, &value);
+    assert_eq!(decoded, value);
+    let decoded: MultiRange<i64> = roundtrip(&desc, 2, &value.clone().into_value());
+    assert_eq!(decoded, value);
+    let decoded: MultiRange<i64> = roundtrip(&desc, 2, &MultiRange::<i64>::default());
+    assert!(decoded.is_empty());
+
+    let ctx = desc.as_queryable_context();
+    <MultiRange<i64> as Queryable>::check_descriptor(&ctx, TypePos(1)).unwrap_err();
+}
+
+#[test]
+fn std_ranges() {
+    assert_eq!(std::ops::Range::try_from(Range::from(1..3)), Ok(1..3));
+    assert_eq!(
+        std::ops::RangeInclusive::try_from(Range::from(1..=3)),
+        Ok(1..=3)
+    );
+    std::ops::Range::try_from(Range::from(1..=3)).unwrap_err();
+    std::ops::RangeInclusive::try_from(Range::from(1..3)).unwrap_err();
+    std::ops::Range::try_from(Range::from(1..)).unwrap_err();
+    std::ops::Range::<i64>::try_from(Range::empty()).unwrap_err();
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn chrono_range() {
+    use chrono::NaiveDate;
+
+    let desc = typedesc();
+    let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    let end = NaiveDate::from_ymd_opt(2024, 2, 1).unwrap();
+    let decoded: Range<NaiveDate> = roundtrip(&desc, 4, &Range::from(start..end));
+    assert_eq!(decoded, Range::from(start..end));
+}
@@ -0,0 +1,358 @@
+#![cfg(feature = "with-serde")]
+
+use std::error::Error;
+
+use serde_json::json;
+
+use gel_protocol::codec::{self, NamedTupleShape, ObjectShape};
+use gel_protocol::descriptors::{ArrayTypeDescriptor, BaseScalarTypeDescriptor};
+use gel_protocol::descriptors::{Descriptor, TypePos};
+use gel_protocol::descriptors::{EnumerationTypeDescriptor, RangeTypeDescriptor};
+use gel_protocol::descriptors::{InputShapeElement, InputShapeTypeDescriptor};
+use gel_protocol::descriptors::{NamedTupleTypeDescriptor, TupleElement};
+use gel_protocol::descriptors::{ObjectShapeDescriptor, ShapeElement};
+use gel_protocol::model::{BigInt, Duration, Json, Range, Uuid};
+use gel_protocol::value::{SparseObject, Value};
+
+fn scalar(id: Uuid) -> Descriptor {
+    Descriptor::BaseScalar(BaseScalarTypeDescriptor { id: id.into() })
+}
+
+fn shape_element(name: &str, type_pos: u16) -> ShapeElement {
+    ShapeElement {
+        flag_implicit: false,
+        flag_link_property: false,
+        flag_link: false,
+        cardinality: None,
+        name: name.into(),
+        type_pos: TypePos(type_pos),
+        source_type_pos: None,
+    }
+}
+
+fn from_json(descriptors: &[Descriptor], pos: u16, json: &str) -> serde_json::Result<Value> {
+    Value::from_json_with_descriptors(Some(TypePos(pos)), descriptors, json)
+}
+
+/// Checks both serialization and deserialization
+fn roundtrip(descriptors: &[Descriptor], pos: u16, value: Value, json: &str) {
+    let serialized = value.to_json().unwrap();
+    assert_eq!(serialized, json);
+    assert_eq!(from_json(descriptors, pos, json).unwrap(), value);
+}
+
+#[test]
+fn scalars() -> Result<(), Box<dyn Error>> {
+    let descriptors = [
+        scalar(codec::STD_STR),
+        scalar(codec::STD_BYTES),
+        scalar(codec::STD_INT64),
+        scalar(codec::STD_FLOAT64),
+        scalar(codec::STD_BIGINT),
+        scalar(codec::STD_DECIMAL),
+        scalar(codec::STD_DATETIME),
+        scalar(codec::CAL_LOCAL_DATETIME),
+        scalar(codec::CAL_LOCAL_DATE),
+        scalar(codec::CAL_LOCAL_TIME),
+        scalar(codec::STD_DURATION),
+        scalar(codec::CAL_RELATIVE_DURATION),
+        scalar(codec::STD_JSON),
+        scalar(codec::STD_UUID),
+        scalar(codec::STD_BOOL),
+    ];
+    roundtrip(&descriptors, 0, Value::Str("a\"b".into()), r#""a\"b""#);
+    roundtrip(
+        &descriptors,
+        1,
+        Value::Bytes(bytes::Bytes::from_static(b"hello")),
+        r#""aGVsbG8=""#,
+    );
+    roundtrip(&descriptors, 2, Value::Int64(-12), "-12");
+    roundtrip(&descriptors, 3, Value::Float64(1.5), "1.5");
+    roundtrip(
+        &descriptors,
+        3,
+        Value::Float64(f64::INFINITY),
+        r#""Infinity""#,
+    );
+    roundtrip(
+        &descriptors,
+        4,
+        Value::BigInt("-123456789012345678901234567890".parse()?),
+        "-123456789012345678901234567890",
+    );
+    roundtrip(
+        &descriptors,
+        5,
+        Value::Decimal("12345678901234567890.0123456789".parse()?),
+        "12345678901234567890.0123456789",
+    );
+    roundtrip(&descriptors, 5, Value::Decimal("100".parse()?), "100");
+    roundtrip(
+        &descriptors,
+        6,
+        Value::Datetime("2022-03-04T05:06:07.5Z".parse()?),
+        r#""2022-03-04T05:06:07.5+00:00""#,
+    );
+    roundtrip(
+        &descriptors,
+        7,
+        Value::LocalDatetime("2022-03-04T05:06:07".parse()?),
+        r#""2022-03-04T05:06:07""#,
+    );
+    roundtrip(
+        &descriptors,
+        8,
+        Value::LocalDate("2022-03-04".parse()?),
+        r#""2022-03-04""#,
+    );
+    roundtrip(
+        &descriptors,
+        9,
+        Value::LocalTime("05:06:07.12".parse()?),
+        r#""05:06:07.12""#,
+    );
+    roundtrip(
+        &descriptors,
+        10,
+        Value::Duration(Duration::from_micros(-3_723_500_000)),
+        r#""PT-1H-2M-3.5S""#,
+    );
+    roundtrip(
+        &descriptors,
+        11,
+        Value::RelativeDuration("P1Y2M3DT4H".parse()?),
+        r#""P1Y2M3DT4H""#,
+    );
+    roundtrip(
+        &descriptors,
+        12,
+        Value::Json(Json::new_unchecked(r#"{"a":[1,2]}"#.into())),
+        r#"{"a":[1,2]}"#,
+    );
+    roundtrip(
+        &descriptors,
+        13,
+        Value::Uuid(Uuid::from_u128(1)),
+        r#""00000000-0000-0000-0000-000000000001""#,
+    );
+    roundtrip(&descriptors, 14, Value::Bool(true), "true");
+
+    // other representations accepted by the server
+    assert_eq!(
+        from_json(&descriptors, 6, r#""2022-03-04T08:06:07+03:00""#)?,
+        Value::Datetime("2022-03-04T05:06:07Z".parse()?)
+    );
+    assert_eq!(
+        from_json(&descriptors, 4, r#""12""#)?,
+        Value::BigInt(BigInt::from(12))
+    );
+    assert_eq!(
+        from_json(&descriptors, 5, "1.5e3")?,
+        Value::Decimal("1500".parse()?)
+    );
+    assert_eq!(
+        from_json(&descriptors, 10, r#""1 hour 30 minutes""#)?,
+        Value::Duration(Duration::from_micros(5_400_000_000))
+    );
+
+    assert!(from_json(&descriptors, 2, "1.5").is_err());
+    assert!(from_json(&descriptors, 2, r#""1""#).is_err());
+    assert!(from_json(&descriptors, 4, "1.5").is_err());
+    assert!(from_json(&descriptors, 10, r#""P1D""#).is_err());
+    assert!(from_json(&descriptors, 1, r#""aGVsbG8""#).is_err());
+    Ok(())
+}
+
+#[test]
+fn object() -> Result<(), Box<dyn Error>> {
+    let elements = vec![
+        ShapeElement {
+            flag_implicit: true,
+            ..shape_element("id", 0)
+        },
+        shape_element("name", 1),
+        shape_element("tags", 2),
+        ShapeElement {
+            flag_link_property: true,
+            ..shape_element("weight", 3)
+        },
+    ];
+    let descriptors = [
+        scalar(codec::STD_UUID),
+        scalar(codec::STD_STR),
+        Descriptor::Array(ArrayTypeDescriptor {
+            id: Uuid::from_u128(0x100).into(),
+            type_pos: TypePos(1),
+            dimensions: vec![None],
+            name: None,
+            schema_defined: None,
+            ancestors: vec![],
+        }),
+        scalar(codec::STD_INT16),
+        Descriptor::ObjectShape(ObjectShapeDescriptor {
+            id: Uuid::from_u128(0x101).into(),
+            ephemeral_free_shape: false,
+            type_pos: None,
+            elements: elements.clone(),
+        }),
+    ];
+    let shape = ObjectShape::from(&elements[..]);
+    let value = Value::Object {
+        shape: shape.clone(),
+        fields: vec![
+            Some(Value::Uuid(Uuid::from_u128(7))),
+            Some(Value::Str("John".into())),
+            Some(Value::Array(vec![Value::Str("a".into())])),
+            None,
+        ],
+    };
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&value.to_json()?)?,
+        json!({"name": "John", "tags": ["a"], "@weight": null})
+    );
+
+    let parsed = from_json(
+        &descriptors,
+        4,
+        r#"{"@weight": 2, "id": "00000000-0000-0000-0000-000000000007"}"#,
+    )?;
+    assert_eq!(
+        parsed,
+        Value::Object {
+            shape,
+            fields: vec![
+                Some(Value::Uuid(Uuid::from_u128(7))),
+                None,
+                None,
+                Some(Value::Int16(2)),
+            ],
+        }
+    );
+
+    assert!(from_json(&descriptors, 4, r#"{"weight": 2}"#).is_err());
+    assert!(from_json(&descriptors, 4, r#"{"name": "a", "name": "b"}"#).is_err());
+    assert!(from_json(&descriptors, 4, r#"{"tags": "a"}"#).is_err());
+    Ok(())
+}
+
+#[test]
+fn named_tuple() -> Result<(), Box<dyn Error>> {
+    let elements = vec![
+        TupleElement {
+            name: "a".into(),
+            type_pos: TypePos(0),
+        },
+        TupleElement {
+            name: "b".into(),
+            type_pos: TypePos(1),
+        },
+    ];
+    let descriptors = [
+        scalar(codec::STD_INT64),
+        scalar(codec::STD_STR),
+        Descriptor::NamedTuple(NamedTupleTypeDescriptor {
+            id: Uuid::from_u128(0x100).into(),
+            elements: elements.clone(),
+            name: None,
+            schema_defined: None,
+            ancestors: vec![],
+        }),
+    ];
+    roundtrip(
+        &descriptors,
+        2,
+        Value::NamedTuple {
+            shape: NamedTupleShape::from(&elements[..]),
+            fields: vec![Value::Int64(1), Value::Str("x".into())],
+        },
+        r#"{"a":1,"b":"x"}"#,
+    );
+    assert!(from_json(&descriptors, 2, r#"{"a": 1}"#).is_err());
+    assert!(from_json(&descriptors, 2, r#"{"a": 1, "b": null}"#).is_err());
+    assert!(from_json(&descriptors, 2, r#"[1, "x"]"#).is_err());
+    Ok(())
+}
+
+#[test]
+fn input_shape() -> Result<(), Box<dyn Error>> {
+    let descriptors = [
+        scalar(codec::STD_STR),
+        Descriptor::InputShape(InputShapeTypeDescriptor {
+            id: Uuid::from_u128(0x100).into(),
+            elements: ["module", "aliases", "config"]
+                .iter()
+                .map(|name| InputShapeElement {
+                    cardinality: None,
+                    name: name.to_string(),
+                    type_pos: TypePos(0),
+                })
+                .collect(),
+        }),
+    ];
+    let parsed = from_json(&descriptors, 1, r#"{"module": "test", "config": null}"#)?;
+    assert_eq!(
+        parsed,
+        Value::SparseObject(SparseObject::from_pairs([
+            ("module", Some(Value::Str("test".into()))),
+            ("config", None),
+        ]))
+    );
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&parsed.to_json()?)?,
+        json!({"module": "test", "config": null})
+    );
+    Ok(())
+}
+
+#[test]
+fn enums_and_ranges() -> Result<(), Box<dyn Error>> {
+    let descriptors = [
+        Descriptor::Enumeration(EnumerationTypeDescriptor {
+            id: Uuid::from_u128(0x100).into(),
+            members: vec!["Red".into(), "Green".into()],
+            name: None,
+            schema_defined: None,
+            ancestors: vec![],
+        }),
+        scalar(codec::STD_INT32),
+        Descriptor::Range(RangeTypeDescriptor {
+            id: Uuid::from_u128(0x101).into(),
+            type_pos: TypePos(1),
+            name: None,
+            schema_defined: None,
+            ancestors: vec![],
+        }),
+    ];
+    roundtrip(&descriptors, 0, Value::Enum("Green".into()), r#""Green""#);
+    assert!(from_json(&descriptors, 0, r#""Blue""#).is_err());
+
+    roundtrip(
+        &descriptors,
+        2,
+        Value::Range(Range::from(
+            Box::new(Value::Int32(1))..Box::new(Value::Int32(5)),
+        )),
+        r#"{"lower":1,"inc_lower":true,"upper":5,"inc_upper":false}"#,
+    );
+    roundtrip(
+        &descriptors,
+        2,
+        Value::Range(Range::empty()),
+        r#"{"empty":true}"#,
+    );
+    assert_eq!(
+        from_json(&descriptors, 2, r#"{"lower": null, "upper": 7}"#)?,
+        Value::Range(Range::from(..Box::new(Value::Int32(7))))
+    );
+    Ok(())
+}
+
+#[test]
+fn nothing() -> Result<(), Box<dyn Error>> {
+    let descriptors = [scalar(codec::STD_INT64)];
+    let value = Value::from_json_with_descriptors(None, &descriptors, "null")?;
+    assert_eq!(value, Value::Nothing);
+    assert_eq!(value.to_json()?, "null");
+    Ok(())
+}
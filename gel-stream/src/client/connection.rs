@@ -1,9 +1,11 @@
 use std::marker::PhantomData;
 use std::net::SocketAddr;
+use std::time::Duration;
 
+use super::happy_eyeballs;
 use crate::common::tokio_stream::{Resolver, TokioStream};
 use crate::{ConnectionError, Ssl, StreamUpgrade, TlsDriver, UpgradableStream};
-use crate::{MaybeResolvedTarget, ResolvedTarget, Target};
+use crate::{MaybeResolvedTarget, Target};
 
 type Connection<S, D> = UpgradableStream<S, D>;
 
@@ -15,9 +17,17 @@ pub struct Connector<D: TlsDriver = Ssl> {
     driver: PhantomData<D>,
     #[cfg(feature = "keepalive")]
     keepalive: Option<std::time::Duration>,
+    attempt_delay: Duration,
+    attempt_timeout: Option<Duration>,
 }
 
 impl Connector<Ssl> {
+    /// Create a connector for the target.
+    ///
+    /// Connection attempts time out after 10 seconds by default, which
+    /// requires the time driver of the tokio runtime. Use
+    /// [`Connector::set_connection_attempt_timeout`] to disable the timeout
+    /// for runtimes without one.
     pub fn new(target: Target) -> Result<Self, std::io::Error> {
         Self::new_explicit(target)
     }
@@ -25,6 +35,10 @@ impl Connector<Ssl> {
 
 #[allow(private_bounds)]
 impl<D: TlsDriver> Connector<D> {
+    /// Create a connector for the target using the TLS driver `D`.
+    ///
+    /// As with [`Connector::new`], the default connection attempt timeout
+    /// requires the time driver of the tokio runtime.
     pub fn new_explicit(target: Target) -> Result<Self, std::io::Error> {
         Ok(Self {
             target,
@@ -32,6 +46,8 @@ impl<D: TlsDriver> Connector<D> {
             driver: PhantomData,
             #[cfg(feature = "keepalive")]
             keepalive: None,
+            attempt_delay: happy_eyeballs::DEFAULT_ATTEMPT_DELAY,
+            attempt_timeout: Some(happy_eyeballs::DEFAULT_ATTEMPT_TIMEOUT),
         })
     }
 
@@ -42,17 +58,42 @@ impl<D: TlsDriver> Connector<D> {
         self.keepalive = keepalive;
     }
 
+    /// Set the delay before starting a connection attempt to the next
+    /// address of a host that resolves to multiple addresses, while the
+    /// previous attempts are still in progress.
+    ///
+    /// Racing the addresses requires the time driver of the tokio runtime.
+    pub fn set_connection_attempt_delay(&mut self, delay: Duration) {
+        self.attempt_delay = delay;
+    }
+
+    /// Set the time a connection attempt to a single address may take, or
+    /// `None` to wait indefinitely.
+    ///
+    /// A timeout requires the time driver of the tokio runtime.
+    pub fn set_connection_attempt_timeout(&mut self, timeout: Option<Duration>) {
+        self.attempt_timeout = timeout;
+    }
+
+    /// Connect to the target. Hosts resolving to multiple addresses are
+    /// connected to as described by RFC 8305 ("Happy Eyeballs"), and if all
+    /// of them fail the error contains a [`crate::ConnectAttemptsError`].
     pub async fn connect(&self) -> Result<Connection<TokioStream, D>, ConnectionError> {
         let stream = match self.target.maybe_resolved() {
             MaybeResolvedTarget::Resolved(target) => target.connect().await?,
             MaybeResolvedTarget::Unresolved(host, port, _) => {
-                let ip = self
+                let ips = self
                     .resolver
                     .resolve_remote(host.clone().into_owned())
                     .await?;
-                ResolvedTarget::SocketAddr(SocketAddr::new(ip, *port))
-                    .connect()
-                    .await?
+                let addrs = happy_eyeballs::sort_addresses(ips)
+                    .into_iter()
+                    .map(|ip| SocketAddr::new(ip, *port))
+                    .collect::<Vec<_>>();
+                let stream =
+                    happy_eyeballs::connect(&addrs, self.attempt_delay, self.attempt_timeout)
+                        .await?;
+                TokioStream::Tcp(stream)
             }
         };
 
@@ -0,0 +1,258 @@
+//! Connecting to hosts with multiple addresses, as described by RFC 8305
+//! ("Happy Eyeballs Version 2").
+//!
+//! Connection attempts are started one at a time, in order of preference,
+//! each one after the previous attempt fails or the attempt delay elapses.
+//! The first attempt to succeed wins and the others are cancelled.
+
+use std::future::{poll_fn, Future};
+use std::net::{IpAddr, SocketAddr};
+use std::pin::pin;
+use std::task::Poll;
+use std::time::Duration;
+
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use tokio::net::TcpStream;
+use tokio::time::Instant;
+
+/// The delay between connection attempts recommended by RFC 8305.
+pub(crate) const DEFAULT_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+/// The default time a single connection attempt may take.
+pub(crate) const DEFAULT_ATTEMPT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// All connection attempts to a host failed.
+///
+/// This is returned as the inner error of a [`std::io::Error`]. If every
+/// attempt failed with the same [`std::io::ErrorKind`], the outer error has
+/// that kind, otherwise it has the kind of the last attempted address.
+#[derive(Debug)]
+pub struct ConnectAttemptsError {
+    attempts: Vec<(SocketAddr, std::io::Error)>,
+}
+
+impl ConnectAttemptsError {
+    /// The attempted addresses and their errors, in order of preference.
+    pub fn attempts(&self) -> &[(SocketAddr, std::io::Error)] {
+        &self.attempts
+    }
+
+    fn into_io_error(self) -> std::io::Error {
+        let kind = self
+            .common_kind()
+            .or_else(|| self.attempts.last().map(|(_, e)| e.kind()))
+            .unwrap_or(std::io::ErrorKind::NotFound);
+        std::io::Error::new(kind, self)
+    }
+
+    fn common_kind(&self) -> Option<std::io::ErrorKind> {
+        let (first, rest) = self.attempts.split_first()?;
+        let kind = first.1.kind();
+        rest.iter().all(|(_, e)| e.kind() == kind).then_some(kind)
+    }
+}
+
+impl std::fmt::Display for ConnectAttemptsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to connect to any address")?;
+        for (i, (addr, error)) in self.attempts.iter().enumerate() {
+            let sep = if i == 0 { ": " } else { ", " };
+            write!(f, "{sep}{addr} ({error})")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConnectAttemptsError {}
+
+/// Orders addresses for connection attempts, alternating between address
+/// families and starting with IPv6. The resolver's order is kept within each
+/// family.
+pub(crate) fn sort_addresses(addrs: impl IntoIterator<Item = IpAddr>) -> Vec<IpAddr> {
+    let (v6, v4): (Vec<_>, Vec<_>) = addrs.into_iter().partition(IpAddr::is_ipv6);
+    let mut result = Vec::with_capacity(v6.len() + v4.len());
+    let (mut v6, mut v4) = (v6.into_iter(), v4.into_iter());
+    loop {
+        match (v6.next(), v4.next()) {
+            (None, None) => break,
+            (a, b) => result.extend(a.into_iter().chain(b)),
+        }
+    }
+    result
+}
+
+async fn attempt(
+    addr: SocketAddr,
+    timeout: Option<Duration>,
+) -> (SocketAddr, std::io::Result<TcpStream>) {
+    let connect = TcpStream::connect(addr);
+    let result = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, connect)
+            .await
+            .unwrap_or_else(|_| {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "connection attempt timed out",
+                ))
+            }),
+        None => connect.await,
+    };
+    (addr, result)
+}
+
+enum Event {
+    /// Start the next connection attempt.
+    Start,
+    /// A connection attempt finished.
+    Done(SocketAddr, std::io::Result<TcpStream>),
+    /// All attempts failed.
+    Exhausted,
+}
+
+/// Connects to the first address that accepts the connection, racing
+/// staggered attempts in the given order.
+///
+/// Racing multiple addresses and the attempt timeout use tokio timers, so
+/// they require the time driver of the runtime. A single address without an
+/// attempt timeout is connected to without timers.
+pub(crate) async fn connect(
+    addrs: &[SocketAddr],
+    attempt_delay: Duration,
+    attempt_timeout: Option<Duration>,
+) -> std::io::Result<TcpStream> {
+    if let [addr] = addrs {
+        let (addr, result) = attempt(*addr, attempt_timeout).await;
+        return result.map_err(|error| {
+            ConnectAttemptsError {
+                attempts: vec![(addr, error)],
+            }
+            .into_io_error()
+        });
+    }
+
+    let mut remaining = addrs.iter().copied();
+    let mut attempts = FuturesUnordered::new();
+    let mut errors = Vec::new();
+    let mut delay = pin!(tokio::time::sleep(Duration::ZERO));
+
+    loop {
+        let event = poll_fn(|cx| {
+            if let Poll::Ready(Some((addr, result))) = attempts.poll_next_unpin(cx) {
+                return Poll::Ready(Event::Done(addr, result));
+            }
+            if remaining.len() == 0 {
+                if attempts.is_empty() {
+                    return Poll::Ready(Event::Exhausted);
+                }
+                return Poll::Pending;
+            }
+            if attempts.is_empty() || delay.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(Event::Start);
+            }
+            Poll::Pending
+        })
+        .await;
+
+        match event {
+            Event::Start => {
+                let addr = remaining.next().unwrap();
+                attempts.push(attempt(addr, attempt_timeout));
+                delay.as_mut().reset(Instant::now() + attempt_delay);
+            }
+            Event::Done(_, Ok(stream)) => return Ok(stream),
+            Event::Done(addr, Err(error)) => {
+                // A failed attempt starts the next one without waiting
+                errors.push((addr, error));
+                delay.as_mut().reset(Instant::now());
+            }
+            Event::Exhausted => break,
+        }
+    }
+
+    // Report the errors in the order the addresses were attempted
+    errors.sort_by_key(|(addr, _)| addrs.iter().position(|a| a == addr));
+    Err(ConnectAttemptsError { attempts: errors }.into_io_error())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn test_sort_addresses() {
+        let v4 = |n| IpAddr::V4(Ipv4Addr::new(10, 0, 0, n));
+        let v6 = |n| IpAddr::V6(Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, n));
+
+        assert_eq!(
+            sort_addresses([v4(1), v4(2), v6(1), v6(2), v6(3)]),
+            [v6(1), v4(1), v6(2), v4(2), v6(3)]
+        );
+        assert_eq!(sort_addresses([v4(2), v4(1)]), [v4(2), v4(1)]);
+        assert_eq!(sort_addresses([v6(1)]), [v6(1)]);
+        assert!(sort_addresses([]).is_empty());
+    }
+
+    /// Returns an address that refuses connections.
+    async fn refused_address() -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .unwrap();
+        listener.local_addr().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_connect_skips_failed() {
+        let refused = refused_address().await;
+        let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // A long delay ensures that the failure starts the next attempt
+        let stream = connect(&[refused, addr], Duration::from_secs(60), None)
+            .await
+            .unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+    }
+
+    #[test]
+    fn test_connect_single_without_timers() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+                .await
+                .unwrap();
+            let addr = listener.local_addr().unwrap();
+
+            let stream = connect(&[addr], DEFAULT_ATTEMPT_DELAY, None).await.unwrap();
+            assert_eq!(stream.peer_addr().unwrap(), addr);
+        });
+    }
+
+    #[tokio::test]
+    async fn test_connect_reports_all_addresses() {
+        let first = refused_address().await;
+        let second = refused_address().await;
+
+        let error = connect(&[first, second], DEFAULT_ATTEMPT_DELAY, None)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::ConnectionRefused);
+        let inner = error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<ConnectAttemptsError>())
+            .unwrap();
+        let attempted = inner
+            .attempts()
+            .iter()
+            .map(|(addr, _)| *addr)
+            .collect::<Vec<_>>();
+        assert_eq!(attempted, [first, second]);
+        assert!(error.to_string().contains(&first.to_string()));
+        assert!(error.to_string().contains(&second.to_string()));
+    }
+}
@@ -1,3 +1,5 @@
 mod connection;
+mod happy_eyeballs;
 
 pub use connection::Connector;
+pub use happy_eyeballs::ConnectAttemptsError;
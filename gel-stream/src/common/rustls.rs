@@ -1,7 +1,6 @@
-use futures::FutureExt;
 use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
 use rustls::client::WebPkiServerVerifier;
-use rustls::server::{Acceptor, ClientHello, WebPkiClientVerifier};
+use rustls::server::{AcceptedAlert, Acceptor, WebPkiClientVerifier};
 use rustls::{
     ClientConfig, ClientConnection, DigitallySignedStruct, RootCertStore, ServerConfig,
     SignatureScheme,
@@ -178,26 +177,50 @@ impl TlsDriver for RustlsDriver {
         };
 
         let mut acceptor = Acceptor::default();
-        acceptor.read_tls(&mut buffer.as_slice())?;
-        let server_config_provider = Arc::new(move |client_hello: ClientHello| {
-            let params = params.clone();
-            let server_name = client_hello
-                .server_name()
-                .map(|name| ServerName::DnsName(DnsName::try_from(name.to_string()).unwrap()));
-            async move {
-                let params = params.lookup(server_name);
-                let config = RustlsDriver::init_server(&params)
-                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
-                Ok::<_, std::io::Error>(config)
+        // Reading an empty buffer would be interpreted as EOF by rustls
+        if !buffer.is_empty() {
+            acceptor.read_tls(&mut buffer.as_slice())?;
+        }
+        // Read the ClientHello here rather than in `TlsStream`: its acceptor
+        // fails on a spurious read readiness, which is left behind when the
+        // plaintext part of a STARTTLS exchange was read to the last byte.
+        let accepted = loop {
+            match acceptor.accept() {
+                Ok(Some(accepted)) => break accepted,
+                Ok(None) => {}
+                Err((e, alert)) => {
+                    send_alert(&stream, alert).await;
+                    return Err(crate::SslError::RustlsError(e));
+                }
             }
-            .boxed()
-        });
-        let mut stream = TlsStream::new_server_side_from_acceptor(
-            acceptor,
-            stream,
-            server_config_provider,
-            None,
-        );
+            stream.readable().await?;
+            let mut buf = [0; 4096];
+            match stream.try_read(&mut buf) {
+                Ok(0) => return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into()),
+                Ok(n) => {
+                    let mut data = &buf[..n];
+                    while !data.is_empty() {
+                        acceptor.read_tls(&mut data)?;
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e.into()),
+            }
+        };
+
+        let server_name = accepted
+            .client_hello()
+            .server_name()
+            .map(|name| ServerName::DnsName(DnsName::try_from(name.to_string()).unwrap()));
+        let config = RustlsDriver::init_server(&params.lookup(server_name))?;
+        let connection = match accepted.into_connection(config) {
+            Ok(connection) => connection,
+            Err((e, alert)) => {
+                send_alert(&stream, alert).await;
+                return Err(crate::SslError::RustlsError(e));
+            }
+        };
+        let mut stream = TlsStream::new_server_side_from(stream, connection, None);
 
         match stream.handshake().await {
             Ok(handshake) => {
@@ -229,6 +252,27 @@ impl TlsDriver for RustlsDriver {
     }
 }
 
+/// Sends the alert of a failed handshake to the client, if possible.
+async fn send_alert(stream: &tokio::net::TcpStream, mut alert: AcceptedAlert) {
+    let mut buf = Vec::new();
+    while let Ok(n) = alert.write(&mut buf) {
+        if n == 0 {
+            break;
+        }
+    }
+    let mut buf = buf.as_slice();
+    while !buf.is_empty() {
+        if stream.writable().await.is_err() {
+            return;
+        }
+        match stream.try_write(buf) {
+            Ok(n) => buf = &buf[n..],
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+            Err(_) => return,
+        }
+    }
+}
+
 fn make_roots(
     root_certs: &[CertificateDer<'static>],
     webpki: bool,
@@ -16,17 +16,18 @@ pub(crate) struct Resolver {
 }
 
 #[allow(unused)]
-async fn resolve_host_to_socket_addrs(host: String) -> std::io::Result<IpAddr> {
-    let res = tokio::task::spawn_blocking(move || format!("{}:0", host).to_socket_addrs())
+async fn resolve_host_to_socket_addrs(host: String) -> std::io::Result<Vec<IpAddr>> {
+    let res = tokio::task::spawn_blocking(move || (host.as_str(), 0).to_socket_addrs())
         .await
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::Interrupted, e.to_string()))??;
-    res.into_iter()
-        .next()
-        .ok_or(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            "No address found",
-        ))
-        .map(|addr| addr.ip())
+    let mut addrs = Vec::new();
+    for addr in res {
+        // The system resolver may return the same address once per socket type
+        if !addrs.contains(&addr.ip()) {
+            addrs.push(addr.ip());
+        }
+    }
+    Ok(addrs)
 }
 
 impl Resolver {
@@ -37,16 +38,24 @@ impl Resolver {
         })
     }
 
-    pub async fn resolve_remote(&self, host: String) -> std::io::Result<IpAddr> {
+    /// Resolves the host to all of its addresses, in the resolver's order.
+    pub async fn resolve_remote(&self, host: String) -> std::io::Result<Vec<IpAddr>> {
         #[cfg(feature = "hickory")]
-        {
-            let addr = self.resolver.lookup_ip(host).await?.iter().next().unwrap();
-            Ok(addr)
-        }
+        let addrs = self
+            .resolver
+            .lookup_ip(host)
+            .await?
+            .iter()
+            .collect::<Vec<_>>();
         #[cfg(not(feature = "hickory"))]
-        {
-            resolve_host_to_socket_addrs(host).await
+        let addrs = resolve_host_to_socket_addrs(host).await?;
+        if addrs.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "No address found",
+            ));
         }
+        Ok(addrs)
     }
 }
 
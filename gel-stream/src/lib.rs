@@ -14,7 +14,7 @@ mod client;
 mod server;
 
 #[cfg(feature = "client")]
-pub use client::Connector;
+pub use client::{ConnectAttemptsError, Connector};
 
 #[cfg(feature = "server")]
 pub use server::Acceptor;
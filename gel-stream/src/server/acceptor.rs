@@ -1,22 +1,24 @@
 use crate::{
     common::tokio_stream::TokioListenerStream, ConnectionError, LocalAddress, ResolvedTarget,
-    RewindStream, Ssl, SslError, StreamUpgrade, TlsDriver, TlsServerParameterProvider,
-    UpgradableStream,
-};
-use futures::{FutureExt, StreamExt};
-use std::{
-    future::Future,
-    pin::Pin,
-    task::{ready, Poll},
+    RewindStream, Ssl, StreamUpgrade, TlsDriver, TlsServerParameterProvider, UpgradableStream,
 };
+use futures::{stream::FuturesUnordered, StreamExt};
+use std::{future::Future, pin::Pin, task::Poll, time::Duration};
 use std::{net::SocketAddr, path::Path};
 
 use super::Connection;
 
+/// The default maximum number of TLS handshakes in progress at once.
+const DEFAULT_MAX_CONCURRENT_HANDSHAKES: usize = 128;
+/// The default time a client is given to complete the TLS handshake.
+const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
 pub struct Acceptor {
     resolved_target: ResolvedTarget,
     tls_provider: Option<TlsServerParameterProvider>,
     should_upgrade: bool,
+    max_concurrent_handshakes: usize,
+    handshake_timeout: Option<Duration>,
 }
 
 impl Acceptor {
@@ -25,14 +27,24 @@ impl Acceptor {
             resolved_target: ResolvedTarget::SocketAddr(addr),
             tls_provider: None,
             should_upgrade: false,
+            max_concurrent_handshakes: DEFAULT_MAX_CONCURRENT_HANDSHAKES,
+            handshake_timeout: Some(DEFAULT_HANDSHAKE_TIMEOUT),
         }
     }
 
+    /// Accept TLS connections, completing the handshake before yielding them.
+    ///
+    /// The handshake times out after 10 seconds by default, which requires
+    /// the time driver of the tokio runtime. Use
+    /// [`Acceptor::set_handshake_timeout`] to disable the timeout for
+    /// runtimes without one.
     pub fn new_tcp_tls(addr: SocketAddr, provider: TlsServerParameterProvider) -> Self {
         Self {
             resolved_target: ResolvedTarget::SocketAddr(addr),
             tls_provider: Some(provider),
             should_upgrade: true,
+            max_concurrent_handshakes: DEFAULT_MAX_CONCURRENT_HANDSHAKES,
+            handshake_timeout: Some(DEFAULT_HANDSHAKE_TIMEOUT),
         }
     }
 
@@ -41,6 +53,8 @@ impl Acceptor {
             resolved_target: ResolvedTarget::SocketAddr(addr),
             tls_provider: Some(provider),
             should_upgrade: false,
+            max_concurrent_handshakes: DEFAULT_MAX_CONCURRENT_HANDSHAKES,
+            handshake_timeout: Some(DEFAULT_HANDSHAKE_TIMEOUT),
         }
     }
 
@@ -53,6 +67,8 @@ impl Acceptor {
                 ),
                 tls_provider: None,
                 should_upgrade: false,
+                max_concurrent_handshakes: DEFAULT_MAX_CONCURRENT_HANDSHAKES,
+                handshake_timeout: Some(DEFAULT_HANDSHAKE_TIMEOUT),
             })
         }
         #[cfg(not(unix))]
@@ -74,6 +90,8 @@ impl Acceptor {
                 ),
                 tls_provider: None,
                 should_upgrade: false,
+                max_concurrent_handshakes: DEFAULT_MAX_CONCURRENT_HANDSHAKES,
+                handshake_timeout: Some(DEFAULT_HANDSHAKE_TIMEOUT),
             })
         }
         #[cfg(not(any(target_os = "linux", target_os = "android")))]
@@ -85,6 +103,24 @@ impl Acceptor {
         }
     }
 
+    /// Set the maximum number of TLS handshakes that may be in progress at
+    /// once. Incoming connections are not accepted while the limit is
+    /// reached. A value of zero is treated as one.
+    pub fn set_max_concurrent_handshakes(&mut self, max: usize) {
+        self.max_concurrent_handshakes = max.max(1);
+    }
+
+    /// Set the time a client is given to complete the TLS handshake, or
+    /// `None` to wait indefinitely. Connections that time out are yielded as
+    /// [`std::io::ErrorKind::TimedOut`] errors.
+    ///
+    /// A timeout requires the time driver of the tokio runtime. It only
+    /// applies to acceptors that upgrade connections themselves, i.e. ones
+    /// created with [`Acceptor::new_tcp_tls`].
+    pub fn set_handshake_timeout(&mut self, timeout: Option<Duration>) {
+        self.handshake_timeout = timeout;
+    }
+
     pub async fn bind(
         self,
     ) -> Result<
@@ -94,10 +130,12 @@ impl Acceptor {
         let stream = self.resolved_target.listen_raw().await?;
         Ok(AcceptedStream {
             stream,
+            stream_done: false,
             should_upgrade: self.should_upgrade,
-            upgrade_future: None,
             tls_provider: self.tls_provider,
-            _phantom: None,
+            max_concurrent_handshakes: self.max_concurrent_handshakes,
+            handshake_timeout: self.handshake_timeout,
+            upgrade_futures: FuturesUnordered::new(),
         })
     }
 
@@ -111,10 +149,12 @@ impl Acceptor {
         let stream = self.resolved_target.listen_raw().await?;
         Ok(AcceptedStream {
             stream,
+            stream_done: false,
             should_upgrade: self.should_upgrade,
-            upgrade_future: None,
             tls_provider: self.tls_provider,
-            _phantom: None,
+            max_concurrent_handshakes: self.max_concurrent_handshakes,
+            handshake_timeout: self.handshake_timeout,
+            upgrade_futures: FuturesUnordered::new(),
         })
     }
 
@@ -128,15 +168,19 @@ impl Acceptor {
     }
 }
 
+type UpgradeFuture<D> =
+    Pin<Box<dyn Future<Output = Result<Connection<D>, ConnectionError>> + Send + 'static>>;
+
 struct AcceptedStream<D: TlsDriver = Ssl> {
     stream: TokioListenerStream,
+    /// Set once the listener stops yielding connections.
+    stream_done: bool,
     should_upgrade: bool,
     tls_provider: Option<TlsServerParameterProvider>,
-    #[allow(clippy::type_complexity)]
-    upgrade_future:
-        Option<Pin<Box<dyn Future<Output = Result<Connection<D>, SslError>> + Send + 'static>>>,
-    // Avoid using PhantomData because it fails to implement certain auto-traits
-    _phantom: Option<&'static D>,
+    max_concurrent_handshakes: usize,
+    handshake_timeout: Option<Duration>,
+    /// TLS handshakes in progress, yielded in order of completion.
+    upgrade_futures: FuturesUnordered<UpgradeFuture<D>>,
 }
 
 impl<D: TlsDriver> LocalAddress for AcceptedStream<D> {
@@ -145,6 +189,22 @@ impl<D: TlsDriver> LocalAddress for AcceptedStream<D> {
     }
 }
 
+impl<D: TlsDriver> AcceptedStream<D> {
+    fn upgrade(&self, mut stream: Connection<D>) -> UpgradeFuture<D> {
+        let timeout = self.handshake_timeout;
+        Box::pin(async move {
+            let upgrade = stream.secure_upgrade();
+            match timeout {
+                Some(timeout) => tokio::time::timeout(timeout, upgrade).await.map_err(|_| {
+                    std::io::Error::new(std::io::ErrorKind::TimedOut, "TLS handshake timed out")
+                })??,
+                None => upgrade.await?,
+            }
+            Ok(stream)
+        })
+    }
+}
+
 impl<D: TlsDriver> futures::Stream for AcceptedStream<D> {
     type Item = Result<Connection<D>, ConnectionError>;
 
@@ -152,45 +212,30 @@ impl<D: TlsDriver> futures::Stream for AcceptedStream<D> {
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> Poll<Option<Self::Item>> {
-        if let Some(mut upgrade_future) = self.upgrade_future.take() {
-            match upgrade_future.poll_unpin(cx) {
-                Poll::Ready(Ok(conn)) => {
-                    return Poll::Ready(Some(Ok(conn)));
-                }
-                Poll::Ready(Err(e)) => {
-                    return Poll::Ready(Some(Err(e.into())));
-                }
-                Poll::Pending => {
-                    self.upgrade_future = Some(upgrade_future);
-                    return Poll::Pending;
+        // Accept as many connections as we can, up to the handshake limit.
+        while !self.stream_done && self.upgrade_futures.len() < self.max_concurrent_handshakes {
+            let (stream, _target) = match self.stream.poll_next_unpin(cx) {
+                Poll::Ready(Some(r)) => r?,
+                Poll::Ready(None) => {
+                    self.stream_done = true;
+                    break;
                 }
+                Poll::Pending => break,
+            };
+            let stream =
+                UpgradableStream::new_server(RewindStream::new(stream), self.tls_provider.clone());
+            if !self.should_upgrade {
+                return Poll::Ready(Some(Ok(stream)));
             }
+            let upgrade_future = self.upgrade(stream);
+            self.upgrade_futures.push(upgrade_future);
         }
-        let r = ready!(self.stream.poll_next_unpin(cx));
-        let Some(r) = r else {
-            return Poll::Ready(None);
-        };
-        let (stream, _target) = r?;
-        let mut stream =
-            UpgradableStream::new_server(RewindStream::new(stream), self.tls_provider.clone());
-        if self.should_upgrade {
-            let mut upgrade_future = Box::pin(async move {
-                stream.secure_upgrade().await?;
-                Ok::<_, SslError>(stream)
-            });
-            match upgrade_future.poll_unpin(cx) {
-                Poll::Ready(Ok(stream)) => {
-                    return Poll::Ready(Some(Ok(stream)));
-                }
-                Poll::Ready(Err(e)) => {
-                    return Poll::Ready(Some(Err(e.into())));
-                }
-                Poll::Pending => {
-                    self.upgrade_future = Some(upgrade_future);
-                    return Poll::Pending;
-                }
-            }
+
+        match self.upgrade_futures.poll_next_unpin(cx) {
+            Poll::Ready(Some(r)) => Poll::Ready(Some(r)),
+            // No handshakes in progress: we're done only if the listener is.
+            Poll::Ready(None) if self.stream_done => Poll::Ready(None),
+            Poll::Ready(None) | Poll::Pending => Poll::Pending,
         }
-        Poll::Ready(Some(Ok(stream)))
     }
 }
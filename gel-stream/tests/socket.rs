@@ -64,3 +64,34 @@ async fn test_target_tcp() -> Result<(), ConnectionError> {
 
     Ok(())
 }
+
+/// `localhost` may resolve to both `::1` and `127.0.0.1`, but the server only
+/// listens on the latter.
+#[tokio::test]
+#[ntest::timeout(30_000)]
+async fn test_target_tcp_hostname() -> Result<(), ConnectionError> {
+    let mut acceptor = Acceptor::new_tcp(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0))
+        .bind()
+        .await?;
+    let port = acceptor.local_address()?.tcp().unwrap().port();
+
+    let accept_task = tokio::spawn(async move {
+        let mut connection = acceptor.next().await.unwrap().unwrap();
+        let mut buf = String::new();
+        connection.read_to_string(&mut buf).await.unwrap();
+        assert_eq!(buf, "Hello, world!");
+    });
+
+    let connect_task = tokio::spawn(async move {
+        let target = Target::new_tcp(("localhost", port));
+        let mut stm = Connector::new(target).unwrap().connect().await.unwrap();
+        stm.write_all(b"Hello, world!").await?;
+        stm.shutdown().await?;
+        Ok::<_, std::io::Error>(())
+    });
+
+    accept_task.await.unwrap();
+    connect_task.await.unwrap().unwrap();
+
+    Ok(())
+}
@@ -405,6 +405,61 @@ tls_test! {
         Ok(())
     }
 
+    /// The server reads exactly the STARTTLS request before upgrading, so the
+    /// TLS handshake begins with an empty rewind buffer.
+    #[tokio::test]
+    #[ntest::timeout(30_000)]
+    async fn test_target_tcp_starttls<C: TlsDriver, S: TlsDriver>() -> Result<(), ConnectionError> {
+        let mut acceptor = Acceptor::new_tcp_starttls(
+            SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0),
+            tls_server_parameters(TlsAlpn::default(), TlsClientCertVerify::Ignore),
+        )
+        .bind_explicit::<S>()
+        .await?;
+        let addr = acceptor.local_address()?;
+
+        let accept_task = tokio::spawn(async move {
+            let mut connection = acceptor.next().await.unwrap()?;
+            let mut buf = [0; 9];
+            connection.read_exact(&mut buf).await?;
+            assert_eq!(&buf, b"STARTTLS\n");
+            connection.write_all(b"OK").await?;
+            connection.secure_upgrade().await?;
+            assert!(connection.handshake().is_some());
+            let mut buf = String::new();
+            connection.read_to_string(&mut buf).await?;
+            assert_eq!(buf, "Hello, world!");
+            connection.shutdown().await?;
+            Ok::<_, ConnectionError>(())
+        });
+
+        let connect_task = tokio::spawn(async move {
+            let target = Target::new_resolved_starttls(
+                addr,
+                TlsParameters {
+                    server_cert_verify: TlsServerCertVerify::Insecure,
+                    ..Default::default()
+                },
+            );
+            let mut stm = Connector::<C>::new_explicit(target).unwrap().connect().await?;
+            stm.write_all(b"STARTTLS\n").await?;
+            let mut buf = [0; 2];
+            stm.read_exact(&mut buf).await?;
+            assert_eq!(&buf, b"OK");
+            stm.secure_upgrade().await?;
+            // Give the server time to find no data after the handshake
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            stm.write_all(b"Hello, world!").await?;
+            stm.shutdown().await?;
+            Ok::<_, ConnectionError>(())
+        });
+
+        accept_task.await.unwrap().unwrap();
+        connect_task.await.unwrap().unwrap();
+
+        Ok(())
+    }
+
     #[tokio::test]
     #[ntest::timeout(30_000)]
     async fn test_target_tcp_tls_crl<C: TlsDriver, S: TlsDriver>() -> Result<(), ConnectionError> {
@@ -598,6 +653,79 @@ tls_test! {
         Ok(())
     }
 
+    /// A client that never completes the handshake must not block others.
+    #[tokio::test]
+    #[ntest::timeout(30_000)]
+    async fn test_target_tcp_tls_stalled_handshake<C: TlsDriver, S: TlsDriver>() -> Result<(), ConnectionError> {
+        let mut acceptor = Acceptor::new_tcp_tls(
+            SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0),
+            tls_server_parameters(TlsAlpn::default(), TlsClientCertVerify::Ignore),
+        );
+        acceptor.set_handshake_timeout(None);
+        let mut acceptor = acceptor.bind_explicit::<S>().await?;
+        let addr = acceptor.local_address()?;
+
+        let stalled = tokio::net::TcpStream::connect(addr.tcp().unwrap()).await?;
+        let connect_task = tokio::spawn(async move {
+            let target = Target::new_resolved_tls(
+                addr,
+                TlsParameters {
+                    server_cert_verify: TlsServerCertVerify::Insecure,
+                    ..Default::default()
+                },
+            );
+            let mut stm = Connector::<C>::new_explicit(target).unwrap().connect().await?;
+            stm.write_all(b"Hello, world!").await?;
+            stm.shutdown().await?;
+            Ok::<_, ConnectionError>(())
+        });
+
+        let mut connection = acceptor.next().await.unwrap()?;
+        let mut buf = String::new();
+        connection.read_to_string(&mut buf).await?;
+        assert_eq!(buf, "Hello, world!");
+        connect_task.await.unwrap().unwrap();
+        drop(stalled);
+        Ok(())
+    }
+
+    /// A client that doesn't complete the handshake in time is rejected.
+    #[tokio::test]
+    #[ntest::timeout(30_000)]
+    async fn test_target_tcp_tls_handshake_timeout<C: TlsDriver, S: TlsDriver>() -> Result<(), ConnectionError> {
+        let mut acceptor = Acceptor::new_tcp_tls(
+            SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0),
+            tls_server_parameters(TlsAlpn::default(), TlsClientCertVerify::Ignore),
+        );
+        acceptor.set_handshake_timeout(Some(std::time::Duration::from_millis(500)));
+        let mut acceptor = acceptor.bind_explicit::<S>().await?;
+        let addr = acceptor.local_address()?;
+
+        let _stalled = tokio::net::TcpStream::connect(addr.tcp().unwrap()).await?;
+        let res = acceptor.next().await.unwrap();
+        assert!(
+            matches!(&res, Err(ConnectionError::Io(e)) if e.kind() == std::io::ErrorKind::TimedOut),
+            "{res:?}"
+        );
+
+        // The acceptor keeps serving other clients
+        let connect_task = tokio::spawn(async move {
+            let target = Target::new_resolved_tls(
+                addr,
+                TlsParameters {
+                    server_cert_verify: TlsServerCertVerify::Insecure,
+                    ..Default::default()
+                },
+            );
+            let mut stm = Connector::<C>::new_explicit(target).unwrap().connect().await?;
+            stm.shutdown().await?;
+            Ok::<_, ConnectionError>(())
+        });
+        acceptor.next().await.unwrap()?;
+        connect_task.await.unwrap().unwrap();
+        Ok(())
+    }
+
 }
 
 macro_rules! tls_client_test (
@@ -26,6 +26,7 @@ use crate::env::{get_env, Env};
 use crate::errors::{ClientError, Error, ErrorKind, ResultExt};
 use crate::errors::{ClientNoCredentialsError, NoCloudConfigFound};
 use crate::errors::{InterfaceError, InvalidArgumentError};
+use crate::metrics::{Instrumentation, Observer};
 use crate::tls::read_root_cert_pem;
 use crate::PROJECT_FILES;
 
@@ -33,6 +34,7 @@ pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
 pub const DEFAULT_WAIT: Duration = Duration::from_secs(30);
 pub const DEFAULT_TCP_KEEPALIVE: Duration = Duration::from_secs(60);
 pub const DEFAULT_POOL_SIZE: usize = 10;
+pub const DEFAULT_QUERY_CACHE_SIZE: usize = 1000;
 pub const DEFAULT_HOST: &str = "localhost";
 pub const DEFAULT_PORT: u16 = 5656;
 const DOMAIN_LABEL_MAX_LENGTH: usize = 63;
@@ -159,6 +161,10 @@ pub struct Builder {
 
     // Pool configuration
     max_concurrency: Option<usize>,
+    query_cache_size: Option<usize>,
+    max_idle_time: Option<Duration>,
+    max_connection_lifetime: Option<Duration>,
+    min_idle: Option<usize>,
 }
 /// Configuration of the client
 ///
@@ -196,6 +202,10 @@ pub(crate) struct ConfigInner {
 
     // Pool configuration
     pub max_concurrency: Option<usize>,
+    pub query_cache_size: usize,
+    pub max_idle_time: Option<Duration>,
+    pub max_connection_lifetime: Option<Duration>,
+    pub min_idle: usize,
 
     pub tls_server_name: Option<String>,
 
@@ -204,7 +214,8 @@ pub(crate) struct ConfigInner {
     pub client_security: ClientSecurity,
     pub pem_certificates: Option<String>,
     // A certificate check function that allows for custom certificate validation.
-    pub cert_check: Option<CertCheck>
+    pub cert_check: Option<CertCheck>,
+    pub instrumentation: Instrumentation,
 }
 
 pub trait CertChecker: Send + Sync + 'static {
@@ -908,11 +919,55 @@ impl Builder {
     }
 
     /// Set the maximum number of underlying database connections.
+    ///
+    /// By default the pool is sized according to the concurrency suggested
+    /// by the server on the first connection. Setting this value disables
+    /// the adaptive sizing.
     pub fn max_concurrency(&mut self, value: usize) -> &mut Self {
         self.max_concurrency = Some(value);
         self
     }
 
+    /// Set the number of query descriptors cached by each connection.
+    ///
+    /// Queries found in the cache are executed without a separate parse
+    /// round trip. The default is 1000. Setting it to zero disables the cache.
+    pub fn query_cache_size(&mut self, value: usize) -> &mut Self {
+        self.query_cache_size = Some(value);
+        self
+    }
+
+    /// Set the time after which an idle connection is closed.
+    ///
+    /// Connections are never closed for being idle by default (although
+    /// the server may close them on its `session_idle_timeout`).
+    /// Connections kept by [`min_idle`](Builder::min_idle) are not closed.
+    pub fn max_idle_time(&mut self, value: Duration) -> &mut Self {
+        self.max_idle_time = Some(value);
+        self
+    }
+
+    /// Set the maximum time a connection is reused for.
+    ///
+    /// Connections older than this are closed when they are returned to
+    /// the pool or found idle. There is no limit by default.
+    pub fn max_connection_lifetime(&mut self, value: Duration) -> &mut Self {
+        self.max_connection_lifetime = Some(value);
+        self
+    }
+
+    /// Set the number of idle connections the pool keeps open.
+    ///
+    /// The pool opens connections in background, starting when the client
+    /// is created, until this number is reached (but never more than
+    /// [`max_concurrency`](Builder::max_concurrency)).
+    /// The default is zero, which means connections are opened only on
+    /// demand.
+    pub fn min_idle(&mut self, value: usize) -> &mut Self {
+        self.min_idle = Some(value);
+        self
+    }
+
     /// Build connection and pool configuration in constrained mode
     ///
     /// Normal [`Builder::build_env()`], reads environment variables and files
@@ -997,6 +1052,10 @@ impl Builder {
             tcp_keepalive: self.tcp_keepalive.unwrap_or_default().as_keepalive(),
             // Pool configuration
             max_concurrency: self.max_concurrency,
+            query_cache_size: self.query_cache_size.unwrap_or(DEFAULT_QUERY_CACHE_SIZE),
+            max_idle_time: self.max_idle_time,
+            max_connection_lifetime: self.max_connection_lifetime,
+            min_idle: self.min_idle.unwrap_or(0),
 
             client_security: self.client_security.unwrap_or_default(),
             tls_security: self
@@ -1004,6 +1063,7 @@ impl Builder {
                 .or_else(|| creds.map(|c| c.tls_security))
                 .unwrap_or_default(),
             cert_check: None,
+            instrumentation: Default::default(),
         };
 
         Ok(Config(Arc::new(cfg)))
@@ -1481,7 +1541,12 @@ impl Builder {
             tcp_keepalive: self.tcp_keepalive.unwrap_or_default().as_keepalive(),
             // Pool configuration
             max_concurrency: self.max_concurrency,
+            query_cache_size: self.query_cache_size.unwrap_or(DEFAULT_QUERY_CACHE_SIZE),
+            max_idle_time: self.max_idle_time,
+            max_connection_lifetime: self.max_connection_lifetime,
+            min_idle: self.min_idle.unwrap_or(0),
             cert_check: None,
+            instrumentation: Default::default(),
         };
 
         cfg.cloud_profile = self
@@ -1898,6 +1963,26 @@ impl Config {
         Ok(self)
     }
 
+    /// Return the same config with an observer receiving client events
+    ///
+    /// See [`Observer`] for the list of events.
+    pub fn with_observer(mut self, observer: Arc<dyn Observer>) -> Config {
+        Arc::make_mut(&mut self.0).instrumentation.observer = observer;
+        self
+    }
+
+    /// Return the same config with query text included or redacted in
+    /// tracing spans
+    ///
+    /// By default, spans emitted for each query contain the query text.
+    /// Disable this if queries may contain sensitive data inlined as
+    /// literals rather than passed as arguments.
+    #[cfg(feature = "tracing")]
+    pub fn with_query_text_in_traces(mut self, value: bool) -> Config {
+        Arc::make_mut(&mut self.0).instrumentation.trace_query_text = value;
+        self
+    }
+
     /// Return the same config with changed certificate check
     #[cfg(any(feature = "unstable", test))]
     pub fn with_cert_check(mut self, cert_check: CertCheck) -> Config {
@@ -2008,6 +2093,10 @@ impl fmt::Debug for Config {
         f.debug_struct("Config")
             .field("address", &self.0.address)
             .field("max_concurrency", &self.0.max_concurrency)
+            .field("query_cache_size", &self.0.query_cache_size)
+            .field("max_idle_time", &self.0.max_idle_time)
+            .field("max_connection_lifetime", &self.0.max_connection_lifetime)
+            .field("min_idle", &self.0.min_idle)
             // TODO(tailhook) more fields
             .finish()
     }
@@ -1,19 +1,25 @@
 use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
-use gel_protocol::common::{Capabilities, Cardinality, IoFormat};
+use futures_util::Stream;
+use gel_protocol::common::{Capabilities, Cardinality, InputLanguage, IoFormat};
 use gel_protocol::model::Json;
 use gel_protocol::query_arg::QueryArgs;
 use gel_protocol::QueryResult;
 use tokio::time::sleep;
 
 use crate::builder::Config;
+use crate::errors::ClientQueryTimeoutError;
 use crate::errors::InvalidArgumentError;
 use crate::errors::NoDataError;
+use crate::errors::ResultCardinalityMismatchError;
+use crate::errors::SessionStateLostError;
 use crate::errors::{Error, ErrorKind, SHOULD_RETRY};
 use crate::options::{RetryOptions, TransactionOptions};
-use crate::raw::{Options, PoolState, Response};
-use crate::raw::{Pool, QueryCapabilities};
+use crate::raw::{Options, PoolConnection, PoolState, Response};
+use crate::raw::{Pool, QueryCacheStats, QueryCapabilities};
+use crate::session::Session;
 use crate::state::{AliasesDelta, ConfigDelta, GlobalsDelta};
 use crate::state::{AliasesModifier, ConfigModifier, Fn, GlobalsModifier};
 use crate::transaction;
@@ -58,11 +64,30 @@ impl Client {
         Ok(())
     }
 
-    /// Query with retry.
+    /// Close all connections of the pool.
+    ///
+    /// Idle connections are terminated gracefully right away. Connections
+    /// that are currently in use are terminated when they are released, this
+    /// method waits for that. After this call all queries on this client (and
+    /// its clones) fail with [`ClientClosedError`](crate::errors::ClientClosedError).
+    pub async fn close(&self) {
+        self.pool.close().await
+    }
+
+    /// Returns hit and miss counters of the query descriptor cache.
+    ///
+    /// Counters are accumulated over all connections of this client's pool.
+    /// See [`Builder::query_cache_size`](crate::Builder::query_cache_size).
+    pub fn query_cache_stats(&self) -> QueryCacheStats {
+        self.pool.query_cache_stats()
+    }
+
+    /// Query with retry and timeout.
     async fn query_helper<R, A>(
         &self,
         query: impl AsRef<str>,
         arguments: &A,
+        input_language: InputLanguage,
         io_format: IoFormat,
         cardinality: Cardinality,
     ) -> Result<Response<Vec<R>>, Error>
@@ -70,26 +95,58 @@ impl Client {
         A: QueryArgs,
         R: QueryResult,
     {
-        let mut iteration = 0;
-        loop {
-            let mut conn = self.pool.acquire().await?;
-
-            let conn = conn.inner();
-            let state = &self.options.state;
-            let caps = Capabilities::MODIFICATIONS | Capabilities::DDL;
-            match conn
-                .query(
-                    query.as_ref(),
+        let query = self._query_helper(query, arguments, input_language, io_format, cardinality);
+        with_query_timeout(self.options.query_timeout, query).await
+    }
+
+    async fn _query_helper<R, A>(
+        &self,
+        query: impl AsRef<str>,
+        arguments: &A,
+        input_language: InputLanguage,
+        io_format: IoFormat,
+        cardinality: Cardinality,
+    ) -> Result<Response<Vec<R>>, Error>
+    where
+        A: QueryArgs,
+        R: QueryResult,
+    {
+        let query = query.as_ref();
+        let state = &self.options.state;
+        let annotations = &self.options.annotations;
+        let caps = Capabilities::MODIFICATIONS | Capabilities::DDL;
+        self.retry(|mut conn| async move {
+            let response = conn
+                .inner()
+                .query_with_language(
+                    query,
                     arguments,
                     state,
-                    &self.options.annotations,
+                    annotations,
                     caps,
+                    input_language,
                     io_format,
                     cardinality,
                 )
-                .await
-            {
-                Ok(resp) => return Ok(resp),
+                .await?;
+            check_state_unchanged(response)
+        })
+        .await
+    }
+
+    /// Runs `attempt` on a connection from the pool, retrying it with the
+    /// backoff of the retry options while it fails with a transient error
+    /// and no changes have been made by the query.
+    async fn retry<T, F, Fut>(&self, mut attempt: F) -> Result<T, Error>
+    where
+        F: FnMut(PoolConnection) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let mut iteration = 0;
+        loop {
+            let conn = self.pool.acquire().await?;
+            match attempt(conn).await {
+                Ok(result) => return Ok(result),
                 Err(e) => {
                     let allow_retry = match e.get::<QueryCapabilities>() {
                         // Error from a weird source, or just a bug
@@ -104,6 +161,7 @@ impl Client {
                         if iteration < rule.attempts {
                             let duration = (rule.backoff)(iteration);
                             log::info!("Error: {:#}. Retrying in {:?}...", e, duration);
+                            self.pool.observer().retry(iteration, duration, &e);
                             sleep(duration).await;
                             continue;
                         }
@@ -134,9 +192,16 @@ impl Client {
         A: QueryArgs,
         R: QueryResult,
     {
-        Client::query_helper(self, query, arguments, IoFormat::Binary, Cardinality::Many)
-            .await
-            .map(|Response { data, warnings, .. }| ResultVerbose { data, warnings })
+        Client::query_helper(
+            self,
+            query,
+            arguments,
+            InputLanguage::EdgeQL,
+            IoFormat::Binary,
+            Cardinality::Many,
+        )
+        .await
+        .map(|Response { data, warnings, .. }| ResultVerbose { data, warnings })
     }
 
     /// Execute a query and return a collection of results.
@@ -163,9 +228,61 @@ impl Client {
         A: QueryArgs,
         R: QueryResult,
     {
-        Client::query_helper(self, query, arguments, IoFormat::Binary, Cardinality::Many)
-            .await
-            .map(|r| r.data)
+        Client::query_helper(
+            self,
+            query,
+            arguments,
+            InputLanguage::EdgeQL,
+            IoFormat::Binary,
+            Cardinality::Many,
+        )
+        .await
+        .map(|r| r.data)
+    }
+
+    /// Execute a query and return a stream of results.
+    ///
+    /// Unlike [`query`](Client::query), results are decoded as they arrive
+    /// from the server, so the whole result set is never buffered in memory.
+    ///
+    /// ```rust,ignore
+    /// let mut users = pool.query_stream::<User, _>("SELECT User { name }", &()).await?;
+    /// while let Some(user) = users.next().await {
+    ///     println!("{}", user?.name);
+    /// }
+    /// ```
+    ///
+    /// The stream holds a connection from the pool until it is exhausted or
+    /// dropped. Dropping the stream before it is exhausted closes the
+    /// connection instead of returning it to the pool.
+    ///
+    /// Errors that occur before the first result is received are retried
+    /// the same way as in [`query`](Client::query). Errors that occur
+    /// later are yielded as the last item of the stream.
+    ///
+    /// The [query timeout](Client::with_query_timeout) applies until the
+    /// first batch of results is received, reading the rest of the stream
+    /// is not limited.
+    pub async fn query_stream<R, A>(
+        &self,
+        query: impl AsRef<str>,
+        arguments: &A,
+    ) -> Result<impl Stream<Item = Result<R, Error>> + 'static, Error>
+    where
+        A: QueryArgs,
+        R: QueryResult + 'static,
+        R::State: Unpin,
+    {
+        let query = query.as_ref();
+        let state = &self.options.state;
+        let annotations = &self.options.annotations;
+        let caps = Capabilities::MODIFICATIONS | Capabilities::DDL;
+        let response = self.retry(|conn| async move {
+            conn.query_stream(query, arguments, state, annotations, caps)
+                .await
+        });
+        let response = with_query_timeout(self.options.query_timeout, response).await?;
+        Ok(response.into_stream_with(check_state_unchanged))
     }
 
     /// Execute a query and return a single result
@@ -197,6 +314,7 @@ impl Client {
             self,
             query,
             arguments,
+            InputLanguage::EdgeQL,
             IoFormat::Binary,
             Cardinality::AtMostOne,
         )
@@ -242,6 +360,7 @@ impl Client {
             self,
             query,
             arguments,
+            InputLanguage::EdgeQL,
             IoFormat::Binary,
             Cardinality::AtMostOne,
         )
@@ -261,7 +380,13 @@ impl Client {
         arguments: &impl QueryArgs,
     ) -> Result<Json, Error> {
         let res = self
-            .query_helper::<String, _>(query, arguments, IoFormat::Json, Cardinality::Many)
+            .query_helper::<String, _>(
+                query,
+                arguments,
+                InputLanguage::EdgeQL,
+                IoFormat::Json,
+                Cardinality::Many,
+            )
             .await?;
 
         let json = res
@@ -299,7 +424,13 @@ impl Client {
         arguments: &impl QueryArgs,
     ) -> Result<Option<Json>, Error> {
         let res = self
-            .query_helper::<String, _>(query, arguments, IoFormat::Json, Cardinality::AtMostOne)
+            .query_helper::<String, _>(
+                query,
+                arguments,
+                InputLanguage::EdgeQL,
+                IoFormat::Json,
+                Cardinality::AtMostOne,
+            )
             .await?;
 
         // we trust database to produce valid json
@@ -332,46 +463,119 @@ impl Client {
     where
         A: QueryArgs,
     {
-        let mut iteration = 0;
-        loop {
-            let mut conn = self.pool.acquire().await?;
-
-            let conn = conn.inner();
-            let state = &self.options.state;
-            let caps = Capabilities::MODIFICATIONS | Capabilities::DDL;
-            match conn
-                .execute(
-                    query.as_ref(),
-                    arguments,
-                    state,
-                    &self.options.annotations,
-                    caps,
-                )
-                .await
-            {
-                Ok(_) => return Ok(()),
-                Err(e) => {
-                    let allow_retry = match e.get::<QueryCapabilities>() {
-                        // Error from a weird source, or just a bug
-                        // Let's keep on the safe side
-                        None => false,
-                        Some(QueryCapabilities::Unparsed) => true,
-                        Some(QueryCapabilities::Parsed(c)) => c.is_empty(),
-                    };
-                    if allow_retry && e.has_tag(SHOULD_RETRY) {
-                        let rule = self.options.retry.get_rule(&e);
-                        iteration += 1;
-                        if iteration < rule.attempts {
-                            let duration = (rule.backoff)(iteration);
-                            log::info!("Error: {:#}. Retrying in {:?}...", e, duration);
-                            sleep(duration).await;
-                            continue;
-                        }
-                    }
-                    return Err(e);
-                }
-            }
-        }
+        self.execute_helper(query, arguments, InputLanguage::EdgeQL)
+            .await
+    }
+
+    /// Execute with retry and timeout.
+    async fn execute_helper<A>(
+        &self,
+        query: impl AsRef<str>,
+        arguments: &A,
+        input_language: InputLanguage,
+    ) -> Result<(), Error>
+    where
+        A: QueryArgs,
+    {
+        let query = self._execute_helper(query, arguments, input_language);
+        with_query_timeout(self.options.query_timeout, query).await
+    }
+
+    async fn _execute_helper<A>(
+        &self,
+        query: impl AsRef<str>,
+        arguments: &A,
+        input_language: InputLanguage,
+    ) -> Result<(), Error>
+    where
+        A: QueryArgs,
+    {
+        let query = query.as_ref();
+        let state = &self.options.state;
+        let annotations = &self.options.annotations;
+        let caps = Capabilities::MODIFICATIONS | Capabilities::DDL;
+        self.retry(|mut conn| async move {
+            let response = conn
+                .inner()
+                .execute_with_language(query, arguments, state, annotations, caps, input_language)
+                .await?;
+            check_state_unchanged(response)?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Execute an SQL query and return a collection of rows.
+    ///
+    /// Rows can be decoded either into [`Value`](gel_protocol::value::Value)
+    /// (as `Value::SQLRow`) or into a struct deriving
+    /// [`Queryable`](crate::Queryable), where columns are matched to fields
+    /// by name:
+    ///
+    /// ```rust,ignore
+    /// #[derive(Queryable)]
+    /// struct User {
+    ///     name: String,
+    /// }
+    /// let users: Vec<User> = pool.query_sql(
+    ///     r#"SELECT name FROM "User" WHERE name LIKE $1"#,
+    ///     &("A%",),
+    /// ).await?;
+    /// ```
+    ///
+    /// SQL queries require protocol version 3.0 or later. On older servers
+    /// an [`UnsupportedFeatureError`](crate::errors::UnsupportedFeatureError)
+    /// is returned.
+    pub async fn query_sql<R, A>(
+        &self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> Result<Vec<R>, Error>
+    where
+        A: QueryArgs,
+        R: QueryResult,
+    {
+        Client::query_helper(
+            self,
+            query,
+            arguments,
+            InputLanguage::SQL,
+            IoFormat::Binary,
+            Cardinality::Many,
+        )
+        .await
+        .map(|r| r.data)
+    }
+
+    /// Execute an SQL query and return a single row
+    ///
+    /// If the query returns more than one row, a
+    /// [`ResultCardinalityMismatchError`][crate::errors::ResultCardinalityMismatchError]
+    /// is raised.
+    ///
+    /// See [`query_sql`](Client::query_sql) for details.
+    pub async fn query_sql_single<R, A>(
+        &self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> Result<Option<R>, Error>
+    where
+        A: QueryArgs,
+        R: QueryResult + Send,
+    {
+        let rows = self.query_sql(query, arguments).await?;
+        single_row(rows)
+    }
+
+    /// Execute an SQL query and don't expect result
+    ///
+    /// See [`query_sql`](Client::query_sql) for details.
+    pub async fn execute_sql<A>(&self, query: impl AsRef<str>, arguments: &A) -> Result<(), Error>
+    where
+        A: QueryArgs,
+    {
+        self.execute_helper(query, arguments, InputLanguage::SQL)
+            .await
     }
 
     /// Execute a transaction and retry.
@@ -479,6 +683,25 @@ impl Client {
         crate::transaction::start(&self.pool, self.options.clone()).await
     }
 
+    /// Start a session pinned to a single connection of the pool
+    ///
+    /// The connection is held until the returned [`Session`] is dropped.
+    /// Queries in the session can change the session state, e.g.:
+    ///
+    /// ```rust,no_run
+    /// # async fn main_() -> Result<(), gel_tokio::Error> {
+    /// let conn = gel_tokio::create_client().await?;
+    /// let mut session = conn.session().await?;
+    /// session.execute("SET MODULE inventory", &()).await?;
+    /// let count: i64 = session.query_required_single("SELECT count(Item)", &()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn session(&self) -> Result<Session, Error> {
+        let conn = self.pool.acquire().await?;
+        Ok(Session::new(self.options.clone(), conn))
+    }
+
     /// Returns client with adjusted options for future transactions.
     ///
     /// This method returns a "shallow copy" of the current client
@@ -495,6 +718,7 @@ impl Client {
                 retry: self.options.retry.clone(),
                 state: self.options.state.clone(),
                 annotations: self.options.annotations.clone(),
+                query_timeout: self.options.query_timeout,
             }),
             pool: self.pool.clone(),
         }
@@ -514,6 +738,53 @@ impl Client {
                 retry: options,
                 state: self.options.state.clone(),
                 annotations: self.options.annotations.clone(),
+                query_timeout: self.options.query_timeout,
+            }),
+            pool: self.pool.clone(),
+        }
+    }
+
+    /// Returns client with the specified query timeout.
+    ///
+    /// This method returns a "shallow copy" of the current client
+    /// with modified query timeout.
+    ///
+    /// Both ``self`` and returned client can be used after, but when using
+    /// them query timeout applied will be different.
+    ///
+    /// The timeout bounds the total time of each `query*` and `execute*`
+    /// call, including waiting for a connection and retries. Queries in
+    /// transactions started from the returned client are bounded
+    /// individually. Streaming queries are bounded until their first batch
+    /// of results is received.
+    ///
+    /// When the timeout expires, the call fails with
+    /// [`ClientQueryTimeoutError`](crate::errors::ClientQueryTimeoutError)
+    /// and the connection running the query is closed, which makes the
+    /// server cancel the query. A timed out query aborts the transaction
+    /// it runs in.
+    ///
+    /// Since the copy is cheap, the method can also be used to set a
+    /// timeout for a single call:
+    ///
+    /// ```rust,no_run
+    /// # use std::time::Duration;
+    /// # async fn run(client: gel_tokio::Client) -> Result<(), gel_tokio::Error> {
+    /// let count = client
+    ///     .with_query_timeout(Duration::from_secs(5))
+    ///     .query_required_single::<i64, _>("SELECT count(User)", &())
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_query_timeout(&self, timeout: Duration) -> Self {
+        Client {
+            options: Arc::new(Options {
+                transaction: self.options.transaction.clone(),
+                retry: self.options.retry.clone(),
+                state: self.options.state.clone(),
+                annotations: self.options.annotations.clone(),
+                query_timeout: Some(timeout),
             }),
             pool: self.pool.clone(),
         }
@@ -526,6 +797,7 @@ impl Client {
                 retry: self.options.retry.clone(),
                 state: Arc::new(f(&self.options.state)),
                 annotations: self.options.annotations.clone(),
+                query_timeout: self.options.query_timeout,
             }),
             pool: self.pool.clone(),
         }
@@ -668,8 +940,57 @@ impl Client {
                 retry: self.options.retry.clone(),
                 state: self.options.state.clone(),
                 annotations,
+                query_timeout: self.options.query_timeout,
             }),
             pool: self.pool.clone(),
         })
     }
 }
+
+/// Runs the query future, failing with [`ClientQueryTimeoutError`] if it
+/// doesn't complete within `timeout`
+///
+/// Dropping the future leaves the connection in the middle of a request, so
+/// it is closed instead of being returned to the pool.
+pub(crate) async fn with_query_timeout<T>(
+    timeout: Option<Duration>,
+    query: impl Future<Output = Result<T, Error>>,
+) -> Result<T, Error> {
+    let Some(duration) = timeout else {
+        return query.await;
+    };
+    match tokio::time::timeout(duration, query).await {
+        Ok(result) => result,
+        Err(_) => Err(ClientQueryTimeoutError::with_message(format!(
+            "query did not complete within {duration:?}"
+        ))),
+    }
+}
+
+/// Fails if the query has changed the session state
+///
+/// Pooled connections receive the state of the client with every query, so
+/// the change would be silently lost. Queries changing the state are
+/// normally rejected by the server, as the client doesn't allow the
+/// `SESSION_CONFIG` capability. Note that the query has been executed (and
+/// maybe committed) anyway.
+fn check_state_unchanged<T>(response: Response<T>) -> Result<Response<T>, Error> {
+    if response.new_state.is_some() {
+        return Err(SessionStateLostError::with_message(
+            "query has changed the session state, which is not retained by a \
+            pooled client; use `with_default_module`, `with_config`, \
+            `with_globals` or run the query in a transaction",
+        ));
+    }
+    Ok(response)
+}
+
+pub(crate) fn single_row<R>(rows: Vec<R>) -> Result<Option<R>, Error> {
+    if rows.len() > 1 {
+        return Err(ResultCardinalityMismatchError::with_message(format!(
+            "the query returned {} rows, at most one was expected",
+            rows.len()
+        )));
+    }
+    Ok(rows.into_iter().next())
+}
@@ -134,24 +134,30 @@ unstable_pub_mods! {
 
 mod client;
 mod errors;
+mod metrics;
 mod options;
+mod pinned;
 mod query_executor;
 mod sealed;
+mod session;
 pub mod state;
 mod tls;
 mod transaction;
 pub mod tutorial;
 
-pub use gel_derive::{ConfigDelta, GlobalsDelta, Queryable};
+pub use gel_derive::{ConfigDelta, GlobalsDelta, QueryArgs, Queryable};
 
 pub use builder::{Builder, ClientSecurity, Config, InstanceName, TcpKeepalive};
 pub use client::Client;
 pub use credentials::TlsSecurity;
 pub use errors::Error;
-pub use options::{RetryCondition, RetryOptions, TransactionOptions};
+pub use metrics::{Observer, QueryInfo};
+pub use options::{IsolationLevel, RetryCondition, RetryOptions, TransactionOptions};
 pub use query_executor::{QueryExecutor, ResultVerbose};
+pub use raw::QueryCacheStats;
+pub use session::Session;
 pub use state::{ConfigDelta, GlobalsDelta};
-pub use transaction::{RetryingTransaction, Transaction};
+pub use transaction::{Batch, RetryingTransaction, Savepoint, Transaction};
 
 /// The ordered list of project filenames supported.
 pub const PROJECT_FILES: &[&str] = &["gel.toml", "edgedb.toml"];
@@ -0,0 +1,121 @@
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use gel_protocol::annotations::Warning;
+use gel_protocol::encoding::Annotations;
+use tokio::time::Instant;
+
+use crate::errors::Error;
+
+/// Information about a query passed to [`Observer`] methods
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct QueryInfo<'a> {
+    /// Text of the query
+    pub query: &'a str,
+    /// Query tag, see [`Client::with_tag`](crate::Client::with_tag)
+    pub tag: Option<&'a str>,
+}
+
+/// Receives events about the activity of a [`Client`](crate::Client)
+///
+/// Installed using [`Config::with_observer`](crate::Config::with_observer).
+/// All methods have empty default implementations, so only events of
+/// interest have to be handled.
+///
+/// Methods are called synchronously from the tasks executing queries, so
+/// they should be cheap and must not block (e.g. update counters or
+/// histograms of a metrics library).
+pub trait Observer: Send + Sync + 'static {
+    /// A new connection to the server has been established
+    fn connection_opened(&self) {}
+    /// A connection has been closed (or dropped because it is broken)
+    fn connection_closed(&self) {}
+    /// A connection has been acquired from the pool
+    ///
+    /// `wait` is the time spent waiting for a free slot in the pool,
+    /// including establishing a new connection if needed.
+    fn connection_acquired(&self, _wait: Duration) {}
+    /// A query is about to be sent to the server
+    fn query_started(&self, _query: &QueryInfo) {}
+    /// A query has completed, either successfully or with an `error`
+    ///
+    /// For [`query_stream`](crate::Client::query_stream) this is called when
+    /// the first results arrive. A batch of statements is reported as a
+    /// single query with the statements separated by `;`.
+    fn query_finished(&self, _query: &QueryInfo, _duration: Duration, _error: Option<&Error>) {}
+    /// A query or transaction is going to be retried after `delay`
+    ///
+    /// `attempt` is the number of the next attempt, starting from `1` for
+    /// the first retry.
+    fn retry(&self, _attempt: u32, _delay: Duration, _error: &Error) {}
+    /// A transaction has been committed
+    fn transaction_committed(&self) {}
+    /// A transaction has been rolled back
+    fn transaction_rolled_back(&self) {}
+    /// The server has sent a warning for a query
+    fn warning(&self, _warning: &Warning) {}
+}
+
+struct NoopObserver;
+
+impl Observer for NoopObserver {}
+
+impl fmt::Debug for dyn Observer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(observer)")
+    }
+}
+
+/// Observer and tracing settings shared by the config and connections
+#[derive(Debug, Clone)]
+pub(crate) struct Instrumentation {
+    pub observer: Arc<dyn Observer>,
+    #[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+    pub trace_query_text: bool,
+}
+
+impl Default for Instrumentation {
+    fn default() -> Instrumentation {
+        Instrumentation {
+            observer: Arc::new(NoopObserver),
+            trace_query_text: true,
+        }
+    }
+}
+
+impl Instrumentation {
+    /// Reports start and finish of the query executed by `fut` and, if
+    /// `tracing` feature is enabled, runs it within a span
+    pub async fn query<T>(
+        &self,
+        query: &str,
+        annotations: &Annotations,
+        fut: impl Future<Output = Result<T, Error>>,
+    ) -> Result<T, Error> {
+        let info = QueryInfo {
+            query,
+            tag: annotations.get("tag").map(|s| s.as_str()),
+        };
+        self.observer.query_started(&info);
+        let start = Instant::now();
+        #[cfg(feature = "tracing")]
+        let result = {
+            use tracing::Instrument;
+
+            let span =
+                tracing::info_span!("gel_query", query = tracing::field::Empty, tag = info.tag);
+            if self.trace_query_text {
+                span.record("query", info.query);
+            }
+            fut.instrument(span).await
+        };
+        #[cfg(not(feature = "tracing"))]
+        let result = fut.await;
+        self.observer
+            .query_finished(&info, start.elapsed(), result.as_ref().err());
+        result
+    }
+}
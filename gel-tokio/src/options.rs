@@ -6,7 +6,10 @@ use std::time::Duration;
 use once_cell::sync::Lazy;
 use rand::{rng, Rng};
 
-use crate::errors::{Error, IdleSessionTimeoutError};
+use gel_protocol::features::ProtocolVersion;
+
+use crate::errors::UnsupportedFeatureError;
+use crate::errors::{Error, ErrorKind, IdleSessionTimeoutError};
 
 /// Single immediate retry on idle is fine
 ///
@@ -28,12 +31,30 @@ pub enum RetryCondition {
     NetworkError,
 }
 
+/// Isolation level of a transaction
+///
+/// See [`TransactionOptions::isolation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum IsolationLevel {
+    /// Transaction behaves as if all transactions were executed serially
+    ///
+    /// This is the default, and the only level supported by all servers.
+    #[default]
+    Serializable,
+    /// Transaction sees a snapshot of data taken at its first statement
+    ///
+    /// Requires protocol version 3.0 or later.
+    RepeatableRead,
+}
+
 /// Options for [`transaction()`](crate::Client::transaction)
 ///
 /// Must be set on a [`Client`](crate::Client) via
 /// [`with_transaction_options`](crate::Client::with_transaction_options).
 #[derive(Debug, Clone, Default)]
 pub struct TransactionOptions {
+    isolation: IsolationLevel,
     read_only: bool,
     deferrable: bool,
 }
@@ -68,6 +89,38 @@ impl TransactionOptions {
         self.deferrable = deferrable;
         self
     }
+    /// Set transaction isolation level
+    pub fn isolation(mut self, isolation: IsolationLevel) -> Self {
+        self.isolation = isolation;
+        self
+    }
+    /// Build the statement that starts a transaction with these options
+    pub(crate) fn start_statement(&self, proto: &ProtocolVersion) -> Result<String, Error> {
+        let isolation = match self.isolation {
+            IsolationLevel::Serializable => "SERIALIZABLE",
+            IsolationLevel::RepeatableRead if proto.is_at_least(3, 0) => "REPEATABLE READ",
+            IsolationLevel::RepeatableRead => {
+                return Err(UnsupportedFeatureError::with_message(format!(
+                    "REPEATABLE READ isolation is not supported by protocol {}.{}",
+                    proto.version_tuple().0,
+                    proto.version_tuple().1,
+                )));
+            }
+        };
+        let read_only = if self.read_only {
+            "READ ONLY"
+        } else {
+            "READ WRITE"
+        };
+        let deferrable = if self.deferrable {
+            "DEFERRABLE"
+        } else {
+            "NOT DEFERRABLE"
+        };
+        Ok(format!(
+            "START TRANSACTION ISOLATION {isolation}, {read_only}, {deferrable}"
+        ))
+    }
 }
 
 impl Default for RetryRule {
@@ -199,3 +252,28 @@ impl fmt::Debug for RetryRule {
             .finish()
     }
 }
+
+#[test]
+fn start_statement() {
+    let proto = ProtocolVersion::new(3, 0);
+    assert_eq!(
+        TransactionOptions::default()
+            .start_statement(&proto)
+            .unwrap(),
+        "START TRANSACTION ISOLATION SERIALIZABLE, READ WRITE, NOT DEFERRABLE"
+    );
+    assert_eq!(
+        TransactionOptions::default()
+            .isolation(IsolationLevel::RepeatableRead)
+            .read_only(true)
+            .deferrable(true)
+            .start_statement(&proto)
+            .unwrap(),
+        "START TRANSACTION ISOLATION REPEATABLE READ, READ ONLY, DEFERRABLE"
+    );
+    let err = TransactionOptions::default()
+        .isolation(IsolationLevel::RepeatableRead)
+        .start_statement(&ProtocolVersion::new(2, 0))
+        .unwrap_err();
+    assert!(err.is::<UnsupportedFeatureError>());
+}
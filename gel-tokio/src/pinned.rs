@@ -0,0 +1,82 @@
+//! Query helpers shared by [`Transaction`](crate::Transaction) and
+//! [`Session`](crate::Session), which run all their queries on a single
+//! connection and keep the session state returned by the database
+
+use std::sync::Arc;
+
+use gel_protocol::common::{Capabilities, Cardinality, InputLanguage, IoFormat};
+use gel_protocol::query_arg::QueryArgs;
+use gel_protocol::QueryResult;
+
+use crate::client::with_query_timeout;
+use crate::errors::Error;
+use crate::raw::{Options, PoolConnection, PoolState, Response};
+
+/// Executes a query and stores the new session state in `options`
+pub(crate) async fn query<R, A>(
+    conn: &mut PoolConnection,
+    options: &mut Arc<Options>,
+    query: &str,
+    arguments: &A,
+    allow_capabilities: Capabilities,
+    input_language: InputLanguage,
+    io_format: IoFormat,
+    cardinality: Cardinality,
+) -> Result<Response<Vec<R>>, Error>
+where
+    A: QueryArgs,
+    R: QueryResult,
+{
+    let fut = conn.inner().query_with_language(
+        query,
+        arguments,
+        &options.state,
+        &options.annotations,
+        allow_capabilities,
+        input_language,
+        io_format,
+        cardinality,
+    );
+    let response = with_query_timeout(options.query_timeout, fut).await?;
+    update_state(conn, options, &response)?;
+    Ok(response)
+}
+
+/// Executes a query without results and stores the new session state in
+/// `options`
+pub(crate) async fn execute<A>(
+    conn: &mut PoolConnection,
+    options: &mut Arc<Options>,
+    query: &str,
+    arguments: &A,
+    allow_capabilities: Capabilities,
+    input_language: InputLanguage,
+) -> Result<Response<()>, Error>
+where
+    A: QueryArgs,
+{
+    let fut = conn.inner().execute_with_language(
+        query,
+        arguments,
+        &options.state,
+        &options.annotations,
+        allow_capabilities,
+        input_language,
+    );
+    let response = with_query_timeout(options.query_timeout, fut).await?;
+    update_state(conn, options, &response)?;
+    Ok(response)
+}
+
+fn update_state<T>(
+    conn: &PoolConnection,
+    options: &mut Arc<Options>,
+    response: &Response<T>,
+) -> Result<(), Error> {
+    if let Some(state) = &response.new_state {
+        let desc = conn.inner_ref().state_descriptor();
+        let state = PoolState::decode(desc, state)?;
+        Arc::make_mut(options).state = Arc::new(state);
+    }
+    Ok(())
+}
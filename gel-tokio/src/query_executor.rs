@@ -3,7 +3,7 @@ use gel_protocol::QueryResult;
 use gel_protocol::{annotations::Warning, model::Json};
 use std::future::Future;
 
-use crate::{Client, Error, Transaction};
+use crate::{Client, Error, Session, Transaction};
 
 /// Query result with additional metadata.
 #[non_exhaustive]
@@ -17,7 +17,7 @@ pub struct ResultVerbose<R> {
 }
 
 /// Abstracts over different query executors
-/// In particular &Client and &mut Transaction
+/// In particular &Client, &mut Transaction and &mut Session
 pub trait QueryExecutor: Sized {
     /// see [Client::query]
     fn query<R, A>(
@@ -88,6 +88,35 @@ pub trait QueryExecutor: Sized {
     ) -> impl Future<Output = Result<(), Error>> + Send
     where
         A: QueryArgs;
+
+    /// see [Client::query_sql]
+    fn query_sql<R, A>(
+        self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> impl Future<Output = Result<Vec<R>, Error>> + Send
+    where
+        A: QueryArgs,
+        R: QueryResult + Send;
+
+    /// see [Client::query_sql_single]
+    fn query_sql_single<R, A>(
+        self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> impl Future<Output = Result<Option<R>, Error>> + Send
+    where
+        A: QueryArgs,
+        R: QueryResult + Send;
+
+    /// see [Client::execute_sql]
+    fn execute_sql<A>(
+        self,
+        query: &str,
+        arguments: &A,
+    ) -> impl Future<Output = Result<(), Error>> + Send
+    where
+        A: QueryArgs;
 }
 
 impl QueryExecutor for &Client {
@@ -169,6 +198,37 @@ impl QueryExecutor for &Client {
     {
         Client::execute(self, query, arguments)
     }
+
+    fn query_sql<R, A>(
+        self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> impl Future<Output = Result<Vec<R>, Error>>
+    where
+        A: QueryArgs,
+        R: QueryResult + Send,
+    {
+        Client::query_sql(self, query, arguments)
+    }
+
+    fn query_sql_single<R, A>(
+        self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> impl Future<Output = Result<Option<R>, Error>>
+    where
+        A: QueryArgs,
+        R: QueryResult + Send,
+    {
+        Client::query_sql_single(self, query, arguments)
+    }
+
+    fn execute_sql<A>(self, query: &str, arguments: &A) -> impl Future<Output = Result<(), Error>>
+    where
+        A: QueryArgs,
+    {
+        Client::execute_sql(self, query, arguments)
+    }
 }
 
 impl<T: std::ops::DerefMut<Target = Transaction>> QueryExecutor for &mut T {
@@ -250,4 +310,147 @@ impl<T: std::ops::DerefMut<Target = Transaction>> QueryExecutor for &mut T {
     {
         Transaction::execute(self, query, arguments)
     }
+
+    fn query_sql<R, A>(
+        self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> impl Future<Output = Result<Vec<R>, Error>>
+    where
+        A: QueryArgs,
+        R: QueryResult + Send,
+    {
+        Transaction::query_sql(self, query, arguments)
+    }
+
+    fn query_sql_single<R, A>(
+        self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> impl Future<Output = Result<Option<R>, Error>>
+    where
+        A: QueryArgs,
+        R: QueryResult + Send,
+    {
+        Transaction::query_sql_single(self, query, arguments)
+    }
+
+    fn execute_sql<A>(self, query: &str, arguments: &A) -> impl Future<Output = Result<(), Error>>
+    where
+        A: QueryArgs,
+    {
+        Transaction::execute_sql(self, query, arguments)
+    }
+}
+
+impl QueryExecutor for &mut Session {
+    fn query<R, A>(
+        self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> impl Future<Output = Result<Vec<R>, Error>>
+    where
+        A: QueryArgs,
+        R: QueryResult,
+    {
+        Session::query(self, query, arguments)
+    }
+
+    fn query_verbose<R, A>(
+        self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> impl Future<Output = Result<ResultVerbose<Vec<R>>, Error>> + Send
+    where
+        A: QueryArgs,
+        R: QueryResult + Send,
+    {
+        Session::query_verbose(self, query, arguments)
+    }
+
+    fn query_single<R, A>(
+        self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> impl Future<Output = Result<Option<R>, Error>>
+    where
+        A: QueryArgs,
+        R: QueryResult + Send,
+    {
+        Session::query_single(self, query, arguments)
+    }
+
+    fn query_required_single<R, A>(
+        self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> impl Future<Output = Result<R, Error>>
+    where
+        A: QueryArgs,
+        R: QueryResult + Send,
+    {
+        Session::query_required_single(self, query, arguments)
+    }
+
+    fn query_json(
+        self,
+        query: &str,
+        arguments: &impl QueryArgs,
+    ) -> impl Future<Output = Result<Json, Error>> {
+        Session::query_json(self, query, arguments)
+    }
+
+    fn query_single_json(
+        self,
+        query: &str,
+        arguments: &impl QueryArgs,
+    ) -> impl Future<Output = Result<Option<Json>, Error>> {
+        Session::query_single_json(self, query, arguments)
+    }
+
+    fn query_required_single_json(
+        self,
+        query: &str,
+        arguments: &impl QueryArgs,
+    ) -> impl Future<Output = Result<Json, Error>> {
+        Session::query_required_single_json(self, query, arguments)
+    }
+
+    fn execute<A>(self, query: &str, arguments: &A) -> impl Future<Output = Result<(), Error>>
+    where
+        A: QueryArgs,
+    {
+        Session::execute(self, query, arguments)
+    }
+
+    fn query_sql<R, A>(
+        self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> impl Future<Output = Result<Vec<R>, Error>>
+    where
+        A: QueryArgs,
+        R: QueryResult + Send,
+    {
+        Session::query_sql(self, query, arguments)
+    }
+
+    fn query_sql_single<R, A>(
+        self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> impl Future<Output = Result<Option<R>, Error>>
+    where
+        A: QueryArgs,
+        R: QueryResult + Send,
+    {
+        Session::query_sql_single(self, query, arguments)
+    }
+
+    fn execute_sql<A>(self, query: &str, arguments: &A) -> impl Future<Output = Result<(), Error>>
+    where
+        A: QueryArgs,
+    {
+        Session::execute_sql(self, query, arguments)
+    }
 }
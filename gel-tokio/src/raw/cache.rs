@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use gel_protocol::common::CompilationOptions;
+use gel_protocol::model::Uuid;
+use gel_protocol::server_message::CommandDataDescription1;
+
+/// Statistics of the per-connection query descriptor cache
+///
+/// Returned by [`Client::query_cache_stats`](crate::Client::query_cache_stats).
+/// Counters are accumulated over all connections of the pool.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryCacheStats {
+    /// Number of queries executed without a `Parse` round trip
+    pub hits: u64,
+    /// Number of queries that had to be parsed first
+    pub misses: u64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CacheKey {
+    query: String,
+    io_format: u8,
+    cardinality: u8,
+    input_language: u8,
+    capabilities: u64,
+    implicit_limit: Option<u64>,
+    implicit_typenames: bool,
+    implicit_typeids: bool,
+    explicit_objectids: bool,
+    state_desc_id: Uuid,
+}
+
+/// Marks the end of the recency list
+const NIL: usize = usize::MAX;
+
+#[derive(Debug)]
+struct Entry {
+    key: CacheKey,
+    desc: CommandDataDescription1,
+    /// More recently used neighbour
+    prev: usize,
+    /// Less recently used neighbour
+    next: usize,
+}
+
+/// LRU cache of command descriptors, keyed by query text, compilation
+/// options and the state descriptor the query was parsed with.
+///
+/// Entries live in a slab and are threaded on a doubly linked list in
+/// order of use, so lookups, insertions and evictions are all O(1).
+#[derive(Debug)]
+pub(crate) struct QueryCache {
+    capacity: usize,
+    index: HashMap<CacheKey, usize>,
+    slots: Vec<Entry>,
+    free: Vec<usize>,
+    /// Most recently used entry
+    head: usize,
+    /// Least recently used entry
+    tail: usize,
+    counters: Arc<CacheCounters>,
+}
+
+impl CacheCounters {
+    pub fn stats(&self) -> QueryCacheStats {
+        QueryCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl CacheKey {
+    pub fn new(flags: &CompilationOptions, query: &str, state_desc_id: Uuid) -> CacheKey {
+        CacheKey {
+            query: query.into(),
+            io_format: flags.io_format as u8,
+            cardinality: flags.expected_cardinality as u8,
+            input_language: flags.input_language as u8,
+            capabilities: flags.allow_capabilities.bits(),
+            implicit_limit: flags.implicit_limit,
+            implicit_typenames: flags.implicit_typenames,
+            implicit_typeids: flags.implicit_typeids,
+            explicit_objectids: flags.explicit_objectids,
+            state_desc_id,
+        }
+    }
+}
+
+impl QueryCache {
+    pub fn new(capacity: usize) -> QueryCache {
+        QueryCache {
+            capacity,
+            index: HashMap::new(),
+            slots: Vec::new(),
+            free: Vec::new(),
+            head: NIL,
+            tail: NIL,
+            counters: Default::default(),
+        }
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+    pub fn set_counters(&mut self, counters: Arc<CacheCounters>) {
+        self.counters = counters;
+    }
+    pub fn get(&mut self, key: &CacheKey) -> Option<CommandDataDescription1> {
+        match self.index.get(key) {
+            Some(&slot) => {
+                self.unlink(slot);
+                self.push_front(slot);
+                self.counters.hits.fetch_add(1, Ordering::Relaxed);
+                Some(self.slots[slot].desc.clone())
+            }
+            None => {
+                self.counters.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+    pub fn insert(&mut self, key: CacheKey, desc: CommandDataDescription1) {
+        if !self.is_enabled() {
+            return;
+        }
+        if let Some(&slot) = self.index.get(&key) {
+            self.slots[slot].desc = desc;
+            self.unlink(slot);
+            self.push_front(slot);
+            return;
+        }
+        let entry = Entry {
+            key: key.clone(),
+            desc,
+            prev: NIL,
+            next: NIL,
+        };
+        let slot = if self.index.len() >= self.capacity {
+            // reuse the slot of the least recently used entry
+            let slot = self.tail;
+            self.unlink(slot);
+            self.index.remove(&self.slots[slot].key);
+            self.slots[slot] = entry;
+            slot
+        } else if let Some(slot) = self.free.pop() {
+            self.slots[slot] = entry;
+            slot
+        } else {
+            self.slots.push(entry);
+            self.slots.len() - 1
+        };
+        self.index.insert(key, slot);
+        self.push_front(slot);
+    }
+    pub fn remove(&mut self, key: &CacheKey) {
+        if let Some(slot) = self.index.remove(key) {
+            self.unlink(slot);
+            self.free.push(slot);
+        }
+    }
+    fn unlink(&mut self, slot: usize) {
+        let Entry { prev, next, .. } = self.slots[slot];
+        match prev {
+            NIL => self.head = next,
+            prev => self.slots[prev].next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            next => self.slots[next].prev = prev,
+        }
+    }
+    fn push_front(&mut self, slot: usize) {
+        self.slots[slot].prev = NIL;
+        self.slots[slot].next = self.head;
+        match self.head {
+            NIL => self.tail = slot,
+            head => self.slots[head].prev = slot,
+        }
+        self.head = slot;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use gel_protocol::common::{Capabilities, Cardinality, CompilationOptions};
+    use gel_protocol::common::{InputLanguage, IoFormat, RawTypedesc};
+    use gel_protocol::model::Uuid;
+    use gel_protocol::server_message::CommandDataDescription1;
+
+    use super::{CacheKey, QueryCache, QueryCacheStats};
+
+    fn key(query: &str) -> CacheKey {
+        let flags = CompilationOptions {
+            implicit_limit: None,
+            implicit_typenames: false,
+            implicit_typeids: false,
+            explicit_objectids: true,
+            allow_capabilities: Capabilities::MODIFICATIONS,
+            io_format: IoFormat::Binary,
+            expected_cardinality: Cardinality::Many,
+            input_language: InputLanguage::EdgeQL,
+        };
+        CacheKey::new(&flags, query, Uuid::from_u128(0))
+    }
+
+    fn desc() -> CommandDataDescription1 {
+        CommandDataDescription1 {
+            annotations: HashMap::new(),
+            capabilities: Capabilities::empty(),
+            result_cardinality: Cardinality::Many,
+            input: RawTypedesc::uninitialized(),
+            output: RawTypedesc::uninitialized(),
+        }
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = QueryCache::new(2);
+        cache.insert(key("SELECT 1"), desc());
+        cache.insert(key("SELECT 2"), desc());
+        assert!(cache.get(&key("SELECT 1")).is_some());
+        cache.insert(key("SELECT 3"), desc());
+        assert!(cache.get(&key("SELECT 1")).is_some());
+        assert!(cache.get(&key("SELECT 2")).is_none());
+        assert!(cache.get(&key("SELECT 3")).is_some());
+        assert_eq!(
+            cache.counters.stats(),
+            QueryCacheStats { hits: 3, misses: 1 }
+        );
+    }
+
+    #[test]
+    fn reuses_removed_slots() {
+        let mut cache = QueryCache::new(2);
+        cache.insert(key("SELECT 1"), desc());
+        cache.insert(key("SELECT 2"), desc());
+        cache.remove(&key("SELECT 1"));
+        cache.insert(key("SELECT 3"), desc());
+        assert_eq!(cache.slots.len(), 2);
+        // re-inserting an existing key refreshes it instead of evicting
+        cache.insert(key("SELECT 2"), desc());
+        cache.insert(key("SELECT 4"), desc());
+        assert!(cache.get(&key("SELECT 1")).is_none());
+        assert!(cache.get(&key("SELECT 2")).is_some());
+        assert!(cache.get(&key("SELECT 3")).is_none());
+        assert!(cache.get(&key("SELECT 4")).is_some());
+    }
+
+    #[test]
+    fn disabled() {
+        let mut cache = QueryCache::new(0);
+        cache.insert(key("SELECT 1"), desc());
+        assert!(cache.get(&key("SELECT 1")).is_none());
+    }
+}
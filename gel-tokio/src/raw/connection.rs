@@ -27,12 +27,14 @@ use crate::builder::Config;
 use crate::errors::{
     AuthenticationError, ClientConnectionEosError, ClientConnectionError,
     ClientConnectionFailedError, ClientConnectionFailedTemporarilyError, ClientEncodingError,
-    Error, ErrorKind, IdleSessionTimeoutError, PasswordRequired,
+    ClientInconsistentError, Error, ErrorKind, IdleSessionTimeoutError, PasswordRequired,
     ProtocolEncodingError, ProtocolError,
 };
+use crate::metrics::Observer;
+use crate::raw::cache::QueryCache;
 use crate::raw::queries::Guard;
 use crate::raw::{Connection, PingInterval};
-use crate::server_params::{ServerParam, ServerParams, SystemConfig};
+use crate::server_params::{ServerParam, ServerParams, SuggestedPoolConcurrency, SystemConfig};
 use crate::ClientSecurity;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -82,13 +84,15 @@ impl Connection {
         }
     }
     pub async fn connect(config: &Config) -> Result<Self, Error> {
-        connect(config).await.map_err(|e| {
+        let conn = connect(config).await.map_err(|e| {
             if e.is::<ClientConnectionError>() {
                 e.refine_kind::<ClientConnectionFailedError>()
             } else {
                 e
             }
-        })
+        })?;
+        conn.observer().connection_opened();
+        Ok(conn)
     }
     pub async fn send_messages<'x>(
         &mut self,
@@ -99,6 +103,9 @@ impl Connection {
     pub async fn message(&mut self) -> Result<ServerMessage, Error> {
         wait_message(&mut self.stream, &mut self.in_buf, &self.proto).await
     }
+    pub(crate) fn observer(&self) -> &dyn Observer {
+        &*self.instrumentation.observer
+    }
     pub fn get_server_param<T: ServerParam>(&self) -> Option<&T::Value> {
         self.server_params.get::<T>()
     }
@@ -157,6 +164,28 @@ impl Connection {
         future::pending::<()>().await;
         unreachable!();
     }
+    /// Sends a ping if the connection was idle for longer than the ping
+    /// interval derived from the server's `session_idle_timeout`
+    pub(crate) async fn ping_if_needed(&mut self) -> Result<(), Error> {
+        if self.ping_interval == PingInterval::Unknown {
+            self.ping_interval = self.calc_ping_interval();
+        }
+        let PingInterval::Interval(interval) = self.ping_interval else {
+            return Ok(());
+        };
+        let Mode::Normal { idle_since } = self.mode else {
+            return Err(ClientInconsistentError::with_message(
+                "cannot ping connection in use",
+            ));
+        };
+        if idle_since.elapsed() < interval {
+            return Ok(());
+        }
+        self.mode = Mode::Dirty;
+        self.send_messages(&[ClientMessage::Sync]).await?;
+        self.mode = Mode::AwaitingPing;
+        self.synchronize_ping().await
+    }
     async fn synchronize_ping<'a>(&mut self) -> Result<(), Error> {
         debug_assert_eq!(self.mode, Mode::AwaitingPing);
 
@@ -245,6 +274,12 @@ impl Connection {
     }
 }
 
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.observer().connection_closed();
+    }
+}
+
 async fn connect(cfg: &Config) -> Result<Connection, Error> {
     let mut target = cfg.0.address.clone();
     let tls = cfg.tls()?;
@@ -256,7 +291,10 @@ async fn connect(cfg: &Config) -> Result<Connection, Error> {
     let warned = &mut false;
     let mut retry = 0;
     let conn = loop {
-        match connect_timeout(cfg, connect2(cfg, target.clone(), warned)).await {
+        // Boxed, as the future is large and would bloat every future that
+        // may open a connection (e.g. acquiring one from the pool)
+        let attempt = Box::pin(connect2(cfg, target.clone(), warned));
+        match connect_timeout(cfg, attempt).await {
             Err(e) if is_temporary(&e) => {
                 log::debug!("Temporary connection error: {:#}", e);
                 if wait > start.elapsed() {
@@ -517,6 +555,20 @@ async fn connect4(cfg: &Config, mut stream: gel_stream::RawStream) -> Result<Con
                 b"system_config" => {
                     handle_system_config(par, &mut server_params)?;
                 }
+                b"suggested_pool_concurrency" => {
+                    let concurrency = match str::from_utf8(&par.value)
+                        .ok()
+                        .and_then(|s| s.parse::<usize>().ok())
+                    {
+                        Some(c) => c,
+                        None => {
+                            log::warn!("Can't decode param {:?}: {:?}", par.name, par.value);
+                            continue;
+                        }
+                    };
+                    server_params
+                        .set::<SuggestedPoolConcurrency>(SuggestedPoolConcurrency(concurrency));
+                }
                 _ => {}
             },
             ServerMessage::StateDataDescription(d) => {
@@ -548,6 +600,11 @@ async fn connect4(cfg: &Config, mut stream: gel_stream::RawStream) -> Result<Con
         out_buf,
         stream,
         ping_interval: PingInterval::Unknown,
+        query_cache: QueryCache::new(cfg.0.query_cache_size),
+        created_at: Instant::now(),
+        released_at: Instant::now(),
+        state_changed: false,
+        instrumentation: cfg.0.instrumentation.clone(),
     })
 }
 
@@ -1,5 +1,6 @@
 #![cfg_attr(not(feature = "unstable"), allow(dead_code))]
 
+mod cache;
 mod connection;
 #[cfg(feature = "unstable")]
 mod dumps;
@@ -9,11 +10,15 @@ mod response;
 pub mod state;
 
 use std::collections::VecDeque;
-use std::sync::{Arc, Mutex as BlockingMutex};
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as BlockingMutex, Weak};
 use std::time::Duration;
 
 use bytes::{Bytes, BytesMut};
-use tokio::sync::{self, Semaphore};
+use tokio::runtime::Handle;
+use tokio::sync::{self, Notify, Semaphore};
+use tokio::time::{sleep, Instant};
 
 use gel_protocol::common::{Capabilities, RawTypedesc};
 use gel_protocol::features::ProtocolVersion;
@@ -21,9 +26,13 @@ use gel_protocol::server_message::CommandDataDescription1;
 use gel_protocol::server_message::TransactionState;
 
 use crate::builder::Config;
-use crate::errors::{ClientError, Error, ErrorKind};
-use crate::server_params::ServerParams;
+use crate::errors::{ClientClosedError, Error, ErrorKind};
+use crate::metrics::{Instrumentation, Observer};
+use crate::server_params::{ServerParams, SuggestedPoolConcurrency};
 
+use cache::{CacheCounters, QueryCache};
+
+pub use cache::QueryCacheStats;
 pub use options::Options;
 pub use response::ResponseStream;
 pub use state::{PoolState, State};
@@ -41,18 +50,25 @@ pub enum QueryCapabilities {
 
 pub struct Description;
 
+/// Upper bound of the interval between pool maintenance runs
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(1);
+
 #[derive(Debug)]
 struct PoolInner {
     pub config: Config,
     pub semaphore: Arc<Semaphore>,
+    pub size: BlockingMutex<usize>,
     pub queue: BlockingMutex<VecDeque<Connection>>,
+    pub cache_counters: Arc<CacheCounters>,
+    pub maintenance_started: AtomicBool,
+    /// Notified when a connection is returned to the pool
+    pub released: Notify,
 }
 
 #[derive(Debug)]
 pub struct PoolConnection {
     inner: Option<Connection>,
-    #[allow(dead_code)] // needed only for Drop side effect
-    permit: sync::OwnedSemaphorePermit,
+    permit: Option<sync::OwnedSemaphorePermit>,
     pool: Arc<PoolInner>,
 }
 
@@ -67,6 +83,12 @@ pub struct Connection {
     out_buf: BytesMut,
     stream: gel_stream::RawStream,
     ping_interval: PingInterval,
+    query_cache: QueryCache,
+    created_at: Instant,
+    released_at: Instant,
+    /// Session state on the server was changed by a [`Session`](crate::Session)
+    state_changed: bool,
+    instrumentation: Instrumentation,
 }
 
 #[derive(Debug)]
@@ -96,20 +118,61 @@ impl gel_errors::Field for Description {
 
 impl Pool {
     pub fn new(config: &Config) -> Pool {
-        let concurrency = config
-            .0
-            .max_concurrency
-            // TODO(tailhook) use 1 and get concurrency from the connection
-            .unwrap_or(crate::builder::DEFAULT_POOL_SIZE);
-        Pool(Arc::new(PoolInner {
+        // Unless configured explicitly, start with a single connection and
+        // resize once the server tells us its suggested pool concurrency
+        let concurrency = config.0.max_concurrency.unwrap_or(1);
+        let inner = Arc::new(PoolInner {
             semaphore: Arc::new(Semaphore::new(concurrency)),
+            size: BlockingMutex::new(concurrency),
             queue: BlockingMutex::new(VecDeque::with_capacity(concurrency)),
+            cache_counters: Default::default(),
+            maintenance_started: AtomicBool::new(false),
+            released: Notify::new(),
             config: config.clone(),
-        }))
+        });
+        // Outside of the runtime maintenance is started on first acquire
+        if Handle::try_current().is_ok() {
+            inner.start_maintenance();
+        }
+        Pool(inner)
     }
     pub async fn acquire(&self) -> Result<PoolConnection, Error> {
         self.0.acquire().await
     }
+    /// Close the pool
+    ///
+    /// Idle connections are terminated immediately, connections in use are
+    /// terminated as soon as they are released. Acquiring a connection from
+    /// a closed pool fails with [`ClientClosedError`].
+    pub async fn close(&self) {
+        self.0.semaphore.close();
+        loop {
+            let released = self.0.released.notified();
+            let connections = mem::take(
+                &mut *self
+                    .0
+                    .queue
+                    .lock()
+                    .expect("pool shared state mutex is not poisoned"),
+            );
+            for conn in connections {
+                conn.terminate()
+                    .await
+                    .map_err(|e| log::debug!("Error terminating connection: {e:#}"))
+                    .ok();
+            }
+            if self.0.in_use() == 0 && self.0.idle_count() == 0 {
+                return;
+            }
+            released.await;
+        }
+    }
+    pub fn query_cache_stats(&self) -> QueryCacheStats {
+        self.0.cache_counters.stats()
+    }
+    pub(crate) fn observer(&self) -> &dyn Observer {
+        self.0.observer()
+    }
 }
 
 impl PoolInner {
@@ -120,36 +183,223 @@ impl PoolInner {
             .pop_front()
     }
     async fn acquire(self: &Arc<Self>) -> Result<PoolConnection, Error> {
+        let start = Instant::now();
         let permit = self
             .semaphore
             .clone()
             .acquire_owned()
             .await
-            .map_err(|e| ClientError::with_source(e).context("cannot acquire connection"))?;
+            .map_err(|_| ClientClosedError::with_message("client is closed"))?;
+        self.start_maintenance();
         while let Some(mut conn) = self._next_conn(&permit) {
             assert!(conn.is_consistent());
+            if self.is_expired(&conn) {
+                log::debug!("Connection reached max lifetime, closing");
+                continue;
+            }
             if conn.is_connection_reset().await {
                 continue;
             }
+            if conn.state_changed {
+                if let Err(e) = conn.reset_state().await {
+                    log::warn!("Error resetting session state: {e:#}");
+                    continue;
+                }
+            }
+            self.observer().connection_acquired(start.elapsed());
             return Ok(PoolConnection {
                 inner: Some(conn),
-                permit,
+                permit: Some(permit),
                 pool: self.clone(),
             });
         }
-        let conn = Connection::connect(&self.config).await?;
+        let mut conn = Connection::connect(&self.config).await?;
+        conn.query_cache.set_counters(self.cache_counters.clone());
+        self.adjust_size(&conn);
+        self.observer().connection_acquired(start.elapsed());
         // Make sure that connection is wrapped before we commit,
         // so that connection is returned into a pool if we fail
         // to commit because of async stuff
         Ok(PoolConnection {
             inner: Some(conn),
-            permit,
+            permit: Some(permit),
             pool: self.clone(),
         })
     }
 }
 
+impl PoolInner {
+    /// Resizes the pool to the concurrency suggested by the server, unless
+    /// `max_concurrency` is set explicitly
+    fn adjust_size(&self, conn: &Connection) {
+        if self.config.0.max_concurrency.is_some() {
+            return;
+        }
+        let suggested = conn
+            .get_server_param::<SuggestedPoolConcurrency>()
+            .map(|c| c.0)
+            .unwrap_or(crate::builder::DEFAULT_POOL_SIZE)
+            .max(1);
+        let mut size = self.size.lock().expect("pool size mutex is not poisoned");
+        if suggested > *size {
+            log::debug!("Growing pool from {} to {suggested} connections", *size);
+            self.semaphore.add_permits(suggested - *size);
+            *size = suggested;
+        } else if suggested < *size {
+            // Permits that are currently in use can't be forgotten, they
+            // are removed when a subsequent connection is opened
+            let forgotten = self.semaphore.forget_permits(*size - suggested);
+            log::debug!(
+                "Shrinking pool from {} to {} connections",
+                *size,
+                *size - forgotten
+            );
+            *size -= forgotten;
+        }
+    }
+    fn observer(&self) -> &dyn Observer {
+        &*self.config.0.instrumentation.observer
+    }
+    fn size(&self) -> usize {
+        *self.size.lock().expect("pool size mutex is not poisoned")
+    }
+    /// Number of connections currently acquired from the pool
+    fn in_use(&self) -> usize {
+        self.size()
+            .saturating_sub(self.semaphore.available_permits())
+    }
+    fn needs_maintenance(&self) -> bool {
+        let cfg = &self.config.0;
+        cfg.max_idle_time.is_some() || cfg.max_connection_lifetime.is_some() || cfg.min_idle > 0
+    }
+    /// Spawns the maintenance task, unless it's not needed or already running
+    fn start_maintenance(self: &Arc<Self>) {
+        if self.needs_maintenance() && !self.maintenance_started.swap(true, Ordering::SeqCst) {
+            tokio::spawn(maintain(Arc::downgrade(self)));
+        }
+    }
+    fn maintenance_interval(&self) -> Duration {
+        let cfg = &self.config.0;
+        [cfg.max_idle_time, cfg.max_connection_lifetime]
+            .into_iter()
+            .flatten()
+            .map(|limit| limit / 2)
+            .fold(MAINTENANCE_INTERVAL, Duration::min)
+            .max(Duration::from_millis(10))
+    }
+    fn is_expired(&self, conn: &Connection) -> bool {
+        self.config
+            .0
+            .max_connection_lifetime
+            .is_some_and(|lifetime| conn.created_at.elapsed() >= lifetime)
+    }
+    fn idle_count(&self) -> usize {
+        self.queue
+            .lock()
+            .expect("pool shared state mutex is not poisoned")
+            .len()
+    }
+    /// Checks every idle connection once: closes the ones that are expired
+    /// or idle for too long, and pings the rest if needed
+    async fn check_idle(self: &Arc<Self>) {
+        for _ in 0..self.idle_count() {
+            let Ok(permit) = self.semaphore.clone().try_acquire_owned() else {
+                return;
+            };
+            let checked = self.check_next(&permit).await;
+            drop(permit);
+            // `Pool::close` may be waiting for this permit
+            self.released.notify_one();
+            if !checked {
+                return;
+            }
+        }
+    }
+    /// Checks the next idle connection, returns `false` if there is none
+    async fn check_next(&self, permit: &sync::OwnedSemaphorePermit) -> bool {
+        let cfg = &self.config.0;
+        let Some(mut conn) = self._next_conn(permit) else {
+            return false;
+        };
+        let idle_too_long = cfg
+            .max_idle_time
+            .is_some_and(|max| conn.released_at.elapsed() >= max)
+            && self.idle_count() >= cfg.min_idle;
+        if self.is_expired(&conn) || idle_too_long {
+            log::debug!("Closing stale connection");
+            conn.terminate()
+                .await
+                .map_err(|e| log::debug!("Error terminating connection: {e:#}"))
+                .ok();
+            return true;
+        }
+        if conn.is_connection_reset().await {
+            return true;
+        }
+        if let Err(e) = conn.ping_if_needed().await {
+            log::debug!("Error pinging idle connection: {e:#}");
+            return true;
+        }
+        // Put it back without updating `released_at`
+        self.queue
+            .lock()
+            .expect("pool shared state mutex is not poisoned")
+            .push_back(conn);
+        true
+    }
+    /// Opens connections until there are at least `min_idle` of them idle
+    ///
+    /// The pool size is re-read on every iteration, as it may grow once the
+    /// first connection reports the suggested pool concurrency.
+    async fn fill_idle(self: &Arc<Self>) {
+        while self.idle_count() < self.config.0.min_idle.min(self.size()) {
+            let Ok(permit) = self.semaphore.clone().try_acquire_owned() else {
+                return;
+            };
+            match Connection::connect(&self.config).await {
+                Ok(mut conn) => {
+                    conn.query_cache.set_counters(self.cache_counters.clone());
+                    self.adjust_size(&conn);
+                    drop(PoolConnection {
+                        inner: Some(conn),
+                        permit: Some(permit),
+                        pool: self.clone(),
+                    });
+                }
+                Err(e) => {
+                    log::warn!("Error opening idle connection: {e:#}");
+                    return;
+                }
+            }
+        }
+    }
+}
+
+async fn maintain(pool: Weak<PoolInner>) {
+    loop {
+        let Some(pool) = pool.upgrade() else {
+            return;
+        };
+        if pool.semaphore.is_closed() {
+            return;
+        }
+        pool.check_idle().await;
+        pool.fill_idle().await;
+        let interval = pool.maintenance_interval();
+        drop(pool);
+        sleep(interval).await;
+    }
+}
+
 impl PoolConnection {
+    pub(crate) fn inner_ref(&self) -> &Connection {
+        self.inner.as_ref().expect("connection is not dropped")
+    }
+    /// Makes the pool reset the session state before the connection is
+    /// acquired next time
+    pub(crate) fn mark_state_changed(&mut self) {
+        self.inner().state_changed = true;
+    }
     pub fn is_consistent(&self) -> bool {
         self.inner
             .as_ref()
@@ -160,8 +410,11 @@ impl PoolConnection {
 
 impl Drop for PoolConnection {
     fn drop(&mut self) {
-        if let Some(conn) = self.inner.take() {
-            if conn.is_consistent() {
+        if let Some(mut conn) = self.inner.take() {
+            // Connections released after the pool is closed are still
+            // queued, so that `Pool::close` terminates them gracefully
+            if conn.is_consistent() && !self.pool.is_expired(&conn) {
+                conn.released_at = Instant::now();
                 self.pool
                     .queue
                     .lock()
@@ -169,6 +422,10 @@ impl Drop for PoolConnection {
                     .push_back(conn);
             }
         }
+        // Return the permit before notifying, so that `Pool::close` sees
+        // the connection is no longer in use
+        self.permit.take();
+        self.pool.released.notify_one();
     }
 }
 
@@ -182,9 +439,10 @@ impl<T> Response<T> {
         })
     }
 
-    fn log_warnings(&self) {
+    fn log_warnings(&self, observer: &dyn Observer) {
         for w in &self.warnings {
             log::warn!(target: "gel_tokio::warning", "{w}");
+            observer.warning(w);
         }
     }
 }
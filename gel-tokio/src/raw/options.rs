@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use gel_protocol::encoding::Annotations;
 
@@ -11,4 +12,5 @@ pub struct Options {
     pub(crate) retry: RetryOptions,
     pub(crate) state: Arc<PoolState>,
     pub(crate) annotations: Arc<Annotations>,
+    pub(crate) query_timeout: Option<Duration>,
 }
@@ -4,8 +4,9 @@ use std::sync::Arc;
 use bytes::{Bytes, BytesMut};
 use tokio::time::Instant;
 
-use gel_errors::fields::QueryText;
+use gel_errors::fields::{BatchIndex, QueryText};
 use gel_protocol::client_message::OptimisticExecute;
+use gel_protocol::client_message::State as EncodedState;
 use gel_protocol::client_message::{ClientMessage, Parse, Prepare};
 use gel_protocol::client_message::{DescribeAspect, DescribeStatement};
 use gel_protocol::client_message::{Execute0, Execute1};
@@ -20,16 +21,44 @@ use gel_protocol::server_message::{CommandDataDescription1, PrepareComplete};
 use gel_protocol::server_message::{Data, ServerMessage};
 use gel_protocol::QueryResult;
 
-use crate::errors::NoResultExpected;
+use crate::errors::UnsupportedFeatureError;
 use crate::errors::{ClientConnectionEosError, ProtocolEncodingError};
 use crate::errors::{ClientInconsistentError, ProtocolOutOfOrderError};
 use crate::errors::{Error, ErrorKind};
+use crate::errors::{NoResultExpected, ParameterTypeMismatchError};
+use crate::raw::cache::CacheKey;
 use crate::raw::connection::Mode;
 use crate::raw::{Connection, PoolConnection, QueryCapabilities};
 use crate::raw::{Description, Response, ResponseStream, State};
 
 pub(crate) struct Guard;
 
+fn statement_flags() -> CompilationOptions {
+    CompilationOptions {
+        implicit_limit: None,
+        implicit_typenames: false,
+        implicit_typeids: false,
+        explicit_objectids: false,
+        allow_capabilities: Capabilities::ALL,
+        input_language: InputLanguage::EdgeQL,
+        io_format: IoFormat::Binary,
+        expected_cardinality: Cardinality::Many, // no result is unsupported
+    }
+}
+
+fn stream_flags(allow_capabilities: Capabilities) -> CompilationOptions {
+    CompilationOptions {
+        implicit_limit: None,
+        implicit_typenames: false,
+        implicit_typeids: false,
+        explicit_objectids: true,
+        allow_capabilities,
+        io_format: IoFormat::Binary,
+        input_language: InputLanguage::EdgeQL,
+        expected_cardinality: Cardinality::Many,
+    }
+}
+
 impl Connection {
     pub(crate) fn begin_request(&mut self) -> Result<Guard, Error> {
         match self.mode {
@@ -217,7 +246,7 @@ impl Connection {
         annotations: &Arc<Annotations>,
         desc: &CommandDataDescription1,
         arguments: &Bytes,
-    ) -> Result<Response<Vec<Data>>, Error> {
+    ) -> Result<(Response<Vec<Data>>, Option<CommandDataDescription1>), Error> {
         if self.proto.is_1() {
             self._execute1(opts, query, state, annotations, desc, arguments)
                 .await
@@ -225,6 +254,7 @@ impl Connection {
         } else {
             self._execute0(arguments)
                 .await
+                .map(|response| (response, None))
                 .map_err(|e| e.set::<QueryText>(query))
         }
     }
@@ -237,7 +267,7 @@ impl Connection {
         annotations: &Arc<Annotations>,
         desc: &CommandDataDescription1,
         arguments: &Bytes,
-    ) -> Result<Response<Vec<Data>>, Error> {
+    ) -> Result<(Response<Vec<Data>>, Option<CommandDataDescription1>), Error> {
         let guard = self.begin_request()?;
         self.send_messages(&[
             ClientMessage::Execute1(Execute1 {
@@ -278,12 +308,13 @@ impl Connection {
                 }
                 ServerMessage::CommandComplete1(complete) => {
                     self.expect_ready(guard).await?;
-                    return Ok(Response {
+                    let response = Response {
                         status_data: complete.status_data,
                         new_state: complete.state,
                         data,
                         warnings,
-                    });
+                    };
+                    return Ok((response, description));
                 }
                 ServerMessage::ErrorResponse(err) => {
                     self.expect_ready_or_eos(guard)
@@ -350,6 +381,35 @@ impl Connection {
             }
         }
     }
+    /// Executes a query and returns a stream of results, using the query
+    /// cache like [`query`](Connection::query) does
+    pub async fn query_stream<R, A>(
+        &mut self,
+        query: &str,
+        arguments: &A,
+        state: &dyn State,
+        annotations: &Arc<Annotations>,
+        allow_capabilities: Capabilities,
+    ) -> Result<ResponseStream<'_, R>, Error>
+    where
+        A: QueryArgs,
+        R: QueryResult,
+        R::State: Unpin,
+    {
+        let flags = stream_flags(allow_capabilities);
+        let mut caps = QueryCapabilities::Unparsed;
+        let result = ResponseStream::execute(
+            self,
+            &flags,
+            query,
+            arguments,
+            state,
+            annotations,
+            &mut caps,
+        )
+        .await;
+        result.map_err(|e| e.set::<QueryCapabilities>(caps))
+    }
     pub async fn execute_stream<R, A>(
         &mut self,
         opts: &CompilationOptions,
@@ -363,6 +423,24 @@ impl Connection {
         A: QueryArgs,
         R: QueryResult,
         R::State: Unpin,
+    {
+        let guard = self
+            .send_execute(opts, query, state, annotations, desc, arguments)
+            .await?;
+        let out_desc = desc.output().map_err(ProtocolEncodingError::with_source)?;
+        ResponseStream::new(self, &out_desc, guard).await
+    }
+    pub(crate) async fn send_execute<A>(
+        &mut self,
+        opts: &CompilationOptions,
+        query: &str,
+        state: &dyn State,
+        annotations: &Arc<Annotations>,
+        desc: &CommandDataDescription1,
+        arguments: &A,
+    ) -> Result<Guard, Error>
+    where
+        A: QueryArgs,
     {
         let inp_desc = desc.input().map_err(ProtocolEncodingError::with_source)?;
 
@@ -404,9 +482,7 @@ impl Connection {
             ])
             .await?;
         }
-
-        let out_desc = desc.output().map_err(ProtocolEncodingError::with_source)?;
-        ResponseStream::new(self, &out_desc, guard).await
+        Ok(guard)
     }
     pub async fn try_execute_stream<R, A>(
         &mut self,
@@ -472,11 +548,34 @@ impl Connection {
         state: &dyn State,
         annotations: &Arc<Annotations>,
     ) -> Result<(), Error> {
-        if self.proto.is_1() {
-            self._statement1(flags, query, state, annotations).await
-        } else {
-            self._statement0(flags, query).await
-        }
+        let instrumentation = self.instrumentation.clone();
+        instrumentation
+            .query(query, annotations, async {
+                if self.proto.is_1() {
+                    self._statement1(flags, query, state, annotations).await
+                } else {
+                    self._statement0(flags, query).await
+                }
+            })
+            .await
+    }
+
+    /// Replaces the session state left on the server by a
+    /// [`Session`](crate::Session) with the default one
+    pub(crate) async fn reset_state(&mut self) -> Result<(), Error> {
+        let default_state = EncodedState {
+            typedesc_id: Uuid::from_u128(0),
+            data: Bytes::new(),
+        };
+        self.statement(
+            &statement_flags(),
+            "SELECT {}",
+            &default_state,
+            &Default::default(),
+        )
+        .await?;
+        self.state_changed = false;
+        Ok(())
     }
 
     async fn _statement1(
@@ -572,34 +671,47 @@ impl Connection {
         }
     }
 
-    pub async fn query<R, A>(
+    pub(crate) async fn describe(
+        &mut self,
+        flags: &CompilationOptions,
+        query: &str,
+        state: &dyn State,
+        annotations: &Arc<Annotations>,
+    ) -> Result<(CommandDataDescription1, bool), Error> {
+        let use_cache = self.proto.is_1() && self.query_cache.is_enabled();
+        if use_cache {
+            let key = CacheKey::new(flags, query, self.state_desc.id);
+            if let Some(desc) = self.query_cache.get(&key) {
+                return Ok((desc, true));
+            }
+        }
+        let desc = self.parse(flags, query, state, annotations).await?;
+        if use_cache {
+            // state descriptor might be updated by parse
+            let key = CacheKey::new(flags, query, self.state_desc.id);
+            self.query_cache.insert(key, desc.clone());
+        }
+        Ok((desc, false))
+    }
+
+    /// Parses the query (or takes the descriptor from the query cache) and
+    /// executes it. Returns the descriptor that should be used to decode
+    /// the response.
+    async fn parse_and_execute<A>(
         &mut self,
+        flags: &CompilationOptions,
         query: &str,
         arguments: &A,
         state: &dyn State,
         annotations: &Arc<Annotations>,
-        allow_capabilities: Capabilities,
-        io_format: IoFormat,
-        cardinality: Cardinality,
-    ) -> Result<Response<Vec<R>>, Error>
+        caps: &mut QueryCapabilities,
+    ) -> Result<(CommandDataDescription1, Response<Vec<Data>>), Error>
     where
-        A: QueryArgs,
-        R: QueryResult,
+        A: QueryArgs + ?Sized,
     {
-        let mut caps = QueryCapabilities::Unparsed;
-        let result = async {
-            let flags = CompilationOptions {
-                implicit_limit: None,
-                implicit_typenames: false,
-                implicit_typeids: false,
-                explicit_objectids: true,
-                allow_capabilities,
-                io_format,
-                input_language: InputLanguage::EdgeQL,
-                expected_cardinality: cardinality,
-            };
-            let desc = self.parse(&flags, query, state, annotations).await?;
-            caps = QueryCapabilities::Parsed(desc.capabilities);
+        let (mut desc, mut cached) = self.describe(flags, query, state, annotations).await?;
+        loop {
+            *caps = QueryCapabilities::Parsed(desc.capabilities);
             let inp_desc = desc.input().map_err(ProtocolEncodingError::with_source)?;
 
             let mut arg_buf = BytesMut::with_capacity(8);
@@ -610,27 +722,125 @@ impl Connection {
                 return Err(e.set::<Description>(desc));
             }
 
-            let response = self
-                ._execute(&flags, query, state, annotations, &desc, &arg_buf.freeze())
-                .await?;
-            response.log_warnings();
-
-            let out_desc = desc.output().map_err(ProtocolEncodingError::with_source)?;
-            match out_desc.root_pos() {
-                Some(root_pos) => {
-                    let ctx = out_desc.as_queryable_context();
-                    let mut state = R::prepare(&ctx, root_pos)?;
-                    response.map(|data| {
-                        data.into_iter()
-                            .flat_map(|chunk| chunk.data)
-                            .map(|chunk| R::decode(&mut state, &chunk))
-                            .collect::<Result<Vec<_>, _>>()
-                    })
-                }
-                None => Err(NoResultExpected::build()),
+            let result = self
+                ._execute(flags, query, state, annotations, &desc, &arg_buf.freeze())
+                .await;
+            match result {
+                Ok((response, None)) => return Ok((desc, response)),
+                Ok((response, Some(new_desc))) => {
+                    // Output descriptor has changed since the query was parsed
+                    if self.query_cache.is_enabled() {
+                        let key = CacheKey::new(flags, query, self.state_desc.id);
+                        self.query_cache.insert(key, new_desc.clone());
+                    }
+                    return Ok((new_desc, response));
+                }
+                Err(e) if cached && e.is::<ParameterTypeMismatchError>() => {
+                    log::debug!("Cached descriptor is outdated for {query:?}, parsing again");
+                    let key = CacheKey::new(flags, query, self.state_desc.id);
+                    self.query_cache.remove(&key);
+                    if let Some(new_desc) = e.get::<Description>() {
+                        desc = new_desc.clone();
+                        self.query_cache.insert(key, desc.clone());
+                    } else {
+                        (desc, _) = self.describe(flags, query, state, annotations).await?;
+                    }
+                    cached = false;
+                }
+                Err(e) => return Err(e),
             }
         }
-        .await;
+    }
+
+    fn check_input_language(&self, input_language: InputLanguage) -> Result<(), Error> {
+        if input_language != InputLanguage::EdgeQL && !self.proto.is_multilingual() {
+            let (major, minor) = self.proto.version_tuple();
+            return Err(UnsupportedFeatureError::with_message(format!(
+                "{input_language:?} queries are not supported by protocol {major}.{minor}, \
+                 protocol 3.0 or later is required"
+            )));
+        }
+        Ok(())
+    }
+
+    pub async fn query<R, A>(
+        &mut self,
+        query: &str,
+        arguments: &A,
+        state: &dyn State,
+        annotations: &Arc<Annotations>,
+        allow_capabilities: Capabilities,
+        io_format: IoFormat,
+        cardinality: Cardinality,
+    ) -> Result<Response<Vec<R>>, Error>
+    where
+        A: QueryArgs,
+        R: QueryResult,
+    {
+        self.query_with_language(
+            query,
+            arguments,
+            state,
+            annotations,
+            allow_capabilities,
+            InputLanguage::EdgeQL,
+            io_format,
+            cardinality,
+        )
+        .await
+    }
+
+    pub async fn query_with_language<R, A>(
+        &mut self,
+        query: &str,
+        arguments: &A,
+        state: &dyn State,
+        annotations: &Arc<Annotations>,
+        allow_capabilities: Capabilities,
+        input_language: InputLanguage,
+        io_format: IoFormat,
+        cardinality: Cardinality,
+    ) -> Result<Response<Vec<R>>, Error>
+    where
+        A: QueryArgs,
+        R: QueryResult,
+    {
+        let mut caps = QueryCapabilities::Unparsed;
+        let instrumentation = self.instrumentation.clone();
+        let result = instrumentation
+            .query(query, annotations, async {
+                self.check_input_language(input_language)?;
+                let flags = CompilationOptions {
+                    implicit_limit: None,
+                    implicit_typenames: false,
+                    implicit_typeids: false,
+                    explicit_objectids: true,
+                    allow_capabilities,
+                    io_format,
+                    input_language,
+                    expected_cardinality: cardinality,
+                };
+                let (desc, response) = self
+                    .parse_and_execute(&flags, query, arguments, state, annotations, &mut caps)
+                    .await?;
+                response.log_warnings(self.observer());
+
+                let out_desc = desc.output().map_err(ProtocolEncodingError::with_source)?;
+                match out_desc.root_pos() {
+                    Some(root_pos) => {
+                        let ctx = out_desc.as_queryable_context();
+                        let mut state = R::prepare(&ctx, root_pos)?;
+                        response.map(|data| {
+                            data.into_iter()
+                                .flat_map(|chunk| chunk.data)
+                                .map(|chunk| R::decode(&mut state, &chunk))
+                                .collect::<Result<Vec<_>, _>>()
+                        })
+                    }
+                    None => Err(NoResultExpected::build()),
+                }
+            })
+            .await;
         result.map_err(|e| e.set::<QueryCapabilities>(caps))
     }
 
@@ -644,43 +854,365 @@ impl Connection {
     ) -> Result<Response<()>, Error>
     where
         A: QueryArgs,
+    {
+        self.execute_with_language(
+            query,
+            arguments,
+            state,
+            annotations,
+            allow_capabilities,
+            InputLanguage::EdgeQL,
+        )
+        .await
+    }
+
+    pub async fn execute_with_language<A>(
+        &mut self,
+        query: &str,
+        arguments: &A,
+        state: &dyn State,
+        annotations: &Arc<Annotations>,
+        allow_capabilities: Capabilities,
+        input_language: InputLanguage,
+    ) -> Result<Response<()>, Error>
+    where
+        A: QueryArgs + ?Sized,
     {
         let mut caps = QueryCapabilities::Unparsed;
-        let result: Result<_, Error> = async {
-            let flags = CompilationOptions {
-                implicit_limit: None,
-                implicit_typenames: false,
-                implicit_typeids: false,
-                explicit_objectids: true,
-                allow_capabilities,
-                input_language: InputLanguage::EdgeQL,
-                io_format: IoFormat::Binary,
-                expected_cardinality: Cardinality::Many,
-            };
-            let desc = self.parse(&flags, query, state, annotations).await?;
-            caps = QueryCapabilities::Parsed(desc.capabilities);
-            let inp_desc = desc.input().map_err(ProtocolEncodingError::with_source)?;
+        let instrumentation = self.instrumentation.clone();
+        let result = instrumentation
+            .query(query, annotations, async {
+                self.check_input_language(input_language)?;
+                let flags = CompilationOptions {
+                    implicit_limit: None,
+                    implicit_typenames: false,
+                    implicit_typeids: false,
+                    explicit_objectids: true,
+                    allow_capabilities,
+                    input_language,
+                    io_format: IoFormat::Binary,
+                    expected_cardinality: Cardinality::Many,
+                };
+                let (_, response) = self
+                    .parse_and_execute(&flags, query, arguments, state, annotations, &mut caps)
+                    .await?;
+                response.log_warnings(self.observer());
+                response.map(|_| Ok::<_, Error>(()))
+            })
+            .await;
+        result.map_err(|e| e.set::<QueryCapabilities>(caps))
+    }
 
-            let mut arg_buf = BytesMut::with_capacity(8);
-            if let Err(e) = arguments.encode(&mut Encoder::new(
-                &inp_desc.as_query_arg_context(),
-                &mut arg_buf,
-            )) {
-                return Err(e.set::<Description>(desc));
+    /// Executes a batch of statements in a pipeline
+    ///
+    /// Statements that aren't in the query cache are parsed first: all
+    /// `Parse` messages are sent followed by a single `Sync`. Then all
+    /// `Execute` messages are sent followed by a single `Sync`. So the whole
+    /// batch takes at most two round trips.
+    ///
+    /// Statements are executed in order, execution stops at the first
+    /// error. The index of the failed statement is stored in the
+    /// [`BatchIndex`] field of the error.
+    ///
+    /// The observer sees the whole batch as a single query, with the text
+    /// of the statements separated by `;`. On protocol 0.x statements are
+    /// executed (and reported) one by one.
+    pub async fn execute_batch(
+        &mut self,
+        items: &[(&str, &dyn QueryArgs)],
+        state: &dyn State,
+        annotations: &Arc<Annotations>,
+        allow_capabilities: Capabilities,
+    ) -> Result<(), Error> {
+        if items.is_empty() {
+            return Ok(());
+        }
+        if !self.proto.is_1() {
+            for (index, (query, arguments)) in items.iter().enumerate() {
+                self.execute_with_language(
+                    query,
+                    *arguments,
+                    state,
+                    annotations,
+                    allow_capabilities,
+                    InputLanguage::EdgeQL,
+                )
+                .await
+                .map_err(|e| e.set::<BatchIndex>(index))?;
             }
+            return Ok(());
+        }
+        let query = items
+            .iter()
+            .map(|(query, _)| *query)
+            .collect::<Vec<_>>()
+            .join(";\n");
+        let instrumentation = self.instrumentation.clone();
+        instrumentation
+            .query(
+                &query,
+                annotations,
+                self._execute_batch(items, state, annotations, allow_capabilities),
+            )
+            .await
+    }
 
-            let response = self
-                ._execute(&flags, query, state, annotations, &desc, &arg_buf.freeze())
-                .await?;
-            response.log_warnings();
-            response.map(|_| Ok::<_, Error>(()))
+    async fn _execute_batch(
+        &mut self,
+        items: &[(&str, &dyn QueryArgs)],
+        state: &dyn State,
+        annotations: &Arc<Annotations>,
+        allow_capabilities: Capabilities,
+    ) -> Result<(), Error> {
+        let flags = CompilationOptions {
+            implicit_limit: None,
+            implicit_typenames: false,
+            implicit_typeids: false,
+            explicit_objectids: true,
+            allow_capabilities,
+            input_language: InputLanguage::EdgeQL,
+            io_format: IoFormat::Binary,
+            expected_cardinality: Cardinality::Many,
+        };
+        let mut descs = items
+            .iter()
+            .map(|(query, _)| {
+                let key = CacheKey::new(&flags, query, self.state_desc.id);
+                self.query_cache
+                    .is_enabled()
+                    .then(|| self.query_cache.get(&key))
+                    .flatten()
+                    .map(|desc| (desc, true))
+            })
+            .collect::<Vec<_>>();
+        let mut start = 0;
+        loop {
+            let missing = (start..items.len())
+                .filter(|&index| descs[index].is_none())
+                .collect::<Vec<_>>();
+            if !missing.is_empty() {
+                let parsed = self
+                    ._parse_batch(&flags, items, &missing, state, annotations)
+                    .await?;
+                for (index, desc) in missing.into_iter().zip(parsed) {
+                    if self.query_cache.is_enabled() {
+                        let key = CacheKey::new(&flags, items[index].0, self.state_desc.id);
+                        self.query_cache.insert(key, desc.clone());
+                    }
+                    descs[index] = Some((desc, false));
+                }
+            }
+            match self
+                ._execute_batch1(&flags, items, &descs, start, state, annotations)
+                .await
+            {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    // I/O errors (e.g. a broken connection) carry no index
+                    let Some(&index) = e.get::<BatchIndex>() else {
+                        return Err(e);
+                    };
+                    let cached = descs[index].as_ref().is_some_and(|(_, cached)| *cached);
+                    if cached && e.is::<ParameterTypeMismatchError>() {
+                        // Statements before `index` have been executed
+                        log::debug!(
+                            "Cached descriptor is outdated for {:?}, parsing again",
+                            items[index].0
+                        );
+                        let key = CacheKey::new(&flags, items[index].0, self.state_desc.id);
+                        self.query_cache.remove(&key);
+                        descs[index] = None;
+                        start = index;
+                        continue;
+                    }
+                    return Err(e);
+                }
+            }
         }
-        .await;
-        result.map_err(|e| e.set::<QueryCapabilities>(caps))
+    }
+
+    async fn _parse_batch(
+        &mut self,
+        flags: &CompilationOptions,
+        items: &[(&str, &dyn QueryArgs)],
+        indexes: &[usize],
+        state: &dyn State,
+        annotations: &Arc<Annotations>,
+    ) -> Result<Vec<CommandDataDescription1>, Error> {
+        let state = state.encode(&self.state_desc)?;
+        let mut messages = indexes
+            .iter()
+            .map(|&index| {
+                ClientMessage::Parse(Parse::new(
+                    flags,
+                    items[index].0,
+                    state.clone(),
+                    self.proto.is_3().then(|| annotations.clone()),
+                ))
+            })
+            .collect::<Vec<_>>();
+        messages.push(ClientMessage::Sync);
+
+        let guard = self.begin_request()?;
+        self.send_messages(&messages).await?;
+
+        let mut result = Vec::with_capacity(indexes.len());
+        loop {
+            let msg = self.message().await?;
+            match msg {
+                ServerMessage::StateDataDescription(d) => {
+                    self.state_desc = d.typedesc;
+                }
+                ServerMessage::CommandDataDescription1(data_desc)
+                    if result.len() < indexes.len() =>
+                {
+                    result.push(data_desc);
+                    if result.len() == indexes.len() {
+                        self.expect_ready(guard).await?;
+                        return Ok(result);
+                    }
+                }
+                ServerMessage::ErrorResponse(err) => {
+                    self.expect_ready_or_eos(guard)
+                        .await
+                        .map_err(|e| log::warn!("Error waiting for Ready after error: {e:#}"))
+                        .ok();
+                    let index = indexes[result.len()];
+                    let err: Error = err.into();
+                    return Err(err
+                        .set::<QueryText>(items[index].0)
+                        .set::<BatchIndex>(index));
+                }
+                _ => {
+                    return Err(ProtocolOutOfOrderError::with_message(format!(
+                        "Unsolicited message {:?}",
+                        msg
+                    )));
+                }
+            }
+        }
+    }
+
+    async fn _execute_batch1(
+        &mut self,
+        opts: &CompilationOptions,
+        items: &[(&str, &dyn QueryArgs)],
+        descs: &[Option<(CommandDataDescription1, bool)>],
+        start: usize,
+        state: &dyn State,
+        annotations: &Arc<Annotations>,
+    ) -> Result<(), Error> {
+        let state = state.encode(&self.state_desc)?;
+        let mut messages = Vec::with_capacity(items.len() - start + 1);
+        for index in start..items.len() {
+            let (query, arguments) = items[index];
+            let (desc, _) = descs[index].as_ref().expect("statement is parsed");
+            let inp_desc = desc.input().map_err(ProtocolEncodingError::with_source)?;
+            let mut arg_buf = BytesMut::with_capacity(8);
+            arguments
+                .encode(&mut Encoder::new(
+                    &inp_desc.as_query_arg_context(),
+                    &mut arg_buf,
+                ))
+                .map_err(|e| e.set::<QueryText>(query).set::<BatchIndex>(index))?;
+            messages.push(ClientMessage::Execute1(Execute1 {
+                annotations: self.proto.is_3().then(|| annotations.clone()),
+                allowed_capabilities: opts.allow_capabilities,
+                compilation_flags: opts.flags(),
+                implicit_limit: opts.implicit_limit,
+                input_language: opts.input_language,
+                output_format: opts.io_format,
+                expected_cardinality: opts.expected_cardinality,
+                command_text: query.into(),
+                state: state.clone(),
+                input_typedesc_id: desc.input.id,
+                output_typedesc_id: desc.output.id,
+                arguments: arg_buf.freeze(),
+            }));
+        }
+        messages.push(ClientMessage::Sync);
+
+        let guard = self.begin_request()?;
+        self.send_messages(&messages).await?;
+
+        let mut index = start;
+        let mut warnings = Vec::new();
+        loop {
+            let msg = self.message().await?;
+            match msg {
+                ServerMessage::StateDataDescription(d) => {
+                    self.state_desc = d.typedesc;
+                }
+                ServerMessage::CommandDataDescription1(desc) => {
+                    warnings.extend(gel_protocol::annotations::decode_warnings(
+                        &desc.annotations,
+                    )?);
+                }
+                ServerMessage::Data(_) => {}
+                ServerMessage::CommandComplete1(_) if index < items.len() => {
+                    index += 1;
+                    if index == items.len() {
+                        self.expect_ready(guard).await?;
+                        break;
+                    }
+                }
+                ServerMessage::ErrorResponse(err) => {
+                    self.expect_ready_or_eos(guard)
+                        .await
+                        .map_err(|e| log::warn!("Error waiting for Ready after error: {e:#}"))
+                        .ok();
+                    let err: Error = err.into();
+                    return Err(err
+                        .set::<QueryText>(items[index].0)
+                        .set::<BatchIndex>(index));
+                }
+                _ => {
+                    return Err(ProtocolOutOfOrderError::with_message(format!(
+                        "Unsolicited message {:?}",
+                        msg
+                    )));
+                }
+            }
+        }
+        let response = Response {
+            status_data: Bytes::new(),
+            new_state: None,
+            data: (),
+            warnings,
+        };
+        response.log_warnings(self.observer());
+        Ok(())
     }
 }
 
 impl PoolConnection {
+    pub async fn query_stream<R, A>(
+        self,
+        query: &str,
+        arguments: &A,
+        state: &dyn State,
+        annotations: &Arc<Annotations>,
+        allow_capabilities: Capabilities,
+    ) -> Result<ResponseStream<'static, R>, Error>
+    where
+        A: QueryArgs,
+        R: QueryResult,
+        R::State: Unpin,
+    {
+        let flags = stream_flags(allow_capabilities);
+        let mut caps = QueryCapabilities::Unparsed;
+        let result = ResponseStream::execute_pooled(
+            self,
+            &flags,
+            query,
+            arguments,
+            state,
+            annotations,
+            &mut caps,
+        )
+        .await;
+        result.map_err(|e| e.set::<QueryCapabilities>(caps))
+    }
     pub async fn parse(
         &mut self,
         flags: &CompilationOptions,
@@ -702,7 +1234,7 @@ impl PoolConnection {
         self.inner()
             ._execute(opts, query, state, annotations, desc, arguments)
             .await
-            .map(|r| r.data)
+            .map(|(r, _)| r.data)
     }
     pub async fn statement(
         &mut self,
@@ -710,16 +1242,7 @@ impl PoolConnection {
         state: &dyn State,
         annotations: &Arc<Annotations>,
     ) -> Result<(), Error> {
-        let flags = CompilationOptions {
-            implicit_limit: None,
-            implicit_typenames: false,
-            implicit_typeids: false,
-            explicit_objectids: false,
-            allow_capabilities: Capabilities::ALL,
-            input_language: InputLanguage::EdgeQL,
-            io_format: IoFormat::Binary,
-            expected_cardinality: Cardinality::Many, // no result is unsupported
-        };
+        let flags = statement_flags();
         self.inner().statement(&flags, query, state, annotations).await
     }
     pub fn proto(&self) -> &ProtocolVersion {
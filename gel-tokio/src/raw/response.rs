@@ -1,19 +1,27 @@
 use std::collections::VecDeque;
 use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
 
 use bytes::Bytes;
+use futures_util::stream::{self, Stream};
 use gel_errors::ProtocolEncodingError;
 use gel_errors::{Error, ErrorKind};
 use gel_errors::{ParameterTypeMismatchError, ProtocolOutOfOrderError};
 use gel_protocol::annotations::Warning;
+use gel_protocol::common::CompilationOptions;
 use gel_protocol::common::State;
 use gel_protocol::descriptors::Typedesc;
+use gel_protocol::encoding::Annotations;
+use gel_protocol::query_arg::QueryArgs;
 use gel_protocol::server_message::CommandDataDescription1;
 use gel_protocol::server_message::{ErrorResponse, ServerMessage};
 use gel_protocol::{annotations, QueryResult};
 
+use crate::raw::cache::CacheKey;
 use crate::raw::queries::Guard;
-use crate::raw::{Connection, Description, Response};
+use crate::raw::State as QueryState;
+use crate::raw::{Connection, Description, PoolConnection, QueryCapabilities, Response};
 
 enum Buffer {
     Reading(VecDeque<Bytes>),
@@ -26,11 +34,16 @@ enum Buffer {
     Reset,
 }
 
+enum ConnectionRef<'a> {
+    Borrowed(&'a mut Connection),
+    Pooled(Box<PoolConnection>),
+}
+
 pub struct ResponseStream<'a, T: QueryResult>
 where
     T::State: Unpin,
 {
-    connection: &'a mut Connection,
+    connection: ConnectionRef<'a>,
     buffer: Buffer,
     state: Option<T::State>,
     guard: Option<Guard>,
@@ -38,6 +51,60 @@ where
     warnings: Vec<Warning>,
 }
 
+impl Deref for ConnectionRef<'_> {
+    type Target = Connection;
+    fn deref(&self) -> &Connection {
+        match self {
+            ConnectionRef::Borrowed(conn) => conn,
+            ConnectionRef::Pooled(conn) => conn.inner_ref(),
+        }
+    }
+}
+
+impl DerefMut for ConnectionRef<'_> {
+    fn deref_mut(&mut self) -> &mut Connection {
+        match self {
+            ConnectionRef::Borrowed(conn) => conn,
+            ConnectionRef::Pooled(conn) => conn.inner(),
+        }
+    }
+}
+
+impl<T: QueryResult> ResponseStream<'static, T>
+where
+    T::State: Unpin,
+{
+    /// Same as [`ResponseStream::execute`] but owns the connection, so it is
+    /// returned to the pool when the stream is dropped
+    ///
+    /// If the stream is dropped before it's complete, the connection is
+    /// left in an inconsistent state and is discarded by the pool.
+    pub(crate) async fn execute_pooled<A>(
+        connection: PoolConnection,
+        flags: &CompilationOptions,
+        query: &str,
+        arguments: &A,
+        state: &dyn QueryState,
+        annotations: &Arc<Annotations>,
+        caps: &mut QueryCapabilities,
+    ) -> Result<ResponseStream<'static, T>, Error>
+    where
+        A: QueryArgs,
+    {
+        let connection = ConnectionRef::Pooled(Box::new(connection));
+        ResponseStream::instrumented(
+            connection,
+            flags,
+            query,
+            arguments,
+            state,
+            annotations,
+            caps,
+        )
+        .await
+    }
+}
+
 impl<'a, T: QueryResult> ResponseStream<'a, T>
 where
     T::State: Unpin,
@@ -47,11 +114,138 @@ where
         out_desc: &Typedesc,
         guard: Guard,
     ) -> Result<ResponseStream<'a, T>, Error> {
+        ResponseStream::start(ConnectionRef::Borrowed(connection), out_desc, guard).await
+    }
+    /// Parses the query (or takes the descriptor from the query cache),
+    /// executes it and waits for the first batch of results
+    ///
+    /// Like other queries, this is reported to the observer, which sees the
+    /// query finished as soon as the results start to arrive.
+    pub(crate) async fn execute<A>(
+        connection: &'a mut Connection,
+        flags: &CompilationOptions,
+        query: &str,
+        arguments: &A,
+        state: &dyn QueryState,
+        annotations: &Arc<Annotations>,
+        caps: &mut QueryCapabilities,
+    ) -> Result<ResponseStream<'a, T>, Error>
+    where
+        A: QueryArgs,
+    {
+        let connection = ConnectionRef::Borrowed(connection);
+        ResponseStream::instrumented(
+            connection,
+            flags,
+            query,
+            arguments,
+            state,
+            annotations,
+            caps,
+        )
+        .await
+    }
+    async fn instrumented<A>(
+        connection: ConnectionRef<'a>,
+        flags: &CompilationOptions,
+        query: &str,
+        arguments: &A,
+        state: &dyn QueryState,
+        annotations: &Arc<Annotations>,
+        caps: &mut QueryCapabilities,
+    ) -> Result<ResponseStream<'a, T>, Error>
+    where
+        A: QueryArgs,
+    {
+        let instrumentation = connection.instrumentation.clone();
+        let execute = ResponseStream::_execute(
+            connection,
+            flags,
+            query,
+            arguments,
+            state,
+            annotations,
+            caps,
+        );
+        instrumentation.query(query, annotations, execute).await
+    }
+    async fn _execute<A>(
+        mut connection: ConnectionRef<'a>,
+        flags: &CompilationOptions,
+        query: &str,
+        arguments: &A,
+        state: &dyn QueryState,
+        annotations: &Arc<Annotations>,
+        caps: &mut QueryCapabilities,
+    ) -> Result<ResponseStream<'a, T>, Error>
+    where
+        A: QueryArgs,
+    {
+        let (mut desc, mut cached) = connection
+            .describe(flags, query, state, annotations)
+            .await?;
+        loop {
+            *caps = QueryCapabilities::Parsed(desc.capabilities);
+            let guard = connection
+                .send_execute(flags, query, state, annotations, &desc, arguments)
+                .await?;
+            let mut guard = Some(guard);
+            match ResponseStream::<T>::read_first(&mut connection, &mut guard).await {
+                Ok((buffer, description)) => {
+                    if let Some(new_desc) = &description {
+                        // Output descriptor has changed since the query was parsed
+                        if connection.query_cache.is_enabled() {
+                            let key = CacheKey::new(flags, query, connection.state_desc.id);
+                            connection.query_cache.insert(key, new_desc.clone());
+                        }
+                    }
+                    let out_desc = desc.output().map_err(ProtocolEncodingError::with_source)?;
+                    return ResponseStream::finish(
+                        connection,
+                        buffer,
+                        description,
+                        &out_desc,
+                        guard,
+                    );
+                }
+                Err(e) if cached && e.is::<ParameterTypeMismatchError>() => {
+                    log::debug!("Cached descriptor is outdated for {query:?}, parsing again");
+                    let key = CacheKey::new(flags, query, connection.state_desc.id);
+                    connection.query_cache.remove(&key);
+                    if let Some(new_desc) = e.get::<Description>() {
+                        desc = new_desc.clone();
+                        connection.query_cache.insert(key, desc.clone());
+                    } else {
+                        (desc, _) = connection
+                            .describe(flags, query, state, annotations)
+                            .await?;
+                    }
+                    cached = false;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    async fn start(
+        mut connection: ConnectionRef<'a>,
+        out_desc: &Typedesc,
+        guard: Guard,
+    ) -> Result<ResponseStream<'a, T>, Error> {
+        let mut guard = Some(guard);
+        let (buffer, description) =
+            ResponseStream::<T>::read_first(&mut connection, &mut guard).await?;
+        ResponseStream::finish(connection, buffer, description, out_desc, guard)
+    }
+    /// Reads messages up to the first batch of data or completion of the
+    /// query. The guard is taken if the query is complete.
+    async fn read_first(
+        connection: &mut Connection,
+        guard: &mut Option<Guard>,
+    ) -> Result<(Buffer, Option<CommandDataDescription1>), Error> {
         use Buffer::*;
 
         let buffer;
         let mut description = None;
-        let mut guard = Some(guard);
         loop {
             match connection.message().await? {
                 ServerMessage::StateDataDescription(d) => {
@@ -110,6 +304,15 @@ where
                 }
             }
         }
+        Ok((buffer, description))
+    }
+    fn finish(
+        connection: ConnectionRef<'a>,
+        buffer: Buffer,
+        description: Option<CommandDataDescription1>,
+        out_desc: &Typedesc,
+        guard: Option<Guard>,
+    ) -> Result<ResponseStream<'a, T>, Error> {
         let warnings = description
             .as_ref()
             .map(|d| annotations::decode_warnings(&d.annotations))
@@ -280,6 +483,37 @@ where
     pub fn warnings(&self) -> &[Warning] {
         &self.warnings
     }
+    /// Convert into a stream of decoded elements
+    ///
+    /// Errors received after the last element (including decoding errors)
+    /// are yielded as the last item of the stream.
+    pub fn into_stream(self) -> impl Stream<Item = Result<T, Error>> + 'a
+    where
+        T: 'a,
+    {
+        self.into_stream_with(Ok)
+    }
+    /// Convert into a stream of decoded elements, passing the completed
+    /// response through `complete`
+    ///
+    /// An error returned by `complete` is yielded as the last item of the
+    /// stream.
+    pub fn into_stream_with<F>(self, complete: F) -> impl Stream<Item = Result<T, Error>> + 'a
+    where
+        T: 'a,
+        F: FnOnce(Response<()>) -> Result<Response<()>, Error> + 'a,
+    {
+        stream::unfold(Some((self, complete)), |state| async move {
+            let (mut response, complete) = state?;
+            match response.next_element().await {
+                Some(element) => Some((Ok(element), Some((response, complete)))),
+                None => match response.process_complete().await.and_then(complete) {
+                    Ok(_) => None,
+                    Err(e) => Some((Err(e), None)),
+                },
+            }
+        })
+    }
     pub async fn complete(mut self) -> Result<Response<()>, Error> {
         self.process_complete().await
     }
@@ -302,7 +536,7 @@ where
                     data: (),
                     warnings,
                 };
-                response.log_warnings();
+                response.log_warnings(self.connection.observer());
                 Ok(response)
             }
             Error(e) => Err(e),
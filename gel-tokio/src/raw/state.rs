@@ -8,7 +8,7 @@ use gel_protocol::client_message::State as EncodedState;
 use gel_protocol::descriptors::{RawTypedesc, StateBorrow};
 use gel_protocol::model::Uuid;
 use gel_protocol::query_arg::QueryArg;
-use gel_protocol::value::Value;
+use gel_protocol::value::{SparseObject, Value};
 
 use crate::errors::{ClientError, Error, ErrorKind, ProtocolEncodingError};
 
@@ -379,6 +379,65 @@ impl PoolState {
             cache: ArcSwapOption::new(None),
         }
     }
+    /// Decode the state returned by the server after executing a query
+    ///
+    /// Received state is also used as a cache of encoded state, so it's sent
+    /// back to the server as is.
+    pub fn decode(desc: &RawTypedesc, state: &EncodedState) -> Result<PoolState, Error> {
+        if state.typedesc_id != desc.id {
+            return Err(ProtocolEncodingError::with_message(
+                "state doesn't match state descriptor",
+            ));
+        }
+        let typedesc = desc.decode().map_err(ProtocolEncodingError::with_source)?;
+        let codec = typedesc
+            .build_codec()
+            .map_err(ProtocolEncodingError::with_source)?;
+        let value = codec
+            .decode(&state.data)
+            .map_err(ProtocolEncodingError::with_source)?;
+        let Value::SparseObject(root) = value else {
+            return Err(invalid_state("state"));
+        };
+        let mut module = None;
+        let mut aliases = BTreeMap::new();
+        let mut config = BTreeMap::new();
+        let mut globals = BTreeMap::new();
+        for (name, value) in root.pairs() {
+            match (name, value) {
+                (_, None) => {}
+                ("module", Some(Value::Str(name))) => module = Some(name.clone()),
+                ("aliases", Some(Value::Array(items))) => {
+                    for item in items {
+                        let Value::Tuple(pair) = item else {
+                            return Err(invalid_state("aliases"));
+                        };
+                        let [Value::Str(alias), Value::Str(module)] = &pair[..] else {
+                            return Err(invalid_state("aliases"));
+                        };
+                        aliases.insert(alias.clone(), module.clone());
+                    }
+                }
+                ("config", Some(Value::SparseObject(obj))) => config = variables(obj),
+                ("globals", Some(Value::SparseObject(obj))) => globals = variables(obj),
+                ("module" | "aliases" | "config" | "globals", Some(_)) => {
+                    return Err(invalid_state(name));
+                }
+                _ => {}
+            }
+        }
+        Ok(PoolState {
+            raw_state: RawState {
+                common: Arc::new(CommonState {
+                    module,
+                    aliases,
+                    config,
+                }),
+                globals,
+            },
+            cache: ArcSwapOption::new(Some(Arc::new(state.clone()))),
+        })
+    }
     pub fn encode(&self, desc: &RawTypedesc) -> Result<EncodedState, Error> {
         if let Some(cache) = &*self.cache.load() {
             if cache.typedesc_id == desc.id {
@@ -397,6 +456,16 @@ impl PoolState {
     }
 }
 
+fn invalid_state(field: &str) -> Error {
+    ProtocolEncodingError::with_message(format!("invalid `{field}` in state returned by server"))
+}
+
+fn variables(obj: &SparseObject) -> BTreeMap<String, Value> {
+    obj.pairs()
+        .map(|(name, value)| (name.into(), value.cloned().unwrap_or(Value::Nothing)))
+        .collect()
+}
+
 impl SealedState for &PoolState {
     fn encode(&self, desc: &RawTypedesc) -> Result<EncodedState, Error> {
         PoolState::encode(self, desc)
@@ -55,6 +55,16 @@ impl ServerParam for SystemConfig {
 
 impl SealedParam for SystemConfig {}
 
+/// Number of connections the server suggests a client pool should use
+#[derive(Debug)]
+pub struct SuggestedPoolConcurrency(pub usize);
+
+impl ServerParam for SuggestedPoolConcurrency {
+    type Value = SuggestedPoolConcurrency;
+}
+
+impl SealedParam for SuggestedPoolConcurrency {}
+
 impl ServerParams {
     pub fn new() -> ServerParams {
         ServerParams(HashMap::new())
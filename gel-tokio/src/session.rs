@@ -0,0 +1,380 @@
+use std::sync::Arc;
+
+use gel_protocol::common::{Capabilities, Cardinality, InputLanguage, IoFormat};
+use gel_protocol::model::Json;
+use gel_protocol::query_arg::QueryArgs;
+use gel_protocol::QueryResult;
+
+use crate::client::single_row;
+use crate::errors::{Error, ErrorKind, NoDataError};
+use crate::raw::{Options, PoolConnection, PoolState, Response};
+use crate::state::{AliasesDelta, ConfigDelta, GlobalsDelta};
+use crate::state::{AliasesModifier, ConfigModifier, Fn, GlobalsModifier};
+use crate::{pinned, ResultVerbose};
+
+/// A session pinned to a single connection of the pool
+///
+/// Obtained with [`Client::session()`](crate::Client::session). Unlike
+/// [`Client`](crate::Client), which picks an arbitrary connection from the
+/// pool for every query, all queries of the session are executed on the
+/// same connection.
+///
+/// Queries can change the session state (e.g. `SET MODULE`, `SET GLOBAL` or
+/// `CONFIGURE SESSION`). The state returned by the database is used for the
+/// subsequent queries of this session only.
+///
+/// Queries are not retried, as the connection can't be replaced in the
+/// middle of the session.
+///
+/// Implements all query & execute functions as [Client](crate::Client) as well as
+/// [QueryExecutor](crate::QueryExecutor).
+///
+/// When the session is dropped, the connection is returned to the pool. If
+/// the session state was changed by a query, the state is reset on the
+/// connection when it's acquired from the pool next time.
+#[derive(Debug)]
+pub struct Session {
+    options: Arc<Options>,
+    conn: PoolConnection,
+    /// Session state was changed by a query
+    state_changed: bool,
+}
+
+impl Session {
+    pub(crate) fn new(options: Arc<Options>, conn: PoolConnection) -> Self {
+        Session {
+            options,
+            conn,
+            state_changed: false,
+        }
+    }
+
+    fn with_state(mut self, f: impl FnOnce(&PoolState) -> PoolState) -> Self {
+        let state = Arc::new(f(&self.options.state));
+        Arc::make_mut(&mut self.options).state = state;
+        self
+    }
+
+    async fn query_helper<R, A>(
+        &mut self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+        input_language: InputLanguage,
+        io_format: IoFormat,
+        cardinality: Cardinality,
+    ) -> Result<Response<Vec<R>>, Error>
+    where
+        A: QueryArgs,
+        R: QueryResult,
+    {
+        let response = pinned::query(
+            &mut self.conn,
+            &mut self.options,
+            query.as_ref(),
+            arguments,
+            Capabilities::MODIFICATIONS | Capabilities::DDL | Capabilities::SESSION_CONFIG,
+            input_language,
+            io_format,
+            cardinality,
+        )
+        .await?;
+        self.state_changed |= response.new_state.is_some();
+        Ok(response)
+    }
+
+    async fn execute_helper<A>(
+        &mut self,
+        query: &str,
+        arguments: &A,
+        input_language: InputLanguage,
+    ) -> Result<(), Error>
+    where
+        A: QueryArgs,
+    {
+        let response = pinned::execute(
+            &mut self.conn,
+            &mut self.options,
+            query,
+            arguments,
+            Capabilities::MODIFICATIONS | Capabilities::DDL | Capabilities::SESSION_CONFIG,
+            input_language,
+        )
+        .await?;
+        self.state_changed |= response.new_state.is_some();
+        Ok(())
+    }
+
+    /// Execute a query and return a collection of results.
+    ///
+    /// See [`Client::query`](crate::Client::query) for details.
+    pub async fn query<R, A>(
+        &mut self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> Result<Vec<R>, Error>
+    where
+        A: QueryArgs,
+        R: QueryResult,
+    {
+        self.query_helper(
+            query,
+            arguments,
+            InputLanguage::EdgeQL,
+            IoFormat::Binary,
+            Cardinality::Many,
+        )
+        .await
+        .map(|x| x.data)
+    }
+
+    /// Execute a query and return a collection of results and warnings produced by the server.
+    ///
+    /// See [`Client::query_verbose`](crate::Client::query_verbose) for
+    /// details.
+    pub async fn query_verbose<R, A>(
+        &mut self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> Result<ResultVerbose<Vec<R>>, Error>
+    where
+        A: QueryArgs,
+        R: QueryResult,
+    {
+        self.query_helper(
+            query,
+            arguments,
+            InputLanguage::EdgeQL,
+            IoFormat::Binary,
+            Cardinality::Many,
+        )
+        .await
+        .map(|Response { data, warnings, .. }| ResultVerbose { data, warnings })
+    }
+
+    /// Execute a query and return a single result
+    ///
+    /// See [`Client::query_single`](crate::Client::query_single) for
+    /// details.
+    pub async fn query_single<R, A>(
+        &mut self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> Result<Option<R>, Error>
+    where
+        A: QueryArgs,
+        R: QueryResult + Send,
+    {
+        self.query_helper(
+            query,
+            arguments,
+            InputLanguage::EdgeQL,
+            IoFormat::Binary,
+            Cardinality::AtMostOne,
+        )
+        .await
+        .map(|x| x.data.into_iter().next())
+    }
+
+    /// Execute a query and return a single result
+    ///
+    /// See [`Client::query_required_single`](crate::Client::query_required_single)
+    /// for details.
+    pub async fn query_required_single<R, A>(
+        &mut self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> Result<R, Error>
+    where
+        A: QueryArgs,
+        R: QueryResult + Send,
+    {
+        self.query_single(query, arguments)
+            .await?
+            .ok_or_else(|| NoDataError::with_message("query row returned zero results"))
+    }
+
+    /// Execute a query and return the result as JSON.
+    pub async fn query_json(
+        &mut self,
+        query: &str,
+        arguments: &impl QueryArgs,
+    ) -> Result<Json, Error> {
+        let res = self
+            .query_helper::<String, _>(
+                query,
+                arguments,
+                InputLanguage::EdgeQL,
+                IoFormat::Json,
+                Cardinality::Many,
+            )
+            .await?;
+
+        let json = res
+            .data
+            .into_iter()
+            .next()
+            .ok_or_else(|| NoDataError::with_message("query row returned zero results"))?;
+
+        // we trust database to produce valid json
+        Ok(Json::new_unchecked(json))
+    }
+
+    /// Execute a query and return a single result as JSON.
+    ///
+    /// See [`Client::query_single_json`](crate::Client::query_single_json)
+    /// for details.
+    pub async fn query_single_json(
+        &mut self,
+        query: &str,
+        arguments: &impl QueryArgs,
+    ) -> Result<Option<Json>, Error> {
+        let res = self
+            .query_helper::<String, _>(
+                query,
+                arguments,
+                InputLanguage::EdgeQL,
+                IoFormat::Json,
+                Cardinality::AtMostOne,
+            )
+            .await?;
+
+        // we trust database to produce valid json
+        Ok(res.data.into_iter().next().map(Json::new_unchecked))
+    }
+
+    /// Execute a query and return a single result as JSON.
+    ///
+    /// See
+    /// [`Client::query_required_single_json`](crate::Client::query_required_single_json)
+    /// for details.
+    pub async fn query_required_single_json(
+        &mut self,
+        query: &str,
+        arguments: &impl QueryArgs,
+    ) -> Result<Json, Error> {
+        self.query_single_json(query, arguments)
+            .await?
+            .ok_or_else(|| NoDataError::with_message("query row returned zero results"))
+    }
+
+    /// Execute a query and don't expect result
+    pub async fn execute<A>(&mut self, query: &str, arguments: &A) -> Result<(), Error>
+    where
+        A: QueryArgs,
+    {
+        self.execute_helper(query, arguments, InputLanguage::EdgeQL)
+            .await
+    }
+
+    /// Execute an SQL query and return a collection of rows.
+    ///
+    /// See [`Client::query_sql`](crate::Client::query_sql) for details.
+    pub async fn query_sql<R, A>(
+        &mut self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> Result<Vec<R>, Error>
+    where
+        A: QueryArgs,
+        R: QueryResult,
+    {
+        self.query_helper(
+            query,
+            arguments,
+            InputLanguage::SQL,
+            IoFormat::Binary,
+            Cardinality::Many,
+        )
+        .await
+        .map(|x| x.data)
+    }
+
+    /// Execute an SQL query and return a single row.
+    ///
+    /// See [`Client::query_sql_single`](crate::Client::query_sql_single) for
+    /// details.
+    pub async fn query_sql_single<R, A>(
+        &mut self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> Result<Option<R>, Error>
+    where
+        A: QueryArgs,
+        R: QueryResult + Send,
+    {
+        let rows = self.query_sql(query, arguments).await?;
+        single_row(rows)
+    }
+
+    /// Execute an SQL query and don't expect result
+    ///
+    /// See [`Client::query_sql`](crate::Client::query_sql) for details.
+    pub async fn execute_sql<A>(&mut self, query: &str, arguments: &A) -> Result<(), Error>
+    where
+        A: QueryArgs,
+    {
+        self.execute_helper(query, arguments, InputLanguage::SQL)
+            .await
+    }
+
+    /// Returns the session with the specified global variables set
+    ///
+    /// Variables are set for the subsequent queries of this session. See
+    /// [`Client::with_globals`](crate::Client::with_globals) for details.
+    pub fn with_globals(self, globals: impl GlobalsDelta) -> Self {
+        self.with_state(|s| s.with_globals(globals))
+    }
+
+    /// Returns the session with the specified global variables set
+    ///
+    /// This is equivalent to `.with_globals(Fn(f))` but more ergonomic as it
+    /// allows type inference for lambda.
+    pub fn with_globals_fn(self, f: impl FnOnce(&mut GlobalsModifier)) -> Self {
+        self.with_state(|s| s.with_globals(Fn(f)))
+    }
+
+    /// Returns the session with the specified aliases set
+    pub fn with_aliases(self, aliases: impl AliasesDelta) -> Self {
+        self.with_state(|s| s.with_aliases(aliases))
+    }
+
+    /// Returns the session with the specified aliases set
+    ///
+    /// This is equivalent to `.with_aliases(Fn(f))` but more ergonomic as it
+    /// allows type inference for lambda.
+    pub fn with_aliases_fn(self, f: impl FnOnce(&mut AliasesModifier)) -> Self {
+        self.with_state(|s| s.with_aliases(Fn(f)))
+    }
+
+    /// Returns the session with the default module set or unset
+    pub fn with_default_module(self, module: Option<impl Into<String>>) -> Self {
+        self.with_state(|s| s.with_default_module(module.map(|m| m.into())))
+    }
+
+    /// Returns the session with the specified config
+    ///
+    /// Settings are applied to the subsequent queries of this session. See
+    /// [`Client::with_config`](crate::Client::with_config) for details.
+    pub fn with_config(self, cfg: impl ConfigDelta) -> Self {
+        self.with_state(|s| s.with_config(cfg))
+    }
+
+    /// Returns the session with the specified config
+    ///
+    /// This is equivalent to `.with_config(Fn(f))` but more ergonomic as it
+    /// allows type inference for lambda.
+    pub fn with_config_fn(self, f: impl FnOnce(&mut ConfigModifier)) -> Self {
+        self.with_state(|s| s.with_config(Fn(f)))
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        // The server keeps the state of the last query on the connection,
+        // so the pool replaces it by the default one before the connection
+        // is used by anyone else
+        if self.state_changed && self.conn.is_consistent() {
+            self.conn.mark_state_changed();
+        }
+    }
+}
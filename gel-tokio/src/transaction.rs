@@ -1,19 +1,21 @@
+use std::collections::VecDeque;
 use std::future::Future;
 use std::sync::Arc;
 
-use bytes::BytesMut;
-use gel_protocol::common::CompilationOptions;
+use futures_util::Stream;
+use gel_errors::fields::{BatchIndex, QueryText};
 use gel_protocol::common::{Capabilities, Cardinality, InputLanguage, IoFormat};
 use gel_protocol::model::Json;
-use gel_protocol::query_arg::{Encoder, QueryArgs};
+use gel_protocol::query_arg::QueryArgs;
 use gel_protocol::QueryResult;
 use tokio::sync::oneshot;
 use tokio::time::sleep;
 
+use crate::client::{single_row, with_query_timeout};
 use crate::errors::{Error, ErrorKind, SHOULD_RETRY};
-use crate::errors::{NoDataError, ProtocolEncodingError};
-use crate::raw::{Options, Pool, PoolConnection, Response};
-use crate::ResultVerbose;
+use crate::errors::{InvalidArgumentError, NoDataError};
+use crate::raw::{Options, Pool, PoolConnection, PoolState, Response};
+use crate::{pinned, ResultVerbose};
 
 /// A representation of a transaction.
 ///
@@ -23,12 +25,52 @@ use crate::ResultVerbose;
 ///
 /// Implements all query & execute functions as [Client](crate::Client) as well as
 /// [QueryExecutor](crate::QueryExecutor).
+///
+/// Unlike on a [Client](crate::Client), queries can change the session state
+/// (e.g. `SET MODULE`, `SET GLOBAL` or `CONFIGURE SESSION`). The state
+/// returned by the database is used for the subsequent queries of this
+/// transaction, and is reverted when rolling back to a savepoint.
 #[derive(Debug)]
 pub struct Transaction {
     options: Arc<Options>,
     conn: PoolConnection,
 
     started: bool,
+    /// Savepoints dropped without release or rollback, to be rolled back
+    /// before the next statement, with the state to restore
+    pending_rollbacks: VecDeque<(String, Arc<PoolState>)>,
+    /// Retryable error that occurred in this transaction, even if the
+    /// caller recovered from it by rolling back to a savepoint
+    retry_error: Option<Error>,
+}
+
+/// A savepoint within a [`Transaction`]
+///
+/// Returned by [`Transaction::savepoint()`]. Dereferences to the
+/// transaction, so all query & execute functions can be called on this
+/// object (including creating nested savepoints).
+///
+/// When this object is dropped without calling [`release`](Savepoint::release)
+/// or [`rollback`](Savepoint::rollback), the transaction implicitly rolls
+/// back to the savepoint. The rollback is sent to the database before the
+/// next statement in the transaction.
+#[derive(Debug)]
+pub struct Savepoint<'a> {
+    transaction: &'a mut Transaction,
+    name: String,
+    /// Session state at the time the savepoint was declared
+    state: Arc<PoolState>,
+    done: bool,
+}
+
+/// A batch of statements executed in a single round trip
+///
+/// Returned by [`Transaction::batch()`]. Statements are added with
+/// [`add`](Batch::add) and sent to the database together by
+/// [`execute`](Batch::execute).
+pub struct Batch<'a> {
+    transaction: &'a mut Transaction,
+    items: Vec<(String, Box<dyn QueryArgs + 'a>)>,
 }
 
 /// Transaction object returned by [`Client::transaction_raw()`](crate::Client::transaction_raw) method.
@@ -149,6 +191,128 @@ impl Drop for RetryingTransaction {
     }
 }
 
+impl Savepoint<'_> {
+    /// Name of the savepoint
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Release the savepoint, keeping the changes made after it.
+    pub async fn release(mut self) -> Result<(), Error> {
+        self.done = true;
+        self.transaction.rollback_pending().await?;
+        log::trace!("release savepoint {}", self.name);
+        let statement = format!("RELEASE SAVEPOINT {}", self.name);
+        let options = &self.transaction.options;
+        self.transaction
+            .conn
+            .statement(&statement, &options.state, &options.annotations)
+            .await
+    }
+
+    /// Roll back the changes made after the savepoint.
+    pub async fn rollback(mut self) -> Result<(), Error> {
+        self.done = true;
+        self.transaction.rollback_pending().await?;
+        log::trace!("rollback to savepoint {}", self.name);
+        let statement = format!("ROLLBACK TO SAVEPOINT {}", self.name);
+        let options = &self.transaction.options;
+        self.transaction
+            .conn
+            .statement(&statement, &options.state, &options.annotations)
+            .await?;
+        self.transaction.set_state(self.state.clone());
+        Ok(())
+    }
+}
+
+impl std::ops::Deref for Savepoint<'_> {
+    type Target = Transaction;
+
+    fn deref(&self) -> &Self::Target {
+        self.transaction
+    }
+}
+
+impl std::ops::DerefMut for Savepoint<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.transaction
+    }
+}
+
+impl<'a> Batch<'a> {
+    /// Add a statement to the batch.
+    ///
+    /// Unlike [`Transaction::execute`] the arguments are taken by value,
+    /// as they are only encoded when the batch is executed.
+    pub fn add(&mut self, query: impl Into<String>, arguments: impl QueryArgs + 'a) -> &mut Self {
+        self.items.push((query.into(), Box::new(arguments)));
+        self
+    }
+
+    /// Returns the number of statements in the batch.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if no statements were added to the batch.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Execute all statements of the batch.
+    ///
+    /// Statements are executed in order, and execution stops at the first
+    /// failed one. Position of the failed statement in the batch can be
+    /// obtained from the error using
+    /// [`BatchIndex`](gel_errors::fields::BatchIndex) field:
+    ///
+    /// ```rust,ignore
+    /// if let Some(index) = err.get::<gel_errors::fields::BatchIndex>() {
+    ///     println!("statement {index} failed");
+    /// }
+    /// ```
+    pub async fn execute(self) -> Result<(), Error> {
+        let items = self
+            .items
+            .iter()
+            .map(|(query, arguments)| (query.as_str(), &**arguments as &dyn QueryArgs))
+            .collect::<Vec<_>>();
+        let tran = self.transaction;
+        tran.ensure_started().await?;
+        let batch = tran.conn.inner().execute_batch(
+            &items,
+            &tran.options.state,
+            &tran.options.annotations,
+            Capabilities::MODIFICATIONS,
+        );
+        let result = with_query_timeout(tran.options.query_timeout, batch).await;
+        tran.track_error(result)
+    }
+}
+
+impl std::fmt::Debug for Batch<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Batch")
+            .field("transaction", &self.transaction)
+            .field(
+                "queries",
+                &self.items.iter().map(|(q, _)| q).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Drop for Savepoint<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.transaction
+                .pending_rollbacks
+                .push_back((std::mem::take(&mut self.name), self.state.clone()));
+        }
+    }
+}
+
 pub(crate) async fn run_and_retry<T, B, F>(
     pool: &Pool,
     options: Arc<Options>,
@@ -171,10 +335,18 @@ where
             result_tx: Some(tx),
         };
         let result = body(tran).await;
-        let tran = rx.try_recv().expect(
+        let mut tran = rx.try_recv().expect(
             "Transaction object must \
             be dropped by the time transaction body finishes.",
         );
+        let result = match (result, tran.retry_error.take()) {
+            (Ok(_), Some(e)) => {
+                // Error was handled by rolling back to a savepoint, but
+                // the transaction as a whole has to be retried anyway
+                Err(e)
+            }
+            (result, _) => result,
+        };
         match result {
             Ok(val) => {
                 log::debug!("Comitting transaction");
@@ -205,7 +377,9 @@ where
                     } else {
                         log::info!("Retrying transaction on {:#}", e);
                         iteration += 1;
-                        sleep((rule.backoff)(iteration)).await;
+                        let duration = (rule.backoff)(iteration);
+                        pool.observer().retry(iteration, duration, e);
+                        sleep(duration).await;
                         continue 'transaction;
                     }
                 }
@@ -220,31 +394,66 @@ impl Transaction {
             options,
             conn,
             started: false,
+            pending_rollbacks: VecDeque::new(),
+            retry_error: None,
         }
     }
 
     async fn ensure_started(&mut self) -> anyhow::Result<(), Error> {
         if !self.started {
             let options = &self.options;
+            let statement = options.transaction.start_statement(self.conn.proto())?;
             self.conn
-                .statement("START TRANSACTION", &options.state, &options.annotations)
+                .statement(&statement, &options.state, &options.annotations)
                 .await?;
             self.started = true;
         }
+        self.rollback_pending().await
+    }
+
+    async fn rollback_pending(&mut self) -> Result<(), Error> {
+        while let Some((name, _)) = self.pending_rollbacks.front() {
+            log::trace!("rollback to savepoint {name}");
+            let statement = format!("ROLLBACK TO SAVEPOINT {name}");
+            let options = &self.options;
+            self.conn
+                .statement(&statement, &options.state, &options.annotations)
+                .await?;
+            let (_, state) = self
+                .pending_rollbacks
+                .pop_front()
+                .expect("rollback is pending");
+            self.set_state(state);
+        }
         Ok(())
     }
 
+    fn set_state(&mut self, state: Arc<PoolState>) {
+        Arc::make_mut(&mut self.options).state = state;
+    }
+
+    fn track_error<T>(&mut self, result: Result<T, Error>) -> Result<T, Error> {
+        if let Err(e) = &result {
+            if e.has_tag(SHOULD_RETRY) && self.retry_error.is_none() {
+                self.retry_error = Some(copy_error(e));
+            }
+        }
+        result
+    }
+
     async fn commit(mut self) -> anyhow::Result<(), Error> {
         if !self.started {
             log::trace!("transaction was never started, noop commit");
             return Ok(());
         }
 
+        self.rollback_pending().await?;
         log::trace!("commit");
         let options = &self.options;
         self.conn
             .statement("COMMIT", &options.state, &options.annotations)
             .await?;
+        self.conn.inner_ref().observer().transaction_committed();
         Ok(())
     }
 
@@ -254,11 +463,18 @@ impl Transaction {
             return Ok(());
         }
 
+        if !self.conn.is_consistent() {
+            // Connection is going to be closed (e.g. a query has timed
+            // out), which rolls back the transaction on the server
+            log::trace!("connection is broken, noop rollback");
+            return Ok(());
+        }
         log::trace!("rollback");
         let options = &self.options;
         self.conn
             .statement("ROLLBACK", &options.state, &options.annotations)
             .await?;
+        self.conn.inner_ref().observer().transaction_rolled_back();
         Ok(())
     }
 
@@ -266,6 +482,7 @@ impl Transaction {
         &mut self,
         query: impl AsRef<str> + Send,
         arguments: &A,
+        input_language: InputLanguage,
         io_format: IoFormat,
         cardinality: Cardinality,
     ) -> Result<Response<Vec<R>>, Error>
@@ -274,19 +491,18 @@ impl Transaction {
         R: QueryResult,
     {
         self.ensure_started().await?;
-
-        self.conn
-            .inner()
-            .query(
-                query.as_ref(),
-                arguments,
-                &self.options.state,
-                &self.options.annotations,
-                Capabilities::MODIFICATIONS,
-                io_format,
-                cardinality,
-            )
-            .await
+        let result = pinned::query(
+            &mut self.conn,
+            &mut self.options,
+            query.as_ref(),
+            arguments,
+            Capabilities::MODIFICATIONS | Capabilities::SESSION_CONFIG,
+            input_language,
+            io_format,
+            cardinality,
+        )
+        .await;
+        self.track_error(result)
     }
 
     /// Execute a query and return a collection of results.
@@ -311,9 +527,46 @@ impl Transaction {
         A: QueryArgs,
         R: QueryResult,
     {
-        self.query_helper(query, arguments, IoFormat::Binary, Cardinality::Many)
-            .await
-            .map(|x| x.data)
+        self.query_helper(
+            query,
+            arguments,
+            InputLanguage::EdgeQL,
+            IoFormat::Binary,
+            Cardinality::Many,
+        )
+        .await
+        .map(|x| x.data)
+    }
+
+    /// Execute a query and return a stream of results.
+    ///
+    /// Results are decoded as they arrive from the server. The stream
+    /// borrows the transaction until it's dropped; if it's dropped before
+    /// it is exhausted, the transaction can't be used anymore and any
+    /// subsequent query returns an error.
+    ///
+    /// See [`Client::query_stream`](crate::Client::query_stream) for
+    /// details.
+    pub async fn query_stream<'a, R, A>(
+        &'a mut self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> Result<impl Stream<Item = Result<R, Error>> + 'a, Error>
+    where
+        A: QueryArgs,
+        R: QueryResult + 'a,
+        R::State: Unpin,
+    {
+        self.ensure_started().await?;
+        let response = self.conn.inner().query_stream(
+            query.as_ref(),
+            arguments,
+            &self.options.state,
+            &self.options.annotations,
+            Capabilities::MODIFICATIONS,
+        );
+        let response = with_query_timeout(self.options.query_timeout, response).await?;
+        Ok(response.into_stream())
     }
 
     /// Execute a query and return a collection of results and warnings produced by the server.
@@ -336,9 +589,15 @@ impl Transaction {
         A: QueryArgs,
         R: QueryResult,
     {
-        self.query_helper(query, arguments, IoFormat::Binary, Cardinality::Many)
-            .await
-            .map(|Response { data, warnings, .. }| ResultVerbose { data, warnings })
+        self.query_helper(
+            query,
+            arguments,
+            InputLanguage::EdgeQL,
+            IoFormat::Binary,
+            Cardinality::Many,
+        )
+        .await
+        .map(|Response { data, warnings, .. }| ResultVerbose { data, warnings })
     }
 
     /// Execute a query and return a single result
@@ -375,9 +634,15 @@ impl Transaction {
         A: QueryArgs,
         R: QueryResult + Send,
     {
-        self.query_helper(query, arguments, IoFormat::Binary, Cardinality::AtMostOne)
-            .await
-            .map(|x| x.data.into_iter().next())
+        self.query_helper(
+            query,
+            arguments,
+            InputLanguage::EdgeQL,
+            IoFormat::Binary,
+            Cardinality::AtMostOne,
+        )
+        .await
+        .map(|x| x.data.into_iter().next())
     }
 
     /// Execute a query and return a single result
@@ -414,14 +679,20 @@ impl Transaction {
         A: QueryArgs,
         R: QueryResult + Send,
     {
-        self.query_helper(query, arguments, IoFormat::Binary, Cardinality::AtMostOne)
-            .await
-            .and_then(|x| {
-                x.data
-                    .into_iter()
-                    .next()
-                    .ok_or_else(|| NoDataError::with_message("query row returned zero results"))
-            })
+        self.query_helper(
+            query,
+            arguments,
+            InputLanguage::EdgeQL,
+            IoFormat::Binary,
+            Cardinality::AtMostOne,
+        )
+        .await
+        .and_then(|x| {
+            x.data
+                .into_iter()
+                .next()
+                .ok_or_else(|| NoDataError::with_message("query row returned zero results"))
+        })
     }
 
     /// Execute a query and return the result as JSON.
@@ -431,7 +702,13 @@ impl Transaction {
         arguments: &impl QueryArgs,
     ) -> Result<Json, Error> {
         let res = self
-            .query_helper::<String, _>(query, arguments, IoFormat::Json, Cardinality::Many)
+            .query_helper::<String, _>(
+                query,
+                arguments,
+                InputLanguage::EdgeQL,
+                IoFormat::Json,
+                Cardinality::Many,
+            )
             .await?;
 
         let json = res
@@ -456,7 +733,13 @@ impl Transaction {
         arguments: &impl QueryArgs,
     ) -> Result<Option<Json>, Error> {
         let res = self
-            .query_helper::<String, _>(query, arguments, IoFormat::Json, Cardinality::AtMostOne)
+            .query_helper::<String, _>(
+                query,
+                arguments,
+                InputLanguage::EdgeQL,
+                IoFormat::Json,
+                Cardinality::AtMostOne,
+            )
             .await?;
 
         // we trust database to produce valid json
@@ -489,40 +772,163 @@ impl Transaction {
     where
         A: QueryArgs,
     {
-        self.ensure_started().await?;
-        let flags = CompilationOptions {
-            implicit_limit: None,
-            implicit_typenames: false,
-            implicit_typeids: false,
-            explicit_objectids: true,
-            allow_capabilities: Capabilities::MODIFICATIONS,
-            input_language: InputLanguage::EdgeQL,
-            io_format: IoFormat::Binary,
-            expected_cardinality: Cardinality::Many,
-        };
-        let state = &self.options.state;
-        let desc = self
-            .conn
-            .parse(&flags, query, state, &self.options.annotations)
-            .await?;
-        let inp_desc = desc.input().map_err(ProtocolEncodingError::with_source)?;
+        self.execute_helper(query, arguments, InputLanguage::EdgeQL)
+            .await
+    }
 
-        let mut arg_buf = BytesMut::with_capacity(8);
-        arguments.encode(&mut Encoder::new(
-            &inp_desc.as_query_arg_context(),
-            &mut arg_buf,
-        ))?;
+    async fn execute_helper<A>(
+        &mut self,
+        query: &str,
+        arguments: &A,
+        input_language: InputLanguage,
+    ) -> Result<(), Error>
+    where
+        A: QueryArgs,
+    {
+        self.ensure_started().await?;
+        let result = pinned::execute(
+            &mut self.conn,
+            &mut self.options,
+            query,
+            arguments,
+            Capabilities::MODIFICATIONS | Capabilities::SESSION_CONFIG,
+            input_language,
+        )
+        .await;
+        self.track_error(result).map(|_| ())
+    }
 
+    /// Declare a savepoint in this transaction.
+    ///
+    /// Changes made after the savepoint can be rolled back without aborting
+    /// the whole transaction:
+    ///
+    /// ```rust,ignore
+    /// let mut savepoint = tx.savepoint("before_insert").await?;
+    /// match savepoint.execute("INSERT User { name := 'x' }", &()).await {
+    ///     Ok(()) => savepoint.release().await?,
+    ///     Err(_) => savepoint.rollback().await?,
+    /// }
+    /// ```
+    ///
+    /// The name must be a valid identifier (letters, digits and
+    /// underscores).
+    ///
+    /// If a retryable error (such as a transaction conflict) occurs, rolling
+    /// back to a savepoint doesn't prevent the whole transaction from being
+    /// retried by [`Client::transaction`](crate::Client::transaction).
+    pub async fn savepoint(&mut self, name: impl Into<String>) -> Result<Savepoint<'_>, Error> {
+        let name = name.into();
+        let valid = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(InvalidArgumentError::with_message(format!(
+                "invalid savepoint name {name:?}"
+            )));
+        }
+        self.ensure_started().await?;
+        log::trace!("declare savepoint {name}");
+        let statement = format!("DECLARE SAVEPOINT {name}");
+        let options = &self.options;
         self.conn
-            .execute(
-                &flags,
-                query,
-                state,
-                &self.options.annotations,
-                &desc,
-                &arg_buf.freeze(),
-            )
+            .statement(&statement, &options.state, &options.annotations)
             .await?;
-        Ok(())
+        Ok(Savepoint {
+            state: self.options.state.clone(),
+            transaction: self,
+            name,
+            done: false,
+        })
+    }
+
+    /// Create a batch of statements to be executed in a single round trip.
+    ///
+    /// This is useful for bulk modifications, where sending statements
+    /// one by one would be dominated by network latency:
+    ///
+    /// ```rust,ignore
+    /// let mut batch = tx.batch();
+    /// for name in names {
+    ///     batch.add("INSERT User { name := <str>$0 }", (name,));
+    /// }
+    /// batch.execute().await?;
+    /// ```
+    pub fn batch(&mut self) -> Batch<'_> {
+        Batch {
+            transaction: self,
+            items: Vec::new(),
+        }
+    }
+
+    /// Execute an SQL query and return a collection of rows.
+    ///
+    /// See [`Client::query_sql`](crate::Client::query_sql) for details.
+    pub async fn query_sql<R, A>(
+        &mut self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> Result<Vec<R>, Error>
+    where
+        A: QueryArgs,
+        R: QueryResult,
+    {
+        self.query_helper(
+            query,
+            arguments,
+            InputLanguage::SQL,
+            IoFormat::Binary,
+            Cardinality::Many,
+        )
+        .await
+        .map(|x| x.data)
+    }
+
+    /// Execute an SQL query and return a single row.
+    ///
+    /// See [`Client::query_sql_single`](crate::Client::query_sql_single) for
+    /// details.
+    pub async fn query_sql_single<R, A>(
+        &mut self,
+        query: impl AsRef<str> + Send,
+        arguments: &A,
+    ) -> Result<Option<R>, Error>
+    where
+        A: QueryArgs,
+        R: QueryResult + Send,
+    {
+        let rows = self.query_sql(query, arguments).await?;
+        single_row(rows)
+    }
+
+    /// Execute an SQL query and don't expect result
+    ///
+    /// See [`Client::query_sql`](crate::Client::query_sql) for details.
+    pub async fn execute_sql<A>(&mut self, query: &str, arguments: &A) -> Result<(), Error>
+    where
+        A: QueryArgs,
+    {
+        self.execute_helper(query, arguments, InputLanguage::SQL)
+            .await
+    }
+}
+
+/// Copies the kind, messages, headers and query fields of the error
+///
+/// [`Error`] isn't `Clone`, as the source error can't be cloned. Errors
+/// tracked for retries come from the server, so don't have one.
+fn copy_error(e: &Error) -> Error {
+    let mut error = Error::from_code(e.code()).with_headers(e.headers().clone());
+    for message in e.initial_message().into_iter().chain(e.contexts()) {
+        error = error.context(message.to_string());
+    }
+    if let Some(query) = e.get::<QueryText>() {
+        error = error.set::<QueryText>(query.clone());
+    }
+    if let Some(index) = e.get::<BatchIndex>() {
+        error = error.set::<BatchIndex>(*index);
     }
+    error
 }
@@ -0,0 +1,533 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::future::try_join_all;
+use futures_util::TryStreamExt;
+use gel_errors::fields::{BatchIndex, QueryText};
+use gel_errors::TransactionConflictError;
+use gel_errors::{ClientClosedError, ClientConnectionError, ClientQueryTimeoutError};
+use gel_errors::{ClientConnectionEosError, Error, SessionStateLostError};
+use gel_mock_server::{MockServer, Reply, Type};
+use gel_protocol::value::Value;
+use gel_tokio::{Builder, Client, Config, Observer, QueryInfo};
+use tokio::time::{sleep, timeout};
+
+async fn connect(server: &MockServer) -> anyhow::Result<Client> {
+    let client = Client::new(&config(server).await?);
+    client.ensure_connected().await?;
+    Ok(client)
+}
+
+async fn config(server: &MockServer) -> anyhow::Result<Config> {
+    Ok(builder(server)?.build_env().await?)
+}
+
+fn builder(server: &MockServer) -> anyhow::Result<Builder> {
+    let mut builder = Builder::new();
+    builder
+        .host("127.0.0.1")?
+        .port(server.address().port())?
+        .tls_server_name("localhost")?
+        .user(MockServer::USER)?
+        .password(MockServer::PASSWORD)
+        .pem_certificates(MockServer::CA_CERTIFICATE)?;
+    Ok(builder)
+}
+
+#[tokio::test]
+async fn query() -> anyhow::Result<()> {
+    let server = MockServer::start().await?;
+    server.on("SELECT 7", Reply::rows(Type::INT64, [Value::Int64(7)]));
+    let client = connect(&server).await?;
+
+    let value = client
+        .query_required_single::<i64, _>("SELECT 7", &())
+        .await?;
+    assert_eq!(value, 7);
+    client.execute("INSERT Foo", &()).await?;
+    assert_eq!(server.queries(), ["SELECT 7", "INSERT Foo"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn retry_conflict() -> anyhow::Result<()> {
+    let server = MockServer::start().await?;
+    server.on(
+        "SELECT 1",
+        Reply::error::<TransactionConflictError>("conflict"),
+    );
+    server.on("SELECT 1", Reply::rows(Type::INT64, [Value::Int64(1)]));
+    let client = connect(&server).await?;
+
+    let value = client
+        .query_required_single::<i64, _>("SELECT 1", &())
+        .await?;
+    assert_eq!(value, 1);
+    assert_eq!(server.queries(), ["SELECT 1", "SELECT 1"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn transaction_retry() -> anyhow::Result<()> {
+    let server = MockServer::start().await?;
+    server.on(
+        "SELECT 1",
+        Reply::error::<TransactionConflictError>("conflict"),
+    );
+    server.on("SELECT 1", Reply::rows(Type::INT64, [Value::Int64(1)]));
+    let client = connect(&server).await?;
+
+    let value = client
+        .transaction(
+            |mut tx| async move { tx.query_required_single::<i64, _>("SELECT 1", &()).await },
+        )
+        .await?;
+    assert_eq!(value, 1);
+    let queries = server.queries();
+    assert_eq!(queries.len(), 6);
+    assert!(queries[0].starts_with("START TRANSACTION"));
+    assert_eq!(queries[1..3], ["SELECT 1", "ROLLBACK"]);
+    assert!(queries[3].starts_with("START TRANSACTION"));
+    assert_eq!(queries[4..], ["SELECT 1", "COMMIT"]);
+    Ok(())
+}
+
+fn modules(server: &MockServer) -> Vec<(String, Option<String>)> {
+    server
+        .requests()
+        .into_iter()
+        .filter(|r| !r.query.starts_with("START TRANSACTION"))
+        .map(|r| (r.query, r.module))
+        .collect()
+}
+
+#[tokio::test]
+async fn transaction_state() -> anyhow::Result<()> {
+    let server = MockServer::start().await?;
+    server.on("SET MODULE foo", Reply::ok().with_module("foo"));
+    let client = connect(&server).await?;
+
+    client
+        .transaction(|mut tx| async move {
+            tx.execute("SET MODULE foo", &()).await?;
+            tx.execute("SELECT 1", &()).await?;
+            Ok(())
+        })
+        .await?;
+    client.execute("SELECT 2", &()).await?;
+    let default = Some("default".to_string());
+    let foo = Some("foo".to_string());
+    assert_eq!(
+        modules(&server),
+        [
+            ("SET MODULE foo".into(), default.clone()),
+            ("SELECT 1".into(), foo.clone()),
+            ("COMMIT".into(), foo),
+            ("SELECT 2".into(), default),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn savepoint_state() -> anyhow::Result<()> {
+    let server = MockServer::start().await?;
+    server.on("SET MODULE foo", Reply::ok().with_module("foo"));
+    let client = connect(&server).await?;
+
+    client
+        .transaction(|mut tx| async move {
+            let mut savepoint = tx.savepoint("sp").await?;
+            savepoint.execute("SET MODULE foo", &()).await?;
+            savepoint.rollback().await?;
+            tx.execute("SELECT 1", &()).await?;
+            Ok(())
+        })
+        .await?;
+    let requests = modules(&server);
+    assert_eq!(requests[2].0, "ROLLBACK TO SAVEPOINT sp");
+    assert_eq!(requests[3], ("SELECT 1".into(), Some("default".into())));
+    Ok(())
+}
+
+#[tokio::test]
+async fn batch_disconnect() -> anyhow::Result<()> {
+    let server = MockServer::start().await?;
+    server.on("INSERT Bar", Reply::disconnect());
+    let client = connect(&server).await?;
+
+    let err = client
+        .transaction(|mut tx| async move {
+            let mut batch = tx.batch();
+            batch.add("INSERT Foo", ()).add("INSERT Bar", ());
+            batch.execute().await
+        })
+        .await
+        .unwrap_err();
+    assert!(err.is::<ClientConnectionEosError>());
+    assert!(err.get::<BatchIndex>().is_none());
+    assert_eq!(server.queries()[1..], ["INSERT Foo", "INSERT Bar"]);
+
+    // the broken connection is replaced
+    client.execute("INSERT Foo", &()).await?;
+    assert_eq!(server.connections(), 2);
+    Ok(())
+}
+
+/// Records kind, message and query of errors passed to [`Observer::retry`]
+#[derive(Debug, Default)]
+struct RetryErrors(Mutex<Vec<(String, String, String)>>);
+
+impl Observer for RetryErrors {
+    fn retry(&self, _attempt: u32, _delay: Duration, error: &Error) {
+        self.0.lock().unwrap().push((
+            error.kind_name().into(),
+            error.initial_message().unwrap_or_default().into(),
+            error.get::<QueryText>().cloned().unwrap_or_default(),
+        ));
+    }
+}
+
+#[tokio::test]
+async fn savepoint_retry_error() -> anyhow::Result<()> {
+    let server = MockServer::start().await?;
+    server.on(
+        "INSERT Foo",
+        Reply::error::<TransactionConflictError>("conflict"),
+    );
+    server.on("INSERT Foo", Reply::ok());
+    let observer = Arc::new(RetryErrors::default());
+    let client = Client::new(&config(&server).await?.with_observer(observer.clone()));
+
+    client
+        .transaction(|mut tx| async move {
+            let mut savepoint = tx.savepoint("sp").await?;
+            if savepoint.execute("INSERT Foo", &()).await.is_err() {
+                savepoint.rollback().await?;
+            }
+            Ok(())
+        })
+        .await?;
+    assert_eq!(
+        *observer.0.lock().unwrap(),
+        [(
+            "TransactionConflictError".into(),
+            "conflict".into(),
+            "INSERT Foo".into()
+        )]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_state_change() -> anyhow::Result<()> {
+    let server = MockServer::start().await?;
+    server.on("SET MODULE foo", Reply::ok().with_module("foo"));
+    let rows = Reply::rows(Type::INT64, [Value::Int64(1)]).with_module("foo");
+    server.on("SELECT foo()", rows);
+    let client = connect(&server).await?;
+
+    // the change would not be retained by the pooled client
+    let err = client.execute("SET MODULE foo", &()).await.unwrap_err();
+    assert!(err.is::<SessionStateLostError>(), "{err:#}");
+    let err = client
+        .query::<i64, _>("SELECT foo()", &())
+        .await
+        .unwrap_err();
+    assert!(err.is::<SessionStateLostError>(), "{err:#}");
+    let stream = client.query_stream::<i64, _>("SELECT foo()", &()).await?;
+    let err = stream.try_collect::<Vec<_>>().await.unwrap_err();
+    assert!(err.is::<SessionStateLostError>(), "{err:#}");
+
+    client.execute("SELECT 1", &()).await?;
+    assert_eq!(
+        modules(&server),
+        [
+            ("SET MODULE foo".into(), Some("default".into())),
+            ("SELECT foo()".into(), Some("default".into())),
+            ("SELECT foo()".into(), Some("default".into())),
+            ("SELECT 1".into(), Some("default".into())),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn session() -> anyhow::Result<()> {
+    let server = MockServer::start().await?;
+    server.set_pool_concurrency(1);
+    server.on("SET MODULE foo", Reply::ok().with_module("foo"));
+    let client = connect(&server).await?;
+
+    let mut session = client.session().await?;
+    session.execute("SET MODULE foo", &()).await?;
+    session.execute("SELECT 1", &()).await?;
+    drop(session);
+    // The state is reset when the connection is acquired again
+    client.execute("SELECT 2", &()).await?;
+    let default = Some("default".to_string());
+    assert_eq!(
+        modules(&server),
+        [
+            ("SET MODULE foo".into(), default.clone()),
+            ("SELECT 1".into(), Some("foo".into())),
+            ("SELECT {}".into(), None),
+            ("SELECT 2".into(), default),
+        ]
+    );
+
+    // Sessions that haven't changed the state leave it as is
+    let mut session = client.session().await?;
+    session.execute("SELECT 3", &()).await?;
+    drop(session);
+    client.execute("SELECT 4", &()).await?;
+    assert_eq!(server.queries()[4..], ["SELECT 3", "SELECT 4"]);
+    assert_eq!(server.connections(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn reconnect() -> anyhow::Result<()> {
+    let server = MockServer::start().await?;
+    server.on("SELECT 'x'", Reply::disconnect());
+    server.on(
+        "SELECT 'x'",
+        Reply::rows(Type::STR, [Value::Str("x".into())]),
+    );
+    let client = connect(&server).await?;
+    assert_eq!(server.connections(), 1);
+
+    let err = client
+        .query_required_single::<String, _>("SELECT 'x'", &())
+        .await
+        .unwrap_err();
+    assert!(err.is::<ClientConnectionError>(), "{err:#}");
+    let value = client
+        .query_required_single::<String, _>("SELECT 'x'", &())
+        .await?;
+    assert_eq!(value, "x");
+    assert_eq!(server.connections(), 2);
+    Ok(())
+}
+
+/// Counts opened and closed connections
+#[derive(Debug, Default)]
+struct ConnectionCounts {
+    opened: AtomicUsize,
+    closed: AtomicUsize,
+}
+
+impl Observer for ConnectionCounts {
+    fn connection_opened(&self) {
+        self.opened.fetch_add(1, Ordering::SeqCst);
+    }
+    fn connection_closed(&self) {
+        self.closed.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Waits until `condition` holds, failing the test after a few seconds
+async fn wait_for(condition: impl Fn() -> bool) {
+    timeout(Duration::from_secs(5), async {
+        while !condition() {
+            sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("condition holds in time");
+}
+
+#[tokio::test]
+async fn pool_maintenance() -> anyhow::Result<()> {
+    let server = MockServer::start().await?;
+    let observer = Arc::new(ConnectionCounts::default());
+    let config = builder(&server)?
+        .min_idle(2)
+        .max_connection_lifetime(Duration::from_secs(1))
+        .build_env()
+        .await?
+        .with_observer(observer.clone());
+    let client = Client::new(&config);
+
+    // idle connections are opened without any query
+    wait_for(|| observer.opened.load(Ordering::SeqCst) == 2).await;
+    assert_eq!(server.connections(), 2);
+    assert_eq!(observer.closed.load(Ordering::SeqCst), 0);
+
+    // expired connections are closed and replaced
+    wait_for(|| observer.closed.load(Ordering::SeqCst) == 2).await;
+    wait_for(|| observer.opened.load(Ordering::SeqCst) == 4).await;
+    assert_eq!(server.connections(), 4);
+    client.execute("SELECT 1", &()).await?;
+    assert_eq!(server.queries(), ["SELECT 1"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn pool_size() -> anyhow::Result<()> {
+    let server = MockServer::start().await?;
+    server.set_pool_concurrency(3);
+    server.on("SELECT 1", Reply::ok().after(Duration::from_millis(100)));
+    let client = connect(&server).await?;
+
+    // the pool grows to the suggested concurrency of the first connection
+    try_join_all((0..6).map(|_| client.execute("SELECT 1", &()))).await?;
+    assert_eq!(server.connections(), 3);
+
+    // and shrinks when a new connection suggests a lower one
+    server.set_pool_concurrency(1);
+    server.disconnect_all();
+    sleep(Duration::from_millis(50)).await;
+    client.execute("SELECT 1", &()).await?;
+    assert_eq!(server.connections(), 4);
+    try_join_all((0..6).map(|_| client.execute("SELECT 1", &()))).await?;
+    assert_eq!(server.connections(), 4);
+    Ok(())
+}
+
+#[tokio::test]
+async fn close() -> anyhow::Result<()> {
+    let server = MockServer::start().await?;
+    server.on("SELECT 1", Reply::ok().after(Duration::from_millis(200)));
+    let client = connect(&server).await?;
+    let busy = tokio::spawn({
+        let client = client.clone();
+        async move { client.execute("SELECT 1", &()).await }
+    });
+    wait_for(|| server.queries() == ["SELECT 1"]).await;
+    // opens a second connection, which stays idle
+    client.execute("INSERT Foo", &()).await?;
+    assert_eq!(server.connections(), 2);
+
+    // both the idle and the busy connection are terminated
+    client.close().await;
+    assert!(busy.is_finished());
+    busy.await??;
+    wait_for(|| server.terminations() == 2).await;
+
+    let err = client.execute("INSERT Foo", &()).await.unwrap_err();
+    assert!(err.is::<ClientClosedError>());
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_timeout() -> anyhow::Result<()> {
+    let server = MockServer::start().await?;
+    let slow = Reply::rows(Type::INT64, [Value::Int64(1)]).after(Duration::from_secs(5));
+    server.on("SELECT slow()", slow);
+    let client = connect(&server).await?;
+
+    let err = client
+        .with_query_timeout(Duration::from_millis(100))
+        .query::<i64, _>("SELECT slow()", &())
+        .await
+        .unwrap_err();
+    assert!(err.is::<ClientQueryTimeoutError>());
+    client.execute("SELECT 2", &()).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_stream_timeout() -> anyhow::Result<()> {
+    let server = MockServer::start().await?;
+    let slow = Reply::rows(Type::INT64, [Value::Int64(1)]).after(Duration::from_secs(5));
+    server.on("SELECT slow()", slow);
+    let client = connect(&server).await?;
+    let timed = client.with_query_timeout(Duration::from_millis(100));
+
+    let err = timed
+        .query_stream::<i64, _>("SELECT slow()", &())
+        .await
+        .err()
+        .unwrap();
+    assert!(err.is::<ClientQueryTimeoutError>());
+    let err = timed
+        .transaction(|mut tx| async move {
+            tx.query_stream::<i64, _>("SELECT slow()", &())
+                .await?
+                .try_collect::<Vec<_>>()
+                .await
+        })
+        .await
+        .unwrap_err();
+    assert!(err.is::<ClientQueryTimeoutError>());
+    client.execute("SELECT 2", &()).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn wrong_password() -> anyhow::Result<()> {
+    let server = MockServer::start().await?;
+    let config = Builder::new()
+        .host("127.0.0.1")?
+        .port(server.address().port())?
+        .tls_server_name("localhost")?
+        .user(MockServer::USER)?
+        .password("wrong")
+        .pem_certificates(MockServer::CA_CERTIFICATE)?
+        .wait_until_available(Duration::from_millis(100))
+        .build_env()
+        .await?;
+    let err = Client::new(&config).ensure_connected().await.unwrap_err();
+    assert!(err.is::<gel_errors::AuthenticationError>(), "{err:#}");
+    Ok(())
+}
+
+/// Records texts of finished queries
+#[derive(Debug, Default)]
+struct FinishedQueries(Mutex<Vec<String>>);
+
+impl Observer for FinishedQueries {
+    fn query_finished(&self, query: &QueryInfo, _duration: Duration, _error: Option<&Error>) {
+        self.0.lock().unwrap().push(query.query.to_string());
+    }
+}
+
+#[tokio::test]
+async fn stream_instrumentation() -> anyhow::Result<()> {
+    let server = MockServer::start().await?;
+    let rows = Reply::rows(Type::INT64, [Value::Int64(1), Value::Int64(2)]);
+    server.on("SELECT {1, 2}", rows.clone());
+    server.on("SELECT {1, 2}", rows);
+    let observer = Arc::new(FinishedQueries::default());
+    let client = Client::new(&config(&server).await?.with_observer(observer.clone()));
+
+    for _ in 0..2 {
+        let values = client
+            .query_stream::<i64, _>("SELECT {1, 2}", &())
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        assert_eq!(values, [1, 2]);
+    }
+    let stats = client.query_cache_stats();
+    assert_eq!((stats.hits, stats.misses), (1, 1));
+    assert_eq!(
+        *observer.0.lock().unwrap(),
+        ["SELECT {1, 2}", "SELECT {1, 2}"]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn batch_instrumentation() -> anyhow::Result<()> {
+    let server = MockServer::start().await?;
+    let observer = Arc::new(FinishedQueries::default());
+    let client = Client::new(&config(&server).await?.with_observer(observer.clone()));
+
+    client
+        .transaction(|mut tx| async move {
+            let mut batch = tx.batch();
+            batch.add("INSERT Foo", ()).add("INSERT Bar", ());
+            batch.execute().await
+        })
+        .await?;
+    let queries = observer.0.lock().unwrap().clone();
+    assert_eq!(queries.len(), 3);
+    assert!(queries[0].starts_with("START TRANSACTION"));
+    assert_eq!(queries[1..], ["INSERT Foo;\nINSERT Bar", "COMMIT"]);
+    let stats = client.query_cache_stats();
+    assert_eq!((stats.hits, stats.misses), (0, 2));
+    Ok(())
+}
@@ -1,11 +1,14 @@
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 use futures_util::stream::{self, StreamExt};
-use gel_errors::NoDataError;
+use gel_errors::{ClientClosedError, ClientQueryTimeoutError, Error, NoDataError};
 use gel_protocol::model::{Json, Uuid};
 use gel_protocol::named_args;
 use gel_protocol::value::{EnumValue, Value};
-use gel_tokio::{Client, Queryable};
+use gel_tokio::{Builder, Client, Observer, QueryInfo, Queryable};
 use serde::{Deserialize, Serialize};
 
 use crate::server::SERVER;
@@ -405,3 +408,163 @@ async fn props_in_wrong_order() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn query_cache() -> anyhow::Result<()> {
+    let client = Client::new(&SERVER.config);
+    client.ensure_connected().await?;
+
+    let before = client.query_cache_stats();
+    let value = client
+        .query::<i64, _>("SELECT 17 + <int64>$0", &(4_i64,))
+        .await?;
+    assert_eq!(value, vec![21]);
+    let value = client
+        .query::<i64, _>("SELECT 17 + <int64>$0", &(5_i64,))
+        .await?;
+    assert_eq!(value, vec![22]);
+    let after = client.query_cache_stats();
+    assert!(after.hits > before.hits);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_stream() -> anyhow::Result<()> {
+    let client = Client::new(&SERVER.config);
+
+    let stream = client
+        .query_stream::<i64, _>("SELECT range_unpack(range(0, <int64>$0))", &(10000_i64,))
+        .await?;
+    let values = stream.collect::<Vec<_>>().await;
+    assert_eq!(values.len(), 10000);
+    for (i, value) in values.into_iter().enumerate() {
+        assert_eq!(value?, i as i64);
+    }
+
+    // dropping the stream early must not break the client
+    let mut stream = Box::pin(
+        client
+            .query_stream::<i64, _>("SELECT range_unpack(range(0, 10000))", &())
+            .await?,
+    );
+    assert_eq!(stream.next().await.transpose()?, Some(0));
+    drop(stream);
+    let value = client
+        .query_required_single::<i64, _>("SELECT 7", &())
+        .await?;
+    assert_eq!(value, 7);
+
+    let Err(err) = client
+        .query_stream::<i64, _>("SELECT 1 // <int64>$0", &(0_i64,))
+        .await
+    else {
+        panic!("query should fail");
+    };
+    assert!(err.is::<gel_errors::DivisionByZeroError>());
+    Ok(())
+}
+
+#[tokio::test]
+async fn pool_health() -> anyhow::Result<()> {
+    let config = Builder::new()
+        .credentials(&SERVER.config.as_credentials()?)?
+        .max_idle_time(Duration::from_millis(200))
+        .max_connection_lifetime(Duration::from_millis(500))
+        .min_idle(1)
+        .constrained_build()?;
+    let observer = Arc::new(CountingObserver::default());
+    let client = Client::new(&config.with_observer(observer.clone()));
+    client.ensure_connected().await?;
+
+    for _ in 0..4 {
+        let value = client
+            .query_required_single::<i64, _>("SELECT 7", &())
+            .await?;
+        assert_eq!(value, 7);
+        tokio::time::sleep(Duration::from_millis(300)).await;
+    }
+    // connections have outlived `max_connection_lifetime` and were replaced
+    assert!(observer.closed.load(Ordering::SeqCst) >= 1);
+    assert!(observer.opened.load(Ordering::SeqCst) >= 2);
+
+    client.close().await;
+    let err = client.query::<i64, _>("SELECT 7", &()).await.unwrap_err();
+    assert!(err.is::<ClientClosedError>());
+    Ok(())
+}
+
+#[derive(Default)]
+struct CountingObserver {
+    opened: AtomicUsize,
+    closed: AtomicUsize,
+    acquired: AtomicUsize,
+    started: AtomicUsize,
+    failed: AtomicUsize,
+    committed: AtomicUsize,
+}
+
+impl Observer for CountingObserver {
+    fn connection_opened(&self) {
+        self.opened.fetch_add(1, Ordering::SeqCst);
+    }
+    fn connection_closed(&self) {
+        self.closed.fetch_add(1, Ordering::SeqCst);
+    }
+    fn connection_acquired(&self, _wait: Duration) {
+        self.acquired.fetch_add(1, Ordering::SeqCst);
+    }
+    fn query_started(&self, _query: &QueryInfo) {
+        self.started.fetch_add(1, Ordering::SeqCst);
+    }
+    fn query_finished(&self, _query: &QueryInfo, _duration: Duration, error: Option<&Error>) {
+        if error.is_some() {
+            self.failed.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+    fn transaction_committed(&self) {
+        self.committed.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+async fn observer() -> anyhow::Result<()> {
+    let observer = Arc::new(CountingObserver::default());
+    let client = Client::new(&SERVER.config.clone().with_observer(observer.clone()));
+
+    client.query::<i64, _>("SELECT 7", &()).await?;
+    client.query::<i64, _>("SELECT 1/0", &()).await.unwrap_err();
+    client
+        .transaction(|mut tx| async move {
+            tx.execute("SELECT 1", &()).await?;
+            Ok(())
+        })
+        .await?;
+
+    assert!(observer.opened.load(Ordering::SeqCst) >= 1);
+    assert_eq!(observer.acquired.load(Ordering::SeqCst), 3);
+    // START TRANSACTION, SELECT 1 and COMMIT are counted too
+    assert_eq!(observer.started.load(Ordering::SeqCst), 5);
+    assert_eq!(observer.failed.load(Ordering::SeqCst), 1);
+    assert_eq!(observer.committed.load(Ordering::SeqCst), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_timeout() -> anyhow::Result<()> {
+    let client = Client::new(&SERVER.config);
+    client.ensure_connected().await?;
+
+    let err = client
+        .with_query_timeout(Duration::from_millis(100))
+        .query::<bool, _>("SELECT sys::_sleep(5)", &())
+        .await
+        .unwrap_err();
+    assert!(err.is::<ClientQueryTimeoutError>());
+
+    // the connection with the cancelled query is not reused
+    let value = client
+        .query_required_single::<i64, _>("SELECT 7", &())
+        .await?;
+    assert_eq!(value, 7);
+    Ok(())
+}
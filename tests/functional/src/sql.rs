@@ -0,0 +1,98 @@
+use gel_derive::Queryable;
+use gel_errors::ResultCardinalityMismatchError;
+use gel_protocol::value::Value;
+use gel_tokio::Client;
+
+use crate::server::SERVER;
+
+#[derive(Queryable, Debug, PartialEq)]
+struct Row {
+    name: String,
+    value: i64,
+}
+
+#[tokio::test]
+async fn query_sql() -> anyhow::Result<()> {
+    let client = Client::new(&SERVER.config);
+
+    let rows = client
+        .query_sql::<Value, _>("SELECT 1 AS one, 'two' AS two", &())
+        .await?;
+    assert_eq!(rows.len(), 1);
+    let Value::SQLRow { shape, fields } = &rows[0] else {
+        panic!("unexpected value {:?}", rows[0]);
+    };
+    let names = shape
+        .elements
+        .iter()
+        .map(|e| e.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["one", "two"]);
+    assert_eq!(fields[1], Some(Value::Str("two".into())));
+
+    // columns are matched to fields by name
+    let rows = client
+        .query_sql::<Row, _>(
+            "SELECT $1::int8 AS value, 'x' AS name UNION ALL SELECT 2, 'y'",
+            &(1_i64,),
+        )
+        .await?;
+    assert_eq!(
+        rows,
+        vec![
+            Row {
+                name: "x".into(),
+                value: 1
+            },
+            Row {
+                name: "y".into(),
+                value: 2
+            },
+        ]
+    );
+
+    let row = client
+        .query_sql_single::<Row, _>("SELECT 'z' AS name, 7::int8 AS value", &())
+        .await?;
+    assert_eq!(
+        row,
+        Some(Row {
+            name: "z".into(),
+            value: 7
+        })
+    );
+
+    let err = client
+        .query_sql_single::<Value, _>("SELECT 1 UNION ALL SELECT 2", &())
+        .await
+        .unwrap_err();
+    assert!(err.is::<ResultCardinalityMismatchError>());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn execute_sql() -> anyhow::Result<()> {
+    let client = Client::new(&SERVER.config);
+
+    client
+        .execute_sql(r#"INSERT INTO "test::X" (a) VALUES ($1)"#, &("sql",))
+        .await?;
+    let count = client
+        .query_required_single::<i64, _>(r#"SELECT count((SELECT test::X FILTER .a = "sql"))"#, &())
+        .await?;
+    assert!(count >= 1);
+
+    client
+        .transaction(|mut tx| async move {
+            tx.execute_sql(r#"DELETE FROM "test::X" WHERE a = $1"#, &("sql",))
+                .await?;
+            let rows = tx
+                .query_sql::<Value, _>(r#"SELECT a FROM "test::X" WHERE a = $1"#, &("sql",))
+                .await?;
+            assert!(rows.is_empty());
+            Ok(())
+        })
+        .await?;
+    Ok(())
+}
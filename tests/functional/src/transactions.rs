@@ -3,8 +3,9 @@ use std::sync::Arc;
 
 use tokio::sync::Mutex;
 
-use gel_errors::{ErrorKind, NoDataError};
-use gel_tokio::{Client, Transaction};
+use gel_errors::fields::BatchIndex;
+use gel_errors::{ErrorKind, NoDataError, TransactionError};
+use gel_tokio::{Client, IsolationLevel, Transaction, TransactionOptions};
 
 use crate::server::SERVER;
 
@@ -267,3 +268,146 @@ async fn abort_01() -> anyhow::Result<()> {
     assert_eq!(err.kind_name(), "UserError");
     Ok(())
 }
+
+#[tokio::test]
+async fn read_only() -> anyhow::Result<()> {
+    let client = Client::new(&SERVER.config)
+        .with_transaction_options(TransactionOptions::default().read_only(true));
+
+    let value = client
+        .transaction(|mut tx| async move {
+            tx.query_required_single::<i64, _>("SELECT 7*93", &()).await
+        })
+        .await?;
+    assert_eq!(value, 651);
+
+    let res = client
+        .transaction(|mut tx| async move {
+            tx.execute(r#"INSERT test::Y { a := "read_only" }"#, &())
+                .await
+        })
+        .await;
+    let err = res.unwrap_err();
+    assert!(err.is::<TransactionError>(), "{err:#}");
+
+    let mut tx = client.transaction_raw().await?;
+    let res = tx
+        .execute(r#"INSERT test::Y { a := "read_only" }"#, &())
+        .await;
+    let err = res.unwrap_err();
+    assert!(err.is::<TransactionError>(), "{err:#}");
+    drop(tx);
+
+    let count = Client::new(&SERVER.config)
+        .query_required_single::<i64, _>(
+            r#"SELECT count((SELECT test::Y FILTER .a = "read_only"))"#,
+            &(),
+        )
+        .await?;
+    assert_eq!(count, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn isolation() -> anyhow::Result<()> {
+    let client = Client::new(&SERVER.config).with_transaction_options(
+        TransactionOptions::default()
+            .isolation(IsolationLevel::Serializable)
+            .read_only(true)
+            .deferrable(true),
+    );
+    let value = client
+        .transaction(|mut tx| async move {
+            tx.query_required_single::<i64, _>("SELECT 5*11", &()).await
+        })
+        .await?;
+    assert_eq!(value, 55);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_stream() -> anyhow::Result<()> {
+    use futures_util::StreamExt;
+
+    let client = Client::new(&SERVER.config);
+    let mut tx = client.transaction_raw().await?;
+    let values = tx
+        .query_stream::<i64, _>("SELECT {1, 2, 3}", &())
+        .await?
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(values, vec![1, 2, 3]);
+    let value = tx.query_required_single::<i64, _>("SELECT 4", &()).await?;
+    assert_eq!(value, 4);
+    tx.commit().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn savepoints() -> anyhow::Result<()> {
+    let client = Client::new(&SERVER.config).with_default_module(Some("test"));
+    let mut tx = client.transaction_raw().await?;
+
+    let mut sp = tx.savepoint("released").await?;
+    sp.execute("insert X { a := 'savepoint_released' }", &())
+        .await?;
+    sp.release().await?;
+
+    let mut sp = tx.savepoint("rolled_back").await?;
+    sp.execute("insert X { a := 'savepoint_rolled_back' }", &())
+        .await?;
+    sp.rollback().await?;
+
+    {
+        let mut sp = tx.savepoint("dropped").await?;
+        sp.execute("insert X { a := 'savepoint_dropped' }", &())
+            .await?;
+        // recover from the error by rolling back on drop
+        let mut inner = sp.savepoint("failed").await?;
+        inner
+            .query::<i64, _>("select 1 // <int64>$0", &(0_i64,))
+            .await
+            .unwrap_err();
+    }
+
+    let names = tx
+        .query::<String, _>("select X.a filter X.a like 'savepoint_%' order by X.a", &())
+        .await?;
+    assert_eq!(names, vec!["savepoint_released"]);
+
+    assert!(tx.savepoint("bad name").await.is_err());
+
+    // no commit
+    Ok(())
+}
+
+#[tokio::test]
+async fn batch() -> anyhow::Result<()> {
+    let client = Client::new(&SERVER.config).with_default_module(Some("test"));
+    let mut tx = client.transaction_raw().await?;
+
+    let mut batch = tx.batch();
+    for idx in 0..10 {
+        batch.add("insert X { a := <str>$0 }", (format!("batch_{idx:02}"),));
+    }
+    assert_eq!(batch.len(), 10);
+    batch.execute().await?;
+
+    let names = tx
+        .query::<String, _>("select X.a filter X.a like 'batch_%' order by X.a", &())
+        .await?;
+    assert_eq!(names.len(), 10);
+    assert_eq!(names[0], "batch_00");
+
+    let mut batch = tx.batch();
+    batch.add("insert X { a := 'batch_ok' }", ());
+    batch.add("select 1 // <int64>$0", (0_i64,));
+    batch.add("insert X { a := 'batch_skipped' }", ());
+    let err = batch.execute().await.unwrap_err();
+    assert_eq!(err.get::<BatchIndex>(), Some(&1));
+
+    // no commit
+    Ok(())
+}